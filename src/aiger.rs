@@ -0,0 +1,321 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::ops::RangeInclusive;
+
+use crate::error::{ParseError, SolverError};
+use crate::{Lit, Model, Solver, Var, compression};
+
+/// An and-inverter graph in the AIGER format, ASCII (`aag`) or binary
+/// (`aig`), read for combinational queries: can some output be true?
+///
+/// AIGER literals are `2·v` for variable `v` and `2·v + 1` for its
+/// negation, with `0` and `1` the constants false and true. Each AIGER
+/// variable `v` becomes solver variable `v`. Latches are cut: their current
+/// states are free variables like the inputs, and their next states are
+/// ignored. Bad-state properties (AIGER 1.9) count as outputs and invariant
+/// constraints must hold; justice and fairness properties are rejected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Aiger {
+    /// Highest variable index, `M` in the header.
+    pub max_var: usize,
+    /// Literals of the inputs, in order.
+    pub inputs: Vec<u64>,
+    /// Literals of the latches' current states.
+    pub latches: Vec<u64>,
+    /// Literals of the outputs, then of the bad-state properties.
+    pub outputs: Vec<u64>,
+    /// Literals that must be true (invariant constraints).
+    pub constraints: Vec<u64>,
+    /// And gates `lhs = rhs0 ∧ rhs1`, in order.
+    pub ands: Vec<(u64, u64, u64)>,
+    /// Names of the inputs given in the symbol table.
+    pub input_names: Vec<Option<String>>,
+}
+
+impl Aiger {
+    /// Loads an AIGER file, possibly compressed like the inputs of
+    /// `Solver::new`.
+    pub fn new(path: &str) -> Result<Self, SolverError> {
+        let file = BufReader::new(File::open(path)?);
+        Self::from_reader(compression::decompress(file)?)
+    }
+
+    /// Reads an AIGER graph, in either format, from any buffered reader.
+    pub fn from_reader(reader: impl BufRead) -> Result<Self, SolverError> {
+        AigerReader { reader, line: 0 }.parse()
+    }
+
+    /// Parses an ASCII AIGER graph held in memory.
+    pub fn from_aag_str(aag: &str) -> Result<Self, SolverError> {
+        Self::from_reader(aag.as_bytes())
+    }
+
+    /// A solver holding the Tseitin encoding of the and gates, the
+    /// constraints, and the clause that some output is true: it is
+    /// satisfiable exactly when an assignment to the inputs and latches
+    /// sets an output. For a miter, that is when the two circuits differ.
+    /// Without outputs only the constraints are asked for.
+    ///
+    /// Variable `max_var + 1` stands for the constant false.
+    pub fn to_solver(&self) -> Solver {
+        let mut solver = Solver::with_vars(self.max_var + 1);
+        let constant = Var::new(self.max_var + 1);
+        solver.add_input_clause(vec![constant.negative()]);
+        let lit = |aiger: u64| {
+            let var = match aiger / 2 {
+                0 => constant,
+                v => Var::new(v as usize),
+            };
+            Lit::new(var, aiger % 2 == 1)
+        };
+
+        for &(lhs, rhs0, rhs1) in &self.ands {
+            let (lhs, rhs0, rhs1) = (lit(lhs), lit(rhs0), lit(rhs1));
+            solver.add_input_clause(vec![-lhs, rhs0]);
+            solver.add_input_clause(vec![-lhs, rhs1]);
+            solver.add_input_clause(vec![lhs, -rhs0, -rhs1]);
+        }
+        for &constraint in &self.constraints {
+            solver.add_input_clause(vec![lit(constraint)]);
+        }
+        if !self.outputs.is_empty() {
+            solver.add_input_clause(self.outputs.iter().map(|&o| lit(o)).collect());
+        }
+        solver
+    }
+
+    /// The values of the inputs in `model`, in order.
+    pub fn input_values(&self, model: &Model) -> Vec<bool> {
+        self.inputs
+            .iter()
+            .map(|&input| model.value(Var::new(input as usize / 2)))
+            .collect()
+    }
+}
+
+/// The reader along with the number of the line read last.
+struct AigerReader<R> {
+    reader: R,
+    line: usize,
+}
+
+impl<R: BufRead> AigerReader<R> {
+    fn parse(mut self) -> Result<Aiger, SolverError> {
+        let header = self.next_line()?;
+        let mut fields = header.split_whitespace();
+        let binary = match fields.next() {
+            Some("aag") => false,
+            Some("aig") => true,
+            _ => return Err(ParseError::InvalidAigerHeader.into()),
+        };
+        let counts = fields
+            .map(|field| field.parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| ParseError::InvalidAigerHeader)?;
+        // M I L O A, then optionally B C J F
+        let count = |i: usize| counts.get(i).copied().unwrap_or(0);
+        // Variable `max_var + 1` stands for the constant false in the CNF
+        if !(5..=9).contains(&counts.len())
+            || count(7) != 0
+            || count(8) != 0
+            || count(0) >= Var::MAX_INDEX
+        {
+            return Err(ParseError::InvalidAigerHeader.into());
+        }
+        let (max_var, inputs, latches, outputs, ands) =
+            (count(0), count(1), count(2), count(3), count(4));
+        let (bad, constraints) = (count(5), count(6));
+        let defined = inputs
+            .checked_add(latches)
+            .and_then(|sum| sum.checked_add(ands));
+        let Some(roots) = outputs.checked_add(bad) else {
+            return Err(ParseError::InvalidAigerHeader.into());
+        };
+        if binary && defined != Some(max_var) {
+            return Err(ParseError::InvalidAigerHeader.into());
+        }
+        let max_lit = 2 * max_var as u64 + 1;
+
+        // The counts come from the file: the sections grow as they are read
+        let mut aiger = Aiger {
+            max_var,
+            inputs: Vec::new(),
+            latches: Vec::new(),
+            outputs: Vec::new(),
+            constraints: Vec::new(),
+            ands: Vec::new(),
+            input_names: Vec::new(),
+        };
+        for i in 0..inputs {
+            let input = if binary {
+                2 * (i as u64 + 1)
+            } else {
+                let literals = self.literals(1..=1, max_lit)?;
+                self.defined(&literals)?
+            };
+            aiger.inputs.push(input);
+        }
+        aiger.input_names = vec![None; inputs];
+        for i in 0..latches {
+            // The next state, and possibly the reset value, follow
+            let latch = if binary {
+                self.literals(1..=2, max_lit)?;
+                2 * (inputs + i + 1) as u64
+            } else {
+                let literals = self.literals(2..=3, max_lit)?;
+                self.defined(&literals)?
+            };
+            aiger.latches.push(latch);
+        }
+        for _ in 0..roots {
+            aiger.outputs.push(self.literals(1..=1, max_lit)?[0]);
+        }
+        for _ in 0..constraints {
+            aiger.constraints.push(self.literals(1..=1, max_lit)?[0]);
+        }
+        for i in 0..ands {
+            let gate = if binary {
+                let lhs = 2 * (inputs + latches + i + 1) as u64;
+                let rhs0 = lhs - self.delta(lhs)?;
+                let rhs1 = rhs0 - self.delta(rhs0)?;
+                (lhs, rhs0, rhs1)
+            } else {
+                let literals = self.literals(3..=3, max_lit)?;
+                (self.defined(&literals)?, literals[1], literals[2])
+            };
+            aiger.ands.push(gate);
+        }
+        self.symbols(&mut aiger)?;
+        Ok(aiger)
+    }
+
+    /// The next line, without its line break.
+    fn next_line(&mut self) -> Result<String, SolverError> {
+        let mut bytes = Vec::new();
+        if self.reader.read_until(b'\n', &mut bytes)? == 0 {
+            return Err(ParseError::UnexpectedEnd {
+                line: self.line + 1,
+            }
+            .into());
+        }
+        self.line += 1;
+        let line = String::from_utf8_lossy(&bytes);
+        Ok(line.trim_end_matches(['\n', '\r']).to_string())
+    }
+
+    /// The literals of the next line, of which there must be `count`.
+    fn literals(
+        &mut self,
+        count: RangeInclusive<usize>,
+        max_lit: u64,
+    ) -> Result<Vec<u64>, SolverError> {
+        let line = self.next_line()?;
+        let mut literals = Vec::new();
+        let mut column = 1;
+        for field in line.split(' ') {
+            let invalid_token = ParseError::InvalidToken {
+                line: self.line,
+                column,
+                token: field.to_string(),
+            };
+            if literals.len() == *count.end() {
+                return Err(invalid_token.into());
+            }
+            let literal: u64 = field.parse().map_err(|_| invalid_token)?;
+            if literal > max_lit {
+                return Err(ParseError::LiteralOutOfRange {
+                    line: self.line,
+                    column,
+                    literal: literal as i64,
+                    variables: (max_lit / 2) as usize,
+                }
+                .into());
+            }
+            literals.push(literal);
+            column += field.len() + 1;
+        }
+        if literals.len() < *count.start() {
+            return Err(ParseError::InvalidToken {
+                line: self.line,
+                column: 1,
+                token: line,
+            }
+            .into());
+        }
+        Ok(literals)
+    }
+
+    /// The first of `literals`, which the line defines: an input, latch or
+    /// and gate. It has to be a positive, non-constant literal.
+    fn defined(&self, literals: &[u64]) -> Result<u64, SolverError> {
+        let literal = literals[0];
+        if literal < 2 || literal % 2 == 1 {
+            return Err(ParseError::InvalidToken {
+                line: self.line,
+                column: 1,
+                token: literal.to_string(),
+            }
+            .into());
+        }
+        Ok(literal)
+    }
+
+    /// A difference between literals of a binary and gate, 7 bits per byte
+    /// with the high bit set on all but the last. It must not exceed `from`.
+    fn delta(&mut self, from: u64) -> Result<u64, SolverError> {
+        let mut delta = 0u64;
+        for shift in (0..64).step_by(7) {
+            let mut byte = [0];
+            if self.reader.read(&mut byte)? == 0 {
+                return Err(ParseError::UnexpectedEnd {
+                    line: self.line + 1,
+                }
+                .into());
+            }
+            delta |= u64::from(byte[0] & 0x7f) << shift;
+            if byte[0] & 0x80 == 0 {
+                break;
+            }
+        }
+        if delta > from {
+            return Err(ParseError::InvalidToken {
+                line: self.line + 1,
+                column: 1,
+                token: format!("delta {delta}"),
+            }
+            .into());
+        }
+        Ok(delta)
+    }
+
+    /// Input names from the optional symbol table, up to the comments.
+    fn symbols(&mut self, aiger: &mut Aiger) -> Result<(), SolverError> {
+        loop {
+            let line = match self.next_line() {
+                Ok(line) => line,
+                Err(SolverError::Parse(ParseError::UnexpectedEnd { .. })) => return Ok(()),
+                Err(e) => return Err(e),
+            };
+            if line == "c" {
+                return Ok(());
+            }
+            let Some(rest) = line.strip_prefix('i') else {
+                continue; // Names of latches and outputs are not needed
+            };
+            let (position, name) = rest.split_once(' ').unwrap_or((rest, ""));
+            let slot = position
+                .parse::<usize>()
+                .ok()
+                .and_then(|i| aiger.input_names.get_mut(i));
+            let Some(slot) = slot else {
+                return Err(ParseError::InvalidToken {
+                    line: self.line,
+                    column: 1,
+                    token: line.to_string(),
+                }
+                .into());
+            };
+            *slot = Some(name.to_string());
+        }
+    }
+}
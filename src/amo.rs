@@ -0,0 +1,164 @@
+use std::cmp::Reverse;
+use std::collections::HashSet;
+
+use crate::{ClauseRef, LBool, Lit, Solver};
+
+/// Adjacency entries `detect_at_most_ones` may scan before it stops
+/// looking for more groups.
+const AMO_EFFORT: u64 = 10_000_000;
+
+/// Smallest group worth a constraint of its own: two literals are just
+/// their binary clause.
+const MIN_AMO_LEN: usize = 3;
+
+impl Solver {
+    /// Preprocessing pass: finds groups of literals of which the binary
+    /// input clauses allow at most one to be true, cliques of the graph
+    /// joining `a` and `b` for every clause `-a ∨ -b`, and replaces the
+    /// clauses inside each group by one constraint. A group of `n`
+    /// literals takes `n(n-1)/2` clauses, and propagates from the literal
+    /// made true in one pass over the group instead of one binary
+    /// implication each.
+    ///
+    /// Cliques are grown greedily from every edge not inside a clique yet,
+    /// starting from the literals with the most neighbours, and may share
+    /// edges with earlier ones, so that overlapping groups (the rows and
+    /// boxes of a sudoku) are found whole. Runs on an empty trail before
+    /// the first search.
+    pub(crate) fn detect_at_most_ones(&mut self) {
+        debug_assert_eq!(self.trail.len(), 0);
+
+        // Lazy reasons count as learnt, so only input clauses are looked at
+        let mut binaries = Vec::new();
+        let mut adjacency: Vec<Vec<Lit>> = vec![Vec::new(); self.watch_lists.len()];
+        for cref in self.clauses.iter() {
+            if self.clauses.is_learnt(cref) {
+                continue;
+            }
+            if let [a, b] = self.clauses[cref] {
+                adjacency[(-a).index()].push(-b);
+                adjacency[(-b).index()].push(-a);
+                binaries.push(cref);
+            }
+        }
+        for list in adjacency.iter_mut() {
+            list.sort_unstable();
+            list.dedup();
+        }
+        let adjacent = |a: Lit, b: Lit| adjacency[a.index()].binary_search(&b).is_ok();
+
+        let mut seeds: Vec<Lit> = (2..adjacency.len())
+            .map(Lit::from_index)
+            .filter(|lit| adjacency[lit.index()].len() >= MIN_AMO_LEN - 1)
+            .collect();
+        seeds.sort_by_key(|lit| Reverse(adjacency[lit.index()].len()));
+        let mut covered = HashSet::new();
+        let mut groups: Vec<Vec<Lit>> = Vec::new();
+        let mut work = 0;
+        'seeds: for seed in seeds {
+            let neighbours = &adjacency[seed.index()];
+            for &first in neighbours {
+                if covered.contains(&edge(seed, first)) {
+                    continue;
+                }
+                let mut clique = vec![seed, first];
+                for &candidate in neighbours {
+                    work += clique.len() as u64;
+                    if candidate != first
+                        && clique[1..]
+                            .iter()
+                            .all(|&member| adjacent(member, candidate))
+                    {
+                        clique.push(candidate);
+                    }
+                }
+                for (i, &a) in clique.iter().enumerate() {
+                    covered.extend(clique[i + 1..].iter().map(|&b| edge(a, b)));
+                }
+                if clique.len() >= MIN_AMO_LEN {
+                    groups.push(clique);
+                }
+                if work > AMO_EFFORT {
+                    break 'seeds;
+                }
+            }
+        }
+        if groups.is_empty() {
+            return;
+        }
+
+        let mut grouped = HashSet::new();
+        for group in &groups {
+            for (i, &a) in group.iter().enumerate() {
+                grouped.extend(group[i + 1..].iter().map(|&b| edge(a, b)));
+            }
+        }
+        // The clauses stay in a DRAT proof: checkers may need them for the
+        // clauses learnt from the groups
+        for cref in binaries {
+            if let [a, b] = self.clauses[cref]
+                && grouped.contains(&edge(-a, -b))
+            {
+                self.clauses.delete(cref);
+            }
+        }
+        for group in groups {
+            self.push_at_most_one(group);
+        }
+        self.collect_garbage();
+        self.rewatch_all();
+    }
+
+    fn push_at_most_one(&mut self, literals: Vec<Lit>) {
+        let index = self.amos.len();
+        for &lit in &literals {
+            self.amo_watches[lit.index()].push(index);
+        }
+        self.amos.push(literals);
+        self.stats.at_most_ones += 1;
+    }
+
+    /// The at-most-one groups found by `config.at_most_one`, each a set of
+    /// literals of which at most one may be true. They stand for the binary
+    /// clauses between their literals, which are no longer stored.
+    pub fn at_most_ones(&self) -> impl Iterator<Item = &[Lit]> {
+        self.amos.iter().map(Vec::as_slice)
+    }
+
+    /// The binary clauses the at-most-one groups stand for, for the code
+    /// that needs the formula as clauses.
+    pub(crate) fn amo_clauses(&self) -> impl Iterator<Item = [Lit; 2]> + '_ {
+        self.amos.iter().flat_map(|group| {
+            group
+                .iter()
+                .enumerate()
+                .flat_map(move |(i, &a)| group[i + 1..].iter().map(move |&b| [-a, -b]))
+        })
+    }
+
+    /// Makes every other literal of the groups of `satisfied_lit` false,
+    /// each with the binary clause of the pair as its reason, or returns
+    /// that clause as the conflict if the other literal is already true.
+    pub(crate) fn propagate_amos(&mut self, satisfied_lit: Lit) -> Option<ClauseRef> {
+        let watches = satisfied_lit.index();
+        for k in 0..self.amo_watches[watches].len() {
+            let index = self.amo_watches[watches][k];
+            for j in 0..self.amos[index].len() {
+                let other = self.amos[index][j];
+                if other == satisfied_lit || self.assignments.value(other) == LBool::False {
+                    continue;
+                }
+                if let Some(conflict) = self.assert_lazy(vec![-other, -satisfied_lit]) {
+                    return Some(conflict);
+                }
+                self.stats.amo_propagations += 1;
+            }
+        }
+        None
+    }
+}
+
+/// The edge between literals `a` and `b`, the same either way round.
+fn edge(a: Lit, b: Lit) -> (Lit, Lit) {
+    (a.min(b), a.max(b))
+}
@@ -0,0 +1,230 @@
+use crate::{ClauseRef, Lit, Minimization, Solver};
+
+/// Outcome of analysing a conflict: the first-UIP clause and the decision
+/// level to jump back to so that the clause becomes asserting.
+pub(crate) struct ConflictAnalysis {
+    /// Asserting literal first, then the literal with the highest remaining level.
+    pub learnt: Vec<Lit>,
+    /// Second-highest decision level in `learnt`, or 0 for a unit clause.
+    pub backjump_level: usize,
+    /// Literal block distance: the number of distinct decision levels in `learnt`.
+    pub lbd: usize,
+    /// IDs of the clauses deriving `learnt`, when an LRAT proof is written.
+    pub hints: Vec<u64>,
+}
+
+impl Solver {
+    /// Derives the first-UIP clause from a conflict by resolving backwards
+    /// along the trail, starting from the falsified clause `conflict`. Every
+    /// clause and variable taking part in the derivation has its activity
+    /// bumped.
+    pub(crate) fn analyze(&mut self, conflict: ClauseRef) -> ConflictAnalysis {
+        let level = self.trail.decision_level();
+        let mut seen = std::mem::take(&mut self.seen);
+        let mut learnt = Vec::new();
+        let mut pending = 0;
+        let mut clause = conflict;
+        let mut index = self.trail.len();
+        let mut resolved: Option<Lit> = None;
+
+        loop {
+            self.bump_clause(clause);
+            for &lit in &self.clauses[clause] {
+                let var = lit.var();
+                if Some(lit) == resolved || seen[var.index()] || self.levels[var.index()] == 0 {
+                    continue;
+                }
+                seen[var.index()] = true;
+                self.order.bump(var);
+                if self.levels[var.index()] == level {
+                    pending += 1;
+                } else {
+                    learnt.push(lit);
+                }
+            }
+
+            // Next literal of the current level to resolve on
+            loop {
+                index -= 1;
+                if seen[self.trail[index].var().index()] {
+                    break;
+                }
+            }
+            let lit = self.trail[index];
+            let var = lit.var().index();
+            seen[var] = false;
+            pending -= 1;
+            if pending == 0 {
+                learnt.insert(0, -lit);
+                break;
+            }
+            resolved = Some(lit);
+            clause = self.reasons[var].expect("implied literal without a reason");
+        }
+
+        seen[learnt[0].var().index()] = true;
+        // Only the variables of the clause are left marked, and those that
+        // minimization proves redundant: clearing them is enough
+        let mut marked: Vec<usize> = learnt.iter().map(|lit| lit.var().index()).collect();
+        self.minimize(&mut learnt, &mut seen, &mut marked);
+        for var in marked {
+            seen[var] = false;
+        }
+        self.seen = seen;
+
+        let mut backjump_level = 0;
+        if let Some(max) = (1..learnt.len()).max_by_key(|&i| self.levels[learnt[i].var().index()]) {
+            learnt.swap(1, max);
+            backjump_level = self.levels[learnt[1].var().index()];
+        }
+
+        let mut levels: Vec<usize> = learnt
+            .iter()
+            .map(|lit| self.levels[lit.var().index()])
+            .collect();
+        levels.sort_unstable();
+        levels.dedup();
+
+        let hints = if self.wants_hints() {
+            self.conflict_hints(conflict, &learnt)
+        } else {
+            Vec::new()
+        };
+        ConflictAnalysis {
+            learnt,
+            backjump_level,
+            lbd: levels.len(),
+            hints,
+        }
+    }
+
+    /// Collects the assumptions responsible for assumption `failed` being
+    /// false, `failed` included, by walking the implication graph back to the
+    /// pseudo-decisions. Assignments at level 0 are facts and never blamed.
+    pub(crate) fn analyze_final(&mut self, failed: Lit) -> Vec<Lit> {
+        let mut involved = vec![failed];
+        if self.trail.decision_level() == 0 {
+            return involved;
+        }
+
+        let mut seen = std::mem::take(&mut self.seen);
+        let mut marked = vec![failed.var().index()];
+        seen[failed.var().index()] = true;
+        for index in (self.trail.level_start(1)..self.trail.len()).rev() {
+            let lit = self.trail[index];
+            let var = lit.var().index();
+            if !seen[var] {
+                continue;
+            }
+            match self.reasons[var] {
+                None => involved.push(lit),
+                Some(reason) => {
+                    for &q in &self.clauses[reason] {
+                        let v = q.var().index();
+                        if self.levels[v] > 0 && !seen[v] {
+                            seen[v] = true;
+                            marked.push(v);
+                        }
+                    }
+                }
+            }
+        }
+        for var in marked {
+            seen[var] = false;
+        }
+        self.seen = seen;
+        involved
+    }
+
+    /// Removes literals of `learnt` (other than the asserting one) whose
+    /// negation is implied by the rest of the clause. `seen` must mark exactly
+    /// the variables of `learnt` on entry; those marked on the way are added
+    /// to `marked`.
+    fn minimize(&self, learnt: &mut Vec<Lit>, seen: &mut [bool], marked: &mut Vec<usize>) {
+        match self.config.minimize {
+            Minimization::Off => {}
+            Minimization::Basic => {
+                let mut i = 1;
+                while i < learnt.len() {
+                    if self.is_locally_redundant(learnt[i], seen) {
+                        learnt.swap_remove(i);
+                    } else {
+                        i += 1;
+                    }
+                }
+            }
+            Minimization::Deep => {
+                let abstract_levels = learnt[1..]
+                    .iter()
+                    .fold(0u64, |acc, &lit| acc | self.abstract_level(lit));
+                let mut i = 1;
+                while i < learnt.len() {
+                    let lit = learnt[i];
+                    if self.reasons[lit.var().index()].is_some()
+                        && self.is_redundant(lit, abstract_levels, seen, marked)
+                    {
+                        learnt.swap_remove(i);
+                    } else {
+                        i += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    /// One resolution step: every other literal of the reason is already in the clause.
+    fn is_locally_redundant(&self, lit: Lit, seen: &[bool]) -> bool {
+        let var = lit.var().index();
+        let Some(reason) = self.reasons[var] else {
+            return false;
+        };
+        self.clauses[reason].iter().all(|q| {
+            let v = q.var().index();
+            v == var || seen[v] || self.levels[v] == 0
+        })
+    }
+
+    /// MiniSat-style recursive check that `lit` follows from the clause by
+    /// walking the implication graph backwards. Variables proven implied stay
+    /// marked in `seen`, so later queries can reuse them, and are added to
+    /// `proven`.
+    fn is_redundant(
+        &self,
+        lit: Lit,
+        abstract_levels: u64,
+        seen: &mut [bool],
+        proven: &mut Vec<usize>,
+    ) -> bool {
+        let mut stack = vec![lit];
+        let mut marked = Vec::new();
+
+        while let Some(p) = stack.pop() {
+            let p_var = p.var().index();
+            let reason = self.reasons[p_var].expect("redundancy check on a decision");
+            for &q in &self.clauses[reason] {
+                let v = q.var().index();
+                if v == p_var || seen[v] || self.levels[v] == 0 {
+                    continue;
+                }
+                if self.reasons[v].is_some() && self.abstract_level(q) & abstract_levels != 0 {
+                    seen[v] = true;
+                    marked.push(v);
+                    stack.push(q);
+                } else {
+                    for v in marked {
+                        seen[v] = false;
+                    }
+                    return false;
+                }
+            }
+        }
+        proven.extend(marked);
+        true
+    }
+
+    /// Cheap over-approximation of a set of decision levels as a 64-bit mask.
+    #[inline]
+    fn abstract_level(&self, lit: Lit) -> u64 {
+        1 << (self.levels[lit.var().index()] & 63)
+    }
+}
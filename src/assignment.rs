@@ -0,0 +1,80 @@
+use crate::{Lit, Var};
+
+/// Truth value of a literal under a partial assignment.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LBool {
+    False = 0,
+    True = 1,
+    Undef = 2,
+}
+
+impl From<LBool> for Option<bool> {
+    #[inline]
+    fn from(value: LBool) -> Self {
+        match value {
+            LBool::False => Some(false),
+            LBool::True => Some(true),
+            LBool::Undef => None,
+        }
+    }
+}
+
+/// The solver's partial assignment, kept as one byte per literal rather
+/// than per variable: assigning a variable writes both of its literals, so
+/// reading the value of any literal is a single load with no sign fix-up.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Assignment {
+    values: Vec<LBool>,
+}
+
+impl Assignment {
+    /// All of variables `1..=variable_count` unassigned.
+    pub(crate) fn new(variable_count: usize) -> Self {
+        Self {
+            values: vec![LBool::Undef; (variable_count + 1) * 2],
+        }
+    }
+
+    /// Adds unassigned variables up to `variable_count`.
+    pub(crate) fn grow(&mut self, variable_count: usize) {
+        self.values.resize((variable_count + 1) * 2, LBool::Undef);
+    }
+
+    /// Number of variables covered.
+    #[inline]
+    pub fn num_vars(&self) -> usize {
+        self.values.len() / 2 - 1
+    }
+
+    #[inline]
+    pub fn value(&self, lit: Lit) -> LBool {
+        self.values[lit.index()]
+    }
+
+    /// Value of `var`, or `None` while it is unassigned.
+    #[inline]
+    pub fn var_value(&self, var: Var) -> Option<bool> {
+        self.value(var.positive()).into()
+    }
+
+    #[inline]
+    pub fn is_assigned(&self, var: Var) -> bool {
+        self.value(var.positive()) != LBool::Undef
+    }
+
+    /// Makes `lit` true, bypassing the trail.
+    #[inline]
+    pub(crate) fn assign(&mut self, lit: Lit) {
+        self.values[lit.index()] = LBool::True;
+        self.values[(-lit).index()] = LBool::False;
+    }
+
+    #[inline]
+    pub(crate) fn unassign(&mut self, var: Var) {
+        self.values[var.positive().index()] = LBool::Undef;
+        self.values[var.negative().index()] = LBool::Undef;
+    }
+}
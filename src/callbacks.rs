@@ -0,0 +1,53 @@
+use crate::{Lit, Solver, SolverConfig, Stats};
+
+/// Called with each decision and the level it opens.
+type DecisionCallback = Box<dyn FnMut(Lit, usize) + Send>;
+/// Called with a falsified clause and the level it was found at, or with a
+/// learnt clause and its LBD.
+type ClauseCallback = Box<dyn FnMut(&[Lit], usize) + Send>;
+/// Called at each restart, free to change the configuration.
+type RestartCallback = Box<dyn FnMut(&Stats, &mut SolverConfig) + Send>;
+
+/// The callbacks registered through `Solver::on_decision` and the like.
+#[derive(Default)]
+pub(crate) struct Callbacks {
+    pub decision: Option<DecisionCallback>,
+    pub conflict: Option<ClauseCallback>,
+    pub learned_clause: Option<ClauseCallback>,
+    pub restart: Option<RestartCallback>,
+}
+
+/// Hooks into the CDCL search, for logging, stopping it on conditions of
+/// one's own (through an `InterruptHandle` moved into the callback) or
+/// tuning it as it goes. Registering a callback replaces the previous one of
+/// the same kind. The other engines call none of them.
+impl Solver {
+    /// Calls `callback` with every decision, assumptions included, and the
+    /// decision level it opens.
+    pub fn on_decision(&mut self, callback: impl FnMut(Lit, usize) + Send + 'static) {
+        self.callbacks.decision = Some(Box::new(callback));
+    }
+
+    /// Calls `callback` with every clause the search finds falsified, and
+    /// the decision level it is found at; a conflict at level 0 ends the
+    /// search with `Unsat`.
+    pub fn on_conflict(&mut self, callback: impl FnMut(&[Lit], usize) + Send + 'static) {
+        self.callbacks.conflict = Some(Box::new(callback));
+    }
+
+    /// Calls `callback` with every clause learnt from a conflict, asserting
+    /// literal first, and its LBD. Unlike `set_learn`, there is no limit on
+    /// the length.
+    pub fn on_learned_clause(&mut self, callback: impl FnMut(&[Lit], usize) + Send + 'static) {
+        self.callbacks.learned_clause = Some(Box::new(callback));
+    }
+
+    /// Calls `callback` at every restart, back at level 0, with the counters
+    /// so far and the configuration, which it may change. Changes to the
+    /// decay, the random frequencies and the budgets take effect right away;
+    /// the restart policy and the first reduction and rephasing intervals
+    /// are only read when `solve` is called.
+    pub fn on_restart(&mut self, callback: impl FnMut(&Stats, &mut SolverConfig) + Send + 'static) {
+        self.callbacks.restart = Some(Box::new(callback));
+    }
+}
@@ -0,0 +1,225 @@
+// The IPASIR interface of the incremental SAT competitions, for C and
+// every tool built on it. The functions are exported unmangled from the
+// `cdylib` when the `capi` feature is enabled; `include/ipasir.h` declares
+// them.
+//
+// A solver handle is a `Box<IpasirSolver>` turned into a raw pointer by
+// `ipasir_init`, and must not be used after `ipasir_release`. Nothing may
+// panic across the C boundary, which would abort the host: IPASIR has no
+// way to report errors, so bad literals are left out as documented below.
+
+use std::ffi::{c_char, c_int, c_void};
+
+use crate::{Lit, Model, SolveResult, Solver};
+
+/// A solver with the state IPASIR adds around it: the clause being added
+/// literal by literal, whether it got a literal that is none, and the
+/// outcome of the last `ipasir_solve`.
+struct IpasirSolver {
+    solver: Solver,
+    clause: Vec<Lit>,
+    invalid: bool,
+    model: Option<Model>,
+    failed: Vec<Lit>,
+}
+
+/// Data pointer handed back to a callback, moved to the solver.
+struct CallbackData(*mut c_void);
+
+// IPASIR makes the caller responsible for the data it registers
+unsafe impl Send for CallbackData {}
+
+const SIGNATURE: &[u8] = concat!("cnf-dpll-2wl-", env!("CARGO_PKG_VERSION"), "\0").as_bytes();
+
+/// The literal `lit` stands for, or `None` for 0 and `INT_MIN`.
+fn lit(lit: i32) -> Option<Lit> {
+    Lit::try_from(lit).ok()
+}
+
+/// # Safety
+///
+/// `solver` must come from `ipasir_init` and not have been released.
+unsafe fn handle<'a>(solver: *mut c_void) -> &'a mut IpasirSolver {
+    unsafe { &mut *solver.cast::<IpasirSolver>() }
+}
+
+/// Name and version of the solver, as a static C string.
+#[unsafe(no_mangle)]
+pub extern "C" fn ipasir_signature() -> *const c_char {
+    SIGNATURE.as_ptr().cast()
+}
+
+/// Creates a solver with no clauses, in the INPUT state.
+#[unsafe(no_mangle)]
+pub extern "C" fn ipasir_init() -> *mut c_void {
+    let solver = IpasirSolver {
+        solver: Solver::with_vars(0),
+        clause: Vec::new(),
+        invalid: false,
+        model: None,
+        failed: Vec::new(),
+    };
+    Box::into_raw(Box::new(solver)).cast()
+}
+
+/// Frees the solver.
+///
+/// # Safety
+///
+/// `solver` must come from `ipasir_init` and not have been released.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ipasir_release(solver: *mut c_void) {
+    drop(unsafe { Box::from_raw(solver.cast::<IpasirSolver>()) });
+}
+
+/// Adds a literal to the clause being built, or with `0` adds that clause
+/// to the formula for good. A clause given `INT_MIN`, which is no literal,
+/// is dropped whole.
+///
+/// # Safety
+///
+/// `solver` must come from `ipasir_init` and not have been released.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ipasir_add(solver: *mut c_void, lit_or_zero: i32) {
+    let ipasir = unsafe { handle(solver) };
+    ipasir.model = None;
+    if lit_or_zero == 0 {
+        let clause = std::mem::take(&mut ipasir.clause);
+        if !std::mem::take(&mut ipasir.invalid) {
+            // Literals were checked as they came, so this cannot fail
+            let _ = ipasir.solver.add_clause(&clause);
+        }
+    } else {
+        match lit(lit_or_zero) {
+            Some(lit) => ipasir.clause.push(lit),
+            None => ipasir.invalid = true,
+        }
+    }
+}
+
+/// Assumes `lit` true for the next `ipasir_solve` only. 0 and `INT_MIN`
+/// are ignored.
+///
+/// # Safety
+///
+/// `solver` must come from `ipasir_init` and not have been released.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ipasir_assume(solver: *mut c_void, lit: i32) {
+    let ipasir = unsafe { handle(solver) };
+    ipasir.model = None;
+    if let Some(lit) = self::lit(lit) {
+        ipasir.solver.assume(lit);
+    }
+}
+
+/// Solves the formula under the assumptions, which are then dropped.
+/// Returns 10 if it is satisfiable, 20 if not, and 0 if the search was
+/// stopped by the terminate callback.
+///
+/// # Safety
+///
+/// `solver` must come from `ipasir_init` and not have been released.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ipasir_solve(solver: *mut c_void) -> c_int {
+    let ipasir = unsafe { handle(solver) };
+    let result = ipasir.solver.solve();
+    ipasir.model = ipasir.solver.model();
+    ipasir.failed = ipasir.solver.failed_assumptions().to_vec();
+    match result {
+        SolveResult::Sat => 10,
+        SolveResult::Unsat => 20,
+        SolveResult::Unknown => 0,
+    }
+}
+
+/// After an `ipasir_solve` that returned 10: `lit` if it is true in the
+/// model, `-lit` if it is false, and 0 for a variable the solver has not
+/// seen, which can take either value. Also 0 without such an answer, or
+/// for 0 and `INT_MIN`.
+///
+/// # Safety
+///
+/// `solver` must come from `ipasir_init` and not have been released.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ipasir_val(solver: *mut c_void, lit: i32) -> i32 {
+    let ipasir = unsafe { handle(solver) };
+    let (Some(model), Some(l)) = (ipasir.model.as_ref(), self::lit(lit)) else {
+        return 0;
+    };
+    if l.var().index() > model.num_vars() {
+        return 0;
+    }
+    if model.lit_is_true(l) { lit } else { -lit }
+}
+
+/// After an `ipasir_solve` that returned 20: 1 if the assumption `lit` was
+/// needed to prove the formula unsatisfiable, 0 otherwise.
+///
+/// # Safety
+///
+/// `solver` must come from `ipasir_init` and not have been released.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ipasir_failed(solver: *mut c_void, lit: i32) -> c_int {
+    let ipasir = unsafe { handle(solver) };
+    self::lit(lit)
+        .is_some_and(|lit| ipasir.failed.contains(&lit))
+        .into()
+}
+
+/// Makes `ipasir_solve` call `terminate(data)` as it searches, and give up
+/// as soon as it returns a non-zero value. A null `terminate` removes it.
+///
+/// # Safety
+///
+/// `solver` must come from `ipasir_init` and not have been released, and
+/// `terminate` must be safe to call with `data` for as long as it is set.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ipasir_set_terminate(
+    solver: *mut c_void,
+    data: *mut c_void,
+    terminate: Option<extern "C" fn(data: *mut c_void) -> c_int>,
+) {
+    let ipasir = unsafe { handle(solver) };
+    match terminate {
+        Some(terminate) => {
+            let data = CallbackData(data);
+            ipasir.solver.set_terminate(move || {
+                let data = &data;
+                terminate(data.0) != 0
+            });
+        }
+        None => ipasir.solver.set_terminate(|| false),
+    }
+}
+
+/// Makes the solver call `learn(data, clause)` with every clause it learns
+/// of at most `max_length` literals, as a zero-terminated array valid
+/// during the call only. A null `learn` removes it.
+///
+/// # Safety
+///
+/// `solver` must come from `ipasir_init` and not have been released, and
+/// `learn` must be safe to call with `data` for as long as it is set.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ipasir_set_learn(
+    solver: *mut c_void,
+    data: *mut c_void,
+    max_length: c_int,
+    learn: Option<extern "C" fn(data: *mut c_void, clause: *mut i32)>,
+) {
+    let ipasir = unsafe { handle(solver) };
+    let Some(learn) = learn else {
+        ipasir.solver.set_learn(0, |_| {});
+        return;
+    };
+    let data = CallbackData(data);
+    let mut buffer: Vec<i32> = Vec::new();
+    let max_length = usize::try_from(max_length).unwrap_or(0);
+    ipasir.solver.set_learn(max_length, move |clause| {
+        let data = &data;
+        buffer.clear();
+        buffer.extend(clause.iter().map(|&lit| i32::from(lit)));
+        buffer.push(0);
+        learn(data.0, buffer.as_mut_ptr());
+    });
+}
@@ -0,0 +1,163 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::SolverError;
+use crate::pb::NativePb;
+use crate::trail::Trail;
+use crate::var_order::VarOrder;
+use crate::{
+    Assignment, Branching, ClauseArena, ClauseRef, Lit, Solver, SolverConfig, Stats, XorClause,
+};
+
+/// What a solver carries from one `solve` call to the next: its clauses,
+/// learnt ones included, and the copy of them as added, the values fixed
+/// at level 0 with their reasons, the XOR, at-most-one and native
+/// pseudo-Boolean constraints, the VSIDS activities and the saved phases. Watch lists are rebuilt from the clauses on loading.
+#[derive(Serialize, Deserialize)]
+struct Checkpoint {
+    clauses: ClauseArena,
+    originals: ClauseArena,
+    assignments: Assignment,
+    trail: Trail,
+    qhead: usize,
+    reasons: Vec<Option<ClauseRef>>,
+    xors: Vec<XorClause>,
+    xor_watches: Vec<Vec<usize>>,
+    pbs: Vec<NativePb>,
+    pb_watches: Vec<Vec<(usize, u64)>>,
+    amos: Vec<Vec<Lit>>,
+    amo_watches: Vec<Vec<usize>>,
+    order: VarOrder,
+    phases: Vec<bool>,
+    input_count: usize,
+    dropped_inputs: Vec<usize>,
+    xor_derived: Vec<usize>,
+    stats: Stats,
+    timeout: Option<Duration>,
+    preprocessed: bool,
+    scored: bool,
+    eliminated: Vec<bool>,
+    elim_stack: Vec<(Lit, Vec<Lit>)>,
+    frozen: Vec<bool>,
+    config: SolverConfig,
+}
+
+impl Checkpoint {
+    /// Moves the state out of `solver`, which must be at level 0, leaving
+    /// it empty until `restore` puts it back.
+    fn take(solver: &mut Solver) -> Self {
+        Self {
+            clauses: std::mem::take(&mut solver.clauses),
+            originals: std::mem::take(&mut solver.originals),
+            assignments: std::mem::replace(&mut solver.assignments, Assignment::new(0)),
+            trail: std::mem::take(&mut solver.trail),
+            qhead: solver.qhead,
+            reasons: std::mem::take(&mut solver.reasons),
+            xors: std::mem::take(&mut solver.xors),
+            xor_watches: std::mem::take(&mut solver.xor_watches),
+            pbs: std::mem::take(&mut solver.pbs),
+            pb_watches: std::mem::take(&mut solver.pb_watches),
+            amos: std::mem::take(&mut solver.amos),
+            amo_watches: std::mem::take(&mut solver.amo_watches),
+            order: std::mem::replace(&mut solver.order, VarOrder::new(Branching::Vsids, 0)),
+            phases: std::mem::take(&mut solver.phases),
+            input_count: solver.input_count,
+            dropped_inputs: std::mem::take(&mut solver.dropped_inputs),
+            xor_derived: std::mem::take(&mut solver.xor_derived),
+            stats: std::mem::take(&mut solver.stats),
+            timeout: solver.timeout,
+            preprocessed: solver.preprocessed,
+            scored: solver.scored,
+            eliminated: std::mem::take(&mut solver.eliminated),
+            elim_stack: std::mem::take(&mut solver.elim_stack),
+            frozen: std::mem::take(&mut solver.frozen),
+            config: solver.config.clone(),
+        }
+    }
+
+    fn restore(self, solver: &mut Solver) {
+        solver.clauses = self.clauses;
+        solver.originals = self.originals;
+        solver.assignments = self.assignments;
+        solver.trail = self.trail;
+        solver.qhead = self.qhead;
+        solver.reasons = self.reasons;
+        solver.xors = self.xors;
+        solver.xor_watches = self.xor_watches;
+        solver.pbs = self.pbs;
+        solver.pb_watches = self.pb_watches;
+        solver.amos = self.amos;
+        solver.amo_watches = self.amo_watches;
+        solver.order = self.order;
+        solver.phases = self.phases;
+        solver.input_count = self.input_count;
+        solver.dropped_inputs = self.dropped_inputs;
+        solver.xor_derived = self.xor_derived;
+        solver.stats = self.stats;
+        solver.timeout = self.timeout;
+        solver.preprocessed = self.preprocessed;
+        solver.scored = self.scored;
+        solver.eliminated = self.eliminated;
+        solver.elim_stack = self.elim_stack;
+        solver.frozen = self.frozen;
+        solver.config = SolverConfig {
+            proof_path: solver.config.proof_path.take(),
+            trace_path: solver.config.trace_path.take(),
+            ..self.config
+        };
+    }
+
+    /// Whether the per-variable tables agree on the number of variables,
+    /// so that a damaged or hand-edited file is refused rather than
+    /// panicking in the middle of a search.
+    fn is_consistent(&self) -> bool {
+        let vars = self.assignments.num_vars() + 1;
+        self.reasons.len() == vars
+            && self.xor_watches.len() == vars
+            && self.pb_watches.len() == vars * 2
+            && self.amo_watches.len() == vars * 2
+            && self.phases.len() == vars
+            && self.eliminated.len() == vars
+            && self.frozen.len() == vars
+            && self.qhead <= self.trail.len()
+    }
+}
+
+impl Solver {
+    /// Writes the state of the solver to `path` as JSON, so that
+    /// `Solver::resume` can carry on from it later, in another process or
+    /// on another machine. The solver is reset to decision level 0 first,
+    /// as between two `solve` calls. The time limit and configuration are
+    /// saved too, but not the proof or trace output, callbacks or interrupt handle.
+    pub fn save(&mut self, path: &str) -> io::Result<()> {
+        self.backtrack_to(0);
+        let checkpoint = Checkpoint::take(self);
+        let result = File::create(path).and_then(|file| {
+            let mut writer = BufWriter::new(file);
+            serde_json::to_writer(&mut writer, &checkpoint)?;
+            writer.flush()
+        });
+        checkpoint.restore(self);
+        result
+    }
+
+    /// Loads a solver saved by `Solver::save`. Learnt clauses, VSIDS
+    /// activities, saved phases and statistics are as they were, so the
+    /// next `solve` call continues the search where the saved solver would
+    /// have.
+    pub fn resume(path: &str) -> Result<Self, SolverError> {
+        let reader = BufReader::new(File::open(path)?);
+        let checkpoint: Checkpoint = serde_json::from_reader(reader).map_err(io::Error::from)?;
+        if !checkpoint.is_consistent() {
+            let message = "inconsistent solver checkpoint";
+            return Err(io::Error::new(io::ErrorKind::InvalidData, message).into());
+        }
+        let mut solver = Solver::with_vars(checkpoint.assignments.num_vars());
+        checkpoint.restore(&mut solver);
+        solver.rewatch_all();
+        Ok(solver)
+    }
+}
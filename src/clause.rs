@@ -0,0 +1,324 @@
+use std::ops::{Index, IndexMut};
+
+use crate::Lit;
+
+// Every clause is a header followed by its literals. The header holds the
+// current length, the number of literal slots allocated (a clause can only
+// shrink in place), the flags, with the LBD in the bits above them, then
+// the activity (the bits of an `f32`), the number of conflict analyses
+// that used the clause, and the low and high halves of its ID.
+const HEADER_LEN: usize = 7;
+const LEN: usize = 0;
+const CAPACITY: usize = 1;
+const META: usize = 2;
+const ACTIVITY: usize = 3;
+const VISITS: usize = 4;
+const ID_LOW: usize = 5;
+const ID_HIGH: usize = 6;
+
+const LEARNT: u32 = 1;
+const VIVIFIED: u32 = 2;
+const DELETED: u32 = 4;
+const LAZY_REASON: u32 = 8;
+const LBD_SHIFT: u32 = 4;
+
+/// Handle to a clause in a `ClauseArena`: the offset of its header. Handles
+/// stay valid until the arena is compacted by `collect_garbage`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClauseRef(u32);
+
+/// Clause database stored in one flat buffer of `u32` words instead of one
+/// heap allocation per clause, so that walking a clause during propagation
+/// stays within a few cache lines. Indexing with a `ClauseRef` yields the
+/// clause's literals; in clauses of three or more literals the first two
+/// are the watched ones.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClauseArena {
+    data: Vec<u32>,
+    len: usize,
+    wasted: usize,
+    // IDs given out so far, the last one included
+    ids: u64,
+}
+
+impl ClauseArena {
+    /// Number of live clauses.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Appends a clause and returns its handle.
+    pub fn push(&mut self, literals: &[Lit], learnt: bool, lbd: usize) -> ClauseRef {
+        let cref =
+            ClauseRef(u32::try_from(self.data.len()).expect("clause arena exceeds 2^32 words"));
+        let flags = if learnt { LEARNT } else { 0 };
+        let id = self.take_id();
+        self.data.extend([
+            literals.len() as u32,
+            literals.len() as u32,
+            flags | (lbd as u32) << LBD_SHIFT,
+            0f32.to_bits(),
+            0,
+            id as u32,
+            (id >> 32) as u32,
+        ]);
+        self.data
+            .extend(literals.iter().map(|lit| lit.index() as u32));
+        self.len += 1;
+        cref
+    }
+
+    /// Appends a clause standing in for an XOR or native pseudo-Boolean
+    /// constraint as the reason for a propagation, or a conflict. It counts
+    /// as learnt and is not watched.
+    pub(crate) fn push_lazy_reason(&mut self, literals: &[Lit]) -> ClauseRef {
+        let cref = self.push(literals, true, 0);
+        *self.header_mut(cref, META) |= LAZY_REASON;
+        cref
+    }
+
+    /// Handles of the live clauses, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = ClauseRef> + '_ {
+        let mut offset = 0;
+        std::iter::from_fn(move || {
+            while offset < self.data.len() {
+                let cref = ClauseRef(offset as u32);
+                offset += HEADER_LEN + self.data[offset + CAPACITY] as usize;
+                if !self.is_deleted(cref) {
+                    return Some(cref);
+                }
+            }
+            None
+        })
+    }
+
+    #[inline]
+    fn header(&self, cref: ClauseRef, word: usize) -> u32 {
+        self.data[cref.0 as usize + word]
+    }
+
+    #[inline]
+    fn header_mut(&mut self, cref: ClauseRef, word: usize) -> &mut u32 {
+        &mut self.data[cref.0 as usize + word]
+    }
+
+    #[inline]
+    pub fn is_learnt(&self, cref: ClauseRef) -> bool {
+        self.header(cref, META) & LEARNT != 0
+    }
+
+    /// Whether the clause was made by `push_lazy_reason`.
+    #[inline]
+    pub fn is_lazy_reason(&self, cref: ClauseRef) -> bool {
+        self.header(cref, META) & LAZY_REASON != 0
+    }
+
+    /// Whether vivification already tried to shorten the clause.
+    #[inline]
+    pub fn is_vivified(&self, cref: ClauseRef) -> bool {
+        self.header(cref, META) & VIVIFIED != 0
+    }
+
+    pub(crate) fn set_vivified(&mut self, cref: ClauseRef) {
+        *self.header_mut(cref, META) |= VIVIFIED;
+    }
+
+    #[inline]
+    pub fn is_deleted(&self, cref: ClauseRef) -> bool {
+        self.header(cref, META) & DELETED != 0
+    }
+
+    /// Literal block distance at the time the clause was learnt (0 for input clauses).
+    #[inline]
+    pub fn lbd(&self, cref: ClauseRef) -> usize {
+        (self.header(cref, META) >> LBD_SHIFT) as usize
+    }
+
+    /// Activity of the clause: bumped whenever conflict analysis resolves
+    /// on it, by an amount that grows after each conflict so that older
+    /// bumps count for less.
+    #[inline]
+    pub fn activity(&self, cref: ClauseRef) -> f32 {
+        f32::from_bits(self.header(cref, ACTIVITY))
+    }
+
+    /// Number of conflict analyses that resolved on the clause.
+    #[inline]
+    pub fn visits(&self, cref: ClauseRef) -> u32 {
+        self.header(cref, VISITS)
+    }
+
+    /// ID of the clause: clauses are numbered from 1 in the order they are
+    /// pushed, and a clause shortened by `shrink` gets a new number, as
+    /// LRAT proofs expect.
+    #[inline]
+    pub fn id(&self, cref: ClauseRef) -> u64 {
+        u64::from(self.header(cref, ID_HIGH)) << 32 | u64::from(self.header(cref, ID_LOW))
+    }
+
+    /// ID the next clause will get.
+    #[inline]
+    pub fn next_id(&self) -> u64 {
+        self.ids + 1
+    }
+
+    /// Gives out an ID without pushing a clause, for one kept outside the
+    /// arena (a clause only written to the proof, or a dropped tautology,
+    /// whose number later input clauses must not take).
+    pub(crate) fn take_id(&mut self) -> u64 {
+        self.ids += 1;
+        self.ids
+    }
+
+    /// Adds `increment` to the activity of the clause and counts a visit.
+    /// Returns the new activity.
+    pub(crate) fn bump(&mut self, cref: ClauseRef, increment: f32) -> f32 {
+        let activity = self.activity(cref) + increment;
+        *self.header_mut(cref, ACTIVITY) = activity.to_bits();
+        let visits = self.header_mut(cref, VISITS);
+        *visits = visits.saturating_add(1);
+        activity
+    }
+
+    /// Multiplies the activity of every clause by `factor`.
+    pub(crate) fn scale_activities(&mut self, factor: f32) {
+        let crefs: Vec<ClauseRef> = self.iter().collect();
+        for cref in crefs {
+            let activity = self.activity(cref) * factor;
+            *self.header_mut(cref, ACTIVITY) = activity.to_bits();
+        }
+    }
+
+    /// Marks the clause as deleted. Its space is reclaimed, and stale handles
+    /// to it are dropped, by the next `collect_garbage`.
+    pub(crate) fn delete(&mut self, cref: ClauseRef) {
+        debug_assert!(!self.is_deleted(cref));
+        *self.header_mut(cref, META) |= DELETED;
+        self.wasted += HEADER_LEN + self.header(cref, CAPACITY) as usize;
+        self.len -= 1;
+    }
+
+    /// Overwrites the literals of `cref` with `literals`, which must not be
+    /// longer than the clause. The LBD is capped at the new length, and the
+    /// clause gets a new ID.
+    pub(crate) fn shrink(&mut self, cref: ClauseRef, literals: &[Lit]) {
+        let old_len = self.header(cref, LEN) as usize;
+        assert!(
+            literals.len() <= old_len,
+            "clauses can only shrink in place"
+        );
+        self[cref][..literals.len()].copy_from_slice(literals);
+        *self.header_mut(cref, LEN) = literals.len() as u32;
+        self.wasted += old_len - literals.len();
+        let lbd = self.lbd(cref).min(literals.len()) as u32;
+        let meta = self.header_mut(cref, META);
+        *meta = *meta & ((1 << LBD_SHIFT) - 1) | lbd << LBD_SHIFT;
+        let id = self.take_id();
+        *self.header_mut(cref, ID_LOW) = id as u32;
+        *self.header_mut(cref, ID_HIGH) = (id >> 32) as u32;
+    }
+
+    /// Words held by deleted clauses and literals dropped by `shrink`.
+    #[inline]
+    pub fn wasted(&self) -> usize {
+        self.wasted
+    }
+
+    /// Words taken by the arena, wasted ones included.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Bytes allocated for the arena.
+    #[inline]
+    pub fn memory(&self) -> usize {
+        self.data.capacity() * size_of::<u32>()
+    }
+
+    /// Gives back the memory allocated beyond the words in use.
+    pub(crate) fn shrink_to_fit(&mut self) {
+        self.data.shrink_to_fit();
+    }
+
+    /// Moves the live clauses into a fresh buffer with no gaps. Every handle
+    /// held elsewhere must then be translated through the returned
+    /// `Relocation`.
+    pub(crate) fn collect_garbage(&mut self) -> Relocation {
+        let mut compacted = Self {
+            data: Vec::with_capacity(self.data.len() - self.wasted),
+            len: 0,
+            wasted: 0,
+            ids: self.ids,
+        };
+        let mut old = std::mem::take(&mut self.data);
+        let mut offset = 0;
+        while offset < old.len() {
+            let capacity = old[offset + CAPACITY] as usize;
+            if old[offset + META] & DELETED == 0 {
+                let len = old[offset + LEN] as usize;
+                let new = compacted.data.len() as u32;
+                compacted.data.extend([
+                    len as u32,
+                    len as u32,
+                    old[offset + META],
+                    old[offset + ACTIVITY],
+                    old[offset + VISITS],
+                    old[offset + ID_LOW],
+                    old[offset + ID_HIGH],
+                ]);
+                compacted
+                    .data
+                    .extend_from_slice(&old[offset + HEADER_LEN..offset + HEADER_LEN + len]);
+                compacted.len += 1;
+                // The old header now forwards to the new position
+                old[offset + LEN] = new;
+            }
+            offset += HEADER_LEN + capacity;
+        }
+        *self = compacted;
+        Relocation { old }
+    }
+}
+
+impl Index<ClauseRef> for ClauseArena {
+    type Output = [Lit];
+
+    #[inline]
+    fn index(&self, cref: ClauseRef) -> &[Lit] {
+        let start = cref.0 as usize + HEADER_LEN;
+        let words = &self.data[start..start + self.header(cref, LEN) as usize];
+        // SAFETY: `Lit` is a `repr(transparent)` wrapper around `u32`, and
+        // the words after a header are literals written by `push` or `shrink`
+        unsafe { &*(words as *const [u32] as *const [Lit]) }
+    }
+}
+
+impl IndexMut<ClauseRef> for ClauseArena {
+    #[inline]
+    fn index_mut(&mut self, cref: ClauseRef) -> &mut [Lit] {
+        let start = cref.0 as usize + HEADER_LEN;
+        let len = self.header(cref, LEN) as usize;
+        let words = &mut self.data[start..start + len];
+        // SAFETY: as in `index`
+        unsafe { &mut *(words as *mut [u32] as *mut [Lit]) }
+    }
+}
+
+/// Where `ClauseArena::collect_garbage` moved each clause: the old buffer,
+/// whose headers now hold the new offsets.
+pub(crate) struct Relocation {
+    old: Vec<u32>,
+}
+
+impl Relocation {
+    /// New handle of the clause formerly at `cref`, or `None` if it was deleted.
+    #[inline]
+    pub fn get(&self, cref: ClauseRef) -> Option<ClauseRef> {
+        let offset = cref.0 as usize;
+        (self.old[offset + META] & DELETED == 0).then(|| ClauseRef(self.old[offset + LEN]))
+    }
+}
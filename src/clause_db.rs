@@ -0,0 +1,103 @@
+use crate::{ClauseRef, Lit, Solver};
+
+/// Factor by which clause activities decay after each conflict, as in MiniSat.
+const CLAUSE_DECAY: f32 = 0.999;
+
+/// Activity above which every clause activity is scaled down.
+const RESCALE_LIMIT: f32 = 1e20;
+
+/// What the solver knows of one of its clauses, from `Solver::clause_info`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClauseInfo<'a> {
+    /// Handle of the clause, valid until the solver next compacts its
+    /// clause database (any `solve` call may).
+    pub cref: ClauseRef,
+    /// Number of the clause in an LRAT proof: input clauses are numbered
+    /// from 1 in the order they were added, and every clause made or
+    /// shortened later gets the next number.
+    pub id: u64,
+    pub literals: &'a [Lit],
+    /// Learnt during a search, rather than added as input.
+    pub learnt: bool,
+    /// Literal block distance when the clause was learnt (0 for input clauses).
+    pub lbd: usize,
+    /// Bumped whenever conflict analysis resolves on the clause, by an
+    /// amount growing with every conflict, so that recent uses weigh most.
+    pub activity: f32,
+    /// Number of conflict analyses that resolved on the clause.
+    pub visits: u32,
+}
+
+impl Solver {
+    /// The clauses of the formula, input and learnt ones alike, oldest
+    /// first. Satisfied clauses and false literals are still there until
+    /// the solver removes them. XOR and native pseudo-Boolean constraints
+    /// are not clauses and have their own accessors.
+    pub fn clauses(&self) -> impl Iterator<Item = &[Lit]> + '_ {
+        self.clause_refs().map(|cref| &self.clauses[cref])
+    }
+
+    /// The literals of clause `cref`, such as a reason from `Solver::reason`.
+    pub fn clause(&self, cref: ClauseRef) -> &[Lit] {
+        &self.clauses[cref]
+    }
+
+    /// What is known of clause `cref`.
+    pub fn clause_info(&self, cref: ClauseRef) -> ClauseInfo<'_> {
+        ClauseInfo {
+            cref,
+            id: self.clauses.id(cref),
+            literals: &self.clauses[cref],
+            learnt: self.clauses.is_learnt(cref),
+            lbd: self.clauses.lbd(cref),
+            activity: self.clauses.activity(cref),
+            visits: self.clauses.visits(cref),
+        }
+    }
+
+    /// `clause_info` of every clause, in the order of `clauses`.
+    pub fn clause_infos(&self) -> impl Iterator<Item = ClauseInfo<'_>> + '_ {
+        self.clause_refs().map(|cref| self.clause_info(cref))
+    }
+
+    /// Number of clauses the solver holds, input and learnt ones alike.
+    pub fn num_clauses(&self) -> usize {
+        self.clause_refs().count()
+    }
+
+    /// Number of clauses added as input and still in the formula. Variable
+    /// elimination and subsumption take some away.
+    pub fn num_original_clauses(&self) -> usize {
+        self.clause_refs()
+            .filter(|&cref| !self.clauses.is_learnt(cref))
+            .count()
+    }
+
+    /// Number of learnt clauses the solver currently keeps.
+    pub fn num_learnt_clauses(&self) -> usize {
+        self.clause_refs()
+            .filter(|&cref| self.clauses.is_learnt(cref))
+            .count()
+    }
+
+    /// Handles of the clauses, leaving out those standing in for XOR and
+    /// pseudo-Boolean constraints.
+    fn clause_refs(&self) -> impl Iterator<Item = ClauseRef> + '_ {
+        self.clauses
+            .iter()
+            .filter(|&cref| !self.clauses.is_lazy_reason(cref))
+    }
+
+    /// Bumps the activity of clause `cref`, used by conflict analysis.
+    pub(crate) fn bump_clause(&mut self, cref: ClauseRef) {
+        if self.clauses.bump(cref, self.clause_increment) > RESCALE_LIMIT {
+            self.clauses.scale_activities(1.0 / RESCALE_LIMIT);
+            self.clause_increment /= RESCALE_LIMIT;
+        }
+    }
+
+    /// Decays every clause activity by raising the increment of later bumps.
+    pub(crate) fn decay_clause_activities(&mut self) {
+        self.clause_increment /= CLAUSE_DECAY;
+    }
+}
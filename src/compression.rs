@@ -0,0 +1,55 @@
+use std::io::{self, BufRead};
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const XZ_MAGIC: &[u8] = &[0xfd, b'7', b'z', b'X', b'Z', 0x00];
+
+/// Whether `head`, the start of an input, is a gzip or xz header.
+pub(crate) fn is_compressed(head: &[u8]) -> bool {
+    head.starts_with(GZIP_MAGIC) || head.starts_with(XZ_MAGIC)
+}
+
+/// Wraps `reader` in a decompressor if its first bytes are a gzip or xz
+/// header. Each format needs its cargo feature (`gzip`, `xz`); without it a
+/// compressed input is reported as unsupported rather than parsed as garbage.
+pub(crate) fn decompress<'a>(mut reader: impl BufRead + 'a) -> io::Result<Box<dyn BufRead + 'a>> {
+    let head = reader.fill_buf()?;
+    if head.starts_with(GZIP_MAGIC) {
+        return gzip(reader);
+    }
+    if head.starts_with(XZ_MAGIC) {
+        return xz(reader);
+    }
+    Ok(Box::new(reader))
+}
+
+#[cfg(feature = "gzip")]
+fn gzip<'a>(reader: impl BufRead + 'a) -> io::Result<Box<dyn BufRead + 'a>> {
+    Ok(Box::new(io::BufReader::new(
+        flate2::bufread::MultiGzDecoder::new(reader),
+    )))
+}
+
+#[cfg(not(feature = "gzip"))]
+fn gzip<'a>(_reader: impl BufRead + 'a) -> io::Result<Box<dyn BufRead + 'a>> {
+    Err(unsupported("gzip"))
+}
+
+#[cfg(feature = "xz")]
+fn xz<'a>(reader: impl BufRead + 'a) -> io::Result<Box<dyn BufRead + 'a>> {
+    Ok(Box::new(io::BufReader::new(
+        xz2::bufread::XzDecoder::new_multi_decoder(reader),
+    )))
+}
+
+#[cfg(not(feature = "xz"))]
+fn xz<'a>(_reader: impl BufRead + 'a) -> io::Result<Box<dyn BufRead + 'a>> {
+    Err(unsupported("xz"))
+}
+
+#[cfg(not(all(feature = "gzip", feature = "xz")))]
+fn unsupported(format: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("{format}-compressed input requires the `{format}` feature"),
+    )
+}
@@ -0,0 +1,467 @@
+use std::path::PathBuf;
+
+use crate::InvalidConfig;
+use crate::pb::PbEncoding;
+use crate::proof::ProofFormat;
+use crate::restart::RestartStrategy;
+
+/// How aggressively learnt clauses are shrunk before being stored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Minimization {
+    /// Keep the first-UIP clause as derived.
+    Off,
+    /// Drop literals whose reason clause is entirely contained in the learnt clause.
+    Basic,
+    /// Recursively drop literals implied by the rest of the clause (MiniSat).
+    #[default]
+    Deep,
+}
+
+/// Decision heuristic of the CDCL search.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Branching {
+    /// Variable state independent decaying sum: decides the variable with
+    /// the highest activity, which conflicts bump and `var_decay` decays.
+    #[default]
+    Vsids,
+    /// Variable move-to-front: the variables of each conflict move to the
+    /// front of a queue, and decisions take the unassigned variable closest
+    /// to the front. Its memory is shorter than that of VSIDS; which of the
+    /// two does better depends on the instance.
+    Vmtf,
+    /// Learning-rate branching: decides the variable with the highest
+    /// exponential moving average of its learning rate, the share of the
+    /// conflicts it took part in while it was assigned.
+    Lrb,
+}
+
+/// How a rephasing resets the saved phases.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rephase {
+    /// The phases the first search started from (true, or the Jeroslow–Wang ones).
+    Original,
+    /// The opposite of `Original`.
+    Inverted,
+    /// The values of the longest trail met without a conflict since the
+    /// last rephasing.
+    Best,
+    /// Random values drawn from `SolverConfig::seed`.
+    Random,
+    /// The best assignment found by local search from the current phases.
+    Walk,
+}
+
+/// Search procedure run by `Solver::solve`. All work on the same clauses,
+/// assignment and propagation, so parsing, preprocessing and models are
+/// shared.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Engine {
+    /// Conflict-driven clause learning with VSIDS, restarts and learnt
+    /// clause database reduction.
+    #[default]
+    Cdcl,
+    /// DPLL with chronological backtracking, no learning, and branching
+    /// chosen by lookahead: every candidate literal is propagated on trial,
+    /// failed ones are fixed to false (also two levels deep, "double
+    /// lookahead"), and the variable whose two literals together imply the
+    /// most is branched on. Strong on small hard random and crafted
+    /// instances, and no match for CDCL on large structured ones.
+    Lookahead,
+    /// Stochastic local search (ProbSAT) from the saved phases: repeatedly
+    /// picks a falsified clause and flips one of its variables, preferring
+    /// those whose flip falsifies few other clauses. Often the fastest way
+    /// to a model of a large satisfiable random formula, but incomplete: it
+    /// answers `Unsat` only when the assumptions conflict by propagation
+    /// alone, and otherwise runs until a model, a budget or the time limit.
+    WalkSat,
+}
+
+/// Tunable parameters of the search.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SolverConfig {
+    /// Search procedure.
+    pub engine: Engine,
+    /// Decision heuristic of the CDCL engine.
+    pub branching: Branching,
+    /// Before the first search, give every variable an initial branching
+    /// score and phase from the Jeroslow–Wang weights of its literals,
+    /// rather than deciding variables in index order and true first.
+    pub jeroslow_wang: bool,
+    /// Factor applied to every variable activity after each conflict (VSIDS).
+    /// Values closer to 1 give the heuristic a longer memory.
+    pub var_decay: f64,
+    /// Restart schedule. Restarts keep learnt clauses and saved phases.
+    pub restart: RestartStrategy,
+    /// Conflicts between two reductions of the learnt clause database. The
+    /// interval grows by `reduce_increment` after every reduction.
+    pub reduce_interval: u64,
+    pub reduce_increment: u64,
+    /// Learnt clauses with an LBD at or below this value ("glue" clauses) are never deleted.
+    pub glue_lbd: usize,
+    /// Learnt clause minimization.
+    pub minimize: Minimization,
+    /// When set, a DRAT or LRAT proof of every UNSAT answer is written to this file.
+    /// Checkpoints leave it out, since the proof cannot be carried on.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub proof_path: Option<PathBuf>,
+    /// Encoding of the proof written to `proof_path`.
+    pub proof_format: ProofFormat,
+    /// Keep every clause derived during the search in memory with the
+    /// clauses it follows from, so that `Solver::resolution_proof` can
+    /// rebuild a resolution refutation after an UNSAT answer. Nothing is
+    /// ever forgotten, so this is meant for small instances.
+    pub resolution_proof: bool,
+    /// When set, the decisions, propagations, conflicts, learnt clauses,
+    /// backjumps and restarts of the CDCL search are written to this file
+    /// as JSON lines. Left out of checkpoints like `proof_path`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub trace_path: Option<PathBuf>,
+    /// Substitute literals that are equivalent through binary clauses before
+    /// the first search.
+    pub equivalences: bool,
+    /// Run subsumption and self-subsuming resolution on the input clauses
+    /// before the first search.
+    pub subsumption: bool,
+    /// Run bounded variable elimination on the input clauses before the first
+    /// search.
+    pub elimination: bool,
+    /// How many more clauses than it removes the elimination of a variable
+    /// may add.
+    pub elim_growth: usize,
+    /// Before the first search, and before the other passes, look for
+    /// symmetries of the input clauses and add lex-leader clauses that
+    /// break them, over new variables. Only one model of each class of
+    /// symmetric ones is left, which keeps satisfiability but not the
+    /// models: enumeration and counting see fewer. The symmetries leave
+    /// frozen variables and the assumptions of the first call in place,
+    /// but clauses added later, or later assumptions on other variables,
+    /// may make answers wrong. Skipped when a proof is written or recorded.
+    pub symmetry_breaking: bool,
+    /// Before the first search, after subsumption, replace the binary input
+    /// clauses that pairwise forbid three or more literals from being true
+    /// together by one at-most-one constraint over those literals,
+    /// propagated natively. Skipped when LRAT hints are needed.
+    pub at_most_one: bool,
+    /// Run failed literal probing at level 0 before the first search.
+    pub probing: bool,
+    /// Vivify learnt clauses after every restart.
+    pub vivification: bool,
+    /// Propagations one round of vivification may spend.
+    pub vivify_effort: u64,
+    /// Now and then, at a restart, reset the saved phases (rephasing), in
+    /// turn as each entry of `rephase_schedule` says. Going back to the
+    /// best trail, or to an assignment found by local search, steers the
+    /// search towards assignments that satisfy most clauses, while the
+    /// other entries make it explore elsewhere.
+    pub rephasing: bool,
+    /// The kinds of rephasing that take turns, cycling.
+    pub rephase_schedule: Vec<Rephase>,
+    /// Conflicts before the first rephasing. Each later one waits that
+    /// many conflicts longer than the one before.
+    pub rephase_interval: u64,
+    /// Flips a `Rephase::Walk` may spend on local search.
+    pub rephase_flips: u64,
+    /// Propagate the XOR clauses together by Gauss-Jordan elimination, on
+    /// top of watching them one by one. The matrix takes a bit per XOR
+    /// clause and variable, and a copy of it is kept per decision level.
+    pub gauss: bool,
+    /// Clauses that `Solver::add_pb_constraint` turns pseudo-Boolean
+    /// constraints into.
+    pub pb_encoding: PbEncoding,
+    /// Conflicts allowed per `solve` call before it gives up with `Unknown`.
+    /// Budgets are checked once propagation settles, so they may be overshot
+    /// slightly.
+    pub conflict_limit: Option<u64>,
+    /// Propagated literals allowed per `solve` call before it gives up with `Unknown`.
+    pub propagation_limit: Option<u64>,
+    /// Bytes of memory, as measured by `Solver::memory_usage`, the CDCL
+    /// search may use. Past it, learnt clauses are deleted until it fits
+    /// again, and if deleting all that may be deleted is not enough, `solve`
+    /// gives up with `Unknown`.
+    pub memory_limit: Option<usize>,
+    /// Probability that a CDCL decision goes to a random unassigned
+    /// variable rather than the one the branching heuristic picks.
+    pub random_var_freq: f64,
+    /// Probability that a CDCL decision takes a random value rather than
+    /// the saved phase.
+    pub random_phase_freq: f64,
+    /// Seed of the pseudo-random choices, such as random decisions or the
+    /// hash constraints of `Solver::approx_count`. Runs with the same seed
+    /// make the same choices.
+    pub seed: u64,
+    /// Progress lines the CDCL search prints as `c` comments on standard
+    /// output: none at 0, the size of the formula and a line every so many
+    /// conflicts at 1, and also a line at every restart and reduction of
+    /// the learnt clauses at 2.
+    pub verbosity: u32,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        Self {
+            engine: Engine::default(),
+            branching: Branching::default(),
+            jeroslow_wang: true,
+            var_decay: 0.95,
+            restart: RestartStrategy::default(),
+            reduce_interval: 2000,
+            reduce_increment: 300,
+            glue_lbd: 2,
+            minimize: Minimization::default(),
+            proof_path: None,
+            proof_format: ProofFormat::default(),
+            resolution_proof: false,
+            trace_path: None,
+            equivalences: false,
+            subsumption: false,
+            elimination: false,
+            elim_growth: 0,
+            symmetry_breaking: false,
+            at_most_one: false,
+            probing: false,
+            vivification: false,
+            vivify_effort: 20_000,
+            rephasing: false,
+            rephase_schedule: vec![
+                Rephase::Best,
+                Rephase::Walk,
+                Rephase::Original,
+                Rephase::Best,
+                Rephase::Walk,
+                Rephase::Inverted,
+                Rephase::Best,
+                Rephase::Walk,
+                Rephase::Random,
+            ],
+            rephase_interval: 1000,
+            rephase_flips: 100_000,
+            gauss: false,
+            pb_encoding: PbEncoding::default(),
+            conflict_limit: None,
+            propagation_limit: None,
+            memory_limit: None,
+            random_var_freq: 0.0,
+            random_phase_freq: 0.0,
+            seed: 0,
+            verbosity: 0,
+        }
+    }
+}
+
+/// Setters that chain, so that a configuration can be written as one
+/// expression ending in `build`, which checks it. Each sets the field of
+/// the same name unless said otherwise.
+impl SolverConfig {
+    pub fn engine(mut self, engine: Engine) -> Self {
+        self.engine = engine;
+        self
+    }
+
+    pub fn branching(mut self, branching: Branching) -> Self {
+        self.branching = branching;
+        self
+    }
+
+    pub fn jeroslow_wang(mut self, enabled: bool) -> Self {
+        self.jeroslow_wang = enabled;
+        self
+    }
+
+    pub fn var_decay(mut self, decay: f64) -> Self {
+        self.var_decay = decay;
+        self
+    }
+
+    /// Sets `restart`.
+    pub fn restarts(mut self, strategy: RestartStrategy) -> Self {
+        self.restart = strategy;
+        self
+    }
+
+    /// Sets `reduce_interval` and `reduce_increment`.
+    pub fn reduce(mut self, interval: u64, increment: u64) -> Self {
+        self.reduce_interval = interval;
+        self.reduce_increment = increment;
+        self
+    }
+
+    pub fn glue_lbd(mut self, lbd: usize) -> Self {
+        self.glue_lbd = lbd;
+        self
+    }
+
+    pub fn minimize(mut self, minimization: Minimization) -> Self {
+        self.minimize = minimization;
+        self
+    }
+
+    /// Sets `proof_path` and `proof_format`.
+    pub fn proof(mut self, path: impl Into<PathBuf>, format: ProofFormat) -> Self {
+        self.proof_path = Some(path.into());
+        self.proof_format = format;
+        self
+    }
+
+    pub fn resolution_proof(mut self, enabled: bool) -> Self {
+        self.resolution_proof = enabled;
+        self
+    }
+
+    /// Sets `trace_path`.
+    pub fn trace(mut self, path: impl Into<PathBuf>) -> Self {
+        self.trace_path = Some(path.into());
+        self
+    }
+
+    pub fn equivalences(mut self, enabled: bool) -> Self {
+        self.equivalences = enabled;
+        self
+    }
+
+    pub fn subsumption(mut self, enabled: bool) -> Self {
+        self.subsumption = enabled;
+        self
+    }
+
+    pub fn elimination(mut self, enabled: bool) -> Self {
+        self.elimination = enabled;
+        self
+    }
+
+    pub fn elim_growth(mut self, growth: usize) -> Self {
+        self.elim_growth = growth;
+        self
+    }
+
+    pub fn symmetry_breaking(mut self, enabled: bool) -> Self {
+        self.symmetry_breaking = enabled;
+        self
+    }
+
+    pub fn at_most_one(mut self, enabled: bool) -> Self {
+        self.at_most_one = enabled;
+        self
+    }
+
+    pub fn probing(mut self, enabled: bool) -> Self {
+        self.probing = enabled;
+        self
+    }
+
+    pub fn vivification(mut self, enabled: bool) -> Self {
+        self.vivification = enabled;
+        self
+    }
+
+    pub fn vivify_effort(mut self, propagations: u64) -> Self {
+        self.vivify_effort = propagations;
+        self
+    }
+
+    pub fn rephasing(mut self, enabled: bool) -> Self {
+        self.rephasing = enabled;
+        self
+    }
+
+    pub fn rephase_schedule(mut self, schedule: Vec<Rephase>) -> Self {
+        self.rephase_schedule = schedule;
+        self
+    }
+
+    pub fn rephase_interval(mut self, conflicts: u64) -> Self {
+        self.rephase_interval = conflicts;
+        self
+    }
+
+    pub fn rephase_flips(mut self, flips: u64) -> Self {
+        self.rephase_flips = flips;
+        self
+    }
+
+    pub fn gauss(mut self, enabled: bool) -> Self {
+        self.gauss = enabled;
+        self
+    }
+
+    pub fn pb_encoding(mut self, encoding: PbEncoding) -> Self {
+        self.pb_encoding = encoding;
+        self
+    }
+
+    pub fn conflict_limit(mut self, conflicts: u64) -> Self {
+        self.conflict_limit = Some(conflicts);
+        self
+    }
+
+    pub fn propagation_limit(mut self, propagations: u64) -> Self {
+        self.propagation_limit = Some(propagations);
+        self
+    }
+
+    /// Sets `memory_limit`, in bytes.
+    pub fn memory_limit(mut self, bytes: usize) -> Self {
+        self.memory_limit = Some(bytes);
+        self
+    }
+
+    pub fn random_var_freq(mut self, probability: f64) -> Self {
+        self.random_var_freq = probability;
+        self
+    }
+
+    pub fn random_phase_freq(mut self, probability: f64) -> Self {
+        self.random_phase_freq = probability;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn verbosity(mut self, level: u32) -> Self {
+        self.verbosity = level;
+        self
+    }
+
+    /// Ends a chain of setters, checking that every setting is within the
+    /// values it may take.
+    pub fn build(self) -> Result<Self, InvalidConfig> {
+        let invalid = |setting, expected| Err(InvalidConfig { setting, expected });
+        if !(self.var_decay > 0.0 && self.var_decay <= 1.0) {
+            return invalid("var_decay", "in (0, 1]");
+        }
+        match self.restart {
+            RestartStrategy::Luby { unit: 0 } => return invalid("restart", "of a positive unit"),
+            RestartStrategy::Geometric { initial, factor }
+                if initial == 0 || !(1.0..).contains(&factor) =>
+            {
+                return invalid(
+                    "restart",
+                    "of a positive initial interval and a factor of 1 or more",
+                );
+            }
+            _ => {}
+        }
+        if self.reduce_interval == 0 {
+            return invalid("reduce_interval", "positive");
+        }
+        if self.rephasing && self.rephase_schedule.is_empty() {
+            return invalid("rephase_schedule", "non-empty when rephasing");
+        }
+        for (setting, probability) in [
+            ("random_var_freq", self.random_var_freq),
+            ("random_phase_freq", self.random_phase_freq),
+        ] {
+            if !(0.0..=1.0).contains(&probability) {
+                return invalid(setting, "in [0, 1]");
+            }
+        }
+        Ok(self)
+    }
+}
@@ -0,0 +1,129 @@
+use crate::rng::Rng;
+use crate::{Lit, SolveResult, Solver, Var};
+
+impl Solver {
+    /// Approximate model counting in the style of ApproxMC. The models are
+    /// split into cells by random XOR constraints, a cell small enough to
+    /// enumerate is found by binary search on the number of constraints, and
+    /// its size scaled by the number of cells estimates the count; the
+    /// median over several rounds is returned.
+    ///
+    /// The answer is `(estimate, confidence)`: with probability at least
+    /// `confidence = 1 - delta`, the estimate lies within a factor of
+    /// `1 + epsilon` of the number of models over variables
+    /// `1..=num_vars()`. Small counts are found exactly, with confidence 1.
+    /// Returns `None` if a budget, timeout or interrupt stopped a `solve`
+    /// call on the way.
+    ///
+    /// The search runs on copies of the input clauses, where each XOR
+    /// constraint is switched on by an assumption, so the solver itself is
+    /// left as it was, apart from clauses removed by variable elimination
+    /// being restored.
+    pub fn approx_count(&mut self, epsilon: f64, delta: f64) -> Option<(f64, f64)> {
+        assert!(epsilon > 0.0, "epsilon must be positive");
+        assert!(delta > 0.0 && delta < 1.0, "delta must lie in (0, 1)");
+        let vars: Vec<Var> = (1..=self.num_vars()).map(Var::new).collect();
+        let threshold = (1.0
+            + 9.84 * (1.0 + epsilon / (1.0 + epsilon)) * (1.0 + 1.0 / epsilon).powi(2))
+        .ceil() as usize;
+
+        // The counting runs on copies of the formula, a fresh one for each
+        // hash, so that none of its variables and clauses pile up here
+        let formula = self.input_clauses();
+
+        let count = self
+            .counting_copy(&formula)
+            .count_cell(&vars, &[], threshold)?;
+        if count < threshold {
+            return Some((count as f64, 1.0));
+        }
+
+        let rounds = (17.0 * (3.0 / delta).log2()).ceil() as usize;
+        let mut rng = Rng::new(self.config.seed);
+        let mut estimates = Vec::with_capacity(rounds);
+        for _ in 0..rounds {
+            let mut copy = self.counting_copy(&formula);
+            let hash: Vec<Lit> = vars
+                .iter()
+                .map(|_| copy.add_random_xor(&vars, &mut rng))
+                .collect();
+            estimates.push(copy.hashed_estimate(&vars, &hash, threshold)?);
+        }
+        estimates.sort_by(f64::total_cmp);
+        Some((estimates[estimates.len() / 2], 1.0 - delta))
+    }
+
+    /// Estimates the model count from the cells of one random hash, given
+    /// as the guards of its XOR constraints. Cells only shrink as more
+    /// constraints are switched on, so binary search finds the fewest that
+    /// leave fewer than `threshold` models in the cell; the whole formula
+    /// (no constraints) is known to have more.
+    fn hashed_estimate(&mut self, vars: &[Var], hash: &[Lit], threshold: usize) -> Option<f64> {
+        let (mut low, mut high) = (0, hash.len());
+        let mut high_count = self.count_cell(vars, hash, threshold)?;
+        while high - low > 1 {
+            let middle = (low + high) / 2;
+            let count = self.count_cell(vars, &hash[..middle], threshold)?;
+            if count < threshold {
+                high = middle;
+                high_count = count;
+            } else {
+                low = middle;
+            }
+        }
+        Some(high_count as f64 * 2f64.powi(high as i32))
+    }
+
+    /// Counts the models over `vars` that satisfy the assumptions `cell`,
+    /// stopping at `limit`.
+    fn count_cell(&mut self, vars: &[Var], cell: &[Lit], limit: usize) -> Option<usize> {
+        // Blocking clauses only hold while `guard` is assumed
+        let guard = self.new_var().positive();
+        let mut assumptions = cell.to_vec();
+        assumptions.push(guard);
+        let mut count = 0;
+        while count < limit {
+            match self.solve_with_assumptions(&assumptions) {
+                SolveResult::Sat => {
+                    count += 1;
+                    let model = self.model.take().expect("Sat answers leave a model");
+                    let mut blocking = vec![-guard];
+                    blocking.extend(vars.iter().map(|&v| Lit::new(v, model.value(v))));
+                    self.push_clause(blocking);
+                }
+                SolveResult::Unsat => break,
+                SolveResult::Unknown => return None,
+            }
+        }
+        self.push_clause(vec![-guard]);
+        Some(count)
+    }
+
+    /// Adds the XOR of a random subset of `vars` with a random parity, and
+    /// returns the guard literal under which it holds. The guard is the
+    /// negation of a fresh variable in the XOR, which can meet any parity
+    /// while it is free.
+    fn add_random_xor(&mut self, vars: &[Var], rng: &mut Rng) -> Lit {
+        let free = self.new_var();
+        let mut literals: Vec<Lit> = vars
+            .iter()
+            .filter(|_| rng.next_bool())
+            .map(|v| v.positive())
+            .collect();
+        literals.push(free.positive());
+        if !rng.next_bool() {
+            literals[0] = -literals[0];
+        }
+        self.push_xor(literals);
+        free.negative()
+    }
+
+    /// A sibling solver over `formula` and the XOR clauses.
+    fn counting_copy(&self, formula: &[Vec<Lit>]) -> Solver {
+        let mut copy = self.sibling();
+        for literals in formula {
+            copy.push_clause(literals.clone());
+        }
+        copy
+    }
+}
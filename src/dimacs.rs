@@ -0,0 +1,769 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+#[cfg(not(target_arch = "wasm32"))]
+use memmap2::Mmap;
+
+use crate::compression;
+use crate::error::{ParseError, SolverError};
+use crate::lit::{Lit, Var};
+
+/// How DIMACS CNF input is read by `Solver::from_reader_with_options` and
+/// `Solver::new_with_options`.
+///
+/// By default, the header may be missing or repeated (the first one
+/// stands), literals above its variable count raise that count, and its
+/// clause count is not checked.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Reject whatever strays from the DIMACS format: a missing or repeated
+    /// header, literals above its variable count, a clause count that
+    /// differs from it, clauses that span several lines or share one (so
+    /// that anything after the `0` ending a clause is an error), a problem
+    /// line whose first field is not exactly `p`, and lines starting with
+    /// `c` that are not comments, such as `cnf`.
+    pub strict: bool,
+    /// Also accept what the default rejects but can be made sense of, as
+    /// found in older benchmark sets: a malformed header is taken as
+    /// missing, a line starting with `%` ends the formula, as in the SATLIB
+    /// files, and the last clause may lack its `0`. Ignored when `strict`
+    /// is set.
+    pub lenient: bool,
+    /// Threads parsing an uncompressed input of several megabytes, each
+    /// reading its own range of lines: 0 starts one per core, 1 parses on
+    /// the calling thread only.
+    pub threads: usize,
+}
+
+impl ParseOptions {
+    /// Options with `strict` set.
+    pub fn strict() -> Self {
+        Self {
+            strict: true,
+            ..Self::default()
+        }
+    }
+
+    /// Options with `lenient` set.
+    pub fn lenient() -> Self {
+        Self {
+            lenient: true,
+            ..Self::default()
+        }
+    }
+}
+
+/// A CNF formula as read from a DIMACS file.
+pub(crate) struct Dimacs {
+    pub variable_count: usize,
+    pub clauses: Vec<Vec<Lit>>,
+    /// XOR clauses, from lines starting with `x`: an odd number of the
+    /// literals must be true.
+    pub xors: Vec<Vec<Lit>>,
+}
+
+/// A weighted CNF formula as read from a WCNF file.
+pub(crate) struct Wcnf {
+    pub variable_count: usize,
+    pub hard: Vec<Vec<Lit>>,
+    /// Soft clauses with their weights.
+    pub soft: Vec<(u64, Vec<Lit>)>,
+}
+
+/// A QBF in the QDIMACS format: a DIMACS CNF formula whose header is
+/// followed by quantifier lines, `e <variables> 0` or `a <variables> 0`,
+/// outermost first.
+pub(crate) struct Qdimacs {
+    pub variable_count: usize,
+    /// Quantifier blocks, each with the line it is on and whether it is
+    /// universal.
+    pub prefix: Vec<(usize, bool, Vec<Var>)>,
+    pub clauses: Vec<Vec<Lit>>,
+}
+
+/// A CNF formula whose clauses are split into groups, as read from a G-CNF
+/// file.
+pub(crate) struct Gcnf {
+    pub variable_count: usize,
+    pub group_count: usize,
+    /// Clauses with their group, 0 for those outside every group.
+    pub clauses: Vec<(usize, Vec<Lit>)>,
+}
+
+/// The problem line: `p cnf <variables> <clauses>`, or
+/// `p wcnf <variables> <clauses> [<top>]` for weighted formulas.
+#[derive(Clone, Copy)]
+struct Header {
+    variable_count: usize,
+    clause_count: usize,
+    top: Option<u64>,
+}
+
+/// Weight given to `h` clauses of the 2022 WCNF format, which has no top.
+/// Soft clauses must weigh less.
+const HARD_WEIGHT: u64 = u64::MAX;
+
+/// A clause with its weight (1 in unweighted formulas).
+struct WeightedClause {
+    weight: u64,
+    literals: Vec<Lit>,
+    xor: bool,
+}
+
+/// Reads a DIMACS CNF formula. Clauses may span several lines and must each
+/// be terminated by `0`. The variable count is that of the header, raised
+/// to the largest variable met, or that variable without a header. A clause starting with `x`, as in `x1 -2 3 0`, is an XOR clause (the
+/// CryptoMiniSat extension), and counts towards the declared clauses.
+pub(crate) fn parse(reader: impl BufRead) -> Result<Dimacs, SolverError> {
+    parse_with(reader, ParseOptions::default())
+}
+
+/// Reads a DIMACS CNF formula like `parse`, as `options` say.
+pub(crate) fn parse_with(
+    reader: impl BufRead,
+    options: ParseOptions,
+) -> Result<Dimacs, SolverError> {
+    Ok(dimacs(read(reader.lines(), false, options)?))
+}
+
+/// Reads an uncompressed DIMACS CNF formula held in memory like
+/// `parse_with`, straight from the bytes: no line is copied. Large inputs
+/// are parsed on several threads by `read_parallel`.
+pub(crate) fn parse_bytes(bytes: &[u8], options: ParseOptions) -> Result<Dimacs, SolverError> {
+    if let Some(formula) = read_parallel(bytes, options) {
+        return Ok(dimacs(formula));
+    }
+    Ok(dimacs(read(byte_lines(bytes), false, options)?))
+}
+
+/// Reads the DIMACS CNF file at `path` like `parse_with`. An uncompressed
+/// file is mapped into memory and parsed by `parse_bytes`; compressed
+/// files, and those that cannot be mapped such as pipes, are read through
+/// a buffer instead.
+pub(crate) fn parse_file(path: &str, options: ParseOptions) -> Result<Dimacs, SolverError> {
+    let file = File::open(path)?;
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        // SAFETY: the mapping is only read, and dropped before returning. A
+        // file truncated by another process meanwhile would fault, as with
+        // any memory-mapped input
+        let map = file
+            .metadata()?
+            .is_file()
+            .then(|| unsafe { Mmap::map(&file) });
+        if let Some(Ok(map)) = map
+            && !compression::is_compressed(&map)
+        {
+            return parse_bytes(&map, options);
+        }
+    }
+    parse_with(compression::decompress(BufReader::new(file))?, options)
+}
+
+/// Splits the clauses read by `read` into plain and XOR clauses.
+fn dimacs((header, clauses): (Header, Vec<WeightedClause>)) -> Dimacs {
+    let (xors, clauses): (Vec<_>, Vec<_>) = clauses.into_iter().partition(|clause| clause.xor);
+    Dimacs {
+        variable_count: header.variable_count,
+        clauses: clauses.into_iter().map(|clause| clause.literals).collect(),
+        xors: xors.into_iter().map(|clause| clause.literals).collect(),
+    }
+}
+
+/// Reads a weighted formula, where each clause is preceded by its weight.
+/// Both formats of the MaxSAT evaluations are accepted:
+///
+/// - the old one, with a `p wcnf <variables> <clauses> [<top>]` header:
+///   clauses weighing at least the top are hard (without a top, all clauses
+///   are soft);
+/// - the 2022 one, with no header at all: hard clauses start with `h`
+///   instead of a weight, and the variables are those that occur.
+pub(crate) fn parse_wcnf(reader: impl BufRead) -> Result<Wcnf, SolverError> {
+    let (header, clauses) = read(reader.lines(), true, ParseOptions::default())?;
+    let mut wcnf = Wcnf {
+        variable_count: header.variable_count,
+        hard: Vec::new(),
+        soft: Vec::new(),
+    };
+    for clause in clauses {
+        if header.top.is_some_and(|top| clause.weight >= top) {
+            wcnf.hard.push(clause.literals);
+        } else {
+            wcnf.soft.push((clause.weight, clause.literals));
+        }
+    }
+    Ok(wcnf)
+}
+
+/// Reads a QDIMACS formula. The quantifier lines must come between the
+/// header and the first clause, and quantify each variable at most once;
+/// the clauses are then read like those of `parse`, XOR clauses aside.
+pub(crate) fn parse_qdimacs(mut reader: impl BufRead) -> Result<Qdimacs, SolverError> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    let mut variable_count = None;
+    let mut prefix = Vec::new();
+    let mut quantified = Vec::new();
+    // Quantifier lines are blanked out, keeping the line numbers of the rest
+    let mut matrix = String::with_capacity(text.len());
+    let mut in_prefix = true;
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let universal = match line.split_whitespace().next() {
+            Some("a") => true,
+            Some("e") => false,
+            Some(token) if token.starts_with('x') => {
+                return Err(ParseError::InvalidToken {
+                    line: line_number,
+                    column: 1,
+                    token: token.to_string(),
+                }
+                .into());
+            }
+            first => {
+                if line.starts_with('p') {
+                    if variable_count.is_none() {
+                        let header = parse_header(line, line_number, false)?;
+                        variable_count = Some(header.variable_count);
+                        quantified = vec![false; header.variable_count + 1];
+                    }
+                } else if first.is_some() && !line.starts_with('c') {
+                    in_prefix = false;
+                }
+                matrix.push_str(line);
+                matrix.push('\n');
+                continue;
+            }
+        };
+        let Some(variable_count) = variable_count else {
+            return Err(ParseError::MissingHeader.into());
+        };
+        let mut tokens = tokens(line);
+        let (offset, quantifier) = tokens.next().unwrap();
+        if !in_prefix {
+            // Quantifiers after the first clause
+            return Err(ParseError::InvalidToken {
+                line: line_number,
+                column: column(line, offset),
+                token: quantifier.to_string(),
+            }
+            .into());
+        }
+        let mut block = Vec::new();
+        let mut terminated = false;
+        for (offset, token) in tokens {
+            let invalid_token = || ParseError::InvalidToken {
+                line: line_number,
+                column: column(line, offset),
+                token: token.to_string(),
+            };
+            let value: i64 = token.parse().map_err(|_| invalid_token())?;
+            if terminated || value < 0 {
+                return Err(invalid_token().into());
+            }
+            if value == 0 {
+                terminated = true;
+                continue;
+            }
+            if value.unsigned_abs() > variable_count as u64 {
+                return Err(ParseError::LiteralOutOfRange {
+                    line: line_number,
+                    column: column(line, offset),
+                    literal: value,
+                    variables: variable_count,
+                }
+                .into());
+            }
+            let var = Var::new(value as usize);
+            if std::mem::replace(&mut quantified[var.index()], true) {
+                return Err(invalid_token().into());
+            }
+            block.push(var);
+        }
+        if !terminated {
+            return Err(ParseError::UnterminatedClause { line: line_number }.into());
+        }
+        prefix.push((line_number, universal, block));
+        matrix.push('\n');
+    }
+
+    let formula = parse(matrix.as_bytes())?;
+    Ok(Qdimacs {
+        variable_count: formula.variable_count,
+        prefix,
+        clauses: formula.clauses,
+    })
+}
+
+/// Reads a G-CNF formula, the group format of the MUS competitions: a
+/// `p gcnf <variables> <clauses> <groups>` header, then clauses that each
+/// start with their group in braces, such as `{2} 1 -3 0`. Group 0 holds
+/// the clauses outside every group; the others are numbered up to the
+/// declared count. The clauses are then read like those of `parse`, XOR
+/// clauses aside.
+pub(crate) fn parse_gcnf(mut reader: impl BufRead) -> Result<Gcnf, SolverError> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    let mut group_count = None;
+    let mut groups = Vec::new();
+    // Group tags are blanked out, keeping the columns of the literals
+    let mut matrix = String::with_capacity(text.len());
+    let mut pending = false;
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        if line.starts_with('c') {
+            matrix.push('\n');
+            continue;
+        }
+        if line.starts_with('p') {
+            let invalid = ParseError::InvalidHeader { line: line_number };
+            let [_, "gcnf", variables, clauses, count] =
+                line.split_whitespace().collect::<Vec<_>>()[..]
+            else {
+                return Err(invalid.into());
+            };
+            if group_count.is_some() {
+                return Err(invalid.into());
+            }
+            group_count = Some(count.parse::<usize>().map_err(|_| invalid)?);
+            matrix.push_str(&format!("p cnf {variables} {clauses}\n"));
+            continue;
+        }
+        let mut stripped = line.to_string();
+        for (offset, token) in tokens(line) {
+            let Some(group_count) = group_count else {
+                return Err(ParseError::MissingHeader.into());
+            };
+            let invalid_token = || ParseError::InvalidToken {
+                line: line_number,
+                column: column(line, offset),
+                token: token.to_string(),
+            };
+            if pending {
+                if token.starts_with(['{', 'x']) {
+                    return Err(invalid_token().into());
+                }
+                pending = token != "0";
+                continue;
+            }
+            let group = token
+                .strip_prefix('{')
+                .and_then(|rest| rest.strip_suffix('}'))
+                .and_then(|group| group.parse::<usize>().ok())
+                .filter(|&group| group <= group_count)
+                .ok_or_else(invalid_token)?;
+            groups.push(group);
+            stripped.replace_range(offset..offset + token.len(), &" ".repeat(token.len()));
+            pending = true;
+        }
+        matrix.push_str(&stripped);
+        matrix.push('\n');
+    }
+
+    let formula = parse(matrix.as_bytes())?;
+    Ok(Gcnf {
+        variable_count: formula.variable_count,
+        group_count: group_count.unwrap_or(0),
+        clauses: groups.into_iter().zip(formula.clauses).collect(),
+    })
+}
+
+/// Reads the header and clauses of a CNF or, when `weighted`, WCNF input.
+/// A weighted input without a header is taken to be in the 2022 format, and
+/// is given a header made up from its body.
+fn read<L: AsRef<str>>(
+    lines: impl Iterator<Item = io::Result<L>>,
+    weighted: bool,
+    options: ParseOptions,
+) -> Result<(Header, Vec<WeightedClause>), SolverError> {
+    let mut reader = Reader::new(weighted, options);
+    for line in lines {
+        if reader.done {
+            break;
+        }
+        reader.line(line?.as_ref())?;
+    }
+    reader.finish()
+}
+
+/// Where `read` stands between two lines of its input.
+struct Reader {
+    weighted: bool,
+    options: ParseOptions,
+    lenient: bool,
+    header: Option<Header>,
+    headerless: bool,
+    clauses: Vec<WeightedClause>,
+    /// The clause being read, with its weight and whether it is an XOR
+    current: Vec<Lit>,
+    weight: Option<u64>,
+    xor: bool,
+    current_line: usize,
+    line_number: usize,
+    max_var: usize,
+    /// Set by a `%` line in lenient parsing: the rest is not read
+    done: bool,
+}
+
+impl Reader {
+    fn new(weighted: bool, options: ParseOptions) -> Self {
+        Self {
+            weighted,
+            options,
+            lenient: options.lenient && !options.strict,
+            header: None,
+            headerless: false,
+            clauses: Vec::new(),
+            current: Vec::new(),
+            weight: None,
+            xor: false,
+            current_line: 0,
+            line_number: 0,
+            max_var: 0,
+            done: false,
+        }
+    }
+
+    /// Whether a clause was started but not terminated yet.
+    fn is_pending(&self) -> bool {
+        !self.current.is_empty() || self.weight.is_some() || self.xor
+    }
+
+    fn line(&mut self, line: &str) -> Result<(), SolverError> {
+        let options = self.options;
+        let lenient = self.lenient;
+        let weighted = self.weighted;
+        self.line_number += 1;
+        let line_number = self.line_number;
+
+        if line.starts_with('c') && !(options.strict && is_malformed_comment(line)) {
+            return Ok(());
+        }
+        if lenient && line.starts_with('%') {
+            self.done = true;
+            return Ok(());
+        }
+        if lenient && line.starts_with('p') {
+            // Any other header is as good as a missing one
+            if self.header.is_none() {
+                self.header = parse_header(line, line_number, weighted).ok();
+            }
+            return Ok(());
+        }
+        if line.starts_with('p') {
+            if self.headerless
+                || (options.strict
+                    && (self.header.is_some() || line.split_whitespace().next() != Some("p")))
+            {
+                return Err(ParseError::InvalidHeader { line: line_number }.into());
+            }
+            // A repeated header must be well-formed too, but the first one stands
+            let header = parse_header(line, line_number, weighted)?;
+            self.header.get_or_insert(header);
+            return Ok(());
+        }
+
+        // Set once a clause ends on this line, for strict parsing
+        let mut ended = false;
+        for (offset, token) in tokens(line) {
+            let variable_count = match &self.header {
+                Some(header) if options.strict => header.variable_count,
+                Some(_) => Var::MAX_INDEX,
+                None if weighted => {
+                    self.headerless = true;
+                    Var::MAX_INDEX
+                }
+                None if options.strict => return Err(ParseError::MissingHeader.into()),
+                None => Var::MAX_INDEX,
+            };
+            let invalid_token = || ParseError::InvalidToken {
+                line: line_number,
+                column: column(line, offset),
+                token: token.to_string(),
+            };
+            if ended {
+                return Err(invalid_token().into());
+            }
+            if self.current.is_empty() && !self.xor {
+                self.current_line = line_number;
+            }
+            if weighted && self.weight.is_none() {
+                self.weight = Some(if self.headerless && token == "h" {
+                    HARD_WEIGHT
+                } else {
+                    token
+                        .parse()
+                        .ok()
+                        .filter(|w| (1..HARD_WEIGHT).contains(w))
+                        .ok_or_else(invalid_token)?
+                });
+                continue;
+            }
+            let mut token = token;
+            if !weighted
+                && self.current.is_empty()
+                && !self.xor
+                && let Some(rest) = token.strip_prefix('x')
+            {
+                self.xor = true;
+                if rest.is_empty() {
+                    continue;
+                }
+                token = rest;
+            }
+            let value: i64 = token.parse().map_err(|_| invalid_token())?;
+            if value == 0 {
+                self.clauses.push(WeightedClause {
+                    weight: self.weight.take().unwrap_or(1),
+                    literals: std::mem::take(&mut self.current),
+                    xor: std::mem::take(&mut self.xor),
+                });
+                ended = options.strict;
+                continue;
+            }
+            if value.unsigned_abs() > variable_count as u64 {
+                return Err(ParseError::LiteralOutOfRange {
+                    line: line_number,
+                    column: column(line, offset),
+                    literal: value,
+                    variables: variable_count,
+                }
+                .into());
+            }
+            let var = Var::new(value.unsigned_abs() as usize);
+            self.max_var = self.max_var.max(var.index());
+            self.current.push(Lit::new(var, value < 0));
+        }
+        if options.strict && self.is_pending() {
+            return Err(ParseError::UnterminatedClause {
+                line: self.current_line,
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Appends what `next` read from the lines that follow those read by
+    /// `self`, carrying a clause left pending at the end of `self` over
+    /// into the first one of `next`. False if `next` started reading in
+    /// the middle of a clause in a way the lines read together would have
+    /// rejected, which only `read` can report at the right line.
+    fn append(&mut self, mut next: Reader) -> bool {
+        if self.done {
+            return true;
+        }
+        if self.is_pending() {
+            if next.xor || next.clauses.first().is_some_and(|clause| clause.xor) {
+                return false;
+            }
+            let mut literals = std::mem::take(&mut self.current);
+            match next.clauses.first_mut() {
+                Some(first) => {
+                    literals.append(&mut first.literals);
+                    first.literals = literals;
+                    first.xor = std::mem::take(&mut self.xor);
+                }
+                None => {
+                    literals.append(&mut next.current);
+                    next.current = literals;
+                    next.xor = std::mem::take(&mut self.xor);
+                }
+            }
+        }
+        self.clauses.append(&mut next.clauses);
+        self.current = next.current;
+        self.xor = next.xor;
+        self.max_var = self.max_var.max(next.max_var);
+        self.done = next.done;
+        true
+    }
+
+    fn finish(self) -> Result<(Header, Vec<WeightedClause>), SolverError> {
+        let strict = self.options.strict;
+        let lenient = self.lenient;
+        let pending = self.is_pending();
+        let mut clauses = self.clauses;
+        if pending {
+            if !lenient {
+                return Err(ParseError::UnterminatedClause {
+                    line: self.current_line,
+                }
+                .into());
+            }
+            clauses.push(WeightedClause {
+                weight: self.weight.unwrap_or(1),
+                literals: self.current,
+                xor: self.xor,
+            });
+        }
+        let max_var = self.max_var;
+        let header = match self.header {
+            Some(header) if strict => header,
+            Some(header) => Header {
+                variable_count: header.variable_count.max(max_var),
+                ..header
+            },
+            None if self.weighted => Header {
+                variable_count: max_var,
+                clause_count: clauses.len(),
+                top: Some(HARD_WEIGHT),
+            },
+            None if strict => return Err(ParseError::MissingHeader.into()),
+            None => Header {
+                variable_count: max_var,
+                clause_count: clauses.len(),
+                top: None,
+            },
+        };
+        if strict && clauses.len() != header.clause_count {
+            return Err(ParseError::ClauseCountMismatch {
+                declared: header.clause_count,
+                found: clauses.len(),
+            }
+            .into());
+        }
+        Ok((header, clauses))
+    }
+}
+
+/// Inputs smaller than this are parsed on one thread.
+const PARALLEL_MIN_BYTES: usize = 4 << 20;
+
+/// Least number of bytes given to each thread of `read_parallel`.
+const CHUNK_MIN_BYTES: usize = 1 << 20;
+
+/// Reads an unweighted formula as `read` does, but splits the lines after
+/// the header into chunks parsed on `options.threads` threads, then merges their clauses in order. A clause cut in two by the end of
+/// a chunk is joined back to its tail at the start of the next one. None
+/// when the input is too small to gain from it, when no header precedes
+/// the first clause, or on any error, which `read` then reports with its
+/// line.
+fn read_parallel(bytes: &[u8], options: ParseOptions) -> Option<(Header, Vec<WeightedClause>)> {
+    if bytes.len() < PARALLEL_MIN_BYTES {
+        return None;
+    }
+    let threads = match options.threads {
+        0 => std::thread::available_parallelism().map_or(1, |threads| threads.get()),
+        threads => threads,
+    }
+    .min(bytes.len() / CHUNK_MIN_BYTES);
+    if threads < 2 {
+        return None;
+    }
+
+    // The comments and the header, up to the end of the problem line
+    let mut reader = Reader::new(false, options);
+    let mut start = 0;
+    while reader.header.is_none() {
+        let end = bytes[start..]
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .map_or(bytes.len(), |end| start + end + 1);
+        let line = byte_lines(&bytes[start..end]).next()?.ok()?;
+        let blank = line.trim().is_empty();
+        if !(blank || line.starts_with('c') || line.starts_with('p')) || end == bytes.len() {
+            return None;
+        }
+        reader.line(line).ok()?;
+        start = end;
+    }
+
+    // Chunks of about the same size, each ending at the end of a line
+    let body = &bytes[start..];
+    let mut bounds = vec![0];
+    for i in 1..threads {
+        let split = (body.len() * i / threads).max(*bounds.last()?);
+        let end = body[split..]
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .map_or(body.len(), |end| split + end + 1);
+        if end < body.len() && end > *bounds.last()? {
+            bounds.push(end);
+        }
+    }
+    bounds.push(body.len());
+
+    let header = reader.header.as_ref()?;
+    let chunks: Vec<Option<Reader>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = bounds
+            .windows(2)
+            .map(|bound| {
+                let chunk = &body[bound[0]..bound[1]];
+                scope.spawn(move || {
+                    let mut reader = Reader::new(false, options);
+                    reader.header = Some(*header);
+                    for line in byte_lines(chunk) {
+                        if reader.done {
+                            break;
+                        }
+                        reader.line(line.ok()?).ok()?;
+                    }
+                    Some(reader)
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().ok().flatten())
+            .collect()
+    });
+    for chunk in chunks {
+        if !reader.append(chunk?) {
+            return None;
+        }
+    }
+    reader.finish().ok()
+}
+
+/// Parses `p cnf <variables> <clauses>`, or with `weighted`,
+/// `p wcnf <variables> <clauses> [<top>]`.
+fn parse_header(line: &str, line_number: usize, weighted: bool) -> Result<Header, ParseError> {
+    let invalid = ParseError::InvalidHeader { line: line_number };
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let (variables, clauses, top) = match (weighted, &fields[..]) {
+        (false, [_, "cnf", variables, clauses]) => (variables, clauses, None),
+        (true, [_, "wcnf", variables, clauses]) => (variables, clauses, None),
+        (true, [_, "wcnf", variables, clauses, top]) => (
+            variables,
+            clauses,
+            Some(top.parse().map_err(|_| invalid.clone())?),
+        ),
+        _ => return Err(invalid),
+    };
+    match (variables.parse::<usize>(), clauses.parse::<usize>()) {
+        (Ok(variable_count), Ok(clause_count)) if variable_count <= Var::MAX_INDEX => Ok(Header {
+            variable_count,
+            clause_count,
+            top,
+        }),
+        _ => Err(invalid),
+    }
+}
+
+/// Whether `line`, which starts with `c`, is something else than a comment
+/// line: `c` alone or followed by whitespace.
+fn is_malformed_comment(line: &str) -> bool {
+    !line[1..].is_empty() && !line[1..].starts_with(char::is_whitespace)
+}
+
+/// Tokens of `line` separated by ASCII whitespace, with their byte offsets.
+fn tokens(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    line.split_ascii_whitespace()
+        .map(move |token| (token.as_ptr() as usize - line.as_ptr() as usize, token))
+}
+
+/// 1-based column of the character at byte `offset` of `line`, for error
+/// messages.
+fn column(line: &str, offset: usize) -> usize {
+    line[..offset].chars().count() + 1
+}
+
+/// The lines of `bytes` as `BufRead::lines` would give them, `\r\n` endings
+/// included, but borrowed rather than copied.
+fn byte_lines(bytes: &[u8]) -> impl Iterator<Item = io::Result<&str>> {
+    let bytes = bytes.strip_suffix(b"\n").unwrap_or(bytes);
+    bytes.split(|&byte| byte == b'\n').map(|line| {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        std::str::from_utf8(line).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    })
+}
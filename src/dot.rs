@@ -0,0 +1,49 @@
+use std::io::{self, Write};
+
+use crate::Solver;
+
+impl Solver {
+    /// Writes the current implication graph in the DOT language of
+    /// GraphViz: a node per assigned literal, labelled with its decision
+    /// level and grouped by level, and an edge to it from the negation of
+    /// every other literal of its reason clause. Decisions and assumptions
+    /// are drawn as boxes. After a `Sat` answer the graph leads to the
+    /// model; otherwise only the values fixed at level 0 are left.
+    pub fn dump_implication_graph(&self, mut w: impl Write) -> io::Result<()> {
+        writeln!(w, "digraph implications {{")?;
+        writeln!(w, "  rankdir=LR;")?;
+        let mut current = None;
+        for index in 0..self.trail.len() {
+            let lit = self.trail[index];
+            let level = self.levels[lit.var().index()];
+            if current != Some(level) {
+                if current.is_some() {
+                    writeln!(w, "  }}")?;
+                }
+                writeln!(w, "  subgraph cluster_{level} {{")?;
+                writeln!(w, "    label=\"level {level}\";")?;
+                current = Some(level);
+            }
+            let decision = level > 0 && self.reasons[lit.var().index()].is_none();
+            let shape = if decision { "box" } else { "ellipse" };
+            writeln!(
+                w,
+                "    \"{lit}\" [label=\"{lit} @{level}\", shape={shape}];"
+            )?;
+        }
+        if current.is_some() {
+            writeln!(w, "  }}")?;
+        }
+
+        for index in 0..self.trail.len() {
+            let lit = self.trail[index];
+            let Some(reason) = self.reasons[lit.var().index()] else {
+                continue;
+            };
+            for &other in self.clauses[reason].iter().filter(|&&other| other != lit) {
+                writeln!(w, "  \"{}\" -> \"{lit}\";", -other)?;
+            }
+        }
+        writeln!(w, "}}")
+    }
+}
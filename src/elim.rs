@@ -0,0 +1,194 @@
+use crate::gates;
+use crate::occ::OccLists;
+use crate::{ClauseRef, Lit, Model, Solver, Var};
+
+/// Resolvents longer than this make a variable too costly to eliminate.
+const MAX_RESOLVENT_LEN: usize = 20;
+
+impl Solver {
+    /// Bounded variable elimination (as in SatELite): a variable `x` is
+    /// removed by replacing every clause containing `x` or `-x` with all
+    /// non-tautological resolvents on `x`, provided that adds at most
+    /// `config.elim_growth` clauses. When a gate defines `x`, only the
+    /// resolvents of its clauses with the others are needed, also as in
+    /// SatELite. The removed clauses are kept on the elimination stack to
+    /// extend models to `x` afterwards. Frozen
+    /// variables, and those of `assumptions`, of XOR clauses, of
+    /// at-most-one groups and of native pseudo-Boolean constraints are
+    /// kept. Runs on an empty trail before the first search.
+    pub(crate) fn eliminate_variables(&mut self, assumptions: &[Lit]) {
+        debug_assert_eq!(self.trail.len(), 0);
+
+        let mut occ = OccLists::new(&self.clauses, self.watch_lists.len());
+        let mut is_frozen = self.frozen.clone();
+        for lit in assumptions {
+            is_frozen[lit.var().index()] = true;
+        }
+        for var in self.xors.iter().flat_map(|xor| xor.vars()) {
+            is_frozen[var.index()] = true;
+        }
+        for (_, lit) in self.pbs.iter().flat_map(|pb| pb.constraint.terms()) {
+            is_frozen[lit.var().index()] = true;
+        }
+        for lit in self.amos.iter().flatten() {
+            is_frozen[lit.var().index()] = true;
+        }
+
+        // Cheapest variables first: few resolvents are likely
+        let mut candidates: Vec<Var> = (1..=self.num_vars())
+            .map(Var::new)
+            .filter(|v| !is_frozen[v.index()] && !self.eliminated[v.index()])
+            .collect();
+        candidates.sort_by_key(|v| occ.count(v.positive()) * occ.count(v.negative()));
+
+        let mut marks = vec![false; self.watch_lists.len()];
+        for var in candidates {
+            let pos = occ.lookup(&self.clauses, var.positive()).to_vec();
+            let neg = occ.lookup(&self.clauses, var.negative()).to_vec();
+            if pos.is_empty() && neg.is_empty() {
+                continue;
+            }
+
+            // With a gate defining `var`, only the gate clauses need
+            // resolving with the others: resolvents of two gate clauses are
+            // tautologies, and those of two others follow from the rest
+            let gate = gates::find_definition(&self.clauses, &mut occ, var)
+                .map(|(_, defining)| defining)
+                .unwrap_or_default();
+            let budget = pos.len() + neg.len() + self.config.elim_growth;
+            // Each resolvent with the IDs of its antecedents, its LRAT hints
+            let mut resolvents = Vec::new();
+            let mut too_costly = false;
+            'pairs: for &p in &pos {
+                for &n in &neg {
+                    if !gate.is_empty() && gate.contains(&p) == gate.contains(&n) {
+                        continue;
+                    }
+                    let Some(resolvent) = self.resolve(p, n, var, &mut marks) else {
+                        continue; // Tautology
+                    };
+                    if resolvent.len() > MAX_RESOLVENT_LEN || resolvents.len() == budget {
+                        too_costly = true;
+                        break 'pairs;
+                    }
+                    let hints = [self.clauses.id(p), self.clauses.id(n)];
+                    resolvents.push((resolvent, hints));
+                }
+            }
+            if too_costly {
+                continue;
+            }
+
+            // Resolvents are RUP, so they are added before their antecedents go
+            for (literals, hints) in resolvents {
+                let cref = self.clauses.push(&literals, false, 0);
+                if let Some(proof) = self.proof.as_mut() {
+                    proof.add(self.clauses.id(cref), &literals, &hints);
+                }
+                occ.insert(cref, &literals);
+            }
+            for (pivot, crefs) in [(var.positive(), &pos), (var.negative(), &neg)] {
+                for &cref in crefs {
+                    occ.remove(&mut self.clauses, cref);
+                    let literals = self.clauses[cref].to_vec();
+                    if let Some(proof) = self.proof.as_mut() {
+                        proof.eliminate(self.clauses.id(cref), &literals);
+                    }
+                    self.elim_stack.push((pivot, literals));
+                }
+            }
+            self.eliminated[var.index()] = true;
+            self.stats.eliminated_vars += 1;
+            if !gate.is_empty() {
+                self.stats.gate_eliminations += 1;
+            }
+        }
+
+        self.collect_garbage();
+        self.rewatch_all();
+    }
+
+    /// Resolvent of clauses `p` (containing `var`) and `n` (containing
+    /// `-var`), or `None` if it is a tautology. `marks` must be all false.
+    fn resolve(
+        &self,
+        p: ClauseRef,
+        n: ClauseRef,
+        var: Var,
+        marks: &mut [bool],
+    ) -> Option<Vec<Lit>> {
+        let mut resolvent: Vec<Lit> = Vec::new();
+        let mut tautology = false;
+        for &lit in self.clauses[p].iter().chain(&self.clauses[n]) {
+            if lit.var() == var || marks[lit.index()] {
+                continue;
+            }
+            if marks[(-lit).index()] {
+                tautology = true;
+                break;
+            }
+            marks[lit.index()] = true;
+            resolvent.push(lit);
+        }
+        for lit in &resolvent {
+            marks[lit.index()] = false;
+        }
+        (!tautology).then_some(resolvent)
+    }
+
+    /// Keeps `var` out of variable elimination and equivalent literal
+    /// substitution until `melt` is called, as `setFrozen` does in MiniSat:
+    /// clauses and assumptions added later may then mention it without
+    /// every eliminated variable being put back first. If preprocessing
+    /// already removed `var`, the eliminated variables are put back now.
+    pub fn freeze(&mut self, var: Var) {
+        self.reserve_vars(var.index());
+        if self.eliminated[var.index()] {
+            self.backtrack_to(0);
+            self.restore_eliminated();
+        }
+        self.frozen[var.index()] = true;
+    }
+
+    /// Lets preprocessing remove `var` again after `freeze`.
+    pub fn melt(&mut self, var: Var) {
+        if let Some(frozen) = self.frozen.get_mut(var.index()) {
+            *frozen = false;
+        }
+    }
+
+    /// Whether `var` is frozen by `freeze`.
+    pub fn is_frozen(&self, var: Var) -> bool {
+        self.frozen.get(var.index()).copied().unwrap_or(false)
+    }
+
+    /// Extends a model of the simplified formula to the eliminated variables
+    /// by replaying the elimination stack backwards: any removed clause the
+    /// model falsifies is repaired by making its pivot literal true.
+    pub(crate) fn extend_model(&self, model: &mut Model) {
+        for (pivot, literals) in self.elim_stack.iter().rev() {
+            if !literals.iter().any(|&lit| model.lit_is_true(lit)) {
+                model.set(*pivot);
+            }
+        }
+    }
+
+    /// Puts every eliminated variable back by re-adding the clauses removed
+    /// with it. Needed before clauses or assumptions mention such variables.
+    pub(crate) fn restore_eliminated(&mut self) {
+        let stack = std::mem::take(&mut self.elim_stack);
+        for (pivot, _) in &stack {
+            self.eliminated[pivot.var().index()] = false;
+            self.order.insert(pivot.var());
+        }
+        for (pivot, mut literals) in stack.into_iter().rev() {
+            if let Some(proof) = self.proof.as_mut() {
+                // Pivot first, so a checker can verify the clause as RAT on it
+                let at = literals.iter().position(|&l| l == pivot).unwrap();
+                literals.swap(0, at);
+                proof.restore(self.clauses.next_id(), &literals);
+            }
+            self.attach_clause(literals);
+        }
+    }
+}
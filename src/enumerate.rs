@@ -0,0 +1,65 @@
+use crate::{Lit, Model, SolveResult, Solver, Var};
+
+/// Iterator over the models of a formula, returned by `Solver::iter_models`
+/// and `Solver::enumerate_models_over`.
+///
+/// Each model is excluded from the rest of the enumeration by a blocking
+/// clause, which is added when the next model is requested. Until then the
+/// solver still holds the model's assignment, so `Solver::verify_model`
+/// can check it. Blocking clauses stay in the formula afterwards.
+pub struct Models<'a> {
+    solver: &'a mut Solver,
+    // Variables the blocking clauses range over; all of them when `None`
+    projection: Option<Vec<Var>>,
+    last: Option<Model>,
+    result: Option<SolveResult>,
+}
+
+impl<'a> Models<'a> {
+    pub(crate) fn new(solver: &'a mut Solver, projection: Option<Vec<Var>>) -> Self {
+        Self {
+            solver,
+            projection,
+            last: None,
+            result: None,
+        }
+    }
+
+    /// Whether every model was found: the enumeration ran until the
+    /// formula became unsatisfiable, rather than stopping on a budget,
+    /// timeout or interrupt.
+    pub fn is_complete(&self) -> bool {
+        self.result == Some(SolveResult::Unsat)
+    }
+
+    /// The solver being enumerated, e.g. to call `verify_model` on the
+    /// model just returned.
+    pub fn solver(&self) -> &Solver {
+        self.solver
+    }
+}
+
+impl Iterator for Models<'_> {
+    type Item = Model;
+
+    fn next(&mut self) -> Option<Model> {
+        if self.result.is_some_and(|result| !result.is_sat()) {
+            return None;
+        }
+        if let Some(model) = self.last.take() {
+            let blocking: Vec<Lit> = match &self.projection {
+                Some(vars) => vars.iter().map(|&v| Lit::new(v, model.value(v))).collect(),
+                None => model.iter().map(|lit| -lit).collect(),
+            };
+            self.solver.push_clause(blocking);
+        }
+
+        let result = self.solver.solve();
+        self.result = Some(result);
+        if !result.is_sat() {
+            return None;
+        }
+        self.last = self.solver.model();
+        self.last.clone()
+    }
+}
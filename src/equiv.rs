@@ -0,0 +1,188 @@
+use crate::{ClauseRef, Lit, Solver};
+
+impl Solver {
+    /// Equivalent literal substitution: literals on a common cycle of the
+    /// binary implication graph (`a ∨ b` gives `-a → b` and `-b → a`) are
+    /// equivalent, so each strongly connected component is replaced by one
+    /// representative literal throughout the input clauses and the XOR
+    /// clauses. Frozen variables are never substituted, and are picked as
+    /// representatives first. Substituted variables are treated as
+    /// eliminated, with their equivalence on the elimination stack for model
+    /// reconstruction. Runs on an empty trail before the first search.
+    pub(crate) fn substitute_equivalences(&mut self) {
+        debug_assert_eq!(self.trail.len(), 0);
+
+        let mut implications = vec![Vec::new(); self.watch_lists.len()];
+        for cref in self
+            .clauses
+            .iter()
+            .filter(|&cref| !self.clauses.is_learnt(cref))
+        {
+            if let [a, b] = self.clauses[cref] {
+                implications[(-a).index()].push(b);
+                implications[(-b).index()].push(a);
+            }
+        }
+
+        // Representative of every literal; `None` for literals left as they are
+        let mut representative: Vec<Option<Lit>> = vec![None; self.watch_lists.len()];
+        for component in strongly_connected_components(&implications) {
+            let rep = *component
+                .iter()
+                .min_by_key(|l| (!self.frozen[l.var().index()], l.var()))
+                .unwrap();
+            if representative[rep.index()].is_some() {
+                continue; // The mirrored component was handled already
+            }
+            if component.iter().any(|&l| l.var() == rep.var() && l != rep) {
+                // x and -x imply each other
+                if let Some(proof) = self.proof.as_mut() {
+                    proof.add(self.clauses.take_id(), &[-rep], &[]);
+                }
+                self.push_clause(Vec::new());
+                return;
+            }
+            for &lit in &component {
+                if lit != rep && self.frozen[lit.var().index()] {
+                    continue; // Still tied to `rep` by its binary clauses
+                }
+                representative[lit.index()] = Some(rep);
+                representative[(-lit).index()] = Some(-rep);
+                if lit != rep {
+                    let var = lit.var();
+                    self.eliminated[var.index()] = true;
+                    self.elim_stack.push((-lit, vec![-lit, rep]));
+                    self.elim_stack.push((lit, vec![lit, -rep]));
+                    self.stats.substituted_vars += 1;
+                }
+            }
+        }
+
+        // Rewrite clauses; the new versions are RUP as long as the old
+        // binaries are still present, so deletions come last
+        let mut replaced = Vec::new();
+        let crefs: Vec<ClauseRef> = self.clauses.iter().collect();
+        for cref in crefs {
+            let old = &self.clauses[cref];
+            if self.clauses.is_learnt(cref)
+                || !old.iter().any(|l| representative[l.index()].is_some())
+            {
+                continue;
+            }
+            let id = self.clauses.id(cref);
+            let old = old.to_vec();
+            let mut literals: Vec<Lit> = old
+                .iter()
+                .map(|&l| representative[l.index()].unwrap_or(l))
+                .collect();
+            literals.sort_unstable();
+            literals.dedup();
+            if literals.windows(2).any(|pair| pair[0] == -pair[1]) {
+                self.clauses.delete(cref);
+            } else {
+                self.clauses.shrink(cref, &literals);
+                if let Some(proof) = self.proof.as_mut() {
+                    proof.add(self.clauses.id(cref), &literals, &[]);
+                }
+            }
+            replaced.push((id, old));
+        }
+        if let Some(proof) = self.proof.as_mut() {
+            for (id, old) in &replaced {
+                proof.delete(*id, old);
+            }
+        }
+
+        self.collect_garbage();
+        self.rewatch_all();
+
+        let xors = std::mem::take(&mut self.xors);
+        for list in self.xor_watches.iter_mut() {
+            list.clear();
+        }
+        for xor in xors {
+            let literals = xor
+                .to_literals()
+                .into_iter()
+                .map(|l| representative[l.index()].unwrap_or(l))
+                .collect();
+            self.push_xor(literals);
+        }
+
+        let pbs = std::mem::take(&mut self.pbs);
+        for list in self.pb_watches.iter_mut() {
+            list.clear();
+        }
+        for pb in pbs {
+            let constraint = pb
+                .constraint
+                .map_literals(|l| representative[l.index()].unwrap_or(l));
+            self.push_native_pb(&constraint);
+        }
+    }
+}
+
+/// Tarjan's algorithm over the literal graph `edges`, iteratively so that
+/// long implication chains cannot overflow the stack. Only components with
+/// more than one literal are returned.
+fn strongly_connected_components(edges: &[Vec<Lit>]) -> Vec<Vec<Lit>> {
+    let mut index_of: Vec<Option<usize>> = vec![None; edges.len()];
+    let mut low = vec![0; edges.len()];
+    let mut on_stack = vec![false; edges.len()];
+    let mut stack = Vec::new();
+    let mut components = Vec::new();
+    let mut next_index = 0;
+
+    for root in 2..edges.len() {
+        if index_of[root].is_some() || edges[root].is_empty() {
+            continue;
+        }
+        // Each frame is a node and the position of its next edge to explore
+        let mut frames = vec![(root, 0)];
+        index_of[root] = Some(next_index);
+        low[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some(&mut (v, ref mut edge)) = frames.last_mut() {
+            if let Some(&w) = edges[v].get(*edge) {
+                *edge += 1;
+                let w = w.index();
+                match index_of[w] {
+                    None => {
+                        index_of[w] = Some(next_index);
+                        low[w] = next_index;
+                        next_index += 1;
+                        stack.push(w);
+                        on_stack[w] = true;
+                        frames.push((w, 0));
+                    }
+                    Some(i) if on_stack[w] => low[v] = low[v].min(i),
+                    Some(_) => {}
+                }
+                continue;
+            }
+
+            frames.pop();
+            if let Some(&(parent, _)) = frames.last() {
+                low[parent] = low[parent].min(low[v]);
+            }
+            if Some(low[v]) == index_of[v] {
+                let mut component = Vec::new();
+                loop {
+                    let w = stack.pop().unwrap();
+                    on_stack[w] = false;
+                    component.push(Lit::from_index(w));
+                    if w == v {
+                        break;
+                    }
+                }
+                if component.len() > 1 {
+                    components.push(component);
+                }
+            }
+        }
+    }
+    components
+}
@@ -0,0 +1,258 @@
+use std::convert::Infallible;
+use std::{fmt, io};
+
+use crate::{Lit, PbConstraint};
+
+/// Reasons a clause can be rejected by `Solver::add_clause`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AddClauseError {
+    /// `0` is the DIMACS clause terminator and cannot appear as a literal.
+    ZeroLiteral,
+    /// The value does not fit the literal encoding.
+    OutOfRange(i32),
+}
+
+impl fmt::Display for AddClauseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ZeroLiteral => write!(f, "0 is not a valid literal"),
+            Self::OutOfRange(value) => write!(f, "literal {value} is out of range"),
+        }
+    }
+}
+
+impl std::error::Error for AddClauseError {}
+
+impl From<InvalidLiteral> for AddClauseError {
+    fn from(err: InvalidLiteral) -> Self {
+        match err.0 {
+            0 => Self::ZeroLiteral,
+            value => Self::OutOfRange(value),
+        }
+    }
+}
+
+impl From<Infallible> for AddClauseError {
+    fn from(err: Infallible) -> Self {
+        match err {}
+    }
+}
+
+/// A DIMACS integer that does not denote a literal or variable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidLiteral(pub i32);
+
+impl fmt::Display for InvalidLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is not a valid DIMACS literal", self.0)
+    }
+}
+
+impl std::error::Error for InvalidLiteral {}
+
+/// An input clause that the current assignment does not satisfy, as
+/// reported by `Solver::verify_model`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsatisfiedClause {
+    pub literals: Vec<Lit>,
+    /// Whether the constraint is an XOR clause, of which an odd number of
+    /// `literals` must be true.
+    pub xor: bool,
+    /// The pseudo-Boolean constraint over `literals`, if it is one.
+    pub pb: Option<PbConstraint>,
+}
+
+impl fmt::Display for UnsatisfiedClause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(pb) = &self.pb {
+            return write!(
+                f,
+                "pseudo-Boolean constraint `{pb}` is not satisfied by the model"
+            );
+        }
+        if self.xor {
+            write!(f, "XOR clause `x")?;
+        } else {
+            write!(f, "clause `")?;
+        }
+        for lit in &self.literals {
+            write!(f, "{lit} ")?;
+        }
+        write!(f, "0` is not satisfied by the model")
+    }
+}
+
+impl std::error::Error for UnsatisfiedClause {}
+
+/// A `SolverConfig` setting outside the values it may take, as reported
+/// by `SolverConfig::build`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidConfig {
+    /// Name of the `SolverConfig` field.
+    pub setting: &'static str,
+    /// The values it may take.
+    pub expected: &'static str,
+}
+
+impl fmt::Display for InvalidConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} must be {}", self.setting, self.expected)
+    }
+}
+
+impl std::error::Error for InvalidConfig {}
+
+/// A problem in a DIMACS CNF (or WCNF, ICNF, QDIMACS), OPB or AIGER input,
+/// or in a formula given to `Formula::parse`. Lines and columns are 1-based.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// No `p cnf <variables> <clauses>` (or `p wcnf ...`) line precedes the
+    /// first clause.
+    MissingHeader,
+    /// The problem line is malformed or appears more than once.
+    InvalidHeader { line: usize },
+    /// The `aag` or `aig` header of an AIGER input is malformed, or its
+    /// counts do not add up.
+    InvalidAigerHeader,
+    /// A literal refers to a variable above the count declared in the header.
+    LiteralOutOfRange {
+        line: usize,
+        column: usize,
+        literal: i64,
+        variables: usize,
+    },
+    /// The input ends in the middle of the clause starting on `line`.
+    UnterminatedClause { line: usize },
+    /// The body holds a different number of clauses than the header declares.
+    ClauseCountMismatch { declared: usize, found: usize },
+    /// The input ends in the middle of the OPB constraint starting on `line`.
+    UnterminatedConstraint { line: usize },
+    /// The formula ends before it is complete.
+    IncompleteFormula,
+    /// An AIGER input ends before the sections its header announces, around
+    /// `line`.
+    UnexpectedEnd { line: usize },
+    /// The quantifier prefix of a QDIMACS input alternates more than
+    /// `QbfSolver` handles, from the block on `line` on.
+    UnsupportedPrefix { line: usize },
+    /// A token that is not an integer, or out of place in an OPB statement
+    /// or a formula.
+    InvalidToken {
+        line: usize,
+        column: usize,
+        token: String,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingHeader => write!(f, "missing `p` header before the first clause"),
+            Self::InvalidHeader { line } => write!(f, "line {line}: invalid `p` header"),
+            Self::InvalidAigerHeader => write!(f, "line 1: invalid AIGER header"),
+            Self::LiteralOutOfRange {
+                line,
+                column,
+                literal,
+                variables,
+            } => write!(
+                f,
+                "line {line}, column {column}: literal {literal} exceeds the {variables} declared variables"
+            ),
+            Self::UnterminatedClause { line } => {
+                write!(f, "line {line}: clause is not terminated by 0")
+            }
+            Self::UnterminatedConstraint { line } => {
+                write!(f, "line {line}: constraint is not terminated by `;`")
+            }
+            Self::IncompleteFormula => write!(f, "the formula is incomplete"),
+            Self::UnexpectedEnd { line } => write!(f, "line {line}: unexpected end of input"),
+            Self::UnsupportedPrefix { line } => write!(
+                f,
+                "line {line}: only ∃ and ∀∃ quantifier prefixes (2QBF) are supported"
+            ),
+            Self::ClauseCountMismatch { declared, found } => write!(
+                f,
+                "header declares {declared} clauses but {found} were found"
+            ),
+            Self::InvalidToken {
+                line,
+                column,
+                token,
+            } => write!(f, "line {line}, column {column}: invalid token `{token}`"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Why `ProofChecker::check` rejected a proof. Steps, the additions and
+/// deletions of the proof, are numbered from 1 without its comment lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProofError {
+    /// The step cannot be read, adds an LRAT clause under an ID in use, or
+    /// names a clause ID that does not exist. RAT hints, which are
+    /// negative, are not supported in LRAT proofs.
+    Malformed { step: usize },
+    /// The clause added by the step does not follow from the clauses before
+    /// it: it is neither RUP nor RAT (DRAT), or its hints do not lead to a
+    /// conflict (LRAT).
+    NotImplied { step: usize, clause: Vec<Lit> },
+    /// Every step checks, but the empty clause is never added.
+    Incomplete,
+}
+
+impl fmt::Display for ProofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed { step } => write!(f, "step {step}: malformed proof step"),
+            Self::NotImplied { step, clause } => {
+                write!(f, "step {step}: clause `")?;
+                for lit in clause {
+                    write!(f, "{lit} ")?;
+                }
+                write!(f, "0` does not follow from the clauses before it")
+            }
+            Self::Incomplete => write!(f, "the proof does not derive the empty clause"),
+        }
+    }
+}
+
+impl std::error::Error for ProofError {}
+
+/// Failure to load a formula into a `Solver`.
+#[derive(Debug)]
+pub enum SolverError {
+    Io(io::Error),
+    Parse(ParseError),
+}
+
+impl fmt::Display for SolverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Parse(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for SolverError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Parse(err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for SolverError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<ParseError> for SolverError {
+    fn from(err: ParseError) -> Self {
+        Self::Parse(err)
+    }
+}
@@ -0,0 +1,147 @@
+use std::io::{self, Write};
+
+use crate::{Lit, Solver, Var};
+
+impl Solver {
+    /// Runs the preprocessing of the first `solve` call now: the passes
+    /// enabled in `config` (equivalent literal substitution, subsumption,
+    /// variable elimination), then propagation of the unit clauses and
+    /// failed literal probing if `config.probing` is set. The passes do not
+    /// run again at the next `solve`. Returns false if the formula turned
+    /// out to be unsatisfiable.
+    pub fn preprocess(&mut self) -> bool {
+        self.backtrack_to(0);
+        let first = !self.preprocessed;
+        if first {
+            self.run_preprocessing(&[]);
+        }
+        if !self.initial_propagation() || (first && self.config.probing && !self.probe()) {
+            self.log_refutation();
+            return false;
+        }
+        true
+    }
+
+    /// Writes the formula as it stands as DIMACS CNF, simplified by the
+    /// values fixed at level 0: satisfied clauses are left out, and so are
+    /// false literals. The variables left are renumbered from 1 in their
+    /// order, and comment lines before the header map them back:
+    ///
+    /// ```text
+    /// c map 1 3
+    /// c map 2 7
+    /// c fixed -5
+    /// p cnf 2 1
+    /// 1 -2 0
+    /// ```
+    ///
+    /// `c map NEW OLD` gives the number a variable had in the solver, and
+    /// `c fixed LIT` a literal fixed at level 0, which the formula no longer
+    /// mentions. XOR clauses are written as `x` lines, and at-most-one
+    /// groups as their binary clauses. Variables removed by
+    /// `preprocess` are left out, so a model of the written formula only
+    /// covers the input once this solver extends it. An unsatisfiable
+    /// formula is written as one empty clause. Fails with
+    /// `ErrorKind::Unsupported` when the solver holds native pseudo-Boolean
+    /// constraints, which DIMACS cannot express.
+    pub fn write_dimacs(&self, mut w: impl Write) -> io::Result<()> {
+        if !self.pbs.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "native pseudo-Boolean constraints have no DIMACS form",
+            ));
+        }
+        let fixed = |lit: Lit| self.value_at_level_zero(lit);
+
+        let stored = self
+            .clauses
+            .iter()
+            .filter(|&cref| !self.clauses.is_learnt(cref) || self.clauses[cref].is_empty())
+            .map(|cref| &self.clauses[cref]);
+        let pairs: Vec<[Lit; 2]> = self.amo_clauses().collect();
+        let mut clauses: Vec<Vec<Lit>> = Vec::new();
+        let mut refuted = false;
+        for clause in stored.chain(pairs.iter().map(|pair| &pair[..])) {
+            if clause.iter().any(|&lit| fixed(lit) == Some(true)) {
+                continue;
+            }
+            let literals: Vec<Lit> = clause
+                .iter()
+                .copied()
+                .filter(|&lit| fixed(lit).is_none())
+                .collect();
+            refuted |= literals.is_empty();
+            clauses.push(literals);
+        }
+        let mut xors: Vec<(Vec<Var>, bool)> = Vec::new();
+        for xor in self.xor_clauses() {
+            let mut rhs = xor.rhs();
+            let mut vars = Vec::new();
+            for &var in xor.vars() {
+                match fixed(var.positive()) {
+                    None => vars.push(var),
+                    Some(true) => rhs = !rhs,
+                    Some(false) => {}
+                }
+            }
+            if vars.is_empty() {
+                refuted |= rhs;
+            } else {
+                xors.push((vars, rhs));
+            }
+        }
+        if refuted {
+            writeln!(w, "p cnf 0 1")?;
+            return writeln!(w, "0");
+        }
+
+        // New numbers of the variables still mentioned, 0 for the others
+        let mut renamed = vec![0i64; self.num_vars() + 1];
+        let vars = clauses
+            .iter()
+            .flatten()
+            .map(|lit| lit.var())
+            .chain(xors.iter().flat_map(|(vars, _)| vars.iter().copied()));
+        for var in vars {
+            renamed[var.index()] = 1;
+        }
+        let mut count = 0;
+        for (old, new) in renamed.iter_mut().enumerate() {
+            if *new != 0 {
+                count += 1;
+                *new = count;
+                writeln!(w, "c map {count} {old}")?;
+            }
+        }
+        for var in (1..=self.num_vars()).map(Var::new) {
+            if let Some(value) = fixed(var.positive()) {
+                writeln!(w, "c fixed {}", Lit::new(var, !value))?;
+            }
+        }
+
+        let rename = |lit: Lit| {
+            let new = renamed[lit.var().index()];
+            if lit.sign() { -new } else { new }
+        };
+        writeln!(w, "p cnf {count} {}", clauses.len() + xors.len())?;
+        for clause in &clauses {
+            for &lit in clause {
+                write!(w, "{} ", rename(lit))?;
+            }
+            writeln!(w, "0")?;
+        }
+        for (vars, rhs) in &xors {
+            write!(w, "x")?;
+            for (i, &var) in vars.iter().enumerate() {
+                let lit = if i == 0 && !rhs {
+                    -var.positive()
+                } else {
+                    var.positive()
+                };
+                write!(w, "{} ", rename(lit))?;
+            }
+            writeln!(w, "0")?;
+        }
+        Ok(())
+    }
+}
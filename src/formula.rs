@@ -0,0 +1,489 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{Lit, Model, ParseError, Solver, Var};
+
+/// A propositional formula over named variables.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Formula {
+    Const(bool),
+    Var(String),
+    Not(Box<Formula>),
+    /// Conjunction of any number of formulas; true when there are none.
+    And(Vec<Formula>),
+    /// Disjunction of any number of formulas; false when there are none.
+    Or(Vec<Formula>),
+    Xor(Box<Formula>, Box<Formula>),
+    Iff(Box<Formula>, Box<Formula>),
+    /// `Ite(c, t, e)` is `t` where `c` holds and `e` elsewhere.
+    Ite(Box<Formula>, Box<Formula>, Box<Formula>),
+}
+
+impl Formula {
+    /// Parses a formula in infix syntax, such as `(a & !b) -> (c | d)`.
+    /// From the tightest binding to the loosest, the operators are `!` (or
+    /// `~`), `&`, `^` (exclusive or), `|`, `->` (right-associative), `<->`
+    /// and `c ? t : e`. Names are made of letters, digits and underscores,
+    /// not starting with a digit; `true` and `false` are the constants.
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut parser = Parser {
+            tokens: tokens(input),
+            position: 0,
+        };
+        let formula = parser.ite()?;
+        match parser.peek() {
+            None => Ok(formula),
+            Some(_) => Err(parser.invalid_token()),
+        }
+    }
+
+    pub fn var(name: impl Into<String>) -> Self {
+        Self::Var(name.into())
+    }
+
+    pub fn and(formulas: impl IntoIterator<Item = Formula>) -> Self {
+        Self::And(formulas.into_iter().collect())
+    }
+
+    pub fn or(formulas: impl IntoIterator<Item = Formula>) -> Self {
+        Self::Or(formulas.into_iter().collect())
+    }
+
+    pub fn xor(self, other: Formula) -> Self {
+        Self::Xor(Box::new(self), Box::new(other))
+    }
+
+    pub fn iff(self, other: Formula) -> Self {
+        Self::Iff(Box::new(self), Box::new(other))
+    }
+
+    /// `self → other`, that is `¬self ∨ other`.
+    pub fn implies(self, other: Formula) -> Self {
+        Self::Or(vec![!self, other])
+    }
+
+    pub fn ite(condition: Formula, then: Formula, otherwise: Formula) -> Self {
+        Self::Ite(Box::new(condition), Box::new(then), Box::new(otherwise))
+    }
+
+    /// Truth value of the formula when the variable named `name` has the
+    /// value `values(name)`.
+    pub fn eval(&self, values: &impl Fn(&str) -> bool) -> bool {
+        match self {
+            Self::Const(value) => *value,
+            Self::Var(name) => values(name),
+            Self::Not(f) => !f.eval(values),
+            Self::And(fs) => fs.iter().all(|f| f.eval(values)),
+            Self::Or(fs) => fs.iter().any(|f| f.eval(values)),
+            Self::Xor(a, b) => a.eval(values) != b.eval(values),
+            Self::Iff(a, b) => a.eval(values) == b.eval(values),
+            Self::Ite(c, t, e) => {
+                if c.eval(values) {
+                    t.eval(values)
+                } else {
+                    e.eval(values)
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for Formula {
+    type Err = ParseError;
+
+    fn from_str(input: &str) -> Result<Self, ParseError> {
+        Self::parse(input)
+    }
+}
+
+impl fmt::Display for Formula {
+    /// The formula in the syntax of `Formula::parse`, with parentheses
+    /// around every operand that is not a variable or constant.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operand = |f: &mut fmt::Formatter<'_>, formula: &Formula| match formula {
+            Self::Const(_) | Self::Var(_) | Self::Not(_) => write!(f, "{formula}"),
+            _ => write!(f, "({formula})"),
+        };
+        let operands = |f: &mut fmt::Formatter<'_>, formulas: &[Formula], separator| {
+            for (i, formula) in formulas.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " {separator} ")?;
+                }
+                operand(f, formula)?;
+            }
+            Ok(())
+        };
+        match self {
+            Self::Const(value) => write!(f, "{value}"),
+            Self::Var(name) => write!(f, "{name}"),
+            Self::Not(formula) => {
+                write!(f, "!")?;
+                operand(f, formula)
+            }
+            Self::And(formulas) if formulas.is_empty() => write!(f, "true"),
+            Self::Or(formulas) if formulas.is_empty() => write!(f, "false"),
+            Self::And(formulas) => operands(f, formulas, "&"),
+            Self::Or(formulas) => operands(f, formulas, "|"),
+            Self::Xor(a, b) | Self::Iff(a, b) => {
+                let separator = if matches!(self, Self::Xor(..)) {
+                    "^"
+                } else {
+                    "<->"
+                };
+                operand(f, a)?;
+                write!(f, " {separator} ")?;
+                operand(f, b)
+            }
+            Self::Ite(c, t, e) => {
+                operand(f, c)?;
+                write!(f, " ? ")?;
+                operand(f, t)?;
+                write!(f, " : ")?;
+                operand(f, e)
+            }
+        }
+    }
+}
+
+impl std::ops::Not for Formula {
+    type Output = Formula;
+
+    fn not(self) -> Formula {
+        match self {
+            Self::Not(f) => *f,
+            f => Self::Not(Box::new(f)),
+        }
+    }
+}
+
+/// Tseitin transformation of formulas into the clauses of a `Solver`.
+///
+/// Every named variable gets a solver variable of its own, and every
+/// connective an auxiliary variable defined as equivalent to it, so the
+/// clauses grow linearly with the formula where distributing it into CNF
+/// could blow up exponentially. Subformulas that occur more than once are
+/// defined once. The same encoder must be used with the same solver
+/// throughout.
+#[derive(Clone, Debug, Default)]
+pub struct Tseitin {
+    vars: HashMap<String, Var>,
+    names: HashMap<Var, String>,
+    // Literal equivalent to each subformula defined so far
+    defined: HashMap<Formula, Lit>,
+    // Subformula each auxiliary variable stands for
+    definitions: HashMap<Var, Formula>,
+    // Variable fixed to true, for constants
+    truth: Option<Var>,
+}
+
+impl Tseitin {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The solver variable of the variable named `name`, allocated on first
+    /// use.
+    pub fn var(&mut self, solver: &mut Solver, name: &str) -> Var {
+        if let Some(&var) = self.vars.get(name) {
+            return var;
+        }
+        let var = solver.new_var();
+        self.vars.insert(name.to_string(), var);
+        self.names.insert(var, name.to_string());
+        var
+    }
+
+    /// The solver variable of `name`, if a formula mentioned it.
+    pub fn var_of(&self, name: &str) -> Option<Var> {
+        self.vars.get(name).copied()
+    }
+
+    /// The name of `var`, if it stands for a named variable.
+    pub fn name_of(&self, var: Var) -> Option<&str> {
+        self.names.get(&var).map(String::as_str)
+    }
+
+    /// The subformula that the auxiliary variable `var` is equivalent to.
+    pub fn definition(&self, var: Var) -> Option<&Formula> {
+        self.definitions.get(&var)
+    }
+
+    /// The named variables with their values in `model`, sorted by name.
+    pub fn named_values(&self, model: &Model) -> Vec<(&str, bool)> {
+        let mut values: Vec<(&str, bool)> = self
+            .vars
+            .iter()
+            .map(|(name, &var)| (name.as_str(), model.value(var)))
+            .collect();
+        values.sort_unstable();
+        values
+    }
+
+    /// Adds the clauses that make `formula` hold. Conjunctions are split
+    /// and disjunctions become a single clause, so only the subformulas
+    /// below them need auxiliary variables.
+    pub fn assert(&mut self, solver: &mut Solver, formula: &Formula) {
+        match formula {
+            Formula::And(fs) => {
+                for f in fs {
+                    self.assert(solver, f);
+                }
+            }
+            Formula::Or(fs) => {
+                let clause = fs.iter().map(|f| self.encode(solver, f)).collect();
+                solver.add_input_clause(clause);
+            }
+            Formula::Not(f) => match &**f {
+                Formula::Or(fs) => {
+                    for f in fs {
+                        self.assert(solver, &!f.clone());
+                    }
+                }
+                Formula::And(fs) => {
+                    let clause = fs.iter().map(|f| -self.encode(solver, f)).collect();
+                    solver.add_input_clause(clause);
+                }
+                _ => {
+                    let lit = self.encode(solver, formula);
+                    solver.add_input_clause(vec![lit]);
+                }
+            },
+            _ => {
+                let lit = self.encode(solver, formula);
+                solver.add_input_clause(vec![lit]);
+            }
+        }
+    }
+
+    /// A literal equivalent to `formula`: that of its variable, or of an
+    /// auxiliary variable defined by clauses added to `solver`.
+    pub fn encode(&mut self, solver: &mut Solver, formula: &Formula) -> Lit {
+        match formula {
+            Formula::Const(value) => {
+                let truth = *self.truth.get_or_insert_with(|| {
+                    let var = solver.new_var();
+                    solver.add_input_clause(vec![var.positive()]);
+                    var
+                });
+                Lit::new(truth, !value)
+            }
+            Formula::Var(name) => self.var(solver, name).positive(),
+            Formula::Not(f) => -self.encode(solver, f),
+            _ => {
+                if let Some(&lit) = self.defined.get(formula) {
+                    return lit;
+                }
+                let lit = self.define(solver, formula);
+                self.defined.insert(formula.clone(), lit);
+                self.definitions.insert(lit.var(), formula.clone());
+                lit
+            }
+        }
+    }
+
+    /// Defines a fresh variable as equivalent to the connective at the top
+    /// of `formula`, over the literals of its operands.
+    fn define(&mut self, solver: &mut Solver, formula: &Formula) -> Lit {
+        let out;
+        let clauses: Vec<Vec<Lit>> = match formula {
+            // out ↔ (x1 ∧ … ∧ xn), and a disjunction is the negation of the
+            // conjunction of the negated operands
+            Formula::And(fs) | Formula::Or(fs) => {
+                let or = matches!(formula, Formula::Or(_));
+                let xs: Vec<Lit> = fs
+                    .iter()
+                    .map(|f| self.encode(solver, f))
+                    .map(|x| if or { -x } else { x })
+                    .collect();
+                out = solver.new_var().positive();
+                let and = if or { -out } else { out };
+                let mut clauses: Vec<Vec<Lit>> = xs.iter().map(|&x| vec![-and, x]).collect();
+                clauses.push(xs.iter().map(|&x| -x).chain([and]).collect());
+                clauses
+            }
+            // out ↔ (a ⊕ b), and an equivalence is the negation of that
+            Formula::Xor(a, b) | Formula::Iff(a, b) => {
+                let (a, b) = (self.encode(solver, a), self.encode(solver, b));
+                out = solver.new_var().positive();
+                let xor = if matches!(formula, Formula::Iff(..)) {
+                    -out
+                } else {
+                    out
+                };
+                vec![
+                    vec![-xor, a, b],
+                    vec![-xor, -a, -b],
+                    vec![xor, -a, b],
+                    vec![xor, a, -b],
+                ]
+            }
+            // out ↔ (c ? t : e), with two more clauses that give `out`
+            // from `t = e` whatever `c` is
+            Formula::Ite(c, t, e) => {
+                let c = self.encode(solver, c);
+                let t = self.encode(solver, t);
+                let e = self.encode(solver, e);
+                out = solver.new_var().positive();
+                vec![
+                    vec![-c, -t, out],
+                    vec![-c, t, -out],
+                    vec![c, -e, out],
+                    vec![c, e, -out],
+                    vec![-t, -e, out],
+                    vec![t, e, -out],
+                ]
+            }
+            Formula::Const(_) | Formula::Var(_) | Formula::Not(_) => unreachable!(),
+        };
+        for clause in clauses {
+            solver.add_input_clause(clause);
+        }
+        out
+    }
+}
+
+/// Tokens of the infix syntax, with their lines and columns.
+fn tokens(input: &str) -> Vec<(usize, usize, &str)> {
+    let mut tokens = Vec::new();
+    for (line_index, line) in input.lines().enumerate() {
+        let mut rest = line;
+        while let Some(start) = rest.find(|c: char| !c.is_whitespace()) {
+            rest = &rest[start..];
+            let len = if rest.starts_with("<->") {
+                3
+            } else if rest.starts_with("->") {
+                2
+            } else if rest.starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+                rest.find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len())
+            } else {
+                rest.chars().next().unwrap().len_utf8()
+            };
+            let offset = rest.as_ptr() as usize - line.as_ptr() as usize;
+            let column = line[..offset].chars().count() + 1;
+            tokens.push((line_index + 1, column, &rest[..len]));
+            rest = &rest[len..];
+        }
+    }
+    tokens
+}
+
+/// Recursive descent over the tokens, one method per precedence level.
+struct Parser<'a> {
+    tokens: Vec<(usize, usize, &'a str)>,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.position).map(|&(_, _, token)| token)
+    }
+
+    /// Consumes the next token if it is `token`.
+    fn eat(&mut self, token: &str) -> bool {
+        let found = self.peek() == Some(token);
+        self.position += usize::from(found);
+        found
+    }
+
+    fn invalid_token(&self) -> ParseError {
+        match self.tokens.get(self.position) {
+            Some(&(line, column, token)) => ParseError::InvalidToken {
+                line,
+                column,
+                token: token.to_string(),
+            },
+            None => ParseError::IncompleteFormula,
+        }
+    }
+
+    // c ? t : e, the loosest
+    fn ite(&mut self) -> Result<Formula, ParseError> {
+        let condition = self.iff()?;
+        if !self.eat("?") {
+            return Ok(condition);
+        }
+        let then = self.ite()?;
+        if !self.eat(":") {
+            return Err(self.invalid_token());
+        }
+        let otherwise = self.ite()?;
+        Ok(Formula::ite(condition, then, otherwise))
+    }
+
+    fn iff(&mut self) -> Result<Formula, ParseError> {
+        let mut formula = self.implies()?;
+        while self.eat("<->") {
+            formula = formula.iff(self.implies()?);
+        }
+        Ok(formula)
+    }
+
+    // Right-associative: a -> b -> c is a -> (b -> c)
+    fn implies(&mut self) -> Result<Formula, ParseError> {
+        let premise = self.or()?;
+        if !self.eat("->") {
+            return Ok(premise);
+        }
+        Ok(premise.implies(self.implies()?))
+    }
+
+    fn or(&mut self) -> Result<Formula, ParseError> {
+        let mut operands = vec![self.xor()?];
+        while self.eat("|") {
+            operands.push(self.xor()?);
+        }
+        Ok(flatten(operands, Formula::Or))
+    }
+
+    fn xor(&mut self) -> Result<Formula, ParseError> {
+        let mut formula = self.and()?;
+        while self.eat("^") {
+            formula = formula.xor(self.and()?);
+        }
+        Ok(formula)
+    }
+
+    fn and(&mut self) -> Result<Formula, ParseError> {
+        let mut operands = vec![self.unary()?];
+        while self.eat("&") {
+            operands.push(self.unary()?);
+        }
+        Ok(flatten(operands, Formula::And))
+    }
+
+    fn unary(&mut self) -> Result<Formula, ParseError> {
+        if self.eat("!") || self.eat("~") {
+            return Ok(!self.unary()?);
+        }
+        let Some(token) = self.peek() else {
+            return Err(ParseError::IncompleteFormula);
+        };
+        let formula = match token {
+            "(" => {
+                self.position += 1;
+                let formula = self.ite()?;
+                if !self.eat(")") {
+                    return Err(self.invalid_token());
+                }
+                return Ok(formula);
+            }
+            "true" => Formula::Const(true),
+            "false" => Formula::Const(false),
+            name if name.starts_with(|c: char| c.is_alphabetic() || c == '_') => Formula::var(name),
+            _ => return Err(self.invalid_token()),
+        };
+        self.position += 1;
+        Ok(formula)
+    }
+}
+
+/// A single operand, or the connective over all of them.
+fn flatten(mut operands: Vec<Formula>, connective: fn(Vec<Formula>) -> Formula) -> Formula {
+    if operands.len() == 1 {
+        operands.pop().unwrap()
+    } else {
+        connective(operands)
+    }
+}
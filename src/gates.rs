@@ -0,0 +1,280 @@
+use std::fmt;
+
+use crate::occ::OccLists;
+use crate::{ClauseArena, ClauseRef, Lit, Solver, Var};
+
+/// XOR gates with more inputs take too many clauses to be worth looking
+/// for: `2^inputs` of them.
+const MAX_XOR_INPUTS: usize = 4;
+
+/// A gate recovered from the clauses that define its output, as the
+/// Tseitin transformation writes them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Gate {
+    /// `output ↔ inputs[0] ∧ inputs[1] ∧ …`, from the clauses
+    /// `-output ∨ input` for every input and `output ∨ -inputs[0] ∨ …`. An
+    /// OR gate is the AND gate of its negated inputs, with the negated
+    /// output.
+    And { output: Lit, inputs: Vec<Lit> },
+    /// `output ↔ inputs[0] ⊕ inputs[1] ⊕ …`, from the clauses that forbid
+    /// each assignment of the wrong parity. The inputs are positive, and
+    /// any of the variables could be taken as the output: it is the last
+    /// one.
+    Xor { output: Lit, inputs: Vec<Lit> },
+    /// `output ↔ (condition ? then : otherwise)`, from the clauses
+    /// `-condition ∨ -then ∨ output`, `-condition ∨ then ∨ -output`,
+    /// `condition ∨ -otherwise ∨ output` and `condition ∨ otherwise ∨
+    /// -output`. The condition and the output are positive.
+    Ite {
+        output: Lit,
+        condition: Lit,
+        then: Lit,
+        otherwise: Lit,
+    },
+}
+
+impl Gate {
+    pub fn output(&self) -> Lit {
+        match self {
+            Gate::And { output, .. } | Gate::Xor { output, .. } | Gate::Ite { output, .. } => {
+                *output
+            }
+        }
+    }
+
+    /// The literals the output is a function of; for an ITE gate, the
+    /// condition, then-branch and else-branch in that order.
+    pub fn inputs(&self) -> Vec<Lit> {
+        match self {
+            Gate::And { inputs, .. } | Gate::Xor { inputs, .. } => inputs.clone(),
+            Gate::Ite {
+                condition,
+                then,
+                otherwise,
+                ..
+            } => vec![*condition, *then, *otherwise],
+        }
+    }
+}
+
+impl fmt::Display for Gate {
+    /// The gate as `output = and(inputs)`, `xor(…)` or `ite(…)`, literals
+    /// written as DIMACS integers.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Gate::And { .. } => "and",
+            Gate::Xor { .. } => "xor",
+            Gate::Ite { .. } => "ite",
+        };
+        write!(f, "{} = {name}(", self.output())?;
+        for (i, lit) in self.inputs().iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{lit}")?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl Solver {
+    /// The gates that the input clauses define, at most one per output
+    /// variable, in increasing order of output. Only
+    /// clauses are looked at, as they stand: after preprocessing, without
+    /// the clauses it removed, and without XOR clauses, at-most-one groups
+    /// or native pseudo-Boolean constraints.
+    pub fn extract_gates(&self) -> Vec<Gate> {
+        let mut occ = OccLists::new(&self.clauses, self.watch_lists.len());
+        (1..=self.num_vars())
+            .map(Var::new)
+            .filter_map(|var| find_definition(&self.clauses, &mut occ, var))
+            .map(|(gate, _)| gate)
+            .collect()
+    }
+}
+
+/// A gate with output `var` among the clauses indexed by `occ`, with the
+/// clauses that define it, all of which contain `var`. AND gates are tried
+/// first, then ITE and XOR gates.
+pub(crate) fn find_definition(
+    clauses: &ClauseArena,
+    occ: &mut OccLists,
+    var: Var,
+) -> Option<(Gate, Vec<ClauseRef>)> {
+    if let Some(found) = and_gate(clauses, occ, var.positive()) {
+        return Some(found);
+    }
+    if let Some(found) = and_gate(clauses, occ, var.negative()) {
+        return Some(found);
+    }
+    if let Some(found) = ite_gate(clauses, occ, var) {
+        return Some(found);
+    }
+    xor_gate(clauses, occ, var)
+}
+
+/// `output ↔ ∧ inputs`: a clause with `output` in which the negation of
+/// every other literal is implied by `output` through a binary clause.
+fn and_gate(
+    clauses: &ClauseArena,
+    occ: &mut OccLists,
+    output: Lit,
+) -> Option<(Gate, Vec<ClauseRef>)> {
+    let implied: Vec<(Lit, ClauseRef)> = occ
+        .lookup(clauses, -output)
+        .iter()
+        .filter_map(|&cref| match clauses[cref] {
+            [a, b] => Some((if a == -output { b } else { a }, cref)),
+            _ => None,
+        })
+        .collect();
+    if implied.len() < 2 {
+        return None;
+    }
+    let binary = |lit: Lit| {
+        implied
+            .iter()
+            .find(|&&(other, _)| other == lit)
+            .map(|&(_, cref)| cref)
+    };
+    for &cref in occ.lookup(clauses, output) {
+        let clause = &clauses[cref];
+        if clause.len() < 3 {
+            continue;
+        }
+        let inputs: Vec<Lit> = clause
+            .iter()
+            .filter(|&&lit| lit != output)
+            .map(|&lit| -lit)
+            .collect();
+        let Some(binaries) = inputs
+            .iter()
+            .map(|&input| binary(input))
+            .collect::<Option<Vec<_>>>()
+        else {
+            continue;
+        };
+        let mut defining = binaries;
+        defining.push(cref);
+        return Some((Gate::And { output, inputs }, defining));
+    }
+    None
+}
+
+/// `var ↔ (c ? t : e)`: the clauses `-c ∨ t ∨ -var` and `-c ∨ -t ∨ var`,
+/// then `c ∨ e ∨ -var` and `c ∨ -e ∨ var`, with `e` other than `t` and
+/// `-t` (which make an equivalence and an XOR gate). The clauses
+/// `-t ∨ -e ∨ var` and `t ∨ e ∨ -var` are taken in too if there.
+fn ite_gate(clauses: &ClauseArena, occ: &mut OccLists, var: Var) -> Option<(Gate, Vec<ClauseRef>)> {
+    let output = var.positive();
+    let ternary: Vec<ClauseRef> = occ
+        .lookup(clauses, -output)
+        .iter()
+        .copied()
+        .filter(|&cref| clauses[cref].len() == 3)
+        .collect();
+    for &first in &ternary {
+        let others: Vec<Lit> = clauses[first]
+            .iter()
+            .copied()
+            .filter(|&lit| lit != -output)
+            .collect();
+        for (not_c, t) in [(others[0], others[1]), (others[1], others[0])] {
+            let c = -not_c;
+            let Some(second) = find_clause(clauses, occ, &[-c, -t, output]) else {
+                continue;
+            };
+            for &third in &ternary {
+                if third == first || !clauses[third].contains(&c) {
+                    continue;
+                }
+                let Some(e) = clauses[third]
+                    .iter()
+                    .copied()
+                    .find(|&lit| lit != c && lit != -output)
+                else {
+                    continue;
+                };
+                if e.var() == t.var() {
+                    continue;
+                }
+                let Some(fourth) = find_clause(clauses, occ, &[c, -e, output]) else {
+                    continue;
+                };
+                let mut defining = vec![first, second, third, fourth];
+                defining.extend(find_clause(clauses, occ, &[-t, -e, output]));
+                defining.extend(find_clause(clauses, occ, &[t, e, -output]));
+                // `c ? t : e` is `-c ? e : t`
+                let (condition, then, otherwise) = if c.sign() { (-c, e, t) } else { (c, t, e) };
+                let gate = Gate::Ite {
+                    output,
+                    condition,
+                    then,
+                    otherwise,
+                };
+                return Some((gate, defining));
+            }
+        }
+    }
+    None
+}
+
+/// `var` as the XOR of the other variables of some clause with `var` of
+/// up to `MAX_XOR_INPUTS + 1` literals: every clause over those variables
+/// with as many negations as that one, modulo 2, is there. `var` must be
+/// the last of the variables, as a Tseitin variable comes after those it
+/// is defined from, so that each XOR gate is found once.
+fn xor_gate(clauses: &ClauseArena, occ: &mut OccLists, var: Var) -> Option<(Gate, Vec<ClauseRef>)> {
+    let mut candidates: Vec<ClauseRef> = occ.lookup(clauses, var.positive()).to_vec();
+    candidates.extend_from_slice(occ.lookup(clauses, var.negative()));
+    for cref in candidates {
+        let clause = &clauses[cref];
+        if !(3..=MAX_XOR_INPUTS + 1).contains(&clause.len()) {
+            continue;
+        }
+        let vars: Vec<Var> = clause.iter().map(|lit| lit.var()).collect();
+        if vars.iter().any(|&other| other > var) {
+            continue;
+        }
+        let negations = clause.iter().filter(|lit| lit.sign()).count() % 2;
+        let mut defining = Vec::new();
+        for signs in 0u32..1 << vars.len() {
+            if signs.count_ones() as usize % 2 != negations {
+                continue;
+            }
+            let literals: Vec<Lit> = vars
+                .iter()
+                .enumerate()
+                .map(|(i, v)| Lit::new(*v, signs >> i & 1 == 1))
+                .collect();
+            match find_clause(clauses, occ, &literals) {
+                Some(found) => defining.push(found),
+                None => break,
+            }
+        }
+        if defining.len() < 1 << (vars.len() - 1) {
+            continue;
+        }
+        // An odd number of negations forbids the assignments with an odd
+        // number of true variables: their XOR is false
+        let output = Lit::new(var, negations == 0);
+        let inputs = vars
+            .iter()
+            .filter(|&&v| v != var)
+            .map(|v| v.positive())
+            .collect();
+        return Some((Gate::Xor { output, inputs }, defining));
+    }
+    None
+}
+
+/// An input clause made of exactly `literals`, in any order.
+fn find_clause(clauses: &ClauseArena, occ: &mut OccLists, literals: &[Lit]) -> Option<ClauseRef> {
+    occ.lookup(clauses, literals[0])
+        .iter()
+        .copied()
+        .find(|&cref| {
+            let clause = &clauses[cref];
+            clause.len() == literals.len() && literals.iter().all(|lit| clause.contains(lit))
+        })
+}
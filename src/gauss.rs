@@ -0,0 +1,191 @@
+use crate::xor::parity_clause;
+use crate::{Assignment, ClauseRef, LBool, Solver, Var, XorClause};
+
+/// One equation of the matrix: the XOR of the variables whose column bits
+/// are set equals `rhs`. Every row is a sum of XOR clauses.
+#[derive(Clone, Debug)]
+struct Row {
+    bits: Vec<u64>,
+    rhs: bool,
+    /// Column eliminated from every other row. Only meaningful while its
+    /// variable is unassigned; otherwise the row needs a new one.
+    pivot: Option<usize>,
+}
+
+impl Row {
+    #[inline]
+    fn has(&self, column: usize) -> bool {
+        self.bits[column / 64] >> (column % 64) & 1 == 1
+    }
+
+    fn add(&mut self, other: &Row) {
+        for (word, &theirs) in self.bits.iter_mut().zip(&other.bits) {
+            *word ^= theirs;
+        }
+        self.rhs ^= other.rhs;
+    }
+
+    fn columns(&self) -> impl Iterator<Item = usize> + '_ {
+        self.bits.iter().enumerate().flat_map(|(i, &word)| {
+            (0..64)
+                .filter(move |bit| word >> bit & 1 == 1)
+                .map(move |bit| i * 64 + bit)
+        })
+    }
+}
+
+/// The XOR clauses as a system of linear equations over GF(2), kept in
+/// reduced row echelon form with respect to the unassigned variables: every
+/// row that still has an unassigned variable pivots on one, and pivots
+/// occur in no other row. A row left with one unassigned variable implies
+/// its value, and a row with none that misses its parity is a conflict, so
+/// the matrix finds every consequence of the XOR clauses taken together,
+/// where watching them one at a time only finds those of single clauses.
+///
+/// Assignments only ever take pivots away, so the matrix is repaired in
+/// place as the search goes deeper, and the rows as they were at each
+/// decision level are saved for backtracking.
+pub(crate) struct GaussMatrix {
+    /// Variable of each column.
+    columns: Vec<Var>,
+    rows: Vec<Row>,
+    /// Rows as they were before their first change at each decision level
+    /// above 0, deepest last.
+    saved: Vec<(usize, Vec<Row>)>,
+}
+
+impl GaussMatrix {
+    /// A matrix with a row per XOR clause and a column per variable occurring
+    /// in one. No pivot is chosen yet.
+    pub fn new(xors: &[XorClause], variable_count: usize) -> Self {
+        let mut column_of = vec![None; variable_count + 1];
+        let mut columns = Vec::new();
+        for &var in xors.iter().flat_map(|xor| xor.vars()) {
+            if column_of[var.index()].is_none() {
+                column_of[var.index()] = Some(columns.len());
+                columns.push(var);
+            }
+        }
+        let words = columns.len().div_ceil(64);
+        let rows = xors
+            .iter()
+            .map(|xor| {
+                let mut bits = vec![0; words];
+                for var in xor.vars() {
+                    let column = column_of[var.index()].unwrap();
+                    bits[column / 64] |= 1 << (column % 64);
+                }
+                Row {
+                    bits,
+                    rhs: xor.rhs(),
+                    pivot: None,
+                }
+            })
+            .collect();
+        Self {
+            columns,
+            rows,
+            saved: Vec::new(),
+        }
+    }
+
+    /// Restores the rows as they were at decision level `level`.
+    pub fn backtrack_to(&mut self, level: usize) {
+        while let Some((saved_level, _)) = self.saved.last()
+            && *saved_level > level
+        {
+            self.rows = self.saved.pop().unwrap().1;
+        }
+    }
+
+    /// Gives a new pivot to every row whose pivot was assigned, eliminating
+    /// it from the other rows, and returns the rows left with at most one
+    /// unassigned variable. `level` is the current decision level.
+    fn update(&mut self, assignments: &Assignment, level: usize) -> Vec<usize> {
+        let columns = &self.columns;
+        let unassigned = |column: usize| !assignments.is_assigned(columns[column]);
+        let mut saved = self.saved.last().is_some_and(|&(l, _)| l == level) || level == 0;
+
+        for r in 0..self.rows.len() {
+            if self.rows[r].pivot.is_some_and(unassigned) {
+                continue;
+            }
+            let Some(pivot) = self.rows[r].columns().find(|&c| unassigned(c)) else {
+                continue;
+            };
+            if !saved {
+                self.saved.push((level, self.rows.clone()));
+                saved = true;
+            }
+            self.rows[r].pivot = Some(pivot);
+            let row = self.rows[r].clone();
+            for (s, other) in self.rows.iter_mut().enumerate() {
+                if s != r && other.has(pivot) {
+                    other.add(&row);
+                }
+            }
+        }
+
+        (0..self.rows.len())
+            .filter(|&r| {
+                self.rows[r]
+                    .columns()
+                    .filter(|&c| unassigned(c))
+                    .nth(1)
+                    .is_none()
+            })
+            .collect()
+    }
+
+    /// Variables of row `r`.
+    fn vars(&self, r: usize) -> Vec<Var> {
+        self.rows[r].columns().map(|c| self.columns[c]).collect()
+    }
+}
+
+impl Solver {
+    /// Runs Gaussian elimination over the XOR clauses once propagation has
+    /// settled, asserting the values it implies; returns a conflict if the
+    /// XOR clauses cannot be met under the current assignment.
+    pub(crate) fn gauss_propagate(&mut self) -> Option<ClauseRef> {
+        let mut matrix = self.gauss.take()?;
+        let rows = matrix.update(&self.assignments, self.trail.decision_level());
+        let mut conflict = None;
+        for r in rows {
+            let vars = matrix.vars(r);
+            let rhs = matrix.rows[r].rhs;
+            let Some(&implied) = vars
+                .iter()
+                .find(|&&v| !self.assignments.is_assigned(v))
+                .or(vars.first())
+            else {
+                // All of the XOR clauses together are contradictory
+                if rhs {
+                    conflict = Some(self.clauses.push_lazy_reason(&[]));
+                    self.lazy_conflict = conflict;
+                    break;
+                }
+                continue;
+            };
+            let clause = parity_clause(&self.assignments, &vars, rhs, implied);
+            match self.assignments.value(clause[0]) {
+                LBool::True => continue,
+                LBool::False => self.stats.gauss_conflicts += 1,
+                LBool::Undef => self.stats.gauss_propagations += 1,
+            }
+            if let Some(cref) = self.assert_lazy(clause) {
+                conflict = Some(cref);
+                break;
+            }
+        }
+        self.gauss = Some(matrix);
+        conflict
+    }
+
+    /// Builds the Gauss matrix of the XOR clauses, if `config.gauss` asks
+    /// for one. Called at level 0 before each search.
+    pub(crate) fn build_gauss_matrix(&mut self) {
+        self.gauss = (self.config.gauss && !self.xors.is_empty())
+            .then(|| GaussMatrix::new(&self.xors, self.num_vars()));
+    }
+}
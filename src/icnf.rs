@@ -0,0 +1,154 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+use crate::compression;
+use crate::error::{ParseError, SolverError};
+use crate::lit::{Lit, Var};
+
+/// An incremental CNF trace in the ICNF format, as written by cube-and-
+/// conquer splitters and by tools that log IPASIR calls:
+///
+/// ```text
+/// p inccnf
+/// 1 2 0
+/// a -1 0
+/// -2 3 0
+/// a -1 -3 0
+/// ```
+///
+/// Clauses are added as they come, and each `a` line asks whether the
+/// clauses so far are satisfiable under its literals as assumptions. The
+/// header declares no counts; the variables are those that occur.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Icnf {
+    /// Highest variable used.
+    pub variable_count: usize,
+    /// Clauses and queries, in the order of the file.
+    pub steps: Vec<IcnfStep>,
+}
+
+/// A line of an ICNF trace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IcnfStep {
+    /// A clause to add for good.
+    Clause(Vec<Lit>),
+    /// A query: a `solve` under these assumptions.
+    Assume(Vec<Lit>),
+}
+
+impl Icnf {
+    /// Loads an ICNF file, possibly compressed like the inputs of
+    /// `Solver::new`.
+    pub fn new(path: &str) -> Result<Self, SolverError> {
+        let file = BufReader::new(File::open(path)?);
+        Self::from_reader(compression::decompress(file)?)
+    }
+
+    /// Reads an ICNF trace from any buffered reader. Clauses and assumption
+    /// lists may span several lines and must each be terminated by `0`.
+    pub fn from_reader(reader: impl BufRead) -> Result<Self, SolverError> {
+        parse(reader)
+    }
+
+    /// Parses an ICNF trace held in memory.
+    pub fn from_icnf_str(icnf: &str) -> Result<Self, SolverError> {
+        Self::from_reader(icnf.as_bytes())
+    }
+
+    /// The clauses of the trace, leaving out the queries.
+    pub fn clauses(&self) -> impl Iterator<Item = &[Lit]> {
+        self.steps.iter().filter_map(|step| match step {
+            IcnfStep::Clause(clause) => Some(clause.as_slice()),
+            IcnfStep::Assume(_) => None,
+        })
+    }
+
+    /// Number of queries, `a` lines, in the trace.
+    pub fn query_count(&self) -> usize {
+        self.steps
+            .iter()
+            .filter(|step| matches!(step, IcnfStep::Assume(_)))
+            .count()
+    }
+}
+
+fn parse(reader: impl BufRead) -> Result<Icnf, SolverError> {
+    let mut icnf = Icnf {
+        variable_count: 0,
+        steps: Vec::new(),
+    };
+    let mut header = false;
+    let mut current: Vec<Lit> = Vec::new();
+    let mut assume = false;
+    let mut current_line = 0;
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line_number = index + 1;
+        if line.starts_with('c') {
+            continue;
+        }
+        if line.starts_with('p') {
+            if header || line.split_whitespace().ne(["p", "inccnf"]) {
+                return Err(ParseError::InvalidHeader { line: line_number }.into());
+            }
+            header = true;
+            continue;
+        }
+
+        for (column, token) in tokens(&line) {
+            if !header {
+                return Err(ParseError::MissingHeader.into());
+            }
+            if current.is_empty() && !assume {
+                current_line = line_number;
+                if token == "a" {
+                    assume = true;
+                    continue;
+                }
+            }
+            let value: i64 = token.parse().map_err(|_| ParseError::InvalidToken {
+                line: line_number,
+                column,
+                token: token.to_string(),
+            })?;
+            if value == 0 {
+                let literals = std::mem::take(&mut current);
+                icnf.steps.push(if std::mem::take(&mut assume) {
+                    IcnfStep::Assume(literals)
+                } else {
+                    IcnfStep::Clause(literals)
+                });
+                continue;
+            }
+            if value.unsigned_abs() > Var::MAX_INDEX as u64 {
+                return Err(ParseError::LiteralOutOfRange {
+                    line: line_number,
+                    column,
+                    literal: value,
+                    variables: Var::MAX_INDEX,
+                }
+                .into());
+            }
+            let var = Var::new(value.unsigned_abs() as usize);
+            icnf.variable_count = icnf.variable_count.max(var.index());
+            current.push(Lit::new(var, value < 0));
+        }
+    }
+
+    if !current.is_empty() || assume {
+        return Err(ParseError::UnterminatedClause { line: current_line }.into());
+    }
+    if !header {
+        return Err(ParseError::MissingHeader.into());
+    }
+    Ok(icnf)
+}
+
+/// Whitespace-separated tokens of `line` with their 1-based columns.
+fn tokens(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    line.split_whitespace().map(move |token| {
+        let offset = token.as_ptr() as usize - line.as_ptr() as usize;
+        (line[..offset].chars().count() + 1, token)
+    })
+}
@@ -0,0 +1,100 @@
+use std::collections::HashSet;
+
+use crate::{Formula, Lit, ResolutionRule, Solver};
+
+impl Solver {
+    /// A Craig interpolant of an unsatisfiable formula split into the
+    /// input clauses `a_clauses`, by ID as in `ClauseInfo::id`, and the rest:
+    /// a formula implied by the first part, unsatisfiable together with the
+    /// second, and over the variables the two parts share only. Variable
+    /// `n` is named `x{n}`.
+    ///
+    /// It follows McMillan's rules along `resolution_proof`, so the same
+    /// conditions apply and the parts are those of the input clauses the
+    /// refutation uses. An input clause of the first part stands for its
+    /// literals over shared variables, one of the second part for true,
+    /// and a resolvent for the disjunction of what its two premises stand
+    /// for if the pivot only occurs in the first part, their conjunction
+    /// otherwise. A step used more than once is copied into each of its
+    /// uses, so the interpolant may grow much larger than the proof.
+    pub fn interpolant(&self, a_clauses: &[u64]) -> Option<Formula> {
+        let proof = self.resolution_proof()?;
+        let in_a: HashSet<u64> = a_clauses.iter().copied().collect();
+        let mut b_vars = HashSet::new();
+        for step in proof.steps() {
+            if let ResolutionRule::Input { id } = step.rule
+                && !in_a.contains(&id)
+            {
+                b_vars.extend(step.clause.iter().map(|lit| lit.var()));
+            }
+        }
+
+        let mut partial: Vec<Formula> = Vec::with_capacity(proof.steps().len());
+        for step in proof.steps() {
+            let formula = match step.rule {
+                ResolutionRule::Input { id } if in_a.contains(&id) => or(step
+                    .clause
+                    .iter()
+                    .filter(|lit| b_vars.contains(&lit.var()))
+                    .map(|&lit| literal(lit))
+                    .collect()),
+                ResolutionRule::Input { .. } => Formula::Const(true),
+                ResolutionRule::Resolve {
+                    positive,
+                    negative,
+                    pivot,
+                } => {
+                    let premises = vec![partial[positive].clone(), partial[negative].clone()];
+                    if b_vars.contains(&pivot) {
+                        and(premises)
+                    } else {
+                        or(premises)
+                    }
+                }
+            };
+            partial.push(formula);
+        }
+        partial.pop()
+    }
+}
+
+fn literal(lit: Lit) -> Formula {
+    let var = Formula::var(format!("x{}", lit.var()));
+    if lit.sign() { !var } else { var }
+}
+
+/// Disjunction of `formulas`, flattened and with constants folded.
+fn or(formulas: Vec<Formula>) -> Formula {
+    let mut operands = Vec::new();
+    for formula in formulas {
+        match formula {
+            Formula::Const(true) => return Formula::Const(true),
+            Formula::Const(false) => {}
+            Formula::Or(inner) => operands.extend(inner),
+            formula => operands.push(formula),
+        }
+    }
+    match operands.len() {
+        0 => Formula::Const(false),
+        1 => operands.pop().unwrap(),
+        _ => Formula::Or(operands),
+    }
+}
+
+/// Conjunction of `formulas`, flattened and with constants folded.
+fn and(formulas: Vec<Formula>) -> Formula {
+    let mut operands = Vec::new();
+    for formula in formulas {
+        match formula {
+            Formula::Const(false) => return Formula::Const(false),
+            Formula::Const(true) => {}
+            Formula::And(inner) => operands.extend(inner),
+            formula => operands.push(formula),
+        }
+    }
+    match operands.len() {
+        0 => Formula::Const(true),
+        1 => operands.pop().unwrap(),
+        _ => Formula::And(operands),
+    }
+}
@@ -0,0 +1,27 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Stops a running `solve` from another thread or a signal handler. The
+/// interrupted call returns `SolveResult::Unknown`; an interrupt requested
+/// while no search is running stops the next one instead.
+#[derive(Clone, Debug, Default)]
+pub struct InterruptHandle {
+    flag: Arc<AtomicBool>,
+}
+
+impl InterruptHandle {
+    pub fn interrupt(&self) {
+        self.flag.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether an interrupt is pending, without consuming it; for instance
+    /// to stop several solvers with one handle through `Solver::set_terminate`.
+    pub fn is_set(&self) -> bool {
+        self.flag.load(Ordering::Relaxed)
+    }
+
+    /// Consumes a pending interrupt, returning whether there was one.
+    pub(crate) fn take(&self) -> bool {
+        self.flag.swap(false, Ordering::Relaxed)
+    }
+}
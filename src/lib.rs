@@ -1,491 +1,1397 @@
-use std::collections::{HashMap, HashSet};
-use std::fs::File;
-use std::io::{BufRead, BufReader, Result};
+use std::io::BufRead;
+use std::time::Duration;
 
-type Literal = i32;
-type Var = usize;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+// std has no clock on wasm32-unknown-unknown; this one reads the browser's
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
-struct Edge {
-    from: Var,
-    to: Var,
-}
+mod aiger;
+mod amo;
+mod analyze;
+mod assignment;
+mod callbacks;
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(feature = "serde")]
+mod checkpoint;
+mod clause;
+mod clause_db;
+mod compression;
+mod config;
+mod count;
+mod dimacs;
+mod dot;
+mod elim;
+mod enumerate;
+mod equiv;
+mod error;
+mod export;
+mod formula;
+mod gates;
+mod gauss;
+mod icnf;
+mod interpolant;
+mod interrupt;
+mod lit;
+mod lookahead;
+mod lrat;
+mod maxsat;
+mod memory;
+mod model;
+mod mus;
+mod occ;
+mod opb;
+mod pb;
+mod portfolio;
+mod probe;
+mod progress;
+mod proof;
+mod proofcheck;
+mod propagator;
+#[cfg(feature = "python")]
+mod python;
+mod qbf;
+mod reduce;
+mod rephase;
+mod resolution;
+mod restart;
+mod rng;
+mod simplify;
+mod stats;
+mod subsume;
+mod symmetry;
+mod trace;
+mod trail;
+mod unsat_core;
+mod var_order;
+mod vivify;
+mod walksat;
+#[cfg(feature = "wasm")]
+mod wasm;
+mod xor;
+
+pub use aiger::Aiger;
+use assignment::Assignment;
+pub use assignment::LBool;
+use callbacks::Callbacks;
+use clause::ClauseArena;
+pub use clause::ClauseRef;
+pub use clause_db::ClauseInfo;
+pub use config::{Branching, Engine, Minimization, Rephase, SolverConfig};
+use dimacs::Dimacs;
+pub use dimacs::ParseOptions;
+pub use enumerate::Models;
+pub use error::{
+    AddClauseError, InvalidConfig, InvalidLiteral, ParseError, ProofError, SolverError,
+    UnsatisfiedClause,
+};
+pub use formula::{Formula, Tseitin};
+pub use gates::Gate;
+use gauss::GaussMatrix;
+pub use icnf::{Icnf, IcnfStep};
+pub use interrupt::InterruptHandle;
+pub use lit::{Lit, Var};
+pub use maxsat::{MaxSatAlgorithm, MaxSatResult, MaxSatSolver};
+use memory::MEMORY_CHECK_INTERVAL;
+pub use model::Model;
+pub use mus::MusSolver;
+pub use opb::OpbProblem;
+use pb::NativePb;
+pub use pb::{PbConstraint, PbEncoding};
+pub use portfolio::PortfolioSolver;
+use portfolio::Sharing;
+use progress::Progress;
+pub use proof::ProofFormat;
+use proof::ProofWriter;
+pub use proofcheck::{ProofCheck, ProofChecker};
+use propagator::External;
+pub use propagator::Propagator;
+pub use qbf::{QbfResult, QbfSolver};
+pub use resolution::{ResolutionProof, ResolutionRule, ResolutionStep};
+use restart::RestartSchedule;
+pub use restart::RestartStrategy;
+use rng::Rng;
+pub use stats::Stats;
+use trace::TraceWriter;
+use trail::Trail;
+use var_order::VarOrder;
+pub use xor::XorClause;
 
-pub struct Clause {
-    pub literals: Vec<Literal>,
-    pub watched_indices: [usize; 2],
-    pub visit_count: usize,
+/// Answer of a `solve` call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolveResult {
+    /// A satisfying assignment was found; see `Solver::model`.
+    Sat,
+    /// The formula is unsatisfiable (under the given assumptions, if any). A
+    /// formula containing the empty clause is always unsatisfiable.
+    Unsat,
+    /// The search stopped before reaching an answer because a budget in
+    /// `SolverConfig` ran out. Learnt clauses are kept, so calling `solve`
+    /// again continues from where this call left off.
+    Unknown,
 }
 
-impl Clause {
-    fn find_replacement_watch(&self, assignments: &[Option<bool>]) -> Option<usize> {
-        self.literals
-            .iter()
-            .enumerate()
-            .position(|(idx, &candidate)| {
-                idx != self.watched_indices[0]
-                    && idx != self.watched_indices[1]
-                    && Solver::get_literal_value(assignments, candidate) != Some(false)
-            })
+impl SolveResult {
+    #[inline]
+    pub fn is_sat(self) -> bool {
+        self == Self::Sat
     }
 }
 
 struct PropagationState<'a> {
-    assignments: &'a mut [Option<bool>],
-    watch_lists: &'a mut Vec<Vec<usize>>,
-    propagation_queue: &'a mut Vec<Literal>,
-    trail: &'a mut Vec<usize>,
+    assignments: &'a mut Assignment,
+    levels: &'a mut [usize],
+    reasons: &'a mut [Option<ClauseRef>],
+    watch_lists: &'a mut Vec<Vec<ClauseRef>>,
+    trail: &'a mut Trail,
+    order: &'a mut VarOrder,
 }
 
-enum Decision {
-    Single {
-        var: Var,
-        tried_polarity: bool,
-        tried_both: bool,
-    },
-    Pair {
-        var1: Var,
-        var2: Var,
-        tried_comb: u8,
-        tried_all: bool,
-    },
-    Implication {
-        edge: Edge,
-    }, // Only one test needed per edge
-}
+/// Receives the clauses learnt by a solver, see `Solver::set_learn`.
+type LearnCallback = Box<dyn FnMut(&[Lit]) + Send>;
 
 pub struct Solver {
-    pub clauses: Vec<Clause>,
-    pub assignments: Vec<Option<bool>>,
-    pub watch_lists: Vec<Vec<usize>>,
-    trail: Vec<usize>,
-    trail_lim: Vec<usize>,
-
-    // Implication graph for 3-SAT clauses
-    implications: HashMap<Edge, bool>, // proven necessary implications
-    pending_implications: HashSet<Edge>, // candidates to test
+    clauses: ClauseArena,
+    // Every clause of the formula as it was added, before preprocessing
+    // removed or rewrote any, for `verify_model`
+    originals: ClauseArena,
+    // Added to the activity of a clause when conflict analysis uses it
+    clause_increment: f32,
+    assignments: Assignment,
+    watch_lists: Vec<Vec<ClauseRef>>,
+    /// Binary clauses are not watched: `implications[l]` lists, for every
+    /// binary clause containing `-l`, its other literal and the clause, so
+    /// propagating `l` over binaries never touches the clause arena.
+    implications: Vec<Vec<(Lit, ClauseRef)>>,
+    trail: Trail,
+    // Trail position of the next literal whose consequences are propagated
+    qhead: usize,
+
+    // Implication graph: decision level and antecedent clause of every assigned variable
+    levels: Vec<usize>,
+    reasons: Vec<Option<ClauseRef>>,
+    // Variables marked by conflict analysis, all false between analyses
+    seen: Vec<bool>,
+
+    xors: Vec<XorClause>,
+    // XOR clauses watching each variable, by index into `xors`
+    xor_watches: Vec<Vec<usize>>,
+    // Clause made for the last XOR or pseudo-Boolean conflict, deleted on
+    // backtracking
+    lazy_conflict: Option<ClauseRef>,
+    // Built for each search when `config.gauss` is set
+    gauss: Option<GaussMatrix>,
+    // Constraints added with `PbEncoding::Native`, and the terms of each
+    // literal as (constraint index, coefficient), indexed like `watch_lists`
+    pbs: Vec<NativePb>,
+    pb_watches: Vec<Vec<(usize, u64)>>,
+    // At-most-one groups found by preprocessing, and the groups of each
+    // literal, by index into `amos`
+    amos: Vec<Vec<Lit>>,
+    amo_watches: Vec<Vec<usize>>,
+
+    order: VarOrder,
+    phases: Vec<bool>,
+    // Random decisions, seeded from `config.seed` at every search
+    rng: Rng,
+    // Values of the longest trail prefix without a conflict since the last
+    // rephasing, and its length; the phases the first search started from
+    best_phases: Vec<bool>,
+    best_trail: usize,
+    original_phases: Vec<bool>,
+    // Given by `assume` for the next `solve` call
+    assumptions: Vec<Lit>,
+    failed_assumptions: Vec<Lit>,
+    unsat_core: Vec<usize>,
+    unsat_core_clauses: Vec<Vec<Lit>>,
+    // Input clauses added so far, and the numbers of those dropped as tautologies
+    input_count: usize,
+    dropped_inputs: Vec<usize>,
+    // Positions among the stored clauses of those made from short XOR clauses
+    xor_derived: Vec<usize>,
+    model: Option<Model>,
+    proof: Option<ProofWriter>,
+    trace: Option<TraceWriter>,
+    // Set while a search prints progress lines
+    progress: Option<Progress>,
+    stats: Stats,
+    timeout: Option<Duration>,
+    interrupt: InterruptHandle,
+    // Polled during the search, which gives up once it returns true
+    terminate: Option<Box<dyn FnMut() -> bool + Send>>,
+    // Called with every learnt clause of at most `learn_max_len` literals
+    learn_callback: Option<LearnCallback>,
+    learn_max_len: usize,
+    callbacks: Callbacks,
+    external: Option<External>,
+    // Set on the workers of a `PortfolioSolver`, which exchange learnt
+    // clauses at restarts
+    sharing: Option<Sharing>,
+    preprocessed: bool,
+    // Whether the Jeroslow–Wang scores and phases were set
+    scored: bool,
+    // Variables removed by elimination, and the clauses they were removed
+    // with, each tagged with the literal of the eliminated variable
+    eliminated: Vec<bool>,
+    elim_stack: Vec<(Lit, Vec<Lit>)>,
+    // Variables kept out of elimination and substitution by `freeze`
+    frozen: Vec<bool>,
+    pub config: SolverConfig,
 }
 
 impl Solver {
-    pub fn new(path: &str) -> Result<Self> {
-        let mut variable_count = 0;
-        let mut clauses = Vec::new();
-        let lines = BufReader::new(File::open(path)?)
-            .lines()
-            .map_while(Result::ok);
+    /// Loads a DIMACS CNF file. Gzip and xz compressed files (`.cnf.gz`,
+    /// `.cnf.xz`) are recognised by their header and decompressed on the fly
+    /// when the matching `gzip`/`xz` feature is enabled.
+    pub fn new(path: &str) -> Result<Self, SolverError> {
+        Self::new_with_options(path, ParseOptions::default())
+    }
+
+    /// Loads a DIMACS CNF file like `new`, parsed as `options` say.
+    pub fn new_with_options(path: &str, options: ParseOptions) -> Result<Self, SolverError> {
+        let start = Instant::now();
+        let formula = dimacs::parse_file(path, options)?;
+        Ok(Self::from_formula(formula, start))
+    }
+
+    /// Reads a DIMACS CNF formula from any buffered reader.
+    pub fn from_reader(reader: impl BufRead) -> Result<Self, SolverError> {
+        Self::from_reader_with_options(reader, ParseOptions::default())
+    }
+
+    /// Reads a DIMACS CNF formula from any buffered reader, parsed as
+    /// `options` say.
+    pub fn from_reader_with_options(
+        reader: impl BufRead,
+        options: ParseOptions,
+    ) -> Result<Self, SolverError> {
+        let start = Instant::now();
+        let formula = dimacs::parse_with(reader, options)?;
+        Ok(Self::from_formula(formula, start))
+    }
 
-        for line in lines {
-            Self::parse_dimacs_line(line, &mut variable_count, &mut clauses);
+    /// A solver over a parsed formula, whose parsing started at `start`.
+    fn from_formula(formula: Dimacs, start: Instant) -> Self {
+        let mut solver = Self::with_vars(formula.variable_count);
+        for literals in formula.clauses {
+            solver.add_input_clause(literals);
+        }
+        for literals in formula.xors {
+            solver.push_xor(literals);
         }
+        solver.stats.parse_time = start.elapsed();
+        solver
+    }
+
+    /// Parses a DIMACS CNF formula held in memory.
+    pub fn from_dimacs_str(dimacs: &str) -> Result<Self, SolverError> {
+        Self::from_bytes(dimacs.as_bytes())
+    }
+
+    /// Parses the contents of a DIMACS CNF file, compressed or not like
+    /// those `new` loads, without touching the file system: for uploads,
+    /// embedded instances or WebAssembly.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SolverError> {
+        if compression::is_compressed(bytes) {
+            return Self::from_reader(compression::decompress(bytes)?);
+        }
+        let start = Instant::now();
+        let formula = dimacs::parse_bytes(bytes, ParseOptions::default())?;
+        Ok(Self::from_formula(formula, start))
+    }
 
-        let mut solver = Self {
-            clauses,
-            assignments: vec![None; variable_count + 1],
+    /// Creates a solver over variables `1..=variable_count` with no clauses.
+    /// More variables are allocated on demand by `add_clause`.
+    pub fn with_vars(variable_count: usize) -> Self {
+        Self {
+            clauses: ClauseArena::default(),
+            originals: ClauseArena::default(),
+            clause_increment: 1.0,
+            assignments: Assignment::new(variable_count),
             watch_lists: vec![Vec::new(); (variable_count + 1) * 2],
-            trail: Vec::new(),
-            trail_lim: Vec::new(),
-            implications: HashMap::new(),
-            pending_implications: HashSet::new(),
-        };
+            implications: vec![Vec::new(); (variable_count + 1) * 2],
+            trail: Trail::default(),
+            qhead: 0,
+            levels: vec![0; variable_count + 1],
+            reasons: vec![None; variable_count + 1],
+            seen: vec![false; variable_count + 1],
+            xors: Vec::new(),
+            xor_watches: vec![Vec::new(); variable_count + 1],
+            lazy_conflict: None,
+            gauss: None,
+            pbs: Vec::new(),
+            pb_watches: vec![Vec::new(); (variable_count + 1) * 2],
+            amos: Vec::new(),
+            amo_watches: vec![Vec::new(); (variable_count + 1) * 2],
+            order: VarOrder::new(Branching::default(), variable_count),
+            phases: vec![true; variable_count + 1],
+            rng: Rng::new(0),
+            best_phases: vec![true; variable_count + 1],
+            best_trail: 0,
+            original_phases: Vec::new(),
+            assumptions: Vec::new(),
+            failed_assumptions: Vec::new(),
+            unsat_core: Vec::new(),
+            unsat_core_clauses: Vec::new(),
+            input_count: 0,
+            dropped_inputs: Vec::new(),
+            xor_derived: Vec::new(),
+            model: None,
+            proof: None,
+            trace: None,
+            progress: None,
+            stats: Stats::default(),
+            timeout: None,
+            interrupt: InterruptHandle::default(),
+            terminate: None,
+            learn_callback: None,
+            learn_max_len: 0,
+            callbacks: Callbacks::default(),
+            external: None,
+            sharing: None,
+            preprocessed: false,
+            scored: false,
+            eliminated: vec![false; variable_count + 1],
+            elim_stack: Vec::new(),
+            frozen: vec![false; variable_count + 1],
+            config: SolverConfig::default(),
+        }
+    }
 
-        solver.initialize_watches();
-        solver.extract_implication_candidates(); // Now safe: no mutable borrow during iteration
-        Ok(solver)
+    /// Creates a solver with no variables or clauses that searches as
+    /// `config` says, see `SolverConfig::build`.
+    pub fn with_config(config: SolverConfig) -> Self {
+        let mut solver = Self::with_vars(0);
+        solver.config = config;
+        solver
     }
 
-    #[inline]
-    pub fn lit_to_var(lit: Literal) -> Var {
-        lit.abs() as usize
+    /// Number of variables the solver currently knows about.
+    pub fn num_vars(&self) -> usize {
+        self.assignments.num_vars()
     }
 
-    #[inline]
-    pub fn lit_to_idx(lit: Literal) -> usize {
-        (lit.abs() as usize * 2) + (lit < 0) as usize
+    /// Adds a clause given as `Lit`s or as DIMACS integers. The solver is
+    /// reset to decision level 0 first, so clauses may be added between
+    /// `solve` calls. An empty slice adds the empty clause, making the formula
+    /// unsatisfiable.
+    pub fn add_clause<L>(&mut self, lits: &[L]) -> Result<(), AddClauseError>
+    where
+        L: Copy + TryInto<Lit>,
+        AddClauseError: From<L::Error>,
+    {
+        let literals = lits
+            .iter()
+            .map(|&l| l.try_into())
+            .collect::<Result<Vec<Lit>, _>>()?;
+        self.add_input_clause(literals);
+        Ok(())
     }
 
-    #[inline]
-    fn make_lit(var: Var, polarity: bool) -> Literal {
-        if polarity { var as i32 } else { -(var as i32) }
+    /// Stores a clause of the formula given by the user, keeping count of
+    /// them so that `unsat_core` can refer to clauses by their number.
+    fn add_input_clause(&mut self, literals: Vec<Lit>) {
+        let tautologies = self.stats.tautologies_dropped;
+        self.push_clause(literals);
+        if self.stats.tautologies_dropped > tautologies {
+            self.dropped_inputs.push(self.input_count);
+        }
+        self.input_count += 1;
     }
 
-    fn parse_dimacs_line(line: String, variable_count: &mut usize, clauses: &mut Vec<Clause>) {
-        if line.starts_with('c') || line.is_empty() {
-            return;
+    /// Stores an input clause. Repeated literals are merged and tautologies
+    /// (clauses holding both `x` and `-x`) are dropped, since they would
+    /// only waste watches. A copy is kept aside for `verify_model`.
+    fn push_clause(&mut self, mut literals: Vec<Lit>) {
+        let max_var = literals.iter().map(|l| l.var().index()).max();
+        self.reserve_vars(max_var.unwrap_or(0));
+        self.backtrack_to(0);
+        if literals.iter().any(|l| self.eliminated[l.var().index()]) {
+            self.restore_eliminated();
         }
-        if line.starts_with("p cnf") {
-            *variable_count = line
-                .split_whitespace()
-                .nth(2)
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(0);
+
+        // Sorting puts `x` and `-x` next to each other
+        literals.sort_unstable();
+        let len = literals.len();
+        literals.dedup();
+        if literals.len() < len {
+            self.stats.clauses_deduplicated += 1;
+        }
+        if literals.windows(2).any(|pair| pair[0] == -pair[1]) {
+            self.stats.tautologies_dropped += 1;
+            // Its ID stays taken, so that input clauses keep their numbers
+            self.clauses.take_id();
             return;
         }
-        let literals: Vec<Literal> = line
-            .split_whitespace()
-            .filter_map(|s| s.parse().ok())
-            .take_while(|&val| val != 0)
-            .collect();
+        self.originals.push(&literals, false, 0);
+        self.attach_clause(literals);
+    }
 
-        if literals.is_empty() || literals.len() > 3 {
-            return;
+    /// Stores and watches a clause with neither repeated literals nor
+    /// eliminated variables, without copying it into `originals`.
+    fn attach_clause(&mut self, mut literals: Vec<Lit>) {
+        // Watch literals that are not already false at level 0 whenever possible
+        literals.sort_by_key(|&l| self.assignments.value(l) == LBool::False);
+        if let Some(proof) = self.proof.as_mut() {
+            proof.input(self.clauses.next_id(), &literals);
         }
 
-        let len = literals.len();
-        clauses.push(Clause {
-            literals,
-            watched_indices: [0, 1.min(len.saturating_sub(1))],
-            visit_count: 0,
-        });
+        let cref = self.clauses.push(&literals, false, 0);
+        self.watch_clause(cref);
     }
 
-    fn initialize_watches(&mut self) {
-        for (id, c) in self.clauses.iter().enumerate() {
-            if let Some(&lit0) = c.literals.get(c.watched_indices[0]) {
-                self.watch_lists[Self::lit_to_idx(lit0)].push(id);
-            }
-            if c.literals.len() > 1 {
-                let lit1 = c.literals[c.watched_indices[1]];
-                self.watch_lists[Self::lit_to_idx(lit1)].push(id);
-            }
+    fn reserve_vars(&mut self, variable_count: usize) {
+        if variable_count <= self.num_vars() {
+            return;
         }
+        self.assignments.grow(variable_count);
+        self.watch_lists
+            .resize((variable_count + 1) * 2, Vec::new());
+        self.implications
+            .resize((variable_count + 1) * 2, Vec::new());
+        self.levels.resize(variable_count + 1, 0);
+        self.reasons.resize(variable_count + 1, None);
+        self.seen.resize(variable_count + 1, false);
+        self.xor_watches.resize(variable_count + 1, Vec::new());
+        self.pb_watches.resize((variable_count + 1) * 2, Vec::new());
+        self.amo_watches
+            .resize((variable_count + 1) * 2, Vec::new());
+        self.phases.resize(variable_count + 1, true);
+        self.best_phases.resize(variable_count + 1, true);
+        self.eliminated.resize(variable_count + 1, false);
+        self.frozen.resize(variable_count + 1, false);
+        self.order.grow(variable_count);
     }
 
-    // Fixed: Collect candidates first, then insert mutably
-    fn extract_implication_candidates(&mut self) {
-        let mut candidates = HashSet::new();
+    /// Allocates a variable above all existing ones, for instance for an
+    /// auxiliary variable of an encoding. Its value, watch lists, phase and
+    /// activity are set up like those of the variables of the input, so it
+    /// may appear in clauses and assumptions at once.
+    pub fn new_var(&mut self) -> Var {
+        let var = Var::new(self.num_vars() + 1);
+        self.reserve_vars(var.index());
+        var
+    }
 
-        for clause in &self.clauses {
-            if clause.literals.len() == 3 {
-                let [a, b, c] = [clause.literals[0], clause.literals[1], clause.literals[2]];
+    /// Allocates `count` variables at once, as `new_var` does, returning
+    /// them in increasing order.
+    pub fn new_vars(&mut self, count: usize) -> Vec<Var> {
+        let first = self.num_vars() + 1;
+        self.reserve_vars(self.num_vars() + count);
+        (first..first + count).map(Var::new).collect()
+    }
 
-                Self::try_add_candidate(&mut candidates, a, b, c);
-                Self::try_add_candidate(&mut candidates, a, c, b);
-                Self::try_add_candidate(&mut candidates, b, c, a);
-            }
+    /// A solver over the same variables and XOR clauses but no other
+    /// clauses, with the configuration, time limit and interrupt handle of this one but no
+    /// proof or trace output or symmetry breaking, for searches run on a modified copy of
+    /// the formula.
+    fn sibling(&self) -> Solver {
+        let mut sibling = Solver::with_vars(self.num_vars());
+        sibling.config = SolverConfig {
+            proof_path: None,
+            resolution_proof: false,
+            trace_path: None,
+            symmetry_breaking: false,
+            ..self.config.clone()
+        };
+        sibling.timeout = self.timeout;
+        sibling.interrupt = self.interrupt.clone();
+        for xor in &self.xors {
+            sibling.push_xor(xor.to_literals());
         }
+        for pb in &self.pbs {
+            sibling.push_native_pb(&pb.constraint);
+        }
+        sibling
+    }
 
-        self.pending_implications = candidates;
+    /// The input clauses, oldest first, with the clauses removed by variable
+    /// elimination restored, then the binary clauses of the at-most-one
+    /// groups.
+    fn input_clauses(&mut self) -> Vec<Vec<Lit>> {
+        self.backtrack_to(0);
+        if !self.elim_stack.is_empty() {
+            self.restore_eliminated();
+        }
+        self.clauses
+            .iter()
+            .filter(|&cref| !self.clauses.is_learnt(cref))
+            .map(|cref| self.clauses[cref].to_vec())
+            .chain(self.amo_clauses().map(|pair| pair.to_vec()))
+            .collect()
     }
 
-    fn try_add_candidate(
-        candidates: &mut HashSet<Edge>,
-        lit1: Literal,
-        lit2: Literal,
-        lit3: Literal,
-    ) {
-        // Look for pattern: (~x ∨ ~y ∨ z)  ==>  (x ∧ y) → z
-        if lit3 > 0 && lit1 < 0 && lit2 < 0 {
-            let from = Self::lit_to_var(-lit1);
-            let to = Self::lit_to_var(lit3);
-            candidates.insert(Edge { from, to });
+    /// Rebuilds every watch list from scratch, watching the first two
+    /// literals of each clause. Only valid while nothing is assigned.
+    fn rewatch_all(&mut self) {
+        for list in self.watch_lists.iter_mut() {
+            list.clear();
+        }
+        for list in self.implications.iter_mut() {
+            list.clear();
+        }
+        let crefs: Vec<ClauseRef> = self
+            .clauses
+            .iter()
+            .filter(|&cref| !self.clauses.is_lazy_reason(cref))
+            .collect();
+        for cref in crefs {
+            self.watch_clause(cref);
         }
     }
 
-    #[inline]
-    pub fn get_literal_value(assignments: &[Option<bool>], lit: Literal) -> Option<bool> {
-        assignments[Self::lit_to_var(lit)].map(|val| val == (lit > 0))
+    fn watch_clause(&mut self, cref: ClauseRef) {
+        match self.clauses[cref] {
+            // Units are asserted at level 0 rather than watched
+            [] | [_] => {}
+            [a, b] => {
+                self.implications[(-a).index()].push((b, cref));
+                self.implications[(-b).index()].push((a, cref));
+            }
+            [a, b, ..] => {
+                self.watch_lists[a.index()].push(cref);
+                self.watch_lists[b.index()].push(cref);
+            }
+        }
     }
 
-    fn assign(assignments: &mut [Option<bool>], trail: &mut Vec<Var>, lit: Literal) -> bool {
-        let var = Self::lit_to_var(lit);
-        let polarity = lit > 0;
-        match assignments[var] {
-            None => {
-                assignments[var] = Some(polarity);
-                trail.push(var);
+    fn assign(state: &mut PropagationState, lit: Lit, reason: Option<ClauseRef>) -> bool {
+        match state.assignments.value(lit) {
+            LBool::Undef => {
+                let var = lit.var().index();
+                state.assignments.assign(lit);
+                state.levels[var] = state.trail.decision_level();
+                state.reasons[var] = reason;
+                state.trail.push(lit);
+                state.order.assigned(lit.var());
                 true
             }
-            Some(p) => p == polarity,
+            value => value == LBool::True,
         }
     }
 
-    fn undo_to_level(&mut self, level: usize) {
-        if level >= self.trail_lim.len() {
-            return;
+    /// Assigns `lit` and puts it on the trail, where `propagate` will pick
+    /// it up. Returns false if `lit` is already false.
+    fn enqueue(&mut self, lit: Lit, reason: Option<ClauseRef>) -> bool {
+        let mut state = PropagationState {
+            assignments: &mut self.assignments,
+            levels: &mut self.levels,
+            reasons: &mut self.reasons,
+            watch_lists: &mut self.watch_lists,
+            trail: &mut self.trail,
+            order: &mut self.order,
+        };
+        Self::assign(&mut state, lit, reason)
+    }
+
+    /// Asserts the first literal of a clause made by `parity_clause` or for a
+    /// native pseudo-Boolean constraint, or returns the clause as a conflict
+    /// if that literal is false. The reason, or conflict, is handed to
+    /// conflict analysis as a clause that is deleted again on backtracking.
+    pub(crate) fn assert_lazy(&mut self, clause: Vec<Lit>) -> Option<ClauseRef> {
+        match self.assignments.value(clause[0]) {
+            LBool::True => None,
+            LBool::False => {
+                let cref = self.clauses.push_lazy_reason(&clause);
+                self.lazy_conflict = Some(cref);
+                Some(cref)
+            }
+            LBool::Undef => {
+                let cref = self.clauses.push_lazy_reason(&clause);
+                self.enqueue(clause[0], Some(cref));
+                None
+            }
+        }
+    }
+
+    /// Unassigns every literal above decision level `level`. Watch lists are
+    /// left as they are: watches on unassigned literals stay valid. Clauses
+    /// made for the reasons and conflicts of XOR and pseudo-Boolean
+    /// constraints are deleted with their use.
+    fn backtrack_to(&mut self, level: usize) {
+        if let Some(cref) = self.lazy_conflict.take() {
+            self.clauses.delete(cref);
+        }
+        let trail_len = self.trail.len();
+        let unassigned = self.trail.backtrack_to(level);
+        // The literals already propagated give back the slack they took
+        // from native pseudo-Boolean constraints
+        let propagated = self.qhead.saturating_sub(trail_len - unassigned.len());
+        for &lit in &unassigned.as_slice()[..propagated] {
+            for &(index, a) in &self.pb_watches[(-lit).index()] {
+                self.pbs[index].slack += i128::from(a);
+            }
+        }
+        for lit in unassigned {
+            let var = lit.var();
+            self.phases[var.index()] = !lit.sign();
+            self.assignments.unassign(var);
+            if let Some(cref) = self.reasons[var.index()].take()
+                && self.clauses.is_lazy_reason(cref)
+            {
+                self.clauses.delete(cref);
+            }
+            self.order.insert(var);
         }
-        let pos = self.trail_lim[level];
-        while self.trail.len() > pos {
-            let var = self.trail.pop().unwrap();
-            self.assignments[var] = None;
+        self.qhead = self.qhead.min(self.trail.len());
+        if let Some(matrix) = self.gauss.as_mut() {
+            matrix.backtrack_to(level);
+        }
+        if let Some(trace) = self.trace.as_mut() {
+            trace.traced = trace.traced.min(self.trail.len());
+        }
+        if let Some(external) = self.external.as_mut() {
+            external.backtracked(level, self.trail.len());
         }
-        self.trail_lim.truncate(level);
     }
 
-    pub fn propagate(&mut self, satisfied_lit: Literal) -> bool {
-        let mut queue = vec![satisfied_lit];
-        while let Some(l) = queue.pop() {
-            if !self.process_watch_list(l, &mut queue) {
-                return false;
+    /// Propagates the consequences of every trail literal not propagated
+    /// yet, in trail order, returning a falsified clause if a conflict is
+    /// reached. The literals after the conflicting one are left unpropagated
+    /// until the next backtrack discards them. With a Gauss matrix, Gaussian
+    /// elimination runs whenever the watches have nothing left to do.
+    fn propagate(&mut self) -> Option<ClauseRef> {
+        loop {
+            if let Some(conflict) = self.propagate_watches() {
+                return Some(conflict);
+            }
+            if let Some(conflict) = self.gauss_propagate() {
+                return Some(conflict);
+            }
+            if self.qhead == self.trail.len() {
+                return None;
             }
         }
-        true
     }
 
-    fn process_watch_list(&mut self, satisfied_lit: Literal, queue: &mut Vec<Literal>) -> bool {
-        let falsified_idx = Self::lit_to_idx(-satisfied_lit);
+    fn propagate_watches(&mut self) -> Option<ClauseRef> {
+        while self.qhead < self.trail.len() {
+            let lit = self.trail[self.qhead];
+            self.qhead += 1;
+            self.stats.propagations += 1;
+            // Native pseudo-Boolean constraints go first, so that their
+            // slack accounts for every propagated literal
+            if !self.pb_watches[(-lit).index()].is_empty()
+                && let Some(conflict) = self.propagate_pbs(lit)
+            {
+                return Some(conflict);
+            }
+            if !self.amo_watches[lit.index()].is_empty()
+                && let Some(conflict) = self.propagate_amos(lit)
+            {
+                return Some(conflict);
+            }
+            if let Some(conflict) = self.process_watch_list(lit) {
+                return Some(conflict);
+            }
+            if !self.xor_watches[lit.var().index()].is_empty()
+                && let Some(conflict) = self.propagate_xors(lit.var())
+            {
+                return Some(conflict);
+            }
+        }
+        None
+    }
+
+    fn process_watch_list(&mut self, satisfied_lit: Lit) -> Option<ClauseRef> {
+        let falsified_idx = (-satisfied_lit).index();
         let mut affected = std::mem::take(&mut self.watch_lists[falsified_idx]);
-        let mut conflict = false;
+        let mut conflict = None;
 
         let mut state = PropagationState {
             assignments: &mut self.assignments,
+            levels: &mut self.levels,
+            reasons: &mut self.reasons,
             watch_lists: &mut self.watch_lists,
-            propagation_queue: queue,
             trail: &mut self.trail,
+            order: &mut self.order,
         };
 
-        affected.retain(|&cid| {
-            if conflict {
+        // Binary clauses first: cheap, and they often find the conflict
+        for &(implied, cref) in &self.implications[satisfied_lit.index()] {
+            match state.assignments.value(implied) {
+                LBool::True => {}
+                LBool::False => {
+                    conflict = Some(cref);
+                    break;
+                }
+                LBool::Undef => {
+                    Self::assign(&mut state, implied, Some(cref));
+                }
+            }
+        }
+
+        affected.retain(|&cref| {
+            if conflict.is_some() {
                 return true;
             }
+            if self.clauses.is_deleted(cref) {
+                return false; // Watch left behind by clause database reduction
+            }
             let (keep, is_conflict) =
-                Self::update_clause(&mut self.clauses[cid], -satisfied_lit, cid, &mut state);
-            conflict = is_conflict;
+                Self::update_clause(&mut self.clauses[cref], -satisfied_lit, cref, &mut state);
+            if is_conflict {
+                conflict = Some(cref);
+            }
             keep
         });
 
         self.watch_lists[falsified_idx].extend(affected);
-        !conflict
+        conflict
     }
 
+    /// Visits a clause watching the newly falsified literal `falsified`,
+    /// which is moved to position 1. Returns whether the clause keeps that
+    /// watch and whether it is in conflict; a unit clause asserts its first
+    /// literal.
     fn update_clause(
-        c: &mut Clause,
-        falsified: Literal,
-        cid: usize,
+        literals: &mut [Lit],
+        falsified: Lit,
+        cref: ClauseRef,
         state: &mut PropagationState,
     ) -> (bool, bool) {
-        c.visit_count += 1;
-        if c.literals[c.watched_indices[0]] == falsified {
-            c.watched_indices.swap(0, 1);
+        if literals[0] == falsified {
+            literals.swap(0, 1);
         }
 
-        let w0 = c.literals[c.watched_indices[0]];
-        if Self::get_literal_value(state.assignments, w0) == Some(true) {
+        let w0 = literals[0];
+        if state.assignments.value(w0) == LBool::True {
             return (true, false);
         }
 
-        if let Some(j) = c.find_replacement_watch(state.assignments) {
-            c.watched_indices[1] = j;
-            state.watch_lists[Self::lit_to_idx(c.literals[j])].push(cid);
+        let replacement =
+            (2..literals.len()).find(|&k| state.assignments.value(literals[k]) != LBool::False);
+        if let Some(k) = replacement {
+            literals.swap(1, k);
+            state.watch_lists[literals[1].index()].push(cref);
             return (false, false);
         }
 
-        match Self::get_literal_value(state.assignments, w0) {
-            Some(false) => (true, true),
-            None => {
-                let w0_lit = w0;
-                if !Self::assign(state.assignments, state.trail, w0_lit) {
+        match state.assignments.value(w0) {
+            LBool::False => (true, true),
+            LBool::Undef => {
+                if !Self::assign(state, w0, Some(cref)) {
                     return (true, true);
                 }
-                state.propagation_queue.push(w0_lit);
                 (true, false)
             }
             _ => (true, false),
         }
     }
 
-    fn pick_branching_pair(&self) -> (Option<Var>, Option<Var>) {
-        let mut iter = self
-            .assignments
-            .iter()
-            .enumerate()
-            .skip(1)
-            .filter(|(_, a)| a.is_none());
-        let var1 = iter.next().map(|(i, _)| i);
-        let var2 = iter.next().map(|(i, _)| i);
-        (var1, var2)
-    }
-
-    fn assign_pair(&mut self, comb: u8, var1: Var, var2: Var) -> bool {
-        let p = match comb {
-            0 => [true, true],
-            1 => [true, false],
-            2 => [false, true],
-            3 => [false, false],
-            _ => return false,
-        };
-        let lit1 = Self::make_lit(var1, p[0]);
-        if !Self::assign(&mut self.assignments, &mut self.trail, lit1) || !self.propagate(lit1) {
-            return false;
+    /// Appends a learnt clause, watches it and asserts its first literal.
+    /// `hints` derive it for an LRAT proof.
+    fn learn(&mut self, literals: Vec<Lit>, lbd: usize, hints: &[u64]) {
+        if let Some(proof) = self.proof.as_mut() {
+            proof.add(self.clauses.next_id(), &literals, hints);
         }
-        let lit2 = Self::make_lit(var2, p[1]);
-        if !Self::assign(&mut self.assignments, &mut self.trail, lit2) || !self.propagate(lit2) {
-            return false;
+        if let Some(trace) = self.trace.as_mut() {
+            trace.learnt(&literals, lbd);
         }
-        true
+        if let Some(callback) = self.callbacks.learned_clause.as_mut() {
+            callback(&literals, lbd);
+        }
+        self.stats.learnt_clauses += 1;
+        if let Some(callback) = self.learn_callback.as_mut()
+            && literals.len() <= self.learn_max_len
+        {
+            callback(&literals);
+        }
+        if let Some(sharing) = self.sharing.as_mut() {
+            sharing.offer(&literals, lbd);
+        }
+        let asserting = literals[0];
+        let cref = self.clauses.push(&literals, true, lbd);
+        self.watch_clause(cref);
+        self.enqueue(asserting, Some(cref));
     }
 
-    // Test if (from → to) is forced: check if assuming from=T and to=F leads to conflict
-    fn test_implication(&mut self, edge: Edge) -> bool {
-        let from = edge.from;
-        let to = edge.to;
+    /// Scores every variable by the Jeroslow–Wang weights of its literals,
+    /// and saves as its phase the literal of higher weight. The weight
+    /// `J(l)` sums `2^-|C|` over the clauses `C` that contain `l` and are
+    /// not satisfied at level 0, counting only their unassigned literals,
+    /// so that variables of many short clauses are decided first, to the
+    /// value that satisfies more of them. Scores are scaled into `[0, 1]`,
+    /// below the bump of the first conflict. Done once per solver, before
+    /// the first search or local search.
+    pub(crate) fn jeroslow_wang(&mut self) {
+        self.scored = true;
+        let mut weights = vec![0.0; (self.num_vars() + 1) * 2];
+        for cref in self.clauses.iter() {
+            let literals = &self.clauses[cref];
+            if self.clauses.is_learnt(cref)
+                || literals
+                    .iter()
+                    .any(|&lit| self.assignments.value(lit) == LBool::True)
+            {
+                continue;
+            }
+            let free = literals
+                .iter()
+                .filter(|&&lit| self.assignments.value(lit) == LBool::Undef);
+            let weight = 0.5f64.powi(free.clone().count().min(64) as i32);
+            for &lit in free {
+                weights[lit.index()] += weight;
+            }
+        }
 
-        let save_lim = self.trail_lim.len();
-        self.trail_lim.push(self.trail.len());
+        let mut scores = vec![0.0; self.num_vars() + 1];
+        for var in (1..=self.num_vars()).map(Var::new) {
+            let (pos, neg) = (
+                weights[var.positive().index()],
+                weights[var.negative().index()],
+            );
+            scores[var.index()] = pos + neg;
+            if !self.assignments.is_assigned(var) {
+                self.phases[var.index()] = pos >= neg;
+            }
+        }
+        self.original_phases.clone_from(&self.phases);
+        let max = scores.iter().copied().fold(0.0, f64::max);
+        if max > 0.0 {
+            for score in scores.iter_mut() {
+                *score /= max;
+            }
+        }
+        self.order.initialize(&scores);
+    }
 
-        // Assume from = true
-        let lit_from = Self::make_lit(from, true);
-        if !Self::assign(&mut self.assignments, &mut self.trail, lit_from)
-            || !self.propagate(lit_from)
+    /// Picks the next unassigned variable of the decision order, or, with
+    /// probability `config.random_var_freq`, a random one.
+    fn pick_branching_variable(&mut self) -> Option<Var> {
+        if self.config.random_var_freq > 0.0
+            && self.num_vars() > 0
+            && self.rng.next_f64() < self.config.random_var_freq
         {
-            self.undo_to_level(save_lim);
-            return false;
+            let var = Var::new(self.rng.below(self.num_vars()) + 1);
+            if !self.assignments.is_assigned(var) && !self.eliminated[var.index()] {
+                self.stats.random_decisions += 1;
+                return Some(var);
+            }
+        }
+        while let Some(var) = self.order.pop_max() {
+            if !self.assignments.is_assigned(var) && !self.eliminated[var.index()] {
+                return Some(var);
+            }
+        }
+        None
+    }
+
+    /// The value to decide `var` to: its saved phase, or, with probability
+    /// `config.random_phase_freq`, a random one.
+    fn pick_phase(&mut self, var: Var) -> bool {
+        if self.config.random_phase_freq > 0.0
+            && self.rng.next_f64() < self.config.random_phase_freq
+        {
+            return self.rng.next_bool();
         }
+        self.phases[var.index()]
+    }
 
-        // Assume to = false
-        let lit_to = Self::make_lit(to, false);
-        let has_conflict = !Self::assign(&mut self.assignments, &mut self.trail, lit_to)
-            || !self.propagate(lit_to);
+    pub fn solve(&mut self) -> SolveResult {
+        self.solve_with_assumptions(&[])
+    }
 
-        self.undo_to_level(save_lim);
+    /// Assumes `lit` true for the next `solve` or `solve_with_assumptions`
+    /// call only, as `ipasir_assume` does.
+    pub fn assume(&mut self, lit: Lit) {
+        self.assumptions.push(lit);
+    }
 
-        has_conflict // if conflict → implication is necessary
+    /// Solves the formula with every literal of `assumptions`, and of the
+    /// `assume` calls since the last search, forced true. Assumptions are
+    /// taken as the first decisions of the search and are dropped again by
+    /// the next call, so they never become part of the formula; learnt
+    /// clauses remain valid across calls. On `Sat` the model is available
+    /// from `model`, and also from `value` until the solver is next
+    /// modified or queried.
+    pub fn solve_with_assumptions(&mut self, assumptions: &[Lit]) -> SolveResult {
+        let start = Instant::now();
+        let result = if self.assumptions.is_empty() {
+            self.solve_inner(assumptions)
+        } else {
+            let mut all = std::mem::take(&mut self.assumptions);
+            all.extend_from_slice(assumptions);
+            self.solve_inner(&all)
+        };
+        self.stats.solve_time += start.elapsed();
+        result
     }
 
-    pub fn solve(&mut self) -> bool {
-        if !self.initial_propagation() {
-            return false;
+    fn solve_inner(&mut self, assumptions: &[Lit]) -> SolveResult {
+        self.backtrack_to(0);
+        self.failed_assumptions.clear();
+        self.model = None;
+        let max_var = assumptions.iter().map(|l| l.var().index()).max();
+        self.reserve_vars(max_var.unwrap_or(0));
+        if self.proof.is_none() {
+            self.proof = match &self.config.proof_path {
+                Some(path) => Some(ProofWriter::create(path, self.config.proof_format)),
+                None if self.config.resolution_proof => Some(ProofWriter::discard()),
+                None => None,
+            };
+            if let Some(proof) = self.proof.as_mut()
+                && self.config.resolution_proof
+            {
+                let inputs = self.clauses.iter().filter(|&cref| {
+                    !self.clauses.is_lazy_reason(cref) && !self.clauses.is_learnt(cref)
+                });
+                proof.record(inputs.map(|cref| (self.clauses.id(cref), &self.clauses[cref])));
+            }
+        }
+        if self.trace.is_none()
+            && let Some(path) = &self.config.trace_path
+        {
+            self.trace = Some(TraceWriter::create(path));
         }
 
-        let mut decision_stack: Vec<Decision> = Vec::new();
+        // Preprocessing runs once, before anything is assigned
+        let first_solve = !self.preprocessed;
+        if first_solve {
+            self.run_preprocessing(assumptions);
+        }
+        if assumptions.iter().any(|l| self.eliminated[l.var().index()]) {
+            self.restore_eliminated();
+        }
+        self.build_gauss_matrix();
 
-        loop {
-            // Process pending implication tests
-            if let Some(&edge) = self.pending_implications.iter().next() {
-                self.pending_implications.remove(&edge);
+        if !self.initial_propagation() || (first_solve && self.config.probing && !self.probe()) {
+            self.log_refutation();
+            return SolveResult::Unsat;
+        }
+        if self.order.branching() != self.config.branching {
+            self.order = VarOrder::new(self.config.branching, self.num_vars());
+        }
+        if !self.scored && self.config.jeroslow_wang {
+            self.jeroslow_wang();
+        }
+        let result = match self.config.engine {
+            _ if self.external.is_some() => self.search(assumptions),
+            Engine::Cdcl => self.search(assumptions),
+            Engine::Lookahead => self.lookahead_search(assumptions),
+            Engine::WalkSat => self.walksat_search(assumptions),
+        };
+        self.finish_progress();
+        self.note_memory();
+        match result {
+            SolveResult::Sat => {
+                let mut model = Model::from_assignments(&self.assignments);
+                self.extend_model(&mut model);
+                self.model = Some(model);
+            }
+            SolveResult::Unsat | SolveResult::Unknown => self.backtrack_to(0),
+        }
+        result
+    }
 
-                decision_stack.push(Decision::Implication { edge });
-                self.trail_lim.push(self.trail.len());
+    /// The preprocessing passes enabled in `config`, which leave the
+    /// variables of `assumptions` in place.
+    fn run_preprocessing(&mut self, assumptions: &[Lit]) {
+        self.preprocessed = true;
+        // Neither may take away a variable a propagator reasons about
+        let external = self.external.is_some();
+        // Lex-leader clauses do not follow from the formula, so no proof
+        // could justify them
+        if self.config.symmetry_breaking && !external && self.proof.is_none() {
+            self.break_symmetries(assumptions);
+        }
+        // Substitution cannot give LRAT hints
+        if self.config.equivalences && !external && !self.wants_hints() {
+            self.substitute_equivalences();
+        }
+        if self.config.subsumption {
+            self.subsume();
+        }
+        // Lazy reasons have no hints either
+        if self.config.at_most_one && !self.wants_hints() {
+            self.detect_at_most_ones();
+        }
+        if self.config.elimination && !external {
+            self.eliminate_variables(assumptions);
+        }
+    }
+
+    /// The satisfying assignment found by the last `solve` call, or `None`
+    /// if that call did not answer `Sat`. Later `add_clause` calls do not
+    /// touch it.
+    pub fn model(&self) -> Option<Model> {
+        self.model.clone()
+    }
 
-                if self.test_implication(edge) {
-                    self.implications.insert(edge, true);
-                    // You could later use this to add (~from ∨ to) as a 2-clause if desired
+    /// Enumerates every model of the formula (AllSAT), excluding each one
+    /// found with a blocking clause over all variables. Variables that occur
+    /// in no clause are enumerated too, doubling the count each.
+    pub fn iter_models(&mut self) -> Models<'_> {
+        Models::new(self, None)
+    }
+
+    /// Enumerates the distinct projections of the models onto `vars`: each
+    /// model returned differs from all earlier ones on some variable of
+    /// `vars`, while the other variables (say, auxiliary variables of an
+    /// encoding) take whatever value the search gave them.
+    pub fn enumerate_models_over(&mut self, vars: &[Var]) -> Models<'_> {
+        let max_var = vars.iter().map(|v| v.index()).max();
+        self.reserve_vars(max_var.unwrap_or(0));
+        Models::new(self, Some(vars.to_vec()))
+    }
+
+    /// Limits every later `solve` call to `timeout` of wall-clock time, after
+    /// which it returns `Unknown`.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = Some(timeout);
+    }
+
+    /// A handle that makes the current (or next) `solve` call return
+    /// `Unknown`. It can be cloned and sent to other threads.
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.interrupt.clone()
+    }
+
+    /// Makes every later `solve` call poll `terminate` as it searches, and
+    /// return `Unknown` once it returns true: the IPASIR way of stopping a
+    /// search, for callers that cannot keep an `InterruptHandle`.
+    pub fn set_terminate(&mut self, terminate: impl FnMut() -> bool + Send + 'static) {
+        self.terminate = Some(Box::new(terminate));
+    }
+
+    /// Passes every clause learnt from now on with at most `max_len`
+    /// literals to `callback`, for instance to share it with another solver.
+    pub fn set_learn(&mut self, max_len: usize, callback: impl FnMut(&[Lit]) + Send + 'static) {
+        self.learn_callback = Some(Box::new(callback));
+        self.learn_max_len = max_len;
+    }
+
+    /// Number of decisions (assumptions included) on the current trail.
+    /// Stays at the level the search ended on after a `Sat` answer, and is 0
+    /// after any other answer.
+    pub fn decision_level(&self) -> usize {
+        self.trail.decision_level()
+    }
+
+    /// Decision level at which `var` was assigned, or `None` if it is unassigned.
+    pub fn level(&self, var: Var) -> Option<usize> {
+        self.assignments
+            .is_assigned(var)
+            .then(|| self.levels[var.index()])
+    }
+
+    /// Current value of `lit`: in the model after a `Sat` answer, until the
+    /// solver is next modified; otherwise as far as the last search left
+    /// it, which after any other answer is the values fixed at level 0.
+    pub fn value(&self, lit: Lit) -> LBool {
+        self.assignments.value(lit)
+    }
+
+    /// Value of `lit` if the solver has proven it, that is, if it holds at
+    /// decision level 0 and so in every model of the formula. Values chosen
+    /// by decisions, or implied by them, give `None`.
+    pub fn value_at_level_zero(&self, lit: Lit) -> Option<bool> {
+        match self.level(lit.var()) {
+            Some(0) => self.assignments.value(lit).into(),
+            _ => None,
+        }
+    }
+
+    /// The clause that implied the current value of `var`, or `None` if
+    /// `var` is unassigned, a decision or an assumption. Every propagated
+    /// literal, including facts derived at level 0, has one.
+    pub fn reason(&self, var: Var) -> Option<ClauseRef> {
+        self.reasons[var.index()]
+    }
+
+    /// Counters gathered so far by this solver.
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// Checks every clause added to the formula, as it was added rather
+    /// than as preprocessing left it, then the XOR clauses and native
+    /// pseudo-Boolean constraints, against the model of the last `Sat`
+    /// answer (eliminated variables included), returning the first one that
+    /// is not satisfied. Meant to be called right after that answer, as a
+    /// guard against propagation and model reconstruction bugs. Without a
+    /// model, the current assignment is checked instead.
+    pub fn verify_model(&self) -> Result<(), UnsatisfiedClause> {
+        let model = self.model.clone().unwrap_or_else(|| {
+            let mut model = Model::from_assignments(&self.assignments);
+            self.extend_model(&mut model);
+            model
+        });
+        let unsatisfied = self.originals.iter().find(|&cref| {
+            !self.originals[cref]
+                .iter()
+                .any(|&lit| model.lit_is_true(lit))
+        });
+        if let Some(cref) = unsatisfied {
+            return Err(UnsatisfiedClause {
+                literals: self.originals[cref].to_vec(),
+                xor: false,
+                pb: None,
+            });
+        }
+        if let Some(xor) = self.xors.iter().find(|xor| !xor.is_satisfied(&model)) {
+            return Err(UnsatisfiedClause {
+                literals: xor.to_literals(),
+                xor: true,
+                pb: None,
+            });
+        }
+        match self.pb_constraints().find(|pb| !pb.is_satisfied(&model)) {
+            Some(pb) => Err(UnsatisfiedClause {
+                literals: pb.terms().iter().map(|&(_, lit)| lit).collect(),
+                xor: false,
+                pb: Some(pb.clone()),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// After `solve_with_assumptions` returned `Unsat`, the subset of the
+    /// assumptions that together made the formula unsatisfiable. Empty if the
+    /// formula is unsatisfiable on its own, or after a `Sat` answer.
+    pub fn failed_assumptions(&self) -> &[Lit] {
+        &self.failed_assumptions
+    }
+
+    /// Flushes the proof requested through `config.proof_path`, reporting
+    /// any error met while creating or writing it. Call once solving is done.
+    pub fn finish_proof(&mut self) -> std::io::Result<()> {
+        match self.proof.as_mut() {
+            Some(proof) => proof.finish(),
+            None => Ok(()),
+        }
+    }
+
+    /// Flushes the search trace requested through `config.trace_path`,
+    /// reporting any error met while creating or writing it.
+    pub fn finish_trace(&mut self) -> std::io::Result<()> {
+        match self.trace.as_mut() {
+            Some(trace) => trace.finish(),
+            None => Ok(()),
+        }
+    }
+
+    /// Writes to the trace the trail literals assigned since it was last
+    /// written to, as decisions or propagations.
+    fn trace_trail(&mut self) {
+        let Some(trace) = self.trace.as_mut() else {
+            return;
+        };
+        for index in trace.traced..self.trail.len() {
+            let lit = self.trail[index];
+            let level = self.levels[lit.var().index()];
+            match self.reasons[lit.var().index()] {
+                Some(cref) => trace.propagation(lit, level, &self.clauses[cref]),
+                None if level > 0 => trace.decision(lit, level),
+                None => trace.propagation(lit, level, &[]),
+            }
+        }
+        trace.traced = self.trail.len();
+    }
+
+    /// Closes the proof with the empty clause once UNSAT is established at level 0.
+    fn log_refutation(&mut self) {
+        let hints = if self.wants_hints() {
+            self.refutation_hints()
+        } else {
+            Vec::new()
+        };
+        if let Some(proof) = self.proof.as_mut() {
+            proof.add(self.clauses.take_id(), &[], &hints);
+        }
+    }
+
+    /// Main CDCL loop. Each iteration propagates the pending trail literals,
+    /// then either resolves a conflict by learning and backjumping or opens a
+    /// new decision level, so the depth of the search never touches the stack.
+    fn search(&mut self, assumptions: &[Lit]) -> SolveResult {
+        let mut restarts = RestartSchedule::new(self.config.restart);
+        let mut conflicts_until_reduce = self.config.reduce_interval;
+        let mut reduce_interval = self.config.reduce_interval;
+        let mut rephase_interval = self.config.rephase_interval;
+        let mut conflicts_until_rephase = rephase_interval;
+        let mut conflicts_until_memory_check = MEMORY_CHECK_INTERVAL;
+        let conflicts_at_start = self.stats.conflicts;
+        let propagations_at_start = self.stats.propagations;
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+        self.rng = Rng::new(self.config.seed);
+        self.start_progress();
+
+        loop {
+            let conflict = self.propagate_with_external();
+            self.trace_trail();
+            if let Some(conflict) = conflict {
+                self.stats.conflicts += 1;
+                if let Some(trace) = self.trace.as_mut() {
+                    trace.conflict(self.trail.decision_level(), &self.clauses[conflict]);
+                }
+                if let Some(callback) = self.callbacks.conflict.as_mut() {
+                    callback(&self.clauses[conflict], self.trail.decision_level());
+                }
+                if self.trail.decision_level() == 0 {
+                    self.log_refutation();
+                    return SolveResult::Unsat;
+                }
+                let analysis = self.analyze(conflict);
+                self.order.decay(self.config.var_decay);
+                self.decay_clause_activities();
+                restarts.on_conflict();
+                conflicts_until_reduce = conflicts_until_reduce.saturating_sub(1);
+                conflicts_until_rephase = conflicts_until_rephase.saturating_sub(1);
+                self.note_best_trail();
+                if let Some(trace) = self.trace.as_mut() {
+                    trace.backjump(self.trail.decision_level(), analysis.backjump_level);
+                }
+                self.backtrack_to(analysis.backjump_level);
+                self.collect_garbage_if_wasteful();
+                self.learn(analysis.learnt, analysis.lbd, &analysis.hints);
+                self.note_progress();
+                conflicts_until_memory_check -= 1;
+                if conflicts_until_memory_check == 0 {
+                    conflicts_until_memory_check = MEMORY_CHECK_INTERVAL;
+                    if !self.enforce_memory_limit() {
+                        return SolveResult::Unknown;
+                    }
                 }
                 continue;
             }
 
-            let (var1_opt, var2_opt) = self.pick_branching_pair();
-            if var1_opt.is_none() {
-                return true; // All variables assigned → SAT
+            if self.should_stop(conflicts_at_start, propagations_at_start, deadline) {
+                return SolveResult::Unknown;
             }
 
-            let var1 = var1_opt.unwrap();
-            if let Some(var2) = var2_opt {
-                decision_stack.push(Decision::Pair {
-                    var1,
-                    var2,
-                    tried_comb: 0,
-                    tried_all: false,
-                });
-                self.trail_lim.push(self.trail.len());
-                if !self.assign_pair(0, var1, var2) {
-                    if !self.backtrack(&mut decision_stack) {
-                        return false;
-                    }
+            if restarts.should_restart() {
+                self.stats.restarts += 1;
+                self.log_progress('R', 2);
+                self.backtrack_to(0);
+                restarts.restarted();
+                if let Some(trace) = self.trace.as_mut() {
+                    trace.restart();
                 }
-            } else {
-                decision_stack.push(Decision::Single {
-                    var: var1,
-                    tried_polarity: true,
-                    tried_both: false,
-                });
-                self.trail_lim.push(self.trail.len());
-                let lit = Self::make_lit(var1, true);
-                if !Self::assign(&mut self.assignments, &mut self.trail, lit)
-                    || !self.propagate(lit)
-                {
-                    if !self.backtrack(&mut decision_stack) {
-                        return false;
-                    }
+                if let Some(callback) = self.callbacks.restart.as_mut() {
+                    callback(&self.stats, &mut self.config);
+                }
+                if !self.exchange_clauses() || (self.config.vivification && !self.vivify()) {
+                    self.log_refutation();
+                    return SolveResult::Unsat;
+                }
+                let schedule = &self.config.rephase_schedule;
+                if self.config.rephasing && conflicts_until_rephase == 0 && !schedule.is_empty() {
+                    self.rephase(schedule[self.stats.rephases as usize % schedule.len()]);
+                    rephase_interval += self.config.rephase_interval;
+                    conflicts_until_rephase = rephase_interval;
                 }
             }
-        }
-    }
 
-    fn backtrack(&mut self, stack: &mut Vec<Decision>) -> bool {
-        while let Some(mut dec) = stack.pop() {
-            let level = stack.len();
-            match dec {
-                Decision::Single {
-                    var,
-                    ref mut tried_polarity,
-                    ref mut tried_both,
-                } => {
-                    if *tried_both {
-                        self.undo_to_level(level);
-                        continue;
-                    }
-                    self.undo_to_level(level);
-                    *tried_polarity = !*tried_polarity;
-                    *tried_both = true;
-                    self.trail_lim.push(self.trail.len());
-                    let lit = Self::make_lit(var, *tried_polarity);
-                    let ok = Self::assign(&mut self.assignments, &mut self.trail, lit)
-                        && self.propagate(lit);
-                    stack.push(dec);
-                    if ok {
-                        return true;
-                    }
-                }
-                Decision::Pair {
-                    var1,
-                    var2,
-                    ref mut tried_comb,
-                    ref mut tried_all,
-                } => {
-                    if *tried_all {
-                        self.undo_to_level(level);
-                        continue;
-                    }
-                    self.undo_to_level(level);
-                    *tried_comb += 1;
-                    if *tried_comb >= 4 {
-                        *tried_all = true;
-                        stack.push(dec);
-                        continue;
+            if conflicts_until_reduce == 0 {
+                self.reduce_learnt_clauses();
+                self.log_progress('-', 2);
+                reduce_interval += self.config.reduce_increment;
+                conflicts_until_reduce = reduce_interval;
+            }
+
+            // Assumptions occupy the first decision levels, one per literal
+            let mut decision = None;
+            while let Some(&lit) = assumptions.get(self.trail.decision_level()) {
+                match self.assignments.value(lit) {
+                    LBool::True => self.trail.new_decision_level(),
+                    LBool::False => {
+                        self.failed_assumptions = self.analyze_final(lit);
+                        return SolveResult::Unsat;
                     }
-                    self.trail_lim.push(self.trail.len());
-                    let ok = self.assign_pair(*tried_comb, var1, var2);
-                    stack.push(dec);
-                    if ok {
-                        return true;
+                    LBool::Undef => {
+                        decision = Some(lit);
+                        break;
                     }
                 }
-                Decision::Implication { .. } => {
-                    // Only one test per implication — no retry
-                    self.undo_to_level(level);
-                    // Just continue searching
-                }
             }
+
+            let lit = match decision {
+                Some(lit) => lit,
+                None => match self.pick_branching_variable() {
+                    Some(var) => Lit::new(var, !self.pick_phase(var)),
+                    None => return SolveResult::Sat, // All variables assigned
+                },
+            };
+            self.trail.new_decision_level();
+            self.stats.decisions += 1;
+            self.stats.max_decision_level = self
+                .stats
+                .max_decision_level
+                .max(self.trail.decision_level());
+            if let Some(callback) = self.callbacks.decision.as_mut() {
+                callback(lit, self.trail.decision_level());
+            }
+            self.enqueue(lit, None);
         }
-        false
     }
 
+    /// Whether the search started when the counters were at
+    /// `conflicts_at_start` and `propagations_at_start` must give up: a
+    /// budget of `config` or the time limit ran out, the terminate callback
+    /// says so, or an interrupt came.
+    fn should_stop(
+        &mut self,
+        conflicts_at_start: u64,
+        propagations_at_start: u64,
+        deadline: Option<Instant>,
+    ) -> bool {
+        let out_of_conflicts = self
+            .config
+            .conflict_limit
+            .is_some_and(|limit| self.stats.conflicts - conflicts_at_start >= limit);
+        let out_of_propagations = self
+            .config
+            .propagation_limit
+            .is_some_and(|limit| self.stats.propagations - propagations_at_start >= limit);
+        let out_of_time = deadline.is_some_and(|deadline| Instant::now() >= deadline);
+        let terminated = self.terminate.as_mut().is_some_and(|terminate| terminate());
+        out_of_conflicts
+            || out_of_propagations
+            || out_of_time
+            || terminated
+            || self.interrupt.take()
+    }
+
+    /// Propagates every clause that is unit (or falsified) at level 0: input
+    /// unit clauses, and clauses added after earlier `solve` calls whose
+    /// other literals were already false.
     fn initial_propagation(&mut self) -> bool {
-        if self.clauses.iter().any(|c| c.literals.is_empty()) {
+        if !self.initial_xor_propagation() || !self.initial_pb_propagation() {
             return false;
         }
-
-        let units: Vec<Literal> = self
-            .clauses
-            .iter()
-            .filter(|c| c.literals.len() == 1)
-            .map(|c| c.literals[0])
-            .collect();
-
-        for l in units {
-            if !Self::assign(&mut self.assignments, &mut self.trail, l) || !self.propagate(l) {
-                return false;
+        let crefs: Vec<ClauseRef> = self.clauses.iter().collect();
+        for cref in crefs {
+            let w0 = match self.clauses[cref] {
+                [] => return false, // Empty clause
+                [w0] => w0,
+                [w0, w1, ..] => {
+                    if self.assignments.value(w1) != LBool::False {
+                        continue;
+                    }
+                    w0
+                }
+            };
+            match self.assignments.value(w0) {
+                LBool::True => {}
+                LBool::False => return false,
+                LBool::Undef => {
+                    self.enqueue(w0, Some(cref));
+                    if self.propagate().is_some() {
+                        return false;
+                    }
+                }
             }
         }
         true
     }
 
+    /// Prints the model of the last `Sat` answer as a DIMACS line.
     pub fn print_model(&self) {
-        for (i, &a) in self.assignments.iter().enumerate().skip(1) {
-            if let Some(val) = a {
-                print!("{}{} ", if val { "" } else { "-" }, i);
-            }
+        if let Some(model) = &self.model {
+            println!("{}", model.to_dimacs_string());
         }
-        println!("0");
     }
 }
@@ -0,0 +1,157 @@
+use std::fmt;
+use std::ops::Neg;
+
+use crate::error::InvalidLiteral;
+
+/// A propositional variable, numbered from 1 as in DIMACS.
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Var(u32);
+
+impl Var {
+    /// Largest variable whose literals still fit the packed encoding.
+    pub const MAX_INDEX: usize = (u32::MAX >> 1) as usize;
+
+    /// Panics if `index` is 0 or above `MAX_INDEX`.
+    #[inline]
+    pub fn new(index: usize) -> Self {
+        assert!(
+            (1..=Self::MAX_INDEX).contains(&index),
+            "variable index {index} out of range"
+        );
+        Self(index as u32)
+    }
+
+    /// Dense index of the variable, usable for per-variable arrays.
+    #[inline]
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    #[inline]
+    pub fn positive(self) -> Lit {
+        Lit::new(self, false)
+    }
+
+    #[inline]
+    pub fn negative(self) -> Lit {
+        Lit::new(self, true)
+    }
+}
+
+impl TryFrom<i32> for Var {
+    type Error = InvalidLiteral;
+
+    fn try_from(value: i32) -> Result<Self, InvalidLiteral> {
+        if value > 0 {
+            Ok(Self(value as u32))
+        } else {
+            Err(InvalidLiteral(value))
+        }
+    }
+}
+
+impl From<Var> for i32 {
+    #[inline]
+    fn from(var: Var) -> i32 {
+        var.0 as i32
+    }
+}
+
+impl fmt::Debug for Var {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "x{}", self.0)
+    }
+}
+
+impl fmt::Display for Var {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A variable or its negation, packed as `var << 1 | sign`, so the two
+/// literals of a variable are adjacent and `index()` can address
+/// per-literal arrays such as watch lists directly.
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Lit(u32);
+
+impl Lit {
+    /// The literal of `var` that is negated when `sign` is true.
+    #[inline]
+    pub fn new(var: Var, sign: bool) -> Self {
+        Self(var.0 << 1 | sign as u32)
+    }
+
+    #[inline]
+    pub fn var(self) -> Var {
+        Var(self.0 >> 1)
+    }
+
+    /// True for a negated literal.
+    #[inline]
+    pub fn sign(self) -> bool {
+        self.0 & 1 == 1
+    }
+
+    #[inline]
+    pub fn negate(self) -> Self {
+        Self(self.0 ^ 1)
+    }
+
+    /// Dense index of the literal, usable for per-literal arrays.
+    #[inline]
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    /// The literal whose `index()` is `index`. Panics if `index` does not
+    /// belong to a variable in the range accepted by `Var::new`.
+    #[inline]
+    pub fn from_index(index: usize) -> Self {
+        Self::new(Var::new(index >> 1), index & 1 == 1)
+    }
+}
+
+impl Neg for Lit {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        self.negate()
+    }
+}
+
+impl TryFrom<i32> for Lit {
+    type Error = InvalidLiteral;
+
+    fn try_from(value: i32) -> Result<Self, InvalidLiteral> {
+        if value == 0 || value == i32::MIN {
+            return Err(InvalidLiteral(value));
+        }
+        Ok(Self::new(Var(value.unsigned_abs()), value < 0))
+    }
+}
+
+impl From<Lit> for i32 {
+    #[inline]
+    fn from(lit: Lit) -> i32 {
+        let var = i32::from(lit.var());
+        if lit.sign() { -var } else { var }
+    }
+}
+
+impl fmt::Debug for Lit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", i32::from(*self))
+    }
+}
+
+impl fmt::Display for Lit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", i32::from(*self))
+    }
+}
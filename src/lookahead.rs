@@ -0,0 +1,253 @@
+use crate::{Instant, Lit, SolveResult, Solver, Var};
+
+/// Most variables whose literals are looked ahead on at each node, picked
+/// by how often their literals occur in the input clauses.
+const MAX_CANDIDATES: usize = 48;
+
+/// Candidates looked ahead on again below a literal by double lookahead.
+const DOUBLE_CANDIDATES: usize = 8;
+
+/// How a decision level of the lookahead search was opened.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Branch {
+    Assumption,
+    /// The first value tried for the variable; the other one is left.
+    First,
+    Second,
+}
+
+/// What looking ahead on the candidates of a node found.
+enum Lookahead {
+    /// The literal to branch on.
+    Branch(Lit),
+    /// Literals failed and were fixed to false, which propagated without a
+    /// conflict; the node is to be looked ahead on again.
+    Failed,
+    /// Fixing the failed literals led to a conflict.
+    Conflict,
+    /// Every variable is assigned.
+    Model,
+}
+
+impl Solver {
+    /// Lookahead DPLL search (`Engine::Lookahead`), run like `search` once
+    /// the input is propagated at level 0. Without learnt clauses a conflict
+    /// undoes the last decision whose other value has not been tried yet.
+    /// A proof gets, for every refuted node, the clause that negates its
+    /// decisions, each of which follows by unit propagation from the ones
+    /// before.
+    pub(crate) fn lookahead_search(&mut self, assumptions: &[Lit]) -> SolveResult {
+        let conflicts_at_start = self.stats.conflicts;
+        let propagations_at_start = self.stats.propagations;
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+        let occurrences = self.input_occurrences();
+        // The literal decided or assumed on each decision level
+        let mut path: Vec<Lit> = Vec::new();
+        let mut branches: Vec<Branch> = Vec::new();
+        let mut conflict = false;
+
+        loop {
+            if std::mem::take(&mut conflict) || self.propagate().is_some() {
+                self.stats.conflicts += 1;
+                self.log_lemma(&path, &[]);
+                loop {
+                    let (Some(lit), Some(branch)) = (path.pop(), branches.pop()) else {
+                        return SolveResult::Unsat;
+                    };
+                    self.backtrack_to(path.len());
+                    match branch {
+                        Branch::Assumption => {
+                            path.push(lit);
+                            self.failed_assumptions = path;
+                            self.backtrack_to(0);
+                            return SolveResult::Unsat;
+                        }
+                        Branch::First => {
+                            self.trail.new_decision_level();
+                            self.enqueue(-lit, None);
+                            path.push(-lit);
+                            branches.push(Branch::Second);
+                            break;
+                        }
+                        // Both values failed, refuting the node above
+                        Branch::Second => self.log_lemma(&path, &[]),
+                    }
+                }
+                continue;
+            }
+
+            if self.should_stop(conflicts_at_start, propagations_at_start, deadline) {
+                return SolveResult::Unknown;
+            }
+
+            if let Some(&lit) = assumptions.get(self.trail.decision_level()) {
+                if self.value_at(lit) == Some(false) {
+                    path.push(lit);
+                    self.failed_assumptions = path;
+                    return SolveResult::Unsat;
+                }
+                self.trail.new_decision_level();
+                if self.value_at(lit).is_none() {
+                    self.enqueue(lit, None);
+                }
+                path.push(lit);
+                branches.push(Branch::Assumption);
+                continue;
+            }
+
+            let lit = match self.look_ahead_all(&occurrences, &mut path) {
+                Lookahead::Branch(lit) => lit,
+                Lookahead::Failed => continue,
+                Lookahead::Conflict => {
+                    conflict = true;
+                    continue;
+                }
+                Lookahead::Model => return SolveResult::Sat,
+            };
+            self.trail.new_decision_level();
+            self.stats.decisions += 1;
+            self.stats.max_decision_level = self
+                .stats
+                .max_decision_level
+                .max(self.trail.decision_level());
+            self.enqueue(lit, None);
+            path.push(lit);
+            branches.push(Branch::First);
+        }
+    }
+
+    /// Occurrences of every literal in the input clauses.
+    fn input_occurrences(&self) -> Vec<u32> {
+        let mut occurrences = vec![0; (self.num_vars() + 1) * 2];
+        for cref in self.clauses.iter() {
+            if !self.clauses.is_learnt(cref) {
+                for &lit in &self.clauses[cref] {
+                    occurrences[lit.index()] += 1;
+                }
+            }
+        }
+        occurrences
+    }
+
+    fn value_at(&self, lit: Lit) -> Option<bool> {
+        self.assignments.value(lit).into()
+    }
+
+    /// Looks ahead on both literals of the candidate variables. A literal
+    /// whose propagation fails is fixed to false and propagated right away;
+    /// if any was, the node is looked ahead on again. Otherwise the literal
+    /// to branch on: of the variable whose two literals maximize
+    /// `(implied(l) + 1) · (implied(-l) + 1)`, the one that implies less,
+    /// leaving more freedom to the first branch.
+    fn look_ahead_all(&mut self, occurrences: &[u32], path: &mut Vec<Lit>) -> Lookahead {
+        let mut candidates: Vec<Var> = (1..=self.num_vars())
+            .map(Var::new)
+            .filter(|&var| !self.assignments.is_assigned(var) && !self.eliminated[var.index()])
+            .collect();
+        let score = |var: &Var| {
+            let (pos, neg) = (var.positive().index(), var.negative().index());
+            u64::from(occurrences[pos] + 1) * u64::from(occurrences[neg] + 1)
+        };
+        if candidates.len() > MAX_CANDIDATES {
+            candidates
+                .select_nth_unstable_by_key(MAX_CANDIDATES, |var| std::cmp::Reverse(score(var)));
+            candidates.truncate(MAX_CANDIDATES);
+        }
+        candidates.sort_by_key(|var| std::cmp::Reverse(score(var)));
+
+        // Double lookahead pays off below literals that imply a lot
+        let unassigned = candidates.len();
+        let mut failed = false;
+        let mut best: Option<(u64, Lit)> = None;
+        for (index, &var) in candidates.iter().enumerate() {
+            let mut implied = [0; 2];
+            for (side, lit) in [var.positive(), var.negative()].into_iter().enumerate() {
+                if self.assignments.is_assigned(var) {
+                    break;
+                }
+                let inner = &candidates[index + 1..];
+                match self.look_ahead(lit, inner, path, unassigned) {
+                    Some(count) => implied[side] = count,
+                    None => {
+                        failed = true;
+                        self.stats.failed_literals += 1;
+                        self.log_lemma(path, &[lit]);
+                        // At level 0 the fact is kept as a unit clause, its reason
+                        let reason = (self.trail.decision_level() == 0)
+                            .then(|| self.clauses.push(&[-lit], true, 1));
+                        self.enqueue(-lit, reason);
+                        if self.propagate().is_some() {
+                            return Lookahead::Conflict;
+                        }
+                    }
+                }
+            }
+            if self.assignments.is_assigned(var) {
+                continue;
+            }
+            let [pos, neg] = implied.map(|count| count as u64 + 1);
+            let lit = if pos <= neg {
+                var.positive()
+            } else {
+                var.negative()
+            };
+            if best.is_none_or(|(score, _)| pos * neg > score) {
+                best = Some((pos * neg, lit));
+            }
+        }
+        match best {
+            _ if failed => Lookahead::Failed,
+            Some((_, lit)) => Lookahead::Branch(lit),
+            None => Lookahead::Model,
+        }
+    }
+
+    /// Propagates `lit` on a new decision level and undoes it again,
+    /// returning how many literals it implied, or `None` if it failed. When
+    /// it implies a tenth of the `unassigned` candidates or more, `inner`
+    /// ones are looked ahead on below it as well, and those that fail are
+    /// fixed to false under `lit`, which fails if that leads to a conflict.
+    fn look_ahead(
+        &mut self,
+        lit: Lit,
+        inner: &[Var],
+        path: &mut Vec<Lit>,
+        unassigned: usize,
+    ) -> Option<usize> {
+        let level = self.trail.decision_level();
+        let start = self.trail.len();
+        self.stats.literals_probed += 1;
+        self.trail.new_decision_level();
+        self.enqueue(lit, None);
+        let mut failed = self.propagate().is_some();
+
+        if !failed && !inner.is_empty() && (self.trail.len() - start) * 10 >= unassigned {
+            path.push(lit);
+            for &var in inner.iter().take(DOUBLE_CANDIDATES) {
+                for inner_lit in [var.positive(), var.negative()] {
+                    if failed || self.assignments.is_assigned(var) {
+                        break;
+                    }
+                    if self.look_ahead(inner_lit, &[], path, unassigned).is_none() {
+                        self.log_lemma(path, &[inner_lit]);
+                        self.enqueue(-inner_lit, None);
+                        failed = self.propagate().is_some();
+                    }
+                }
+            }
+            path.pop();
+        }
+
+        let implied = self.trail.len() - start;
+        self.backtrack_to(level);
+        (!failed).then_some(implied)
+    }
+
+    /// Adds to the proof the clause ruling out `path` followed by `extra`.
+    fn log_lemma(&mut self, path: &[Lit], extra: &[Lit]) {
+        if let Some(proof) = self.proof.as_mut() {
+            let clause: Vec<Lit> = path.iter().chain(extra).map(|&lit| -lit).collect();
+            proof.add(self.clauses.take_id(), &clause, &[]);
+        }
+    }
+}
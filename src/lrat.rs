@@ -0,0 +1,134 @@
+use crate::{ClauseRef, LBool, Lit, Solver, Var};
+
+impl Solver {
+    /// Whether an LRAT proof is being written, so that added clauses need
+    /// their hints.
+    pub(crate) fn wants_hints(&self) -> bool {
+        self.proof.as_ref().is_some_and(|proof| proof.takes_hints())
+    }
+
+    /// LRAT hints deriving `clause`, whose literals are all false, from
+    /// `conflict`, whose literals are false too: the unit clauses of the
+    /// variables fixed at level 0 that the derivation uses, then the
+    /// reasons of the implied literals it resolves on, in trail order, and
+    /// `conflict` last. Assuming `clause` false, each of them becomes unit
+    /// in turn and the last one is falsified, so a checker only has to
+    /// propagate these clauses. The literals of `clause` are never resolved
+    /// on, which covers the first-UIP clause, its minimization, failed
+    /// literals and vivified clauses alike.
+    pub(crate) fn conflict_hints(&mut self, conflict: ClauseRef, clause: &[Lit]) -> Vec<u64> {
+        let mut given = vec![false; self.num_vars() + 1];
+        let mut seen = vec![false; self.num_vars() + 1];
+        for lit in clause {
+            given[lit.var().index()] = true;
+            seen[lit.var().index()] = true;
+        }
+        let mut fixed = Vec::new();
+        let mut chain = vec![self.clauses.id(conflict)];
+        self.mark_antecedents(conflict, &mut seen, &mut fixed);
+
+        let start = match self.trail.decision_level() {
+            0 => self.trail.len(),
+            _ => self.trail.level_start(1),
+        };
+        for index in (start..self.trail.len()).rev() {
+            let var = self.trail[index].var().index();
+            if !seen[var] || given[var] {
+                continue;
+            }
+            if let Some(reason) = self.reasons[var] {
+                chain.push(self.clauses.id(reason));
+                self.mark_antecedents(reason, &mut seen, &mut fixed);
+            }
+        }
+
+        let mut hints = self.unit_hints(&fixed);
+        hints.extend(chain.into_iter().rev());
+        hints
+    }
+
+    /// Marks the variables of clause `cref` not marked in `seen` yet,
+    /// collecting those fixed at level 0 in `fixed`.
+    fn mark_antecedents(&self, cref: ClauseRef, seen: &mut [bool], fixed: &mut Vec<Var>) {
+        for &lit in &self.clauses[cref] {
+            let var = lit.var();
+            if seen[var.index()] {
+                continue;
+            }
+            seen[var.index()] = true;
+            if self.levels[var.index()] == 0 {
+                fixed.push(var);
+            }
+        }
+    }
+
+    /// IDs of unit clauses stating the values of `vars`, fixed at level 0.
+    /// A variable fixed by a longer clause gets its unit clause written to
+    /// the proof the first time it is needed, derived from that clause and
+    /// the unit clauses of its other variables.
+    pub(crate) fn unit_hints(&mut self, vars: &[Var]) -> Vec<u64> {
+        let mut hints = Vec::with_capacity(vars.len());
+        let mut expanded = Vec::new();
+        for &var in vars {
+            if let Some(id) = self.proof.as_ref().and_then(|proof| proof.unit(var)) {
+                hints.push(id);
+                continue;
+            }
+            if expanded.is_empty() {
+                expanded = vec![false; self.num_vars() + 1];
+            }
+            // Depth first, writing a unit clause once those it needs are written
+            let mut stack = vec![var];
+            while let Some(&top) = stack.last() {
+                let Some(proof) = self.proof.as_mut() else {
+                    return hints;
+                };
+                if proof.unit(top).is_some() {
+                    stack.pop();
+                    continue;
+                }
+                let Some(reason) = self.reasons[top.index()] else {
+                    stack.pop(); // Fixed without a clause: no hint to give
+                    continue;
+                };
+                let others = self.clauses[reason].iter().map(|lit| lit.var());
+                if !expanded[top.index()] {
+                    expanded[top.index()] = true;
+                    stack.extend(others.filter(|&v| v != top && proof.unit(v).is_none()));
+                    continue;
+                }
+                stack.pop();
+                let id = if self.clauses[reason].len() == 1 {
+                    self.clauses.id(reason)
+                } else {
+                    let mut unit_hints: Vec<u64> = others.filter_map(|v| proof.unit(v)).collect();
+                    unit_hints.push(self.clauses.id(reason));
+                    let lit = Lit::new(top, self.assignments.value(top.positive()) == LBool::False);
+                    let id = self.clauses.take_id();
+                    proof.add(id, &[lit], &unit_hints);
+                    id
+                };
+                proof.set_unit(top, id);
+            }
+            hints.extend(self.proof.as_ref().and_then(|proof| proof.unit(var)));
+        }
+        hints
+    }
+
+    /// LRAT hints for the empty clause once a clause is falsified at level
+    /// 0, or none if no such clause is found (a conflict of an XOR clause
+    /// or a native pseudo-Boolean constraint).
+    pub(crate) fn refutation_hints(&mut self) -> Vec<u64> {
+        let falsified = self.clauses.iter().find(|&cref| {
+            !self.clauses.is_lazy_reason(cref)
+                && self.clauses[cref].iter().all(|&lit| {
+                    self.assignments.value(lit) == LBool::False
+                        && self.levels[lit.var().index()] == 0
+                })
+        });
+        match falsified {
+            Some(cref) => self.conflict_hints(cref, &[]),
+            None => Vec::new(),
+        }
+    }
+}
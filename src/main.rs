@@ -1,19 +1,1393 @@
-use std::env;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
 use std::process;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
 
-use cnf_dpll_2wl::Solver;
+use clap::{ArgAction, CommandFactory, Parser, ValueEnum};
+use cnf_dpll_2wl::{
+    Aiger, Branching, Engine, Icnf, IcnfStep, InterruptHandle, Lit, MaxSatAlgorithm, MaxSatResult,
+    MaxSatSolver, Model, MusSolver, OpbProblem, ParseOptions, PbEncoding, PortfolioSolver,
+    ProofChecker, ProofFormat, QbfResult, QbfSolver, Rephase, SolveResult, Solver, SolverConfig,
+    Stats,
+};
+use signal_hook::consts::SIGINT;
+use signal_hook::iterator::Signals;
 
-fn main() {
-    let path = env::args().nth(1).expect("Usage: solver <file.cnf>");
-    let mut solver = Solver::new(&path).unwrap_or_else(|e| {
-        eprintln!("Error: {e}");
-        process::exit(1);
+/// Input format selected by `--format`.
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+enum Format {
+    #[default]
+    Cnf,
+    Wcnf,
+    Opb,
+    Aiger,
+    Icnf,
+    Qdimacs,
+    Gcnf,
+}
+
+/// Preprocessing pass selected by `--pre`.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Pass {
+    /// Substitute equivalent literals
+    Equiv,
+    /// Subsumption and self-subsuming resolution
+    Subsume,
+    /// Bounded variable elimination
+    Elim,
+    /// Lex-leader clauses breaking the symmetries of the input
+    Symmetry,
+    /// At-most-one groups in place of the binary clauses between their literals
+    Amo,
+}
+
+/// Format of the `--report` file.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ReportFormat {
+    Csv,
+    Json,
+}
+
+/// A CDCL SAT solver with two watched literals.
+///
+/// Reads DIMACS CNF (plain, gzip or xz), WCNF, OPB, AIGER, incremental CNF,
+/// QDIMACS or group CNF, and answers in the format of the SAT competitions: a status
+/// line, the model as `v` lines, and exit code 10 (SAT), 20 (UNSAT) or 0
+/// (unknown).
+#[derive(Parser)]
+#[command(version)]
+struct Options {
+    /// Input file, or `-` (the default) for standard input. Several files
+    /// or a directory are solved in batch, one table row per file
+    #[arg(value_name = "FILE")]
+    inputs: Vec<String>,
+    /// Solve N batch files at a time
+    #[arg(long, value_name = "N", value_parser = parse_jobs, default_value = "1")]
+    jobs: usize,
+    /// Write a DRAT or LRAT proof of an UNSAT answer to FILE
+    #[arg(long, value_name = "FILE")]
+    proof: Option<PathBuf>,
+    /// Encoding of the proof: drat, binary or lrat
+    #[arg(long, value_name = "FORMAT", value_parser = parse_proof_format, default_value = "drat")]
+    proof_format: ProofFormat,
+    /// Check that PROOF, a DRAT or LRAT proof in any encoding, refutes the
+    /// input instead of solving it
+    #[arg(long, value_name = "PROOF")]
+    check_proof: Option<PathBuf>,
+    /// Check the model against the input before printing it
+    #[arg(long)]
+    check_model: bool,
+    /// Print a resolution refutation of an unsatisfiable input as a tree of
+    /// `c` lines. Inputs of more than 1000 clauses are skipped
+    #[arg(long)]
+    resolution_proof: bool,
+    /// Write the input clauses an UNSAT answer depends on to FILE as DIMACS
+    /// CNF, each after a `c clause N` line giving its number in the input.
+    /// Found from the proof with --proof or --resolution-proof, or else by
+    /// solving with a selector literal per clause
+    #[arg(long, value_name = "FILE")]
+    core: Option<PathBuf>,
+    /// Write the implication graph left by the search to FILE as GraphViz DOT
+    #[arg(long, value_name = "FILE")]
+    dump_graph: Option<PathBuf>,
+    /// Preprocess the input, write the simplified formula to FILE as DIMACS
+    /// CNF with its variable renaming in `c map` lines, and stop. Runs
+    /// every --pre pass when none is chosen
+    #[arg(long, value_name = "FILE")]
+    simplify_only: Option<PathBuf>,
+    /// Record the search in FILE as JSON lines
+    #[arg(long, value_name = "FILE")]
+    trace: Option<PathBuf>,
+    /// Print search statistics as `c` lines
+    #[arg(long)]
+    stats: bool,
+    /// Print progress lines during the search; -vv also at every restart
+    /// and reduction
+    #[arg(short, long, action = ArgAction::Count, conflicts_with = "quiet")]
+    verbose: u8,
+    /// Leave out the model, printing only the status line
+    #[arg(short, long)]
+    quiet: bool,
+    /// Write the statistics of each input to FILE, as csv or json
+    #[arg(long, num_args = 2, value_names = ["FORMAT", "FILE"])]
+    report: Option<Vec<String>>,
+    /// Give up with `s UNKNOWN` after SECS seconds, per file in batch mode
+    #[arg(long, value_name = "SECS", value_parser = parse_timeout)]
+    timeout: Option<Duration>,
+    /// Delete learnt clauses to stay within MB megabytes, and give up with
+    /// `s UNKNOWN` if that is not enough
+    #[arg(long, value_name = "MB", value_parser = parse_mem_limit)]
+    mem_limit: Option<usize>,
+    /// Run a portfolio of N differently configured solvers
+    #[arg(long, value_name = "N", value_parser = parse_threads, default_value = "1")]
+    threads: usize,
+    /// Search procedure: cdcl, lookahead or walksat. Walksat finds models
+    /// but cannot prove an input unsatisfiable
+    #[arg(long, value_name = "ENGINE", value_parser = parse_engine, default_value = "cdcl")]
+    engine: Engine,
+    /// Decision heuristic: vsids, vmtf or lrb
+    #[arg(long, value_name = "HEURISTIC", value_parser = parse_branching, default_value = "vsids")]
+    branching: Branching,
+    /// Probability of a random decision variable and value
+    #[arg(long, value_name = "P", default_value_t = 0.0)]
+    random_freq: f64,
+    /// Seed of the random choices
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    seed: u64,
+    /// Preprocessing passes to run before the first search
+    #[arg(long, value_name = "PASSES", value_enum, value_delimiter = ',')]
+    pre: Vec<Pass>,
+    /// Run failed literal probing before the first search
+    #[arg(long)]
+    probe: bool,
+    /// Vivify learnt clauses at restarts
+    #[arg(long)]
+    vivify: bool,
+    /// Reset the saved phases now and then
+    #[arg(long)]
+    rephase: bool,
+    /// Kinds of rephasing taking turns: best, walk, original, inverted,
+    /// random (implies --rephase)
+    #[arg(long, value_name = "KINDS", value_parser = parse_rephase, value_delimiter = ',')]
+    rephase_schedule: Option<Vec<Rephase>>,
+    /// Propagate XOR clauses by Gauss-Jordan elimination
+    #[arg(long)]
+    gauss: bool,
+    /// Print every model
+    #[arg(long)]
+    all: bool,
+    /// Print at most N models (implies --all)
+    #[arg(long, value_name = "N")]
+    max_models: Option<usize>,
+    /// Solve a WCNF input as a MaxSAT instance
+    #[arg(long)]
+    maxsat: bool,
+    /// MaxSAT algorithm: linear, binary or oll (implies --maxsat)
+    #[arg(long = "maxsat-algo", value_name = "ALGORITHM", value_parser = parse_maxsat_algorithm)]
+    maxsat_algorithm: Option<MaxSatAlgorithm>,
+    /// Input format
+    #[arg(long, value_enum, default_value_t = Format::Cnf)]
+    format: Format,
+    /// Reject CNF input that strays from the DIMACS format in any way
+    #[arg(long, conflicts_with = "lenient")]
+    strict: bool,
+    /// Make what sense can be made of a CNF input with a malformed header
+    /// or a truncated last clause, as in old SATLIB files
+    #[arg(long)]
+    lenient: bool,
+    /// Encoding of pseudo-Boolean constraints: bdd, adder or native
+    #[arg(long, value_name = "ENCODING", value_parser = parse_pb_encoding, default_value = "bdd")]
+    pb_encoding: PbEncoding,
+}
+
+impl Options {
+    /// The single input, `None` for standard input.
+    fn input(&self) -> Option<&str> {
+        self.inputs.first().map(String::as_str)
+    }
+
+    /// Whether the inputs are solved in batch: several of them, or a
+    /// directory.
+    fn is_batch(&self) -> bool {
+        self.inputs.len() > 1 || self.inputs.iter().any(|path| Path::new(path).is_dir())
+    }
+
+    /// How a CNF input is parsed.
+    fn parse_options(&self) -> ParseOptions {
+        ParseOptions {
+            strict: self.strict,
+            lenient: self.lenient,
+            ..ParseOptions::default()
+        }
+    }
+
+    /// The format and path of the `--report` file, once `check_options`
+    /// has checked the format.
+    fn report(&self) -> Option<(ReportFormat, &Path)> {
+        let [format, path] = self.report.as_deref()? else {
+            unreachable!("--report takes two values");
+        };
+        let format = ReportFormat::from_str(format, true).expect("checked by check_options");
+        Some((format, Path::new(path)))
+    }
+}
+
+fn parse_proof_format(format: &str) -> Result<ProofFormat, String> {
+    match format {
+        "drat" => Ok(ProofFormat::Drat),
+        "binary" => Ok(ProofFormat::BinaryDrat),
+        "lrat" => Ok(ProofFormat::Lrat),
+        _ => Err("--proof-format must be drat, binary or lrat".into()),
+    }
+}
+
+fn parse_timeout(secs: &str) -> Result<Duration, String> {
+    let secs = secs.parse::<f64>().ok();
+    let timeout = secs.and_then(|s| Duration::try_from_secs_f64(s).ok());
+    timeout.ok_or_else(|| "--timeout needs a number of seconds".into())
+}
+
+fn parse_threads(threads: &str) -> Result<usize, String> {
+    let threads = threads.parse().ok().filter(|&n| n > 0);
+    threads.ok_or_else(|| "--threads needs a positive number".into())
+}
+
+fn parse_mem_limit(megabytes: &str) -> Result<usize, String> {
+    let megabytes = megabytes.parse::<usize>().ok().filter(|&mb| mb > 0);
+    let bytes = megabytes.and_then(|mb| mb.checked_mul(1 << 20));
+    bytes.ok_or_else(|| "--mem-limit needs a positive number of megabytes".into())
+}
+
+fn parse_jobs(jobs: &str) -> Result<usize, String> {
+    let jobs = jobs.parse().ok().filter(|&n| n > 0);
+    jobs.ok_or_else(|| "--jobs needs a positive number".into())
+}
+
+fn parse_engine(engine: &str) -> Result<Engine, String> {
+    match engine {
+        "cdcl" => Ok(Engine::Cdcl),
+        "lookahead" => Ok(Engine::Lookahead),
+        "walksat" => Ok(Engine::WalkSat),
+        engine => Err(format!("unknown engine {engine}")),
+    }
+}
+
+fn parse_branching(branching: &str) -> Result<Branching, String> {
+    match branching {
+        "vsids" => Ok(Branching::Vsids),
+        "vmtf" => Ok(Branching::Vmtf),
+        "lrb" => Ok(Branching::Lrb),
+        branching => Err(format!("unknown branching heuristic {branching}")),
+    }
+}
+
+fn parse_rephase(kind: &str) -> Result<Rephase, String> {
+    match kind {
+        "best" => Ok(Rephase::Best),
+        "walk" => Ok(Rephase::Walk),
+        "original" => Ok(Rephase::Original),
+        "inverted" => Ok(Rephase::Inverted),
+        "random" => Ok(Rephase::Random),
+        _ => Err(format!("unknown rephasing {kind}")),
+    }
+}
+
+fn parse_maxsat_algorithm(algorithm: &str) -> Result<MaxSatAlgorithm, String> {
+    match algorithm {
+        "linear" => Ok(MaxSatAlgorithm::Linear),
+        "binary" => Ok(MaxSatAlgorithm::Binary),
+        "oll" => Ok(MaxSatAlgorithm::Oll),
+        algo => Err(format!("unknown MaxSAT algorithm {algo}")),
+    }
+}
+
+fn parse_pb_encoding(encoding: &str) -> Result<PbEncoding, String> {
+    match encoding {
+        "bdd" => Ok(PbEncoding::Bdd),
+        "adder" => Ok(PbEncoding::Adder),
+        "native" => Ok(PbEncoding::Native),
+        encoding => Err(format!("unknown pseudo-Boolean encoding {encoding}")),
+    }
+}
+
+/// Completes the options with those other options imply, and rejects
+/// combinations that do not go together.
+fn check_options(mut options: Options) -> Result<Options, String> {
+    if !(0.0..=1.0).contains(&options.random_freq) {
+        return Err("--random-freq needs a probability between 0 and 1".into());
+    }
+    options.all |= options.max_models.is_some();
+    options.maxsat |= options.maxsat_algorithm.is_some();
+    options.rephase |= options.rephase_schedule.is_some();
+    match options.format {
+        Format::Wcnf => options.maxsat = true,
+        Format::Opb if options.maxsat => {
+            return Err("--maxsat reads WCNF; OPB objectives are minimized anyway".into());
+        }
+        Format::Aiger | Format::Icnf | Format::Qdimacs | Format::Gcnf if options.maxsat => {
+            return Err("--maxsat reads WCNF".into());
+        }
+        Format::Opb | Format::Aiger | Format::Icnf | Format::Qdimacs | Format::Gcnf
+            if options.all || options.proof.is_some() =>
+        {
+            // Neither covers the auxiliary variables of the encoding
+            return Err("--all and --proof only apply to CNF and WCNF inputs".into());
+        }
+        Format::Cnf if options.maxsat => options.format = Format::Wcnf,
+        _ => {}
+    }
+    if options.pre.contains(&Pass::Symmetry)
+        && (options.all
+            || options.proof.is_some()
+            || options.resolution_proof
+            || options.core.is_some()
+            || options.format == Format::Icnf)
+    {
+        // Symmetric models are lost, later clauses may not share the
+        // symmetries, and the clauses follow from no proof
+        return Err(
+            "--pre=symmetry only applies to a single search, without --all, --proof, --resolution-proof or --core"
+                .into(),
+        );
+    }
+    if options.all && options.proof.is_some() {
+        // Blocking clauses are not implied by the formula
+        return Err("--proof cannot be combined with model enumeration".into());
+    }
+    if options.threads > 1 {
+        if !matches!(options.format, Format::Cnf | Format::Aiger) {
+            return Err("--threads only applies to CNF and AIGER inputs".into());
+        }
+        if options.all || options.proof.is_some() {
+            return Err("--threads cannot be combined with --all or --proof".into());
+        }
+    }
+    if options.trace.is_some()
+        && (options.all
+            || options.threads > 1
+            || !matches!(options.format, Format::Cnf | Format::Aiger))
+    {
+        return Err("--trace only applies to a single search of a CNF or AIGER input".into());
+    }
+    if options.proof_format == ProofFormat::Lrat && options.engine != Engine::Cdcl {
+        // Lookahead lemmas come without hints
+        return Err("--proof-format lrat needs the cdcl engine".into());
+    }
+    if options.core.is_some()
+        && (options.all
+            || options.threads > 1
+            || options.trace.is_some()
+            || options.dump_graph.is_some()
+            || options.format != Format::Cnf
+            || options.maxsat)
+    {
+        return Err(
+            "--core only applies to a single search of a CNF input, without --trace or --dump-graph"
+                .into(),
+        );
+    }
+    if options.resolution_proof {
+        if options.all
+            || options.threads > 1
+            || !matches!(options.format, Format::Cnf | Format::Aiger)
+        {
+            return Err(
+                "--resolution-proof only applies to a single search of a CNF or AIGER input".into(),
+            );
+        }
+        if options.engine != Engine::Cdcl {
+            return Err("--resolution-proof needs the cdcl engine".into());
+        }
+    }
+    if options.maxsat && (options.all || options.proof.is_some() || options.check_model) {
+        return Err("--maxsat cannot be combined with --all, --proof or --check-model".into());
+    }
+    if let Some([format, _]) = options.report.as_deref() {
+        if ReportFormat::from_str(format, true).is_err() {
+            return Err("--report needs csv or json as its format".into());
+        }
+        if options.all || options.maxsat || !matches!(options.format, Format::Cnf | Format::Aiger) {
+            return Err("--report only applies to single searches of CNF or AIGER inputs".into());
+        }
+    }
+    if (options.strict || options.lenient) && (options.format != Format::Cnf || options.maxsat) {
+        return Err("--strict and --lenient only apply to CNF inputs".into());
+    }
+    if options.check_proof.is_some() {
+        if options.format != Format::Cnf || options.maxsat {
+            return Err("--check-proof only applies to CNF inputs".into());
+        }
+        if options.all
+            || options.proof.is_some()
+            || options.trace.is_some()
+            || options.dump_graph.is_some()
+            || options.report.is_some()
+            || options.simplify_only.is_some()
+            || options.threads > 1
+        {
+            return Err(
+                "--check-proof cannot be combined with --all, --proof, --trace, --dump-graph, --report, --simplify-only or --threads"
+                    .into(),
+            );
+        }
+    }
+    if options.simplify_only.is_some() {
+        if options.format != Format::Cnf || options.maxsat {
+            return Err("--simplify-only only applies to CNF inputs".into());
+        }
+        if options.all
+            || options.proof.is_some()
+            || options.trace.is_some()
+            || options.dump_graph.is_some()
+            || options.report.is_some()
+            || options.threads > 1
+        {
+            return Err(
+                "--simplify-only cannot be combined with --all, --proof, --trace, --dump-graph, --report or --threads"
+                    .into(),
+            );
+        }
+        if options.pre.is_empty() {
+            options.pre = vec![Pass::Equiv, Pass::Subsume, Pass::Elim];
+        }
+    }
+    if options.is_batch() {
+        if options.inputs.iter().any(|input| input == "-") {
+            return Err("standard input cannot be solved in batch".into());
+        }
+        if options.format != Format::Cnf || options.maxsat {
+            return Err("several inputs are only solved as CNF".into());
+        }
+        if options.all
+            || options.proof.is_some()
+            || options.trace.is_some()
+            || options.dump_graph.is_some()
+            || options.simplify_only.is_some()
+            || options.check_proof.is_some()
+            || options.resolution_proof
+            || options.core.is_some()
+            || options.stats
+            || options.threads > 1
+            || options.verbose > 0
+        {
+            return Err(
+                "--all, --proof, --trace, --dump-graph, --simplify-only, --check-proof, --resolution-proof, --core, --stats, --threads and --verbose only apply to a single input"
+                    .into(),
+            );
+        }
+    } else if options.jobs > 1 {
+        return Err("--jobs only applies to several inputs or a directory".into());
+    }
+    Ok(options)
+}
+
+/// Set by `--quiet`: the model lines are left out.
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Inputs with more clauses than this get no `--resolution-proof`: the
+/// derivation of every learnt clause is kept, and the tree would be too
+/// large to read anyway.
+const RESOLUTION_PROOF_MAX_CLAUSES: usize = 1000;
+
+/// Prints the model as SAT-competition `v` lines no wider than 78 columns.
+fn print_values(model: &Model) {
+    print_model_lines(model.iter().map(i32::from).chain([0]));
+}
+
+/// Prints `values` as `v` lines, unless `--quiet` was given.
+fn print_model_lines(values: impl Iterator<Item = impl fmt::Display>) {
+    if !QUIET.load(Ordering::Relaxed) {
+        print_lines("v", values);
+    }
+}
+
+/// Prints `values` on lines starting with `prefix` (`v` for values), no
+/// wider than 78 columns.
+fn print_lines(prefix: &str, values: impl Iterator<Item = impl fmt::Display>) {
+    let mut line = String::from(prefix);
+    for value in values {
+        let value = value.to_string();
+        if line.len() + 1 + value.len() > 78 {
+            println!("{line}");
+            line.truncate(prefix.len());
+        }
+        line.push(' ');
+        line.push_str(&value);
+    }
+    println!("{line}");
+}
+
+/// Prints the models of the formula, at most `max_models` of them, under a
+/// single status line, and returns the exit code.
+fn print_all_models(solver: &mut Solver, max_models: Option<usize>, check_model: bool) -> i32 {
+    let mut models = solver.iter_models();
+    let mut count = 0;
+    while max_models.is_none_or(|max| count < max) {
+        let Some(model) = models.next() else {
+            break;
+        };
+        if check_model && let Err(e) = models.solver().verify_model() {
+            fail(format!("model check failed: {e}"));
+        }
+        if count == 0 {
+            println!("s SATISFIABLE");
+        }
+        print_values(&model);
+        count += 1;
+    }
+
+    let code = match count {
+        0 if models.is_complete() => {
+            println!("s UNSATISFIABLE");
+            20
+        }
+        0 => {
+            println!("s UNKNOWN");
+            0
+        }
+        _ => 10,
+    };
+    println!("c models {count}");
+    code
+}
+
+/// Solves the WCNF input as a MaxSAT instance, prints the result in the
+/// MaxSAT-evaluation format (`o` cost line, status line, model) and returns
+/// the exit code: 30 for an optimum, 10 for a model not proven optimal.
+fn solve_maxsat(options: &Options) -> i32 {
+    let loaded = match options.input() {
+        None | Some("-") => MaxSatSolver::from_reader(io::stdin().lock()),
+        Some(path) => MaxSatSolver::new(path),
+    };
+    let mut solver = loaded.unwrap_or_else(|e| fail(e));
+    solver.algorithm = options.maxsat_algorithm.unwrap_or_default();
+    configure(&mut solver.config, options);
+    if let Some(timeout) = options.timeout {
+        solver.set_timeout(timeout);
+    }
+
+    handle_sigint(solver.interrupt_handle());
+    let result = solver.solve();
+    if options.stats {
+        print_stats(solver.stats());
+    }
+    let (status, code) = match (result, solver.model()) {
+        (MaxSatResult::Optimum, _) => ("OPTIMUM FOUND", 30),
+        (MaxSatResult::Unsat, _) => ("UNSATISFIABLE", 20),
+        (MaxSatResult::Unknown, Some(_)) => ("SATISFIABLE", 10),
+        (MaxSatResult::Unknown, None) => ("UNKNOWN", 0),
+    };
+    if let Some(cost) = solver.cost() {
+        println!("o {cost}");
+    }
+    println!("s {status}");
+    if let Some(model) = solver.model() {
+        print_values(&model);
+    }
+    code
+}
+
+/// Solves the OPB input, prints the result like the PB competitions do
+/// (`o` objective line for optimization problems, status line, `v` lines of
+/// `x1 -x2` literals over the input variables) and returns the exit code.
+/// An objective is minimized as a MaxSAT problem with soft units.
+fn solve_opb(options: &Options) -> i32 {
+    let loaded = match options.input() {
+        None | Some("-") => OpbProblem::from_reader(io::stdin().lock()),
+        Some(path) => OpbProblem::new(path),
+    };
+    let problem = loaded.unwrap_or_else(|e| fail(e));
+
+    let (result, model, stats) = match &problem.objective {
+        Some(objective) => {
+            let mut solver = MaxSatSolver::with_vars(problem.variable_count);
+            solver.algorithm = options.maxsat_algorithm.unwrap_or_default();
+            configure(&mut solver.config, options);
+            for constraint in &problem.constraints {
+                solver.add_pb_constraint(constraint.clone());
+            }
+            for &(a, lit) in objective {
+                // `a·l` costs `a` when `l` is true, or `-a` less when it is false
+                match a {
+                    0 => {}
+                    1.. => solver.add_soft(&[-lit], a.unsigned_abs()).unwrap(),
+                    _ => solver.add_soft(&[lit], a.unsigned_abs()).unwrap(),
+                }
+            }
+            if let Some(timeout) = options.timeout {
+                solver.set_timeout(timeout);
+            }
+            handle_sigint(solver.interrupt_handle());
+            let result = solver.solve();
+            (result, solver.model(), solver.stats().clone())
+        }
+        None => {
+            let mut solver = Solver::with_vars(problem.variable_count);
+            configure(&mut solver.config, options);
+            for constraint in &problem.constraints {
+                solver.add_pb_constraint(constraint);
+            }
+            if let Some(timeout) = options.timeout {
+                solver.set_timeout(timeout);
+            }
+            handle_sigint(solver.interrupt_handle());
+            let result = match solver.solve() {
+                SolveResult::Sat => MaxSatResult::Optimum,
+                SolveResult::Unsat => MaxSatResult::Unsat,
+                SolveResult::Unknown => MaxSatResult::Unknown,
+            };
+            (result, solver.model(), solver.stats().clone())
+        }
+    };
+    if options.stats {
+        print_stats(&stats);
+    }
+
+    let optimizing = problem.objective.is_some();
+    let (status, code) = match (result, &model) {
+        (MaxSatResult::Optimum, _) if optimizing => ("OPTIMUM FOUND", 30),
+        (MaxSatResult::Optimum, _) => ("SATISFIABLE", 10),
+        (MaxSatResult::Unsat, _) => ("UNSATISFIABLE", 20),
+        (MaxSatResult::Unknown, Some(_)) => ("SATISFIABLE", 10),
+        (MaxSatResult::Unknown, None) => ("UNKNOWN", 0),
+    };
+    if let Some(model) = &model {
+        if options.check_model
+            && let Some(c) = problem.constraints.iter().find(|c| !c.is_satisfied(model))
+        {
+            fail(format!("model check failed: `{c}` is not satisfied"));
+        }
+        if let Some(value) = problem.objective_value(model) {
+            println!("o {value}");
+        }
+    }
+    println!("s {status}");
+    if let Some(model) = &model {
+        let literal = |lit: Lit| {
+            let sign = if lit.sign() { "-" } else { "" };
+            format!("{sign}x{}", lit.var().index())
+        };
+        print_model_lines(model.iter().take(problem.variable_count).map(literal));
+    }
+    code
+}
+
+/// Replays the ICNF trace: adds its clauses in order and solves under the
+/// assumptions of each `a` line, printing a status line per query followed
+/// by the model, or by an `f` line of the failed assumptions. A trace
+/// without queries is solved once at its end. Returns the exit code of the
+/// last answer; an `Unknown` one ends the replay.
+fn solve_icnf(options: &Options) -> i32 {
+    let loaded = match options.input() {
+        None | Some("-") => Icnf::from_reader(io::stdin().lock()),
+        Some(path) => Icnf::new(path),
+    };
+    let icnf = loaded.unwrap_or_else(|e| fail(e));
+
+    let mut solver = Solver::with_vars(icnf.variable_count);
+    configure(&mut solver.config, options);
+    if let Some(timeout) = options.timeout {
+        solver.set_timeout(timeout);
+    }
+    handle_sigint(solver.interrupt_handle());
+
+    let mut code = 0;
+    let end = (icnf.query_count() == 0).then(|| IcnfStep::Assume(Vec::new()));
+    for step in icnf.steps.iter().chain(&end) {
+        let assumptions = match step {
+            IcnfStep::Clause(clause) => {
+                solver.add_clause(clause).unwrap();
+                continue;
+            }
+            IcnfStep::Assume(assumptions) => assumptions,
+        };
+        code = match solver.solve_with_assumptions(assumptions) {
+            SolveResult::Sat => {
+                if options.check_model
+                    && let Err(e) = solver.verify_model()
+                {
+                    fail(format!("model check failed: {e}"));
+                }
+                println!("s SATISFIABLE");
+                print_values(&solver.model().expect("a Sat answer has a model"));
+                10
+            }
+            SolveResult::Unsat => {
+                println!("s UNSATISFIABLE");
+                let failed = solver.failed_assumptions().iter().map(|&l| i32::from(l));
+                print_lines("f", failed.chain([0]));
+                20
+            }
+            SolveResult::Unknown => {
+                println!("s UNKNOWN");
+                0
+            }
+        };
+        if code == 0 {
+            break;
+        }
+    }
+    if options.stats {
+        print_stats(solver.stats());
+    }
+    code
+}
+
+/// Decides the QDIMACS input and prints the result in the QDIMACS output
+/// format: `s cnf <1|0|-1> <variables> <clauses>`, then a `V <literal> 0`
+/// line for each variable of the outermost block when it certifies the
+/// answer. Returns the exit code 10, 20 or 0.
+fn solve_qdimacs(options: &Options) -> i32 {
+    let loaded = match options.input() {
+        None | Some("-") => QbfSolver::from_reader(io::stdin().lock()),
+        Some(path) => QbfSolver::new(path),
+    };
+    let mut solver = loaded.unwrap_or_else(|e| fail(e));
+    configure(&mut solver.config, options);
+    if let Some(timeout) = options.timeout {
+        solver.set_timeout(timeout);
+    }
+
+    handle_sigint(solver.interrupt_handle());
+    let result = solver.solve();
+    if options.stats {
+        println!("c {:<20} {:>12}", "refinements", solver.refinements());
+    }
+    let (value, code) = match result {
+        QbfResult::True => (1, 10),
+        QbfResult::False => (0, 20),
+        QbfResult::Unknown => (-1, 0),
+    };
+    println!(
+        "s cnf {value} {} {}",
+        solver.num_vars(),
+        solver.num_clauses()
+    );
+    if !QUIET.load(Ordering::Relaxed) {
+        for &lit in solver.witness().unwrap_or_default() {
+            println!("V {lit} 0");
+        }
+    }
+    code
+}
+
+/// Extracts a group MUS of the G-CNF input. An unsatisfiable input gets its
+/// MUS as a `v` line of group numbers after `s UNSATISFIABLE`, as in the
+/// MUS competitions. Returns the exit code 10, 20 or 0.
+fn solve_gcnf(options: &Options) -> i32 {
+    let loaded = match options.input() {
+        None | Some("-") => MusSolver::from_reader(io::stdin().lock()),
+        Some(path) => MusSolver::new(path),
+    };
+    let mut solver = loaded.unwrap_or_else(|e| fail(e));
+    configure(&mut solver.config, options);
+    if let Some(timeout) = options.timeout {
+        solver.set_timeout(timeout);
+    }
+
+    handle_sigint(solver.interrupt_handle());
+    let result = solver.solve();
+    if options.stats {
+        println!("c {:<20} {:>12}", "groups", solver.num_groups());
+        println!("c {:<20} {:>12}", "MUS groups", solver.mus().len());
+        println!("c {:<20} {:>12}", "SAT calls", solver.checks());
+    }
+    match result {
+        SolveResult::Sat => {
+            println!("s SATISFIABLE");
+            10
+        }
+        SolveResult::Unsat => {
+            println!("s UNSATISFIABLE");
+            print_lines("v", solver.mus().iter().chain([&0]));
+            20
+        }
+        SolveResult::Unknown => {
+            println!("s UNKNOWN");
+            0
+        }
+    }
+}
+
+/// The outcome of solving one file: a row of the batch table and of the
+/// `--report`.
+struct Run {
+    answer: Result<SolveResult, String>,
+    time: Duration,
+    stats: Stats,
+}
+
+impl Run {
+    fn new(answer: Result<SolveResult, String>, start: Instant, solver: &Solver) -> Self {
+        Self {
+            answer,
+            time: start.elapsed(),
+            stats: solver.stats().clone(),
+        }
+    }
+
+    /// The answer as printed in the batch table and the report.
+    fn answer_name(&self) -> &'static str {
+        match self.answer {
+            Ok(SolveResult::Sat) => "SAT",
+            Ok(SolveResult::Unsat) => "UNSAT",
+            Ok(SolveResult::Unknown) => "UNKNOWN",
+            Err(_) => "ERROR",
+        }
+    }
+}
+
+/// Solves the input files, those in the directories among them included,
+/// `--jobs` at a time, and prints a table of their answers, times and
+/// conflict counts in input order as they finish. After a Ctrl-C the
+/// remaining files are left `UNKNOWN`. Returns 1 if some file failed to
+/// load or its model failed `--check-model`, and 0 otherwise.
+fn solve_batch(options: &Options) -> i32 {
+    let paths = batch_files(&options.inputs).unwrap_or_else(|e| fail(e));
+    let stop = InterruptHandle::default();
+    handle_sigint(stop.clone());
+
+    let width = paths.iter().map(String::len).chain([4]).max().unwrap();
+    println!(
+        "{:<width$}  {:<7}  {:>9}  {:>10}",
+        "file", "answer", "time", "conflicts"
+    );
+    let next = AtomicUsize::new(0);
+    let mut runs: Vec<Option<Run>> = paths.iter().map(|_| None).collect();
+    let mut failed = false;
+    thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel();
+        for _ in 0..options.jobs.min(paths.len()) {
+            let (sender, next, paths, stop) = (sender.clone(), &next, &paths, &stop);
+            scope.spawn(move || {
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(path) = paths.get(index) else {
+                        break;
+                    };
+                    let run = solve_file(path, options, stop);
+                    if sender.send((index, run)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        let mut printed = 0;
+        for (index, run) in receiver {
+            runs[index] = Some(run);
+            while let Some(Some(run)) = runs.get(printed) {
+                let path = &paths[printed];
+                if let Err(e) = &run.answer {
+                    eprintln!("Error: {path}: {e}");
+                    failed = true;
+                }
+                let answer = run.answer_name();
+                let time = format!("{:.3}s", run.time.as_secs_f64());
+                let conflicts = run.stats.conflicts;
+                println!("{path:<width$}  {answer:<7}  {time:>9}  {conflicts:>10}");
+                printed += 1;
+            }
+        }
     });
+    if let Some((format, path)) = options.report() {
+        let rows = paths.iter().map(String::as_str).zip(runs.iter().flatten());
+        write_report(format, path, rows);
+    }
+    i32::from(failed)
+}
+
+/// The files to solve in batch: the inputs in order, with each directory
+/// replaced by the files in it, sorted by name.
+fn batch_files(inputs: &[String]) -> io::Result<Vec<String>> {
+    let mut paths = Vec::new();
+    for input in inputs {
+        if !Path::new(input).is_dir() {
+            paths.push(input.clone());
+            continue;
+        }
+        let mut files = Vec::new();
+        for entry in fs::read_dir(input)? {
+            let path = entry?.path();
+            if path.is_file()
+                && let Some(path) = path.to_str()
+            {
+                files.push(path.to_owned());
+            }
+        }
+        files.sort();
+        paths.append(&mut files);
+    }
+    Ok(paths)
+}
+
+/// Loads and solves one file of a batch, unless `stop` is already set.
+fn solve_file(path: &str, options: &Options, stop: &InterruptHandle) -> Run {
+    let start = Instant::now();
+    let unsolved = |answer| Run {
+        answer,
+        time: start.elapsed(),
+        stats: Stats::default(),
+    };
+    if stop.is_set() {
+        return unsolved(Ok(SolveResult::Unknown));
+    }
+    let mut solver = match Solver::new_with_options(path, options.parse_options()) {
+        Ok(solver) => solver,
+        Err(e) => return unsolved(Err(e.to_string())),
+    };
+    configure(&mut solver.config, options);
+    if let Some(timeout) = options.timeout {
+        solver.set_timeout(timeout);
+    }
+    let stop = stop.clone();
+    solver.set_terminate(move || stop.is_set());
+
+    let mut answer = Ok(solver.solve());
+    if options.check_model
+        && answer == Ok(SolveResult::Sat)
+        && let Err(e) = solver.verify_model()
+    {
+        answer = Err(format!("model check failed: {e}"));
+    }
+    Run::new(answer, start, &solver)
+}
 
-    if solver.solve() {
-        println!("SAT");
-        solver.print_model();
+/// Writes the statistics of the solved files to `path` as CSV, with a
+/// header row, or as a JSON array of objects, with times in seconds and
+/// memory in bytes. Exits on a write failure.
+fn write_report<'a>(
+    format: ReportFormat,
+    path: &Path,
+    rows: impl Iterator<Item = (&'a str, &'a Run)>,
+) {
+    let written = File::create(path).and_then(|file| {
+        let mut out = BufWriter::new(file);
+        if format == ReportFormat::Csv {
+            writeln!(
+                out,
+                "file,answer,time,decisions,conflicts,propagations,learnt_clauses,memory"
+            )?;
+        } else {
+            writeln!(out, "[")?;
+        }
+        for (index, (file, run)) in rows.enumerate() {
+            let (answer, time, stats) = (run.answer_name(), run.time.as_secs_f64(), &run.stats);
+            match format {
+                ReportFormat::Csv => writeln!(
+                    out,
+                    "{},{answer},{time:.6},{},{},{},{},{}",
+                    csv_field(file),
+                    stats.decisions,
+                    stats.conflicts,
+                    stats.propagations,
+                    stats.learnt_clauses,
+                    stats.peak_memory
+                )?,
+                ReportFormat::Json => {
+                    if index > 0 {
+                        writeln!(out, ",")?;
+                    }
+                    write!(
+                        out,
+                        r#"  {{"file":{},"answer":"{answer}","time":{time:.6},"decisions":{},"conflicts":{},"propagations":{},"learnt_clauses":{},"memory":{}}}"#,
+                        json_string(file),
+                        stats.decisions,
+                        stats.conflicts,
+                        stats.propagations,
+                        stats.learnt_clauses,
+                        stats.peak_memory
+                    )?;
+                }
+            }
+        }
+        if format == ReportFormat::Json {
+            writeln!(out, "\n]")?;
+        }
+        out.flush()
+    });
+    if let Err(e) = written {
+        fail(format!("cannot write report: {e}"));
+    }
+}
+
+/// `field` as a CSV field, quoted if it holds a comma, quote or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
     } else {
-        println!("UNSAT");
+        field.to_owned()
+    }
+}
+
+/// `text` as a quoted JSON string.
+fn json_string(text: &str) -> String {
+    let mut quoted = String::from('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Preprocesses the formula for `--simplify-only` and writes what is left
+/// to `path`. Exits with 20 if preprocessing refuted it, 0 otherwise.
+fn simplify_only(solver: &mut Solver, path: &Path, stats: bool) -> i32 {
+    let refuted = !solver.preprocess();
+    let written = File::create(path).and_then(|file| {
+        let mut writer = BufWriter::new(file);
+        solver.write_dimacs(&mut writer)?;
+        writer.flush()
+    });
+    if let Err(e) = written {
+        fail(format!("cannot write simplified formula: {e}"));
+    }
+    if stats {
+        print_stats(solver.stats());
     }
+    if refuted {
+        println!("s UNSATISFIABLE");
+        return 20;
+    }
+    0
+}
+
+/// Checks `proof` against the CNF formula at `path` (standard input for
+/// `-`) for `--check-proof`. Exits with 0 if it is a refutation, 1 if not.
+fn check_proof(path: &str, proof: &Path, stats: bool) -> i32 {
+    let loaded = if path == "-" {
+        let mut cnf = Vec::new();
+        io::stdin()
+            .lock()
+            .read_to_end(&mut cnf)
+            .map_err(Into::into)
+            .and_then(|_| ProofChecker::from_bytes(&cnf))
+    } else {
+        ProofChecker::new(path)
+    };
+    let checker = loaded.unwrap_or_else(|e| fail(e));
+    let bytes = fs::read(proof).unwrap_or_else(|e| fail(format!("cannot read proof: {e}")));
+    let start = Instant::now();
+    match checker.check(&bytes) {
+        Ok(check) => {
+            if stats {
+                let row =
+                    |name: &str, value: &dyn fmt::Display| println!("c {name:<20} {value:>12}");
+                row("proof format", &format!("{:?}", check.format));
+                row("added clauses", &check.added);
+                row("deleted clauses", &check.deleted);
+                row(
+                    "check time",
+                    &format!("{:.3}s", start.elapsed().as_secs_f64()),
+                );
+            }
+            println!("s VERIFIED");
+            0
+        }
+        Err(e) => {
+            println!("c {e}");
+            println!("s NOT VERIFIED");
+            1
+        }
+    }
+}
+
+/// Applies the preprocessing and inprocessing flags to `config`.
+fn configure(config: &mut SolverConfig, options: &Options) {
+    config.engine = options.engine;
+    config.branching = options.branching;
+    config.random_var_freq = options.random_freq;
+    config.random_phase_freq = options.random_freq;
+    config.seed = options.seed;
+    config.equivalences = options.pre.contains(&Pass::Equiv);
+    config.subsumption = options.pre.contains(&Pass::Subsume);
+    config.elimination = options.pre.contains(&Pass::Elim);
+    config.symmetry_breaking = options.pre.contains(&Pass::Symmetry);
+    config.at_most_one = options.pre.contains(&Pass::Amo);
+    config.probing = options.probe;
+    config.vivification = options.vivify;
+    config.rephasing = options.rephase;
+    if let Some(schedule) = &options.rephase_schedule {
+        config.rephase_schedule.clone_from(schedule);
+    }
+    config.gauss = options.gauss;
+    config.pb_encoding = options.pb_encoding;
+    config.verbosity = options.verbose.into();
+    config.memory_limit = options.mem_limit;
+}
+
+/// Prints the refutation of `--resolution-proof` as `c` comment lines.
+fn print_resolution_proof(solver: &Solver) {
+    let Some(proof) = solver.resolution_proof() else {
+        println!("c no resolution proof: a step of the search has none");
+        return;
+    };
+    println!(
+        "c resolution proof: {} resolutions of {} input clauses",
+        proof.num_resolutions(),
+        proof.inputs().count()
+    );
+    for line in proof.to_string().lines() {
+        println!("c {line}");
+    }
+}
+
+/// Writes the core of `--core` to `path`: the clauses of `proof_core` if
+/// the proof was recorded, those of `unsat_core` otherwise.
+fn write_core(solver: &Solver, path: &Path, from_proof: bool) {
+    let core: Vec<(u64, &[Lit])> = if from_proof {
+        let Some(core) = solver.proof_core() else {
+            println!("c no core: a step of the search has no hints");
+            return;
+        };
+        core
+    } else {
+        let numbers = solver.unsat_core().iter().map(|&number| number as u64 + 1);
+        numbers
+            .zip(solver.unsat_core_clauses().iter().map(Vec::as_slice))
+            .collect()
+    };
+    let written = File::create(path).and_then(|file| {
+        let mut w = BufWriter::new(file);
+        writeln!(w, "p cnf {} {}", solver.num_vars(), core.len())?;
+        for (number, literals) in &core {
+            writeln!(w, "c clause {number}")?;
+            for lit in *literals {
+                write!(w, "{lit} ")?;
+            }
+            writeln!(w, "0")?;
+        }
+        w.flush()
+    });
+    if let Err(e) = written {
+        fail(format!("cannot write core: {e}"));
+    }
+    println!("c core of {} clauses written", core.len());
+}
+
+/// Prints the search statistics as `c` comment lines.
+fn print_stats(stats: &Stats) {
+    let row = |name: &str, value: &dyn fmt::Display| println!("c {name:<20} {value:>12}");
+    let rate_row = |name: &str, count: u64| {
+        let rate = count as f64 / stats.solve_time.as_secs_f64().max(1e-9);
+        println!("c {name:<20} {count:>12} ({rate:.0}/s)");
+    };
+    row("decisions", &stats.decisions);
+    row("random decisions", &stats.random_decisions);
+    rate_row("propagations", stats.propagations);
+    rate_row("conflicts", stats.conflicts);
+    row("restarts", &stats.restarts);
+    row("learnt clauses", &stats.learnt_clauses);
+    row("deleted clauses", &stats.deleted_clauses);
+    row("max decision level", &stats.max_decision_level);
+    row("tautologies dropped", &stats.tautologies_dropped);
+    row("deduplicated clauses", &stats.clauses_deduplicated);
+    row("subsumed clauses", &stats.subsumed_clauses);
+    row("strengthened clauses", &stats.strengthened_clauses);
+    row("substituted variables", &stats.substituted_vars);
+    row("eliminated variables", &stats.eliminated_vars);
+    row("gate eliminations", &stats.gate_eliminations);
+    row("symmetries broken", &stats.symmetry_generators);
+    row("at-most-one groups", &stats.at_most_ones);
+    row("probed literals", &stats.literals_probed);
+    row("failed literals", &stats.failed_literals);
+    row("gauss propagations", &stats.gauss_propagations);
+    row("gauss conflicts", &stats.gauss_conflicts);
+    row("pb propagations", &stats.pb_propagations);
+    row("pb conflicts", &stats.pb_conflicts);
+    row("amo propagations", &stats.amo_propagations);
+    row("vivified clauses", &stats.vivified_clauses);
+    row("satisfied clauses", &stats.satisfied_clauses);
+    row("false literals", &stats.false_literals_removed);
+    rate_row("flips", stats.flips);
+    row("rephases", &stats.rephases);
+    row("external propagations", &stats.external_propagations);
+    row("external clauses", &stats.external_clauses);
+    row("imported clauses", &stats.imported_clauses);
+    row("reclaimed bytes", &stats.reclaimed_bytes);
+    row(
+        "peak memory",
+        &format!("{:.1} MB", stats.peak_memory as f64 / f64::from(1 << 20)),
+    );
+    row(
+        "parse time",
+        &format!("{:.3}s", stats.parse_time.as_secs_f64()),
+    );
+    row(
+        "solve time",
+        &format!("{:.3}s", stats.solve_time.as_secs_f64()),
+    );
+}
+
+/// Turns the first Ctrl-C into a solver interrupt, so the result line and
+/// statistics are still printed; a second one exits right away.
+fn handle_sigint(handle: InterruptHandle) {
+    let Ok(mut signals) = Signals::new([SIGINT]) else {
+        return; // Keep the default behaviour of dying on SIGINT
+    };
+    thread::spawn(move || {
+        let mut signals = signals.forever();
+        if signals.next().is_some() {
+            handle.interrupt();
+        }
+        if signals.next().is_some() {
+            process::exit(130);
+        }
+    });
+}
+
+fn fail(message: impl fmt::Display) -> ! {
+    eprintln!("Error: {message}");
+    process::exit(1);
+}
+
+fn main() {
+    let start = Instant::now();
+    let options = check_options(Options::parse())
+        .unwrap_or_else(|e| fail(format!("{e}\n{}", Options::command().render_usage())));
+    QUIET.store(options.quiet, Ordering::Relaxed);
+    if options.is_batch() {
+        process::exit(solve_batch(&options));
+    }
+    match options.format {
+        Format::Wcnf => process::exit(solve_maxsat(&options)),
+        Format::Opb => process::exit(solve_opb(&options)),
+        Format::Icnf => process::exit(solve_icnf(&options)),
+        Format::Qdimacs => process::exit(solve_qdimacs(&options)),
+        Format::Gcnf => process::exit(solve_gcnf(&options)),
+        Format::Cnf | Format::Aiger => {}
+    }
+    if let Some(proof) = &options.check_proof {
+        let path = options.input().unwrap_or("-");
+        process::exit(check_proof(path, proof, options.stats));
+    }
+
+    // Read the formula from stdin when no file (or `-`) is given. An AIGER
+    // graph is asked whether some output can be true
+    let stdin = matches!(options.input(), None | Some("-"));
+    let path = options.input().unwrap_or("-");
+    let (loaded, aiger) = match options.format {
+        Format::Aiger => {
+            let loaded = if stdin {
+                Aiger::from_reader(io::stdin().lock())
+            } else {
+                Aiger::new(path)
+            };
+            let aiger = loaded.unwrap_or_else(|e| fail(e));
+            (Ok(aiger.to_solver()), Some(aiger))
+        }
+        _ if stdin => (
+            Solver::from_reader_with_options(io::stdin().lock(), options.parse_options()),
+            None,
+        ),
+        _ => (
+            Solver::new_with_options(path, options.parse_options()),
+            None,
+        ),
+    };
+    let mut solver = loaded.unwrap_or_else(|e| fail(e));
+    // Symmetry breaking adds variables after these, left out of the model
+    let variable_count = solver.num_vars();
+    configure(&mut solver.config, &options);
+    solver.config.proof_path.clone_from(&options.proof);
+    solver.config.proof_format = options.proof_format;
+    solver.config.trace_path.clone_from(&options.trace);
+    // With proof logging, the core comes from the hints of the proof
+    let proof_core =
+        options.core.is_some() && (options.proof.is_some() || options.resolution_proof);
+    solver.config.resolution_proof = proof_core;
+    let print_tree =
+        options.resolution_proof && solver.num_clauses() <= RESOLUTION_PROOF_MAX_CLAUSES;
+    if options.resolution_proof {
+        if print_tree {
+            solver.config.resolution_proof = true;
+        } else {
+            println!(
+                "c resolution proof skipped: more than {RESOLUTION_PROOF_MAX_CLAUSES} clauses"
+            );
+        }
+    }
+    if let Some(timeout) = options.timeout {
+        solver.set_timeout(timeout);
+    }
+
+    if let Some(out) = &options.simplify_only {
+        process::exit(simplify_only(&mut solver, out, options.stats));
+    }
+
+    handle_sigint(solver.interrupt_handle());
+    if options.all {
+        let code = print_all_models(&mut solver, options.max_models, options.check_model);
+        if options.stats {
+            print_stats(solver.stats());
+        }
+        process::exit(code);
+    }
+
+    let (result, mut solver) = if options.threads > 1 {
+        let mut portfolio = PortfolioSolver::new(solver, options.threads);
+        let result = portfolio.solve();
+        (result, portfolio.into_solver())
+    } else if options.core.is_some() && !proof_core {
+        (solver.solve_with_selectors(), solver)
+    } else {
+        (solver.solve(), solver)
+    };
+    if let Err(e) = solver.finish_proof() {
+        fail(format!("cannot write proof: {e}"));
+    }
+    if let Err(e) = solver.finish_trace() {
+        fail(format!("cannot write trace: {e}"));
+    }
+    if let Some(path) = &options.dump_graph {
+        let written = File::create(path).and_then(|file| {
+            let mut writer = BufWriter::new(file);
+            solver.dump_implication_graph(&mut writer)?;
+            writer.flush()
+        });
+        if let Err(e) = written {
+            fail(format!("cannot write implication graph: {e}"));
+        }
+    }
+    if print_tree && result == SolveResult::Unsat {
+        print_resolution_proof(&solver);
+    }
+    if let Some(path) = &options.core
+        && result == SolveResult::Unsat
+    {
+        write_core(&solver, path, proof_core);
+    }
+    if options.stats {
+        print_stats(solver.stats());
+    }
+    if let Some((format, report)) = options.report() {
+        let run = Run::new(Ok(result), start, &solver);
+        write_report(format, report, [(path, &run)].into_iter());
+    }
+
+    // SAT-competition output: status line, model, and exit code 10/20/0
+    let code = match result {
+        SolveResult::Sat => {
+            if options.check_model
+                && let Err(e) = solver.verify_model()
+            {
+                fail(format!("model check failed: {e}"));
+            }
+            println!("s SATISFIABLE");
+            let model = solver.model().expect("a Sat answer has a model");
+            match &aiger {
+                // The values of the inputs, numbered from 1 in their order
+                Some(aiger) => print_model_lines(
+                    (1i64..)
+                        .zip(aiger.input_values(&model))
+                        .map(|(i, value)| if value { i } else { -i })
+                        .chain([0]),
+                ),
+                None => {
+                    print_model_lines(model.iter().take(variable_count).map(i32::from).chain([0]))
+                }
+            }
+            10
+        }
+        SolveResult::Unsat => {
+            println!("s UNSATISFIABLE");
+            20
+        }
+        SolveResult::Unknown => {
+            println!("s UNKNOWN");
+            0
+        }
+    };
+    process::exit(code);
 }
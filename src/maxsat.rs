@@ -0,0 +1,454 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::time::Duration;
+
+use crate::{
+    AddClauseError, Instant, InterruptHandle, Lit, Model, PbConstraint, SolveResult, Solver,
+    SolverConfig, SolverError, Stats, compression, dimacs,
+};
+
+/// How `MaxSatSolver` searches for the optimum.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum MaxSatAlgorithm {
+    /// SAT-UNSAT search: each model found must cost less than the previous
+    /// one, until no model does.
+    #[default]
+    Linear,
+    /// Binary search on the cost, between a proven lower bound and the cost
+    /// of the best model so far.
+    Binary,
+    /// Core-guided search (OLL): raises a lower bound with the unsatisfiable
+    /// cores of the soft clauses until a model meets it. Scales to many soft
+    /// clauses of distinct weights, where the other two do not.
+    Oll,
+}
+
+/// Answer of `MaxSatSolver::solve`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MaxSatResult {
+    /// `MaxSatSolver::model` satisfies the hard clauses, and no assignment
+    /// that does has a lower cost.
+    Optimum,
+    /// The hard clauses are unsatisfiable.
+    Unsat,
+    /// A budget, timeout or interrupt stopped the search. The best model
+    /// found until then, if any, is still available.
+    Unknown,
+}
+
+/// Weighted partial MaxSAT: finds an assignment that satisfies every hard
+/// clause and minimizes the cost, the total weight of the soft clauses it
+/// falsifies.
+///
+/// Each soft clause is extended with a relaxation variable that allows it to
+/// be falsified, and a totalizer adds up the weights of the relaxation
+/// variables that are true; bounds on the sum are then set by assuming its
+/// outputs false, so a single incremental SAT solver serves every step of
+/// the search. The totalizer has an output per sum of weights up to the
+/// cost of the first model, so this suits instances with few distinct
+/// weights, however large; see `MaxSatAlgorithm::Oll` for the others.
+pub struct MaxSatSolver {
+    variable_count: usize,
+    hard: Vec<Vec<Lit>>,
+    /// Hard pseudo-Boolean constraints, encoded as `config.pb_encoding` says.
+    pb: Vec<PbConstraint>,
+    soft: Vec<(u64, Vec<Lit>)>,
+    model: Option<Model>,
+    cost: Option<u64>,
+    stats: Stats,
+    timeout: Option<Duration>,
+    interrupt: InterruptHandle,
+    pub algorithm: MaxSatAlgorithm,
+    /// Configuration of the SAT solver underneath. No proof is written.
+    pub config: SolverConfig,
+}
+
+impl MaxSatSolver {
+    /// Loads a WCNF file, possibly compressed like the inputs of `Solver::new`.
+    pub fn new(path: &str) -> Result<Self, SolverError> {
+        let file = BufReader::new(File::open(path)?);
+        Self::from_reader(compression::decompress(file)?)
+    }
+
+    /// Reads a WCNF formula, in the old (`p wcnf` header) or the 2022 (`h`
+    /// marking hard clauses) format, from any buffered reader.
+    pub fn from_reader(reader: impl BufRead) -> Result<Self, SolverError> {
+        let formula = dimacs::parse_wcnf(reader)?;
+        let mut solver = Self::with_vars(formula.variable_count);
+        solver.hard = formula.hard;
+        solver.soft = formula.soft;
+        Ok(solver)
+    }
+
+    /// Parses a WCNF formula held in memory.
+    pub fn from_wcnf_str(wcnf: &str) -> Result<Self, SolverError> {
+        Self::from_reader(wcnf.as_bytes())
+    }
+
+    /// Creates a solver over variables `1..=variable_count` with no clauses.
+    pub fn with_vars(variable_count: usize) -> Self {
+        Self {
+            variable_count,
+            hard: Vec::new(),
+            pb: Vec::new(),
+            soft: Vec::new(),
+            model: None,
+            cost: None,
+            stats: Stats::default(),
+            timeout: None,
+            interrupt: InterruptHandle::default(),
+            algorithm: MaxSatAlgorithm::default(),
+            config: SolverConfig::default(),
+        }
+    }
+
+    /// Adds a clause every solution must satisfy.
+    pub fn add_hard<L>(&mut self, lits: &[L]) -> Result<(), AddClauseError>
+    where
+        L: Copy + TryInto<Lit>,
+        AddClauseError: From<L::Error>,
+    {
+        let literals = self.convert(lits)?;
+        self.hard.push(literals);
+        Ok(())
+    }
+
+    /// Adds a pseudo-Boolean constraint every solution must satisfy, such as
+    /// those of an OPB file.
+    pub fn add_pb_constraint(&mut self, constraint: PbConstraint) {
+        let max_var = constraint
+            .terms()
+            .iter()
+            .map(|(_, l)| l.var().index())
+            .max();
+        self.variable_count = self.variable_count.max(max_var.unwrap_or(0));
+        self.pb.push(constraint);
+    }
+
+    /// Adds a clause that costs `weight` when falsified.
+    pub fn add_soft<L>(&mut self, lits: &[L], weight: u64) -> Result<(), AddClauseError>
+    where
+        L: Copy + TryInto<Lit>,
+        AddClauseError: From<L::Error>,
+    {
+        let literals = self.convert(lits)?;
+        self.soft.push((weight, literals));
+        Ok(())
+    }
+
+    fn convert<L>(&mut self, lits: &[L]) -> Result<Vec<Lit>, AddClauseError>
+    where
+        L: Copy + TryInto<Lit>,
+        AddClauseError: From<L::Error>,
+    {
+        let literals = lits
+            .iter()
+            .map(|&l| l.try_into())
+            .collect::<Result<Vec<Lit>, _>>()?;
+        let max_var = literals.iter().map(|l| l.var().index()).max();
+        self.variable_count = self.variable_count.max(max_var.unwrap_or(0));
+        Ok(literals)
+    }
+
+    pub fn num_vars(&self) -> usize {
+        self.variable_count
+    }
+
+    pub fn num_soft_clauses(&self) -> usize {
+        self.soft.len()
+    }
+
+    /// Searches for an assignment that satisfies the hard clauses at the
+    /// lowest cost.
+    pub fn solve(&mut self) -> MaxSatResult {
+        self.model = None;
+        self.cost = None;
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+
+        let mut solver = Solver::with_vars(self.variable_count);
+        solver.config = SolverConfig {
+            proof_path: None,
+            resolution_proof: false,
+            symmetry_breaking: false,
+            ..self.config.clone()
+        };
+        solver.interrupt = self.interrupt.clone();
+        for literals in &self.hard {
+            solver.push_clause(literals.clone());
+        }
+        for constraint in &self.pb {
+            solver.add_pb_constraint(constraint);
+        }
+        let result = match self.algorithm {
+            MaxSatAlgorithm::Linear | MaxSatAlgorithm::Binary => {
+                self.search_models(&mut solver, deadline)
+            }
+            MaxSatAlgorithm::Oll => self.oll(&mut solver, deadline),
+        };
+        self.stats = solver.stats().clone();
+        result
+    }
+
+    /// Model-improving search shared by the linear and binary algorithms:
+    /// every soft clause gets a relaxation variable, whose weights a
+    /// totalizer adds up once the first model gives an upper bound.
+    fn search_models(&mut self, solver: &mut Solver, deadline: Option<Instant>) -> MaxSatResult {
+        let relaxation: Vec<(Lit, u64)> = self
+            .soft
+            .iter()
+            .map(|(weight, literals)| {
+                let relax = solver.new_var().positive();
+                let mut relaxed = literals.clone();
+                relaxed.push(relax);
+                solver.push_clause(relaxed);
+                (relax, *weight)
+            })
+            .collect();
+
+        match self.solve_under(solver, &[], deadline) {
+            SolveResult::Sat => {
+                // Costs from the first model up are all alike
+                let cap = self.cost.unwrap_or(0);
+                let at_least = solver.totalizer(&relaxation, cap);
+                if self.algorithm == MaxSatAlgorithm::Binary {
+                    self.binary_search(solver, &at_least, deadline)
+                } else {
+                    self.linear_search(solver, &at_least, deadline)
+                }
+            }
+            SolveResult::Unsat => MaxSatResult::Unsat,
+            SolveResult::Unknown => MaxSatResult::Unknown,
+        }
+    }
+
+    /// Asks for a model cheaper than the best one, until there is none.
+    fn linear_search(
+        &mut self,
+        solver: &mut Solver,
+        at_least: &[(u64, Lit)],
+        deadline: Option<Instant>,
+    ) -> MaxSatResult {
+        while let Some(cost) = self.cost.filter(|&cost| cost > 0) {
+            let Some(bound) = reaching(at_least, cost) else {
+                break;
+            };
+            match self.solve_under(solver, &[-bound], deadline) {
+                SolveResult::Sat => {}
+                SolveResult::Unsat => break,
+                SolveResult::Unknown => return MaxSatResult::Unknown,
+            }
+        }
+        MaxSatResult::Optimum
+    }
+
+    /// Halves the gap between the costs known to be impossible and the best
+    /// model until they meet.
+    fn binary_search(
+        &mut self,
+        solver: &mut Solver,
+        at_least: &[(u64, Lit)],
+        deadline: Option<Instant>,
+    ) -> MaxSatResult {
+        let mut lower = 0;
+        while let Some(upper) = self.cost.filter(|&cost| cost > lower) {
+            let middle = lower + (upper - lower) / 2;
+            let Some(bound) = reaching(at_least, middle + 1) else {
+                break;
+            };
+            match self.solve_under(solver, &[-bound], deadline) {
+                SolveResult::Sat => {}
+                SolveResult::Unsat => lower = middle + 1,
+                SolveResult::Unknown => return MaxSatResult::Unknown,
+            }
+        }
+        MaxSatResult::Optimum
+    }
+
+    /// Core-guided search (OLL). Each soft clause holds whenever its
+    /// selector does, and selectors are assumed true; every unsatisfiable
+    /// core raises the lower bound by the smallest weight in it, which is
+    /// taken off every assumption of the core. The core is then relaxed:
+    /// a totalizer counts its falsified assumptions, and "at most one" of
+    /// them becomes a new assumption of that weight, moving on to "at most
+    /// two" when it turns up in a core in turn. The first model is optimal.
+    fn oll(&mut self, solver: &mut Solver, deadline: Option<Instant>) -> MaxSatResult {
+        // Remaining weight of every assumption, in a deterministic order
+        let mut weights: BTreeMap<Lit, u64> = BTreeMap::new();
+        for (weight, literals) in &self.soft {
+            let selector = match literals[..] {
+                // A unit soft clause is its own selector
+                [lit] => lit,
+                _ => {
+                    let selector = solver.new_var().positive();
+                    let mut guarded = literals.clone();
+                    guarded.push(-selector);
+                    solver.push_clause(guarded);
+                    selector
+                }
+            };
+            *weights.entry(selector).or_default() += weight;
+        }
+        // Totalizer outputs of the relaxed cores, and for every assumption
+        // that bounds one, the totalizer and the bound
+        let mut totals: Vec<Vec<Lit>> = Vec::new();
+        let mut bounds: HashMap<Lit, (usize, usize)> = HashMap::new();
+
+        loop {
+            let assumptions: Vec<Lit> = weights
+                .iter()
+                .filter(|&(_, &weight)| weight > 0)
+                .map(|(&lit, _)| lit)
+                .collect();
+            match self.solve_under(solver, &assumptions, deadline) {
+                SolveResult::Sat => return MaxSatResult::Optimum,
+                SolveResult::Unknown => return MaxSatResult::Unknown,
+                SolveResult::Unsat => {}
+            }
+            let core = solver.failed_assumptions().to_vec();
+            let Some(min_weight) = core.iter().map(|lit| weights[lit]).min() else {
+                return MaxSatResult::Unsat; // No assumption involved
+            };
+            for lit in &core {
+                *weights.get_mut(lit).unwrap() -= min_weight;
+                if let Some(&(total, bound)) = bounds.get(lit)
+                    && let Some(&next) = totals[total].get(bound + 1)
+                {
+                    *weights.entry(-next).or_default() += min_weight;
+                    bounds.insert(-next, (total, bound + 1));
+                }
+            }
+            if let [lit] = core[..] {
+                solver.push_clause(vec![-lit]);
+                continue;
+            }
+            let falsified: Vec<(Lit, u64)> = core.iter().map(|&lit| (-lit, 1)).collect();
+            let outputs: Vec<Lit> = solver
+                .totalizer(&falsified, core.len() as u64)
+                .into_iter()
+                .map(|(_, lit)| lit)
+                .collect();
+            // One of them is falsified anyway, at the cost just counted
+            *weights.entry(-outputs[1]).or_default() += min_weight;
+            bounds.insert(-outputs[1], (totals.len(), 1));
+            totals.push(outputs);
+        }
+    }
+
+    /// Solves under `assumptions`, recording the model on `Sat`.
+    fn solve_under(
+        &mut self,
+        solver: &mut Solver,
+        assumptions: &[Lit],
+        deadline: Option<Instant>,
+    ) -> SolveResult {
+        if let Some(deadline) = deadline {
+            solver.set_timeout(deadline.saturating_duration_since(Instant::now()));
+        }
+        let result = solver.solve_with_assumptions(assumptions);
+        if result.is_sat() {
+            let mut model = solver.model().expect("Sat answers leave a model");
+            model.truncate(self.variable_count);
+            let falsified = self
+                .soft
+                .iter()
+                .filter(|(_, literals)| !literals.iter().any(|&lit| model.lit_is_true(lit)))
+                .map(|(weight, _)| weight)
+                .sum();
+            self.cost = Some(falsified);
+            self.model = Some(model);
+        }
+        result
+    }
+
+    /// The best assignment found by the last `solve` call, over the
+    /// variables of the formula.
+    pub fn model(&self) -> Option<Model> {
+        self.model.clone()
+    }
+
+    /// Total weight of the soft clauses falsified by `model`.
+    pub fn cost(&self) -> Option<u64> {
+        self.cost
+    }
+
+    /// Limits every later `solve` call, all of its SAT calls together, to
+    /// `timeout` of wall-clock time.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = Some(timeout);
+    }
+
+    /// A handle that makes the current (or next) `solve` call return
+    /// `Unknown`.
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.interrupt.clone()
+    }
+
+    /// Counters of the SAT solver used by the last `solve` call.
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+}
+
+impl Solver {
+    /// Totalizer encoding of the total weight of the true literals among
+    /// `inputs`, generalized to weights (GTE): returns an output for every
+    /// sum of weights the inputs can make, in increasing order, sums above
+    /// `cap` being counted as `cap`. The output of a sum is implied as soon
+    /// as the true inputs weigh that much, and implies the outputs of the
+    /// smaller sums. Only that direction is encoded, which is all an upper
+    /// bound needs. With weights of 1 the outputs are those of the plain
+    /// totalizer, "at least 1" first.
+    fn totalizer(&mut self, inputs: &[(Lit, u64)], cap: u64) -> Vec<(u64, Lit)> {
+        match inputs {
+            [] => return Vec::new(),
+            &[(_, 0)] => return Vec::new(),
+            &[(lit, weight)] => return vec![(weight.min(cap), lit)],
+            _ => {}
+        }
+        let (left, right) = inputs.split_at(inputs.len() / 2);
+        let left = self.totalizer(left, cap);
+        let right = self.totalizer(right, cap);
+        let mut sums: BTreeSet<u64> = left.iter().chain(&right).map(|&(a, _)| a).collect();
+        for &(a, _) in &left {
+            for &(b, _) in &right {
+                sums.insert(a.saturating_add(b).min(cap));
+            }
+        }
+        let outputs: Vec<(u64, Lit)> = sums
+            .into_iter()
+            .map(|sum| (sum, self.new_var().positive()))
+            .collect();
+        let output = |sum: u64| {
+            let k = outputs.binary_search_by_key(&sum.min(cap), |&(s, _)| s);
+            outputs[k.expect("every sum has an output")].1
+        };
+        // Inputs weighing `a` on the left and `b` on the right weigh `a + b`
+        // in all
+        let mut clauses = Vec::new();
+        for &(a, lit) in left.iter().chain(&right) {
+            clauses.push(vec![output(a), -lit]);
+        }
+        for &(a, left_lit) in &left {
+            for &(b, right_lit) in &right {
+                clauses.push(vec![output(a.saturating_add(b)), -left_lit, -right_lit]);
+            }
+        }
+        for pair in outputs.windows(2) {
+            clauses.push(vec![pair[0].1, -pair[1].1]);
+        }
+        for clause in clauses {
+            self.push_clause(clause);
+        }
+        outputs
+    }
+}
+
+/// The output of a totalizer implied whenever the inputs weigh `sum` or
+/// more: that of the smallest sum it has from `sum` up.
+fn reaching(outputs: &[(u64, Lit)], sum: u64) -> Option<Lit> {
+    outputs
+        .iter()
+        .find(|&&(total, _)| total >= sum)
+        .map(|&(_, lit)| lit)
+}
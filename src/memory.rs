@@ -0,0 +1,66 @@
+use crate::{ClauseRef, LBool, Solver};
+
+/// Conflicts between two checks of `SolverConfig::memory_limit`.
+pub(crate) const MEMORY_CHECK_INTERVAL: u64 = 1000;
+
+impl Solver {
+    /// Approximate bytes held by the clause database (input and learnt
+    /// clauses alike, and the input clauses as added), the watch and implication lists, and the arrays
+    /// kept per variable. Heap overhead and the smaller structures are
+    /// left out.
+    pub fn memory_usage(&self) -> usize {
+        let slots = self.num_vars() + 1;
+        self.clauses.memory()
+            + self.originals.memory()
+            + list_bytes(&self.watch_lists)
+            + list_bytes(&self.implications)
+            + list_bytes(&self.xor_watches)
+            + list_bytes(&self.pb_watches)
+            + list_bytes(&self.amos)
+            + list_bytes(&self.amo_watches)
+            + slots * 2 * size_of::<LBool>()
+            + self.levels.capacity() * size_of::<usize>()
+            + self.reasons.capacity() * size_of::<Option<ClauseRef>>()
+            + self.phases.capacity()
+            + self.best_phases.capacity()
+            + self.eliminated.capacity()
+            + self.seen.capacity()
+    }
+
+    /// Measures the memory in use, raising `stats.peak_memory` to it.
+    pub(crate) fn note_memory(&mut self) -> usize {
+        let usage = self.memory_usage();
+        self.stats.peak_memory = self.stats.peak_memory.max(usage as u64);
+        usage
+    }
+
+    /// Whether the solver fits in `config.memory_limit`, once learnt
+    /// clauses have been deleted, half of the deletable ones at a time, to
+    /// bring it there. False if even that is not enough.
+    pub(crate) fn enforce_memory_limit(&mut self) -> bool {
+        let Some(limit) = self.config.memory_limit else {
+            self.note_memory();
+            return true;
+        };
+        while self.note_memory() > limit {
+            let deleted = self.stats.deleted_clauses;
+            self.reduce_learnt_clauses();
+            if self.stats.deleted_clauses == deleted {
+                return false;
+            }
+            if self.clauses.wasted() > 0 {
+                self.collect_garbage();
+            }
+            self.clauses.shrink_to_fit();
+            for list in self.watch_lists.iter_mut() {
+                list.shrink_to_fit();
+            }
+        }
+        true
+    }
+}
+
+/// Bytes taken by `lists` and their elements.
+fn list_bytes<T>(lists: &[Vec<T>]) -> usize {
+    size_of_val(lists) + lists.iter().map(|list| list.capacity()).sum::<usize>() * size_of::<T>()
+}
@@ -0,0 +1,73 @@
+use crate::{Assignment, Lit, Var};
+
+/// A satisfying assignment, detached from the solver that found it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Model {
+    // Indexed by variable like the solver's own arrays; slot 0 is unused
+    values: Vec<bool>,
+}
+
+impl Model {
+    /// Snapshot of a complete assignment. Unassigned variables (there are
+    /// none after a `Sat` answer) read as false.
+    pub(crate) fn from_assignments(assignments: &Assignment) -> Self {
+        let values =
+            (1..=assignments.num_vars()).map(|i| assignments.var_value(Var::new(i)) == Some(true));
+        Self {
+            values: [false].into_iter().chain(values).collect(),
+        }
+    }
+
+    /// Makes `lit` true.
+    pub(crate) fn set(&mut self, lit: Lit) {
+        self.values[lit.var().index()] = !lit.sign();
+    }
+
+    /// Drops the values of variables above `variable_count`, such as the
+    /// auxiliary variables of an encoding.
+    pub(crate) fn truncate(&mut self, variable_count: usize) {
+        self.values.truncate(variable_count + 1);
+    }
+
+    /// Number of variables covered by the model.
+    pub fn num_vars(&self) -> usize {
+        self.values.len().saturating_sub(1)
+    }
+
+    /// Truth value of `var`. Panics if `var` is beyond `num_vars`.
+    #[inline]
+    pub fn value(&self, var: Var) -> bool {
+        self.values[var.index()]
+    }
+
+    #[inline]
+    pub fn lit_is_true(&self, lit: Lit) -> bool {
+        self.value(lit.var()) != lit.sign()
+    }
+
+    /// The literal made true by the model for every variable, in variable order.
+    pub fn iter(&self) -> impl Iterator<Item = Lit> + '_ {
+        self.values
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(i, &value)| Lit::new(Var::new(i), !value))
+    }
+
+    /// The literals of `iter` as DIMACS integers, signed by the value of
+    /// their variable: `[1, -2, 3]` when variable 2 alone is false.
+    pub fn to_dimacs(&self) -> Vec<i32> {
+        self.iter().map(i32::from).collect()
+    }
+
+    /// The model as a zero-terminated line of DIMACS literals, e.g. `1 -2 3 0`.
+    pub fn to_dimacs_string(&self) -> String {
+        let mut line = String::new();
+        for lit in self.iter() {
+            line.push_str(&lit.to_string());
+            line.push(' ');
+        }
+        line.push('0');
+        line
+    }
+}
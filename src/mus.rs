@@ -0,0 +1,202 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::time::Duration;
+
+use crate::{
+    AddClauseError, Instant, InterruptHandle, Lit, SolveResult, Solver, SolverConfig, SolverError,
+    Var, compression, dimacs,
+};
+
+/// Group-oriented MUS extraction: finds a minimal set of clause groups
+/// that is unsatisfiable together with the clauses outside every group,
+/// so that leaving out any one of the groups makes it satisfiable.
+///
+/// Clauses are tagged with a group number when added, or by their `{g}`
+/// prefix in a G-CNF file; group 0 holds the background clauses, which are
+/// always in. Each other group gets a selector literal added to its
+/// clauses, and one incremental SAT solver is asked about sets of groups by
+/// assuming their selectors true. The search is deletion-based: starting
+/// from the groups of an unsatisfiable core, every group is left out in
+/// turn, and kept only if the rest becomes satisfiable without it. When
+/// the rest is still unsatisfiable, its core replaces the current set, so
+/// that the groups outside that core are dropped without a SAT call each.
+pub struct MusSolver {
+    variable_count: usize,
+    clauses: Vec<(usize, Vec<Lit>)>,
+    group_count: usize,
+    mus: Vec<usize>,
+    checks: u64,
+    timeout: Option<Duration>,
+    interrupt: InterruptHandle,
+    /// Configuration of the SAT solver underneath. No proof is written.
+    pub config: SolverConfig,
+}
+
+impl MusSolver {
+    /// Loads a G-CNF file, possibly compressed like the inputs of
+    /// `Solver::new`.
+    pub fn new(path: &str) -> Result<Self, SolverError> {
+        let file = BufReader::new(File::open(path)?);
+        Self::from_reader(compression::decompress(file)?)
+    }
+
+    /// Reads a G-CNF formula, a `p gcnf <variables> <clauses> <groups>`
+    /// header followed by clauses prefixed with their group, such as
+    /// `{2} 1 -3 0`, from any buffered reader.
+    pub fn from_reader(reader: impl BufRead) -> Result<Self, SolverError> {
+        let formula = dimacs::parse_gcnf(reader)?;
+        let mut solver = Self::with_vars(formula.variable_count);
+        solver.group_count = formula.group_count;
+        solver.clauses = formula.clauses;
+        Ok(solver)
+    }
+
+    /// Parses a G-CNF formula held in memory.
+    pub fn from_gcnf_str(gcnf: &str) -> Result<Self, SolverError> {
+        Self::from_reader(gcnf.as_bytes())
+    }
+
+    /// Creates a solver over variables `1..=variable_count` with no clauses.
+    pub fn with_vars(variable_count: usize) -> Self {
+        Self {
+            variable_count,
+            clauses: Vec::new(),
+            group_count: 0,
+            mus: Vec::new(),
+            checks: 0,
+            timeout: None,
+            interrupt: InterruptHandle::default(),
+            config: SolverConfig::default(),
+        }
+    }
+
+    /// Adds a clause to `group`, or to the background clauses for group 0.
+    pub fn add_clause<L>(&mut self, group: usize, lits: &[L]) -> Result<(), AddClauseError>
+    where
+        L: Copy + TryInto<Lit>,
+        AddClauseError: From<L::Error>,
+    {
+        let literals = lits
+            .iter()
+            .map(|&l| l.try_into())
+            .collect::<Result<Vec<Lit>, _>>()?;
+        let max_var = literals.iter().map(|l| l.var().index()).max();
+        self.variable_count = self.variable_count.max(max_var.unwrap_or(0));
+        self.group_count = self.group_count.max(group);
+        self.clauses.push((group, literals));
+        Ok(())
+    }
+
+    pub fn num_vars(&self) -> usize {
+        self.variable_count
+    }
+
+    pub fn num_clauses(&self) -> usize {
+        self.clauses.len()
+    }
+
+    /// Highest group number, as declared by a G-CNF header or used by
+    /// `add_clause`.
+    pub fn num_groups(&self) -> usize {
+        self.group_count
+    }
+
+    /// Looks for a minimal unsatisfiable set of groups. `Sat` if all the
+    /// clauses together are satisfiable, in which case there is none;
+    /// `Unsat` once `mus` holds one.
+    pub fn solve(&mut self) -> SolveResult {
+        self.mus.clear();
+        self.checks = 0;
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+
+        let mut solver = Solver::with_vars(self.variable_count);
+        solver.config = SolverConfig {
+            proof_path: None,
+            resolution_proof: false,
+            symmetry_breaking: false,
+            ..self.config.clone()
+        };
+        solver.interrupt = self.interrupt.clone();
+        // Group `g` is selected by variable `first + g - 1`
+        let first = self.variable_count + 1;
+        solver.new_vars(self.group_count);
+        let selector = |group: usize| Var::new(first + group - 1).positive();
+        let mut used = vec![false; self.group_count + 1];
+        for (group, literals) in &self.clauses {
+            let mut literals = literals.clone();
+            if *group > 0 {
+                literals.push(-selector(*group));
+                used[*group] = true;
+            }
+            solver.push_clause(literals);
+        }
+
+        let mut core: Vec<usize> = (1..=self.group_count).filter(|&g| used[g]).collect();
+        let mut necessary = vec![false; self.group_count + 1];
+        let mut left_out = None;
+        loop {
+            let assumptions: Vec<Lit> = core
+                .iter()
+                .filter(|&&group| Some(group) != left_out)
+                .map(|&group| selector(group))
+                .collect();
+            self.checks += 1;
+            if let Some(deadline) = deadline {
+                solver.set_timeout(deadline.saturating_duration_since(Instant::now()));
+            }
+            match solver.solve_with_assumptions(&assumptions) {
+                SolveResult::Sat => match left_out {
+                    None => return SolveResult::Sat,
+                    Some(group) => necessary[group] = true,
+                },
+                SolveResult::Unsat => {
+                    core = solver
+                        .failed_assumptions()
+                        .iter()
+                        .map(|lit| lit.var().index() + 1 - first)
+                        .collect();
+                    core.sort_unstable();
+                }
+                SolveResult::Unknown => {
+                    // The smallest unsatisfiable set found so far
+                    if left_out.is_some() {
+                        self.mus = core;
+                    }
+                    return SolveResult::Unknown;
+                }
+            }
+            left_out = core.iter().copied().find(|&group| !necessary[group]);
+            if left_out.is_none() {
+                break;
+            }
+        }
+        self.mus = core;
+        SolveResult::Unsat
+    }
+
+    /// After `solve` returned `Unsat`, the groups of a minimal
+    /// unsatisfiable set, in increasing order: empty if the background
+    /// clauses are unsatisfiable on their own. After `Unknown`, the
+    /// smallest unsatisfiable set of groups found before the search was
+    /// stopped, which may not be minimal, or none.
+    pub fn mus(&self) -> &[usize] {
+        &self.mus
+    }
+
+    /// Number of SAT calls made by the last `solve` call.
+    pub fn checks(&self) -> u64 {
+        self.checks
+    }
+
+    /// Limits every later `solve` call, all of its SAT calls together, to
+    /// `timeout` of wall-clock time.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = Some(timeout);
+    }
+
+    /// A handle that makes the current (or next) `solve` call return
+    /// `Unknown`.
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.interrupt.clone()
+    }
+}
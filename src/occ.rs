@@ -0,0 +1,92 @@
+use crate::{ClauseArena, ClauseRef, Lit};
+
+/// Occurrence lists for the preprocessing passes: for every literal, the
+/// input clauses containing it. Removing a clause deletes it from the arena
+/// and only leaves a tombstone here; a list is purged of deleted clauses
+/// when it is next looked up, and all lists are swept once tombstones make
+/// up half of the entries.
+pub(crate) struct OccLists {
+    lists: Vec<Vec<ClauseRef>>,
+    dirty: Vec<bool>,
+    entries: usize,
+    tombstones: usize,
+}
+
+impl OccLists {
+    /// Indexes the input (non-learnt) clauses of `clauses`, for
+    /// `literal_count` literal indices.
+    pub fn new(clauses: &ClauseArena, literal_count: usize) -> Self {
+        let mut occ = Self {
+            lists: vec![Vec::new(); literal_count],
+            dirty: vec![false; literal_count],
+            entries: 0,
+            tombstones: 0,
+        };
+        for cref in clauses.iter() {
+            if !clauses.is_learnt(cref) {
+                occ.insert(cref, &clauses[cref]);
+            }
+        }
+        occ
+    }
+
+    /// Registers clause `cref`, whose literals are `literals`.
+    pub fn insert(&mut self, cref: ClauseRef, literals: &[Lit]) {
+        for lit in literals {
+            self.lists[lit.index()].push(cref);
+        }
+        self.entries += literals.len();
+    }
+
+    /// Deletes clause `cref` from `clauses` and tombstones its occurrences.
+    /// Its literals stay readable until the arena is compacted.
+    pub fn remove(&mut self, clauses: &mut ClauseArena, cref: ClauseRef) {
+        clauses.delete(cref);
+        for lit in &clauses[cref] {
+            self.dirty[lit.index()] = true;
+        }
+        self.tombstones += clauses[cref].len();
+        if self.tombstones * 2 > self.entries {
+            self.collect_garbage(clauses);
+        }
+    }
+
+    /// Drops `lit` from the occurrences of clause `cref`, after the clause lost that literal.
+    pub fn remove_literal(&mut self, cref: ClauseRef, lit: Lit) {
+        self.lists[lit.index()].retain(|&other| other != cref);
+        self.entries -= 1;
+    }
+
+    /// Live clauses containing `lit`.
+    pub fn lookup(&mut self, clauses: &ClauseArena, lit: Lit) -> &[ClauseRef] {
+        let index = lit.index();
+        if self.dirty[index] {
+            self.clean(clauses, index);
+        }
+        &self.lists[index]
+    }
+
+    /// Upper bound on the number of live clauses containing `lit`, without cleaning.
+    #[inline]
+    pub fn count(&self, lit: Lit) -> usize {
+        self.lists[lit.index()].len()
+    }
+
+    fn clean(&mut self, clauses: &ClauseArena, index: usize) {
+        let before = self.lists[index].len();
+        self.lists[index].retain(|&cref| !clauses.is_deleted(cref));
+        let purged = before - self.lists[index].len();
+        self.entries -= purged;
+        self.tombstones -= purged;
+        self.dirty[index] = false;
+    }
+
+    /// Purges deleted clauses from every list.
+    pub fn collect_garbage(&mut self, clauses: &ClauseArena) {
+        for index in 0..self.lists.len() {
+            if self.dirty[index] {
+                self.clean(clauses, index);
+            }
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+use crate::error::{ParseError, SolverError};
+use crate::lit::{Lit, Var};
+use crate::{Model, PbConstraint, compression};
+
+/// A pseudo-Boolean problem in the OPB format of the PB competitions:
+///
+/// ```text
+/// * #variable= 3 #constraint= 2
+/// min: +2 x1 -1 x3 ;
+/// +1 x1 +2 ~x2 +1 x3 >= 2 ;
+/// +1 x2 -1 x3 = 0 ;
+/// ```
+///
+/// Constraints compare a linear sum of literals (`x3`, or `~x3` for its
+/// negation) with `>=`, `<=` or `=`; an equality becomes two constraints.
+/// The optional `min:` line is an objective to minimize.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpbProblem {
+    /// Variables declared in the `* #variable=` comment, or the highest one
+    /// used if that is more.
+    pub variable_count: usize,
+    pub constraints: Vec<PbConstraint>,
+    /// Terms of the objective, if there is one.
+    pub objective: Option<Vec<(i64, Lit)>>,
+}
+
+impl OpbProblem {
+    /// Loads an OPB file, possibly compressed like the inputs of `Solver::new`.
+    pub fn new(path: &str) -> Result<Self, SolverError> {
+        let file = BufReader::new(File::open(path)?);
+        Self::from_reader(compression::decompress(file)?)
+    }
+
+    /// Reads an OPB problem from any buffered reader. Only linear
+    /// constraints are accepted, not products of literals.
+    pub fn from_reader(reader: impl BufRead) -> Result<Self, SolverError> {
+        parse(reader)
+    }
+
+    /// Parses an OPB problem held in memory.
+    pub fn from_opb_str(opb: &str) -> Result<Self, SolverError> {
+        Self::from_reader(opb.as_bytes())
+    }
+
+    /// Value of the objective in `model`, or `None` without an objective.
+    pub fn objective_value(&self, model: &Model) -> Option<i64> {
+        let objective = self.objective.as_ref()?;
+        Some(
+            objective
+                .iter()
+                .filter(|&&(_, lit)| model.lit_is_true(lit))
+                .map(|&(a, _)| a)
+                .sum(),
+        )
+    }
+}
+
+/// A statement in the making: the terms so far, then the comparison and
+/// its right-hand side.
+#[derive(Default)]
+struct Statement {
+    line: usize,
+    objective: bool,
+    terms: Vec<(i64, Lit)>,
+    coefficient: Option<i64>,
+    relation: Option<String>,
+    bound: Option<i64>,
+}
+
+fn parse(reader: impl BufRead) -> Result<OpbProblem, SolverError> {
+    let mut problem = OpbProblem {
+        variable_count: 0,
+        constraints: Vec::new(),
+        objective: None,
+    };
+    let mut statement: Option<Statement> = None;
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line_number = index + 1;
+        if line.starts_with('*') {
+            if let Some(count) = declared_variables(&line) {
+                problem.variable_count = problem.variable_count.max(count);
+            }
+            continue;
+        }
+
+        for (column, token) in tokens(&line) {
+            let invalid_token = || ParseError::InvalidToken {
+                line: line_number,
+                column,
+                token: token.to_string(),
+            };
+            let current = statement.get_or_insert_with(|| Statement {
+                line: line_number,
+                ..Statement::default()
+            });
+
+            if token == ";" {
+                let finished = statement.take().unwrap();
+                add_statement(&mut problem, finished).ok_or_else(invalid_token)?;
+                continue;
+            }
+            if token == "min:" {
+                if !current.terms.is_empty()
+                    || current.coefficient.is_some()
+                    || problem.objective.is_some()
+                    || !problem.constraints.is_empty()
+                {
+                    return Err(invalid_token().into());
+                }
+                current.objective = true;
+                continue;
+            }
+            if current.bound.is_some() {
+                return Err(invalid_token().into());
+            }
+            if current.relation.is_some() {
+                current.bound = Some(parse_integer(token).ok_or_else(invalid_token)?);
+                continue;
+            }
+            if matches!(token, ">=" | "<=" | "=") {
+                if current.objective || current.coefficient.is_some() {
+                    return Err(invalid_token().into());
+                }
+                current.relation = Some(token.to_string());
+                continue;
+            }
+            match current.coefficient.take() {
+                Some(coefficient) => {
+                    let lit = parse_literal(token).ok_or_else(invalid_token)?;
+                    problem.variable_count = problem.variable_count.max(lit.var().index());
+                    current.terms.push((coefficient, lit));
+                }
+                None => current.coefficient = Some(parse_integer(token).ok_or_else(invalid_token)?),
+            }
+        }
+    }
+
+    if let Some(statement) = statement {
+        return Err(ParseError::UnterminatedConstraint {
+            line: statement.line,
+        }
+        .into());
+    }
+    Ok(problem)
+}
+
+/// Files `statement` into `problem`, or returns `None` if it is incomplete.
+fn add_statement(problem: &mut OpbProblem, statement: Statement) -> Option<()> {
+    if statement.coefficient.is_some() {
+        return None;
+    }
+    if statement.objective {
+        problem.objective = Some(statement.terms);
+        return Some(());
+    }
+    let bound = statement.bound?;
+    let terms = &statement.terms;
+    match statement.relation?.as_str() {
+        ">=" => problem
+            .constraints
+            .push(PbConstraint::at_least(terms, bound)),
+        "<=" => problem
+            .constraints
+            .push(PbConstraint::at_most(terms, bound)),
+        _ => {
+            problem
+                .constraints
+                .push(PbConstraint::at_least(terms, bound));
+            problem
+                .constraints
+                .push(PbConstraint::at_most(terms, bound));
+        }
+    }
+    Some(())
+}
+
+/// The count of `* #variable= <n> #constraint= <m>`, the customary first line.
+fn declared_variables(line: &str) -> Option<usize> {
+    let mut fields = line.split_whitespace();
+    fields.find(|&field| field == "#variable=")?;
+    fields.next()?.parse().ok()
+}
+
+/// An integer with an optional sign, `+3` as well as `3` or `-3`.
+fn parse_integer(token: &str) -> Option<i64> {
+    token.strip_prefix('+').unwrap_or(token).parse().ok()
+}
+
+/// `x<n>`, or `~x<n>` for the negation.
+fn parse_literal(token: &str) -> Option<Lit> {
+    let (negated, name) = match token.strip_prefix('~') {
+        Some(name) => (true, name),
+        None => (false, token),
+    };
+    let index: usize = name.strip_prefix('x')?.parse().ok()?;
+    (1..=Var::MAX_INDEX)
+        .contains(&index)
+        .then(|| Lit::new(Var::new(index), negated))
+}
+
+/// Whitespace-separated tokens of `line` with their 1-based columns, with
+/// every `;` a token of its own even when it sticks to the previous one.
+fn tokens(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    let column = move |token: &str| {
+        let offset = token.as_ptr() as usize - line.as_ptr() as usize;
+        line[..offset].chars().count() + 1
+    };
+    line.split_whitespace().flat_map(move |word| {
+        word.split_inclusive(';').flat_map(move |piece| {
+            let (token, semicolon) = match piece.strip_suffix(';') {
+                Some(token) => (token, Some(&piece[token.len()..])),
+                None => (piece, None),
+            };
+            [Some(token).filter(|t| !t.is_empty()), semicolon]
+                .into_iter()
+                .flatten()
+                .map(move |t| (column(t), t))
+        })
+    })
+}
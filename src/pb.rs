@@ -0,0 +1,437 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use crate::{ClauseRef, LBool, Lit, Model, Solver};
+
+/// How `Solver::add_pb_constraint` translates a pseudo-Boolean constraint
+/// into clauses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PbEncoding {
+    /// A binary decision diagram over the terms, largest coefficient first,
+    /// with a variable per node (as in MiniSat+). Compact when the
+    /// coefficients are small or few distinct, as in cardinality constraints.
+    #[default]
+    Bdd,
+    /// Adder networks that sum the coefficients bit by bit, then compare the
+    /// sum with the bound. Grows with the number of bits of the coefficients
+    /// rather than their size, but propagates less.
+    Adder,
+    /// No clauses: the constraint is kept as it is and propagated by
+    /// counting how far the falsified terms take it below its largest sum.
+    /// Suits large constraints, whose encodings run into millions of
+    /// clauses. Like XOR clauses, such constraints are not covered by DRAT
+    /// proofs and always hold for `solve_with_selectors`.
+    Native,
+}
+
+/// A pseudo-Boolean constraint `Σ a_i·l_i ≥ k`, normalized so that every
+/// coefficient is positive and at most the bound, and every variable occurs
+/// in one term only.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PbConstraint {
+    /// Terms by decreasing coefficient.
+    terms: Vec<(u64, Lit)>,
+    bound: u64,
+}
+
+impl PbConstraint {
+    /// `Σ a_i·l_i ≥ bound`. Negative coefficients are moved onto the negated
+    /// literal and terms on the same variable are merged, adjusting the
+    /// bound to match.
+    pub fn at_least(terms: &[(i64, Lit)], bound: i64) -> Self {
+        let terms = terms.iter().map(|&(a, lit)| (i128::from(a), lit));
+        Self::normalize(terms, i128::from(bound))
+    }
+
+    fn normalize(terms: impl Iterator<Item = (i128, Lit)>, mut bound: i128) -> Self {
+        // Coefficient of each variable's positive literal
+        let mut coefficients: BTreeMap<Lit, i128> = BTreeMap::new();
+        for (a, lit) in terms {
+            if lit.sign() {
+                // a·-x = a - a·x
+                bound -= a;
+                *coefficients.entry(-lit).or_default() -= a;
+            } else {
+                *coefficients.entry(lit).or_default() += a;
+            }
+        }
+
+        let mut normalized = Vec::new();
+        for (lit, a) in coefficients {
+            if a < 0 {
+                bound -= a;
+                normalized.push((-a, -lit));
+            } else if a > 0 {
+                normalized.push((a, lit));
+            }
+        }
+        let sum: i128 = normalized.iter().map(|&(a, _)| a).sum();
+        if bound <= 0 {
+            return Self {
+                terms: Vec::new(),
+                bound: 0,
+            };
+        }
+        if bound > sum {
+            // Out of reach: `0 ≥ 1`
+            return Self {
+                terms: Vec::new(),
+                bound: 1,
+            };
+        }
+
+        let bound = u64::try_from(bound).expect("pseudo-Boolean bound exceeds 2^64");
+        let mut terms: Vec<(u64, Lit)> = normalized
+            .into_iter()
+            .map(|(a, lit)| (u64::try_from(a).unwrap_or(u64::MAX).min(bound), lit))
+            .collect();
+        terms.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        Self { terms, bound }
+    }
+
+    /// `Σ a_i·l_i ≤ bound`, that is `Σ -a_i·l_i ≥ -bound`.
+    pub fn at_most(terms: &[(i64, Lit)], bound: i64) -> Self {
+        let negated: Vec<(i64, Lit)> = terms.iter().map(|&(a, lit)| (-a, lit)).collect();
+        Self::at_least(&negated, -bound)
+    }
+
+    /// The terms with their positive coefficients, largest first.
+    pub fn terms(&self) -> &[(u64, Lit)] {
+        &self.terms
+    }
+
+    pub fn bound(&self) -> u64 {
+        self.bound
+    }
+
+    /// Whether the constraint holds in `model`.
+    pub fn is_satisfied(&self, model: &Model) -> bool {
+        let sum: u64 = self
+            .terms
+            .iter()
+            .filter(|&&(_, lit)| model.lit_is_true(lit))
+            .map(|&(a, _)| a)
+            .sum();
+        sum >= self.bound
+    }
+
+    /// The constraint with every literal replaced by `map(literal)`.
+    pub(crate) fn map_literals(&self, map: impl Fn(Lit) -> Lit) -> Self {
+        let terms = self.terms.iter().map(|&(a, lit)| (i128::from(a), map(lit)));
+        Self::normalize(terms, i128::from(self.bound))
+    }
+}
+
+impl fmt::Display for PbConstraint {
+    /// The constraint in OPB syntax, e.g. `+2 x1 +1 ~x3 >= 2`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for &(a, lit) in &self.terms {
+            let negation = if lit.sign() { "~" } else { "" };
+            write!(f, "+{a} {negation}x{} ", lit.var().index())?;
+        }
+        write!(f, ">= {}", self.bound)
+    }
+}
+
+/// A constraint propagated by `PbEncoding::Native`, with its slack: how
+/// much more than the bound its terms not falsified yet can add up to.
+/// Only falsifications that propagation has seen count.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct NativePb {
+    pub constraint: PbConstraint,
+    pub slack: i128,
+}
+
+/// A node of the decision diagram: a constant, or the literal that is true
+/// when the remaining terms can reach the remaining bound.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Node {
+    Const(bool),
+    Lit(Lit),
+}
+
+impl Solver {
+    /// Adds the clauses of a pseudo-Boolean constraint, in the encoding
+    /// chosen by `config.pb_encoding`. Auxiliary variables are allocated
+    /// above the existing ones. Constraints whose every coefficient reaches
+    /// the bound are a single clause in any encoding.
+    pub fn add_pb_constraint(&mut self, constraint: &PbConstraint) {
+        let PbConstraint { terms, bound } = constraint;
+        if *bound == 0 {
+            return;
+        }
+        if terms.iter().all(|&(a, _)| a >= *bound) {
+            self.push_clause(terms.iter().map(|&(_, lit)| lit).collect());
+            return;
+        }
+        match self.config.pb_encoding {
+            PbEncoding::Bdd => self.encode_pb_bdd(terms, *bound),
+            PbEncoding::Adder => self.encode_pb_adder(terms, *bound),
+            PbEncoding::Native => self.push_native_pb(constraint),
+        }
+    }
+
+    /// The pseudo-Boolean constraints propagated natively, over the literals
+    /// left once values fixed at level 0 and equivalent variables are taken
+    /// out.
+    pub fn pb_constraints(&self) -> impl Iterator<Item = &PbConstraint> {
+        self.pbs.iter().map(|pb| &pb.constraint)
+    }
+
+    /// Stores a constraint for native propagation. Literals assigned at
+    /// level 0 are taken out first, lowering the bound if they are true, so
+    /// that the slack only ever counts falsifications made during search;
+    /// what is left may well be a clause.
+    pub(crate) fn push_native_pb(&mut self, constraint: &PbConstraint) {
+        let max_var = constraint.terms.iter().map(|(_, l)| l.var().index()).max();
+        self.reserve_vars(max_var.unwrap_or(0));
+        self.backtrack_to(0);
+        if constraint
+            .terms
+            .iter()
+            .any(|(_, l)| self.eliminated[l.var().index()])
+        {
+            self.restore_eliminated();
+        }
+
+        let mut bound = i128::from(constraint.bound);
+        let mut terms = Vec::new();
+        for &(a, lit) in &constraint.terms {
+            match self.assignments.value(lit) {
+                LBool::True => bound -= i128::from(a),
+                LBool::False => {}
+                LBool::Undef => terms.push((i128::from(a), lit)),
+            }
+        }
+        let constraint = PbConstraint::normalize(terms.into_iter(), bound);
+        let PbConstraint { terms, bound } = &constraint;
+        if *bound == 0 {
+            return;
+        }
+        if terms.iter().all(|&(a, _)| a >= *bound) {
+            self.push_clause(terms.iter().map(|&(_, lit)| lit).collect());
+            return;
+        }
+
+        let index = self.pbs.len();
+        let sum: i128 = terms.iter().map(|&(a, _)| i128::from(a)).sum();
+        for &(a, lit) in terms {
+            self.pb_watches[lit.index()].push((index, a));
+        }
+        self.pbs.push(NativePb {
+            slack: sum - i128::from(*bound),
+            constraint,
+        });
+    }
+
+    /// Takes the terms falsified by `satisfied_lit` off the slack of their
+    /// constraints, then propagates each of them. Every slack is updated
+    /// before any conflict is returned, as backtracking restores them all.
+    pub(crate) fn propagate_pbs(&mut self, satisfied_lit: Lit) -> Option<ClauseRef> {
+        let falsified_idx = (-satisfied_lit).index();
+        for &(index, a) in &self.pb_watches[falsified_idx] {
+            self.pbs[index].slack -= i128::from(a);
+        }
+        for k in 0..self.pb_watches[falsified_idx].len() {
+            let index = self.pb_watches[falsified_idx][k].0;
+            if let Some(conflict) = self.propagate_pb(index) {
+                return Some(conflict);
+            }
+        }
+        None
+    }
+
+    /// Asserts every unassigned literal of native constraint `index` whose
+    /// coefficient exceeds the slack, or returns the conflict if the slack
+    /// is negative. The reason for each is the literal and the falsified
+    /// literals of the constraint: without them, the rest falls short.
+    fn propagate_pb(&mut self, index: usize) -> Option<ClauseRef> {
+        let NativePb { constraint, slack } = &self.pbs[index];
+        let slack = *slack;
+        // Terms come largest first, so the candidates are a prefix
+        if constraint
+            .terms
+            .first()
+            .is_none_or(|&(a, _)| i128::from(a) <= slack)
+        {
+            return None;
+        }
+        let falsified: Vec<Lit> = constraint
+            .terms
+            .iter()
+            .map(|&(_, lit)| lit)
+            .filter(|&lit| self.assignments.value(lit) == LBool::False)
+            .collect();
+        if slack < 0 {
+            self.stats.pb_conflicts += 1;
+            return self.assert_lazy(falsified);
+        }
+        let implied: Vec<Lit> = constraint
+            .terms
+            .iter()
+            .take_while(|&&(a, _)| i128::from(a) > slack)
+            .map(|&(_, lit)| lit)
+            .filter(|&lit| self.assignments.value(lit) == LBool::Undef)
+            .collect();
+        for lit in implied {
+            self.stats.pb_propagations += 1;
+            let mut reason = vec![lit];
+            reason.extend_from_slice(&falsified);
+            self.assert_lazy(reason);
+        }
+        None
+    }
+
+    /// Propagates the native constraints at level 0, where adding them may
+    /// have left literals to assert right away.
+    pub(crate) fn initial_pb_propagation(&mut self) -> bool {
+        for index in 0..self.pbs.len() {
+            if self.propagate_pb(index).is_some() || self.propagate().is_some() {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn encode_pb_bdd(&mut self, terms: &[(u64, Lit)], bound: u64) {
+        // `sums[i]` is the most that terms `i..` can add up to
+        let mut sums = vec![0; terms.len() + 1];
+        for i in (0..terms.len()).rev() {
+            sums[i] = sums[i + 1] + terms[i].0;
+        }
+        let mut memo = HashMap::new();
+        match self.bdd_node(terms, &sums, 0, bound, &mut memo) {
+            Node::Const(true) => {}
+            Node::Const(false) => self.push_clause(Vec::new()),
+            Node::Lit(root) => self.push_clause(vec![root]),
+        }
+    }
+
+    /// The node for terms `i..` reaching `bound`. Only the implication from
+    /// a node to its branches is encoded, which is all that asserting the
+    /// root needs: `v → hi` and `v → x ∨ lo`, where `lo` implies `hi`.
+    fn bdd_node(
+        &mut self,
+        terms: &[(u64, Lit)],
+        sums: &[u64],
+        i: usize,
+        bound: u64,
+        memo: &mut HashMap<(usize, u64), Node>,
+    ) -> Node {
+        if bound == 0 {
+            return Node::Const(true);
+        }
+        if sums[i] < bound {
+            return Node::Const(false);
+        }
+        if let Some(&node) = memo.get(&(i, bound)) {
+            return node;
+        }
+        let (a, x) = terms[i];
+        let hi = self.bdd_node(terms, sums, i + 1, bound.saturating_sub(a), memo);
+        let lo = self.bdd_node(terms, sums, i + 1, bound, memo);
+        let node = match (hi, lo) {
+            (Node::Const(true), Node::Const(false)) => Node::Lit(x),
+            _ => {
+                let v = self.new_var().positive();
+                if let Node::Lit(hi) = hi {
+                    self.push_clause(vec![-v, hi]);
+                }
+                match lo {
+                    Node::Lit(lo) => self.push_clause(vec![-v, x, lo]),
+                    Node::Const(false) => self.push_clause(vec![-v, x]),
+                    Node::Const(true) => {}
+                }
+                Node::Lit(v)
+            }
+        };
+        memo.insert((i, bound), node);
+        node
+    }
+
+    fn encode_pb_adder(&mut self, terms: &[(u64, Lit)], bound: u64) {
+        // Literals to add up at each bit position
+        let mut buckets: Vec<Vec<Lit>> = vec![Vec::new(); 64];
+        for &(a, lit) in terms {
+            for (bit, bucket) in buckets.iter_mut().enumerate() {
+                if a >> bit & 1 == 1 {
+                    bucket.push(lit);
+                }
+            }
+        }
+
+        // Full and half adders reduce every position to one bit of the sum,
+        // carrying into the next one
+        let mut sum: Vec<Option<Lit>> = Vec::new();
+        let mut bit = 0;
+        while bit < buckets.len() {
+            while buckets[bit].len() >= 2 {
+                let inputs: Vec<Lit> = if buckets[bit].len() >= 3 {
+                    buckets[bit].drain(..3).collect()
+                } else {
+                    buckets[bit].drain(..2).collect()
+                };
+                let sum_bit = self.define_xor(&inputs);
+                let carry = self.define_at_least_two(&inputs);
+                buckets[bit].push(sum_bit);
+                if bit + 1 == buckets.len() {
+                    buckets.push(Vec::new());
+                }
+                buckets[bit + 1].push(carry);
+            }
+            sum.push(buckets[bit].pop());
+            bit += 1;
+        }
+
+        // Forbid the sum falling below the bound: at the highest bit where
+        // they differ, the bound has a 1 and the sum a 0
+        for i in (0..64).filter(|&i| bound >> i & 1 == 1) {
+            let mut clause: Vec<Lit> = sum[i].into_iter().collect();
+            clause.extend(
+                (i + 1..sum.len())
+                    .filter(|&j| j >= 64 || bound >> j & 1 == 0)
+                    .filter_map(|j| sum[j]),
+            );
+            self.push_clause(clause);
+        }
+    }
+
+    /// A fresh variable defined as the XOR of `inputs`.
+    fn define_xor(&mut self, inputs: &[Lit]) -> Lit {
+        let output = self.new_var().positive();
+        // One clause against each assignment of the wrong parity
+        for values in 0u32..1 << inputs.len() {
+            let odd = values.count_ones() % 2 == 1;
+            let mut clause: Vec<Lit> = inputs
+                .iter()
+                .enumerate()
+                .map(|(i, &lit)| if values >> i & 1 == 1 { -lit } else { lit })
+                .collect();
+            clause.push(if odd { output } else { -output });
+            self.push_clause(clause);
+        }
+        output
+    }
+
+    /// A fresh variable defined as "at least two of `inputs`" (two or three
+    /// of them): the carry of an adder.
+    fn define_at_least_two(&mut self, inputs: &[Lit]) -> Lit {
+        let output = self.new_var().positive();
+        for i in 0..inputs.len() {
+            for j in i + 1..inputs.len() {
+                self.push_clause(vec![-inputs[i], -inputs[j], output]);
+                if inputs.len() == 3 {
+                    self.push_clause(vec![inputs[i], inputs[j], -output]);
+                }
+            }
+        }
+        if inputs.len() == 2 {
+            self.push_clause(vec![inputs[0], -output]);
+            self.push_clause(vec![inputs[1], -output]);
+        }
+        output
+    }
+}
@@ -0,0 +1,294 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::rng::Rng;
+use crate::{
+    InterruptHandle, LBool, Lit, Model, RestartStrategy, SolveResult, Solver, SolverConfig, Stats,
+    UnsatisfiedClause,
+};
+
+/// Most clauses the exchange holds; the oldest are dropped first.
+const EXCHANGE_CAPACITY: usize = 4096;
+
+/// Longest learnt clause a worker shares.
+const SHARE_MAX_LEN: usize = 16;
+
+/// Restart policies the workers cycle through, the first one being the
+/// default.
+const RESTARTS: [RestartStrategy; 4] = [
+    RestartStrategy::Luby { unit: 100 },
+    RestartStrategy::Geometric {
+        initial: 100,
+        factor: 1.5,
+    },
+    RestartStrategy::Luby { unit: 512 },
+    RestartStrategy::Geometric {
+        initial: 32,
+        factor: 1.2,
+    },
+];
+
+/// VSIDS decays the workers cycle through.
+const VAR_DECAYS: [f64; 3] = [0.95, 0.85, 0.99];
+
+/// Runs several copies of a solver on their own threads, each configured
+/// differently, and takes the answer of the first one to find one; the
+/// others are then stopped. Search heuristics are erratic enough that the
+/// fastest of a few differently tuned solvers often beats any single one.
+///
+/// Worker 0 keeps the configuration of the solver given to `new`. The
+/// others change its restart policy, VSIDS decay and seed, and start from
+/// random saved phases drawn from that seed. No worker writes a proof or a trace,
+/// and only worker 0 prints progress lines.
+///
+/// Workers pass their short learnt clauses of low LBD to each other: each
+/// one collects those it learns, and at every restart publishes them and
+/// adds the ones published by the others since its last restart.
+pub struct PortfolioSolver {
+    formula: Solver,
+    threads: usize,
+    /// Learnt clauses of at most 16 literals with at most this LBD are
+    /// shared with the other workers; 0 turns sharing off.
+    pub share_lbd: usize,
+    /// The worker that answered the last `solve` call, or worker 0 if none
+    /// did, with its model and statistics.
+    last: Option<Solver>,
+    winner: Option<usize>,
+}
+
+impl PortfolioSolver {
+    /// A portfolio of `threads` workers (at least one) over the formula of
+    /// `solver`, whose configuration, timeout and interrupt handle they
+    /// start from.
+    pub fn new(solver: Solver, threads: usize) -> Self {
+        Self {
+            formula: solver,
+            threads: threads.max(1),
+            share_lbd: 4,
+            last: None,
+            winner: None,
+        }
+    }
+
+    pub fn num_threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Solves the formula on every worker at once. The first `Sat` or
+    /// `Unsat` answer is returned and stops the other workers; `Unknown`
+    /// means every worker gave up.
+    pub fn solve(&mut self) -> SolveResult {
+        self.last = None;
+        self.winner = None;
+        let interrupt = self.formula.interrupt_handle();
+        let cancel = InterruptHandle::default();
+        let exchange = Arc::new(Mutex::new(Exchange::default()));
+        let clauses = self.formula.input_clauses();
+        let workers: Vec<Solver> = (0..self.threads)
+            .map(|index| {
+                let mut worker = self.formula.sibling();
+                worker.config = self.worker_config(index);
+                worker.interrupt = InterruptHandle::default();
+                let (interrupt, cancel) = (interrupt.clone(), cancel.clone());
+                worker.set_terminate(move || interrupt.is_set() || cancel.is_set());
+                if self.share_lbd > 0 {
+                    worker.sharing = Some(Sharing {
+                        exchange: Arc::clone(&exchange),
+                        worker: index,
+                        max_lbd: self.share_lbd,
+                        next: 0,
+                        outbox: Vec::new(),
+                    });
+                }
+                for clause in &clauses {
+                    worker.push_clause(clause.clone());
+                }
+                if index > 0 {
+                    let mut rng = Rng::new(worker.config.seed);
+                    for phase in worker.phases.iter_mut() {
+                        *phase = rng.next_bool();
+                    }
+                }
+                worker
+            })
+            .collect();
+
+        let winner = Mutex::new(None);
+        let mut results: Vec<(SolveResult, Solver)> = thread::scope(|scope| {
+            let handles: Vec<_> = workers
+                .into_iter()
+                .enumerate()
+                .map(|(index, mut worker)| {
+                    let (winner, cancel) = (&winner, &cancel);
+                    scope.spawn(move || {
+                        let result = worker.solve();
+                        if result != SolveResult::Unknown {
+                            winner.lock().unwrap().get_or_insert(index);
+                            cancel.interrupt();
+                        }
+                        (result, worker)
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("portfolio worker panicked"))
+                .collect()
+        });
+
+        self.winner = winner.into_inner().unwrap();
+        let (result, worker) = results.swap_remove(self.winner.unwrap_or(0));
+        if result == SolveResult::Unknown {
+            // Consume the interrupt that stopped the workers, as `Solver::solve` does
+            interrupt.take();
+        }
+        self.last = Some(worker);
+        result
+    }
+
+    /// The configuration of worker `index`.
+    fn worker_config(&self, index: usize) -> SolverConfig {
+        let base = &self.formula.config;
+        if index == 0 {
+            return SolverConfig {
+                proof_path: None,
+                resolution_proof: false,
+                trace_path: None,
+                ..base.clone()
+            };
+        }
+        SolverConfig {
+            restart: RESTARTS[index % RESTARTS.len()],
+            var_decay: VAR_DECAYS[index % VAR_DECAYS.len()],
+            seed: base.seed.wrapping_add(index as u64),
+            verbosity: 0,
+            proof_path: None,
+            resolution_proof: false,
+            trace_path: None,
+            ..base.clone()
+        }
+    }
+
+    /// Index of the worker that answered the last `solve` call, or `None`
+    /// if it returned `Unknown`.
+    pub fn winner(&self) -> Option<usize> {
+        self.winner
+    }
+
+    /// The model found by the last `solve` call, or `None` if it did not
+    /// answer `Sat`.
+    pub fn model(&self) -> Option<Model> {
+        self.last.as_ref().and_then(Solver::model)
+    }
+
+    /// Counters of the worker that answered the last `solve` call (worker 0
+    /// if none did), or of the formula before any call.
+    pub fn stats(&self) -> &Stats {
+        self.last.as_ref().unwrap_or(&self.formula).stats()
+    }
+
+    /// Checks the model of the last `solve` call like `Solver::verify_model`.
+    pub fn verify_model(&self) -> Result<(), UnsatisfiedClause> {
+        self.last.as_ref().unwrap_or(&self.formula).verify_model()
+    }
+
+    /// The worker that answered the last `solve` call (worker 0 if none
+    /// did), with its model and statistics, or the solver given to `new`
+    /// before any call.
+    pub fn into_solver(self) -> Solver {
+        self.last.unwrap_or(self.formula)
+    }
+
+    /// Limits every later `solve` call to `timeout` of wall-clock time.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.formula.set_timeout(timeout);
+    }
+
+    /// A handle that makes the current (or next) `solve` call return
+    /// `Unknown`, stopping every worker.
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.formula.interrupt_handle()
+    }
+}
+
+/// Learnt clauses published by the workers, each with the worker that
+/// learnt it and its LBD, numbered in order of publication.
+#[derive(Default)]
+struct Exchange {
+    /// Number of the first clause of `clauses`
+    first: u64,
+    clauses: VecDeque<(usize, usize, Vec<Lit>)>,
+}
+
+/// A worker's access to the clauses shared in a portfolio.
+pub(crate) struct Sharing {
+    exchange: Arc<Mutex<Exchange>>,
+    worker: usize,
+    max_lbd: usize,
+    /// Number of the next published clause to import
+    next: u64,
+    /// Clauses learnt since the last restart, with their LBD
+    outbox: Vec<(usize, Vec<Lit>)>,
+}
+
+impl Sharing {
+    /// Keeps a clause just learnt for publication if it is worth sharing.
+    pub(crate) fn offer(&mut self, literals: &[Lit], lbd: usize) {
+        if lbd <= self.max_lbd && literals.len() <= SHARE_MAX_LEN {
+            self.outbox.push((lbd, literals.to_vec()));
+        }
+    }
+}
+
+impl Solver {
+    /// Publishes the clauses offered since the last restart and adds those
+    /// the other workers published meanwhile, at level 0, then propagates
+    /// them. Returns false if they make the formula unsatisfiable. Does
+    /// nothing outside a portfolio.
+    pub(crate) fn exchange_clauses(&mut self) -> bool {
+        let Some(sharing) = self.sharing.as_mut() else {
+            return true;
+        };
+        let imported: Vec<(usize, Vec<Lit>)> = {
+            let mut exchange = sharing.exchange.lock().unwrap();
+            for (lbd, clause) in sharing.outbox.drain(..) {
+                exchange.clauses.push_back((sharing.worker, lbd, clause));
+            }
+            let dropped = exchange.clauses.len().saturating_sub(EXCHANGE_CAPACITY);
+            exchange.clauses.drain(..dropped);
+            exchange.first += dropped as u64;
+            let unread = sharing.next.saturating_sub(exchange.first) as usize;
+            sharing.next = exchange.first + exchange.clauses.len() as u64;
+            exchange
+                .clauses
+                .iter()
+                .skip(unread)
+                .filter(|(worker, _, _)| *worker != sharing.worker)
+                .map(|(_, lbd, clause)| (*lbd, clause.clone()))
+                .collect()
+        };
+
+        for (lbd, mut literals) in imported {
+            // Clauses over variables this worker eliminated are left out, as
+            // are clauses already satisfied at level 0
+            if literals.iter().any(|&lit| {
+                self.eliminated[lit.var().index()] || self.assignments.value(lit) == LBool::True
+            }) {
+                continue;
+            }
+            literals.retain(|&lit| self.assignments.value(lit) == LBool::Undef);
+            self.stats.imported_clauses += 1;
+            if literals.is_empty() {
+                return false;
+            }
+            let cref = self.clauses.push(&literals, true, lbd.min(literals.len()));
+            self.watch_clause(cref);
+            if let [unit] = literals[..] {
+                self.enqueue(unit, Some(cref));
+            }
+        }
+        self.propagate().is_none()
+    }
+}
@@ -0,0 +1,47 @@
+use crate::{Lit, Solver, Var};
+
+impl Solver {
+    /// Failed literal probing: every literal of an unassigned variable is
+    /// tried at decision level 1, and if propagating it reaches a conflict
+    /// its negation is learnt as a unit clause and asserted at level 0.
+    /// Returns false if the formula turns out to be unsatisfiable.
+    pub(crate) fn probe(&mut self) -> bool {
+        for var in (1..=self.num_vars()).map(Var::new) {
+            for lit in [var.positive(), var.negative()] {
+                if self.assignments.is_assigned(var) || self.eliminated[var.index()] {
+                    break;
+                }
+                self.stats.literals_probed += 1;
+                let Some(hints) = self.probe_failed(lit) else {
+                    continue;
+                };
+                self.stats.failed_literals += 1;
+                let unit = self.clauses.push(&[-lit], true, 1);
+                if let Some(proof) = self.proof.as_mut() {
+                    proof.add(self.clauses.id(unit), &[-lit], &hints);
+                }
+                self.enqueue(-lit, Some(unit));
+                if self.propagate().is_some() {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// If assuming `lit` leads to a conflict by unit propagation alone, the
+    /// LRAT hints deriving `-lit` (none unless an LRAT proof is written).
+    fn probe_failed(&mut self, lit: Lit) -> Option<Vec<u64>> {
+        self.trail.new_decision_level();
+        self.enqueue(lit, None);
+        let hints = self.propagate().map(|conflict| {
+            if self.wants_hints() {
+                self.conflict_hints(conflict, &[-lit])
+            } else {
+                Vec::new()
+            }
+        });
+        self.backtrack_to(0);
+        hints
+    }
+}
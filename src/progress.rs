@@ -0,0 +1,103 @@
+use crate::{Instant, Solver};
+
+/// Conflicts before the first periodic progress line. Each later one comes
+/// half as many conflicts again after the one before, as in MiniSat.
+const FIRST_LINE: u64 = 100;
+
+/// Progress lines of one CDCL search, printed as `c` comment lines on
+/// standard output when `SolverConfig::verbosity` is set:
+///
+/// ```text
+/// c variables 42 clauses 133
+/// c    conflicts  restarts   original     learnt    fixed    trail      time
+/// c *        100         0        133        100        0       34    0.002s
+/// c R        104         1        133        104        0       24    0.002s
+/// ```
+///
+/// The first column tells what printed the line: `*` for the periodic
+/// lines of verbosity 1, `R` for a restart and `-` for a reduction of the
+/// learnt clauses (verbosity 2), and `=` for the end of the search. The
+/// columns count conflicts and restarts over every search, live input and
+/// learnt clauses, variables fixed at level 0, and assigned variables.
+pub(crate) struct Progress {
+    start: Instant,
+    next_line: u64,
+    interval: u64,
+}
+
+impl Solver {
+    /// Prints the banner of a search and starts its periodic lines, if
+    /// `config.verbosity` asks for them.
+    pub(crate) fn start_progress(&mut self) {
+        if self.config.verbosity == 0 {
+            self.progress = None;
+            return;
+        }
+        println!(
+            "c variables {} clauses {}",
+            self.num_vars(),
+            self.clauses
+                .iter()
+                .filter(|&c| !self.clauses.is_learnt(c))
+                .count()
+        );
+        println!(
+            "c   {:>10} {:>9} {:>10} {:>10} {:>8} {:>8} {:>9}",
+            "conflicts", "restarts", "original", "learnt", "fixed", "trail", "time"
+        );
+        self.progress = Some(Progress {
+            start: Instant::now(),
+            next_line: self.stats.conflicts + FIRST_LINE,
+            interval: FIRST_LINE,
+        });
+    }
+
+    /// Prints a periodic line once enough conflicts went by since the last one.
+    pub(crate) fn note_progress(&mut self) {
+        let Some(progress) = self.progress.as_mut() else {
+            return;
+        };
+        if self.stats.conflicts >= progress.next_line {
+            progress.interval += progress.interval / 2;
+            progress.next_line = self.stats.conflicts + progress.interval;
+            self.progress_line('*');
+        }
+    }
+
+    /// Prints a line for `event` (see `Progress`) if the verbosity is at
+    /// least `verbosity`.
+    pub(crate) fn log_progress(&self, event: char, verbosity: u32) {
+        if self.config.verbosity >= verbosity {
+            self.progress_line(event);
+        }
+    }
+
+    /// Prints the last line of a search, if it printed any.
+    pub(crate) fn finish_progress(&mut self) {
+        self.progress_line('=');
+        self.progress = None;
+    }
+
+    fn progress_line(&self, event: char) {
+        let Some(progress) = &self.progress else {
+            return;
+        };
+        let learnt = self
+            .clauses
+            .iter()
+            .filter(|&cref| self.clauses.is_learnt(cref))
+            .count();
+        let fixed = match self.trail.decision_level() {
+            0 => self.trail.len(),
+            _ => self.trail.level_start(1),
+        };
+        let time = format!("{:.3}s", progress.start.elapsed().as_secs_f64());
+        println!(
+            "c {event} {:>10} {:>9} {:>10} {learnt:>10} {fixed:>8} {:>8} {time:>9}",
+            self.stats.conflicts,
+            self.stats.restarts,
+            self.clauses.len() - learnt,
+            self.trail.len(),
+        );
+    }
+}
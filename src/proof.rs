@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::{Lit, Var};
+
+/// Encoding of the proof file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ProofFormat {
+    /// Plain-text DRAT, one clause per line.
+    #[default]
+    Drat,
+    /// Binary DRAT as read by `drat-trim`: an `a`/`d` byte, then each literal
+    /// `2 * var + sign` as a variable-length 7-bit integer, then a 0 byte.
+    BinaryDrat,
+    /// Plain-text LRAT: every added clause has an ID and is followed by the
+    /// IDs of the clauses that derive it by unit propagation, in the order
+    /// they become unit, so that a checker (`lrat-check`, `cake_lpr`) never
+    /// searches. Input clauses are numbered from 1 in the order of the
+    /// input. Equivalent literal substitution is skipped while an LRAT
+    /// proof is written, and XOR clauses, native pseudo-Boolean constraints
+    /// and the lookahead engine give no hints.
+    Lrat,
+}
+
+/// Streams a DRAT or LRAT proof: every learnt clause is written as an
+/// addition and every clause removed from the database as a deletion, so
+/// an UNSAT answer can be checked independently (e.g. with `drat-trim`).
+/// It may also keep every clause with its hints in memory, written or not,
+/// for `Solver::resolution_proof`.
+///
+/// Write failures do not interrupt solving; the first one is kept and
+/// reported by `finish`.
+pub(crate) struct ProofWriter {
+    out: Option<BufWriter<File>>,
+    format: ProofFormat,
+    error: Option<io::Error>,
+    // LRAT only: the last ID added, which starts deletion lines, the IDs of
+    // the unit clauses written for variables fixed at level 0 (0 for none
+    // yet), and those of the clauses on the elimination stack
+    last_id: u64,
+    units: Vec<u64>,
+    eliminated: Vec<u64>,
+    derivations: Option<HashMap<u64, Derivation>>,
+}
+
+/// A clause kept by `ProofWriter::record`: its literals and, unless it is
+/// an input clause, the hints it was added with.
+pub(crate) struct Derivation {
+    pub literals: Vec<Lit>,
+    pub hints: Option<Vec<u64>>,
+}
+
+impl ProofWriter {
+    pub fn create(path: &Path, format: ProofFormat) -> Self {
+        match File::create(path) {
+            Ok(file) => Self::with_output(Some(BufWriter::new(file)), format, None),
+            Err(err) => Self::with_output(None, format, Some(err)),
+        }
+    }
+
+    /// A proof that is not written anywhere, for `record` alone.
+    pub fn discard() -> Self {
+        Self::with_output(None, ProofFormat::Drat, None)
+    }
+
+    fn with_output(
+        out: Option<BufWriter<File>>,
+        format: ProofFormat,
+        error: Option<io::Error>,
+    ) -> Self {
+        Self {
+            out,
+            format,
+            error,
+            last_id: 0,
+            units: Vec::new(),
+            eliminated: Vec::new(),
+            derivations: None,
+        }
+    }
+
+    /// Keeps from now on every added clause with its hints, starting with
+    /// the input clauses `inputs`.
+    pub fn record<'a>(&mut self, inputs: impl Iterator<Item = (u64, &'a [Lit])>) {
+        self.derivations = Some(HashMap::new());
+        for (id, lits) in inputs {
+            self.input(id, lits);
+        }
+    }
+
+    /// Input clause `id`, added after `record`. Proofs do not list input
+    /// clauses, so this only matters to the clauses kept.
+    pub fn input(&mut self, id: u64, lits: &[Lit]) {
+        if let Some(derivations) = self.derivations.as_mut() {
+            let literals = lits.to_vec();
+            derivations.insert(
+                id,
+                Derivation {
+                    literals,
+                    hints: None,
+                },
+            );
+        }
+    }
+
+    /// The clauses kept since `record`, by ID.
+    pub fn derivations(&self) -> Option<&HashMap<u64, Derivation>> {
+        self.derivations.as_ref()
+    }
+
+    /// Whether added clauses need their hints: LRAT writes them, and the
+    /// clauses kept by `record` need them too.
+    pub fn takes_hints(&self) -> bool {
+        self.format == ProofFormat::Lrat || self.derivations.is_some()
+    }
+
+    /// Adds clause `id`, derived by unit propagation on the clauses `hints`.
+    pub fn add(&mut self, id: u64, lits: &[Lit], hints: &[u64]) {
+        self.last_id = self.last_id.max(id);
+        if let Some(derivations) = self.derivations.as_mut() {
+            let derivation = Derivation {
+                literals: lits.to_vec(),
+                hints: Some(hints.to_vec()),
+            };
+            derivations.insert(id, derivation);
+        }
+        self.write_clause(|out, format| match format {
+            ProofFormat::Drat => write_text(out, false, lits),
+            ProofFormat::BinaryDrat => write_binary(out, false, lits),
+            ProofFormat::Lrat => write_lrat(out, id, lits, hints),
+        });
+    }
+
+    /// Deletes clause `id`.
+    pub fn delete(&mut self, id: u64, lits: &[Lit]) {
+        let last_id = self.last_id;
+        self.write_clause(|out, format| match format {
+            ProofFormat::Drat => write_text(out, true, lits),
+            ProofFormat::BinaryDrat => write_binary(out, true, lits),
+            ProofFormat::Lrat => writeln!(out, "{last_id} d {id} 0"),
+        });
+    }
+
+    /// Takes clause `id` out for variable elimination. DRAT deletes it;
+    /// LRAT keeps it, so that `restore` can derive it again.
+    pub fn eliminate(&mut self, id: u64, lits: &[Lit]) {
+        if self.takes_hints() {
+            self.eliminated.push(id);
+        }
+        if self.format != ProofFormat::Lrat {
+            self.delete(id, lits);
+        }
+    }
+
+    /// Adds back as clause `id` the clause most recently taken out by
+    /// `eliminate`, whose literals `lits` start with the eliminated one, so
+    /// that a DRAT checker can verify it as RAT on that literal.
+    pub fn restore(&mut self, id: u64, lits: &[Lit]) {
+        let hints: Vec<u64> = self.eliminated.pop().into_iter().collect();
+        self.add(id, lits, &hints);
+    }
+
+    /// ID of the unit clause written for `var` by `set_unit`.
+    pub fn unit(&self, var: Var) -> Option<u64> {
+        self.units.get(var.index()).copied().filter(|&id| id != 0)
+    }
+
+    /// Records clause `id` as the unit clause fixing `var`.
+    pub fn set_unit(&mut self, var: Var, id: u64) {
+        if self.units.len() <= var.index() {
+            self.units.resize(var.index() + 1, 0);
+        }
+        self.units[var.index()] = id;
+    }
+
+    fn write_clause(
+        &mut self,
+        write: impl FnOnce(&mut BufWriter<File>, ProofFormat) -> io::Result<()>,
+    ) {
+        let Some(out) = self.out.as_mut() else {
+            return;
+        };
+        if let Err(err) = write(out, self.format) {
+            self.fail(err);
+        }
+    }
+
+    /// Flushes the proof and returns the first error met while writing it.
+    pub fn finish(&mut self) -> io::Result<()> {
+        if let Some(out) = self.out.as_mut()
+            && let Err(err) = out.flush()
+        {
+            self.fail(err);
+        }
+        match self.error.take() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    fn fail(&mut self, err: io::Error) {
+        self.out = None;
+        self.error.get_or_insert(err);
+    }
+}
+
+fn write_text(out: &mut impl Write, deletion: bool, lits: &[Lit]) -> io::Result<()> {
+    if deletion {
+        out.write_all(b"d ")?;
+    }
+    for lit in lits {
+        write!(out, "{lit} ")?;
+    }
+    out.write_all(b"0\n")
+}
+
+fn write_lrat(out: &mut impl Write, id: u64, lits: &[Lit], hints: &[u64]) -> io::Result<()> {
+    write!(out, "{id} ")?;
+    for lit in lits {
+        write!(out, "{lit} ")?;
+    }
+    out.write_all(b"0 ")?;
+    for hint in hints {
+        write!(out, "{hint} ")?;
+    }
+    out.write_all(b"0\n")
+}
+
+fn write_binary(out: &mut impl Write, deletion: bool, lits: &[Lit]) -> io::Result<()> {
+    out.write_all(if deletion { b"d" } else { b"a" })?;
+    for lit in lits {
+        let mut code = lit.index() as u32;
+        while code > 0x7f {
+            out.write_all(&[(code & 0x7f) as u8 | 0x80])?;
+            code >>= 7;
+        }
+        out.write_all(&[code as u8])?;
+    }
+    out.write_all(&[0])
+}
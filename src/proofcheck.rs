@@ -0,0 +1,583 @@
+use std::collections::HashMap;
+
+use crate::dimacs::{self, Dimacs};
+use crate::{Lit, ParseOptions, ProofError, ProofFormat, SolverError, Var, compression};
+
+/// What `ProofChecker::check` found in a proof it accepted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProofCheck {
+    /// Encoding of the proof, told apart by its contents.
+    pub format: ProofFormat,
+    /// Clauses added up to the empty clause, which is counted.
+    pub added: usize,
+    /// Deletions before the empty clause.
+    pub deleted: usize,
+}
+
+/// Checks DRAT and LRAT refutations of a DIMACS CNF formula, from this
+/// solver or any other, without an external tool such as `drat-trim`.
+///
+/// DRAT proofs are checked forwards: every added clause must follow from
+/// the clauses before it by reverse unit propagation (RUP), that is
+/// assuming it false must lead to a conflict by unit propagation alone, or
+/// else be a resolution asymmetric tautology (RAT) on its first literal.
+/// Like `drat-trim`, deletions of clauses that are the reason for a value
+/// fixed by unit propagation are ignored. LRAT proofs are checked by
+/// propagating only the hinted clauses, in their order. Checking stops at
+/// the first empty clause; a proof that never derives it is rejected.
+pub struct ProofChecker {
+    clauses: Vec<Vec<Lit>>,
+}
+
+/// One addition or deletion of a proof.
+enum Step {
+    /// A clause with, in LRAT, its ID and hints.
+    Add {
+        id: u64,
+        literals: Vec<Lit>,
+        hints: Vec<i64>,
+    },
+    /// A DRAT deletion, naming the clause by its literals.
+    Delete(Vec<Lit>),
+    /// An LRAT deletion, naming clauses by their IDs.
+    DeleteIds(Vec<u64>),
+}
+
+impl ProofChecker {
+    /// Loads the formula from a DIMACS CNF file, compressed or not as for
+    /// `Solver::new`. XOR clauses are no clauses to a proof checker and are
+    /// left out.
+    pub fn new(path: &str) -> Result<Self, SolverError> {
+        let formula = dimacs::parse_file(path, ParseOptions::default())?;
+        Ok(Self::from_formula(formula))
+    }
+
+    /// Reads the formula from the contents of a DIMACS CNF file.
+    pub fn from_bytes(cnf: &[u8]) -> Result<Self, SolverError> {
+        let formula = if compression::is_compressed(cnf) {
+            dimacs::parse(compression::decompress(cnf)?)?
+        } else {
+            dimacs::parse_bytes(cnf, ParseOptions::default())?
+        };
+        Ok(Self::from_formula(formula))
+    }
+
+    fn from_formula(formula: Dimacs) -> Self {
+        let clauses = formula.clauses.into_iter().map(normalize).collect();
+        Self { clauses }
+    }
+
+    /// Checks the contents of a proof file: text or binary DRAT, or text
+    /// LRAT, whose IDs number the input clauses from 1 in file order.
+    pub fn check(&self, proof: &[u8]) -> Result<ProofCheck, ProofError> {
+        let format = detect_format(proof);
+        let steps = match format {
+            ProofFormat::BinaryDrat => read_binary(proof)?,
+            ProofFormat::Drat | ProofFormat::Lrat => read_text(proof, format)?,
+        };
+        let (added, deleted) = match format {
+            ProofFormat::Lrat => self.check_lrat(steps)?,
+            ProofFormat::Drat | ProofFormat::BinaryDrat => self.check_drat(steps)?,
+        };
+        Ok(ProofCheck {
+            format,
+            added,
+            deleted,
+        })
+    }
+
+    fn check_drat(&self, steps: Vec<Step>) -> Result<(usize, usize), ProofError> {
+        let mut db = Drat::default();
+        for clause in &self.clauses {
+            db.add(clause.clone());
+        }
+        let (mut added, mut deleted) = (0, 0);
+        for (index, step) in steps.into_iter().enumerate() {
+            match step {
+                Step::Add { literals, .. } => {
+                    added += 1;
+                    if !db.is_rup(&literals) && !db.is_rat(&literals) {
+                        return Err(ProofError::NotImplied {
+                            step: index + 1,
+                            clause: literals,
+                        });
+                    }
+                    if literals.is_empty() {
+                        return Ok((added, deleted));
+                    }
+                    db.add(normalize(literals));
+                }
+                Step::Delete(literals) => {
+                    deleted += 1;
+                    db.delete(normalize(literals));
+                }
+                Step::DeleteIds(_) => unreachable!("DRAT names clauses by their literals"),
+            }
+        }
+        Err(ProofError::Incomplete)
+    }
+
+    fn check_lrat(&self, steps: Vec<Step>) -> Result<(usize, usize), ProofError> {
+        let mut db: HashMap<u64, Vec<Lit>> = (1..).zip(self.clauses.iter().cloned()).collect();
+        let mut values = Values::default();
+        let (mut added, mut deleted) = (0, 0);
+        for (index, step) in steps.into_iter().enumerate() {
+            let malformed = ProofError::Malformed { step: index + 1 };
+            match step {
+                Step::Add {
+                    id,
+                    literals,
+                    hints,
+                } => {
+                    added += 1;
+                    let literals = normalize(literals);
+                    if db.contains_key(&id) {
+                        return Err(malformed);
+                    }
+                    let implied = values.follows(&literals, &hints, &db);
+                    values.clear();
+                    match implied {
+                        None => return Err(malformed),
+                        Some(false) => {
+                            return Err(ProofError::NotImplied {
+                                step: index + 1,
+                                clause: literals,
+                            });
+                        }
+                        Some(true) if literals.is_empty() => return Ok((added, deleted)),
+                        Some(true) => {
+                            db.insert(id, literals);
+                        }
+                    }
+                }
+                Step::DeleteIds(ids) => {
+                    deleted += 1;
+                    for id in ids {
+                        db.remove(&id).ok_or(malformed.clone())?;
+                    }
+                }
+                Step::Delete(_) => unreachable!("LRAT names clauses by their IDs"),
+            }
+        }
+        Err(ProofError::Incomplete)
+    }
+}
+
+/// Sorts the literals and merges repeated ones, so that clauses are
+/// compared as sets and count their open literals right.
+fn normalize(mut literals: Vec<Lit>) -> Vec<Lit> {
+    literals.sort_unstable();
+    literals.dedup();
+    literals
+}
+
+/// Binary DRAT if the proof holds a 0 byte, which ends every binary step
+/// and never appears in text; otherwise LRAT if the first step is shaped
+/// like one (an ID before `d`, or two zeros), and text DRAT if not.
+fn detect_format(proof: &[u8]) -> ProofFormat {
+    if proof.contains(&0) {
+        return ProofFormat::BinaryDrat;
+    }
+    let first = proof
+        .split(|&byte| byte == b'\n')
+        .map(|line| line.trim_ascii())
+        .find(|line| !line.is_empty() && line[0] != b'c');
+    let Some(line) = first else {
+        return ProofFormat::Drat;
+    };
+    let tokens: Vec<&[u8]> = line
+        .split(u8::is_ascii_whitespace)
+        .filter(|token| !token.is_empty())
+        .collect();
+    let zeros = tokens.iter().filter(|&&token| token == b"0").count();
+    if tokens.get(1) == Some(&&b"d"[..]) || zeros >= 2 {
+        ProofFormat::Lrat
+    } else {
+        ProofFormat::Drat
+    }
+}
+
+/// The steps of a text DRAT or LRAT proof, skipping comment lines.
+fn read_text(proof: &[u8], format: ProofFormat) -> Result<Vec<Step>, ProofError> {
+    let mut steps = Vec::new();
+    let lines = proof.split(|&byte| byte == b'\n').map(<[u8]>::trim_ascii);
+    for line in lines.filter(|line| !line.is_empty() && line[0] != b'c') {
+        let malformed = ProofError::Malformed {
+            step: steps.len() + 1,
+        };
+        let mut tokens = line
+            .split(u8::is_ascii_whitespace)
+            .filter(|token| !token.is_empty())
+            .peekable();
+        let step = match format {
+            ProofFormat::Lrat => {
+                let id = number(tokens.next()).ok_or(malformed.clone())?;
+                if tokens.next_if_eq(&&b"d"[..]).is_some() {
+                    let ids = numbers(&mut tokens).and_then(|ids| {
+                        ids.into_iter()
+                            .map(|id| u64::try_from(id).ok())
+                            .collect::<Option<Vec<u64>>>()
+                    });
+                    Step::DeleteIds(ids.ok_or(malformed.clone())?)
+                } else {
+                    let literals = numbers(&mut tokens).and_then(literals);
+                    let hints = numbers(&mut tokens);
+                    match (literals, hints) {
+                        (Some(literals), Some(hints)) if id > 0 => Step::Add {
+                            id: id as u64,
+                            literals,
+                            hints,
+                        },
+                        _ => return Err(malformed),
+                    }
+                }
+            }
+            _ => {
+                let deletion = tokens.next_if_eq(&&b"d"[..]).is_some();
+                let literals = numbers(&mut tokens)
+                    .and_then(literals)
+                    .ok_or(malformed.clone())?;
+                if deletion {
+                    Step::Delete(literals)
+                } else {
+                    Step::Add {
+                        id: 0,
+                        literals,
+                        hints: Vec::new(),
+                    }
+                }
+            }
+        };
+        if tokens.next().is_some() {
+            return Err(malformed);
+        }
+        steps.push(step);
+    }
+    Ok(steps)
+}
+
+/// The integer `token` stands for, if it is one.
+fn number(token: Option<&[u8]>) -> Option<i64> {
+    std::str::from_utf8(token?).ok()?.parse().ok()
+}
+
+/// The integers up to the next 0, which is consumed, or `None` if a token
+/// is not an integer or no 0 comes.
+fn numbers<'a>(tokens: &mut impl Iterator<Item = &'a [u8]>) -> Option<Vec<i64>> {
+    let mut values = Vec::new();
+    loop {
+        match number(tokens.next())? {
+            0 => return Some(values),
+            value => values.push(value),
+        }
+    }
+}
+
+/// DIMACS integers as literals, or `None` if one is out of range.
+fn literals(values: Vec<i64>) -> Option<Vec<Lit>> {
+    values
+        .into_iter()
+        .map(|value| {
+            let var = usize::try_from(value.unsigned_abs()).ok()?;
+            (var <= Var::MAX_INDEX).then(|| Lit::new(Var::new(var), value < 0))
+        })
+        .collect()
+}
+
+/// The steps of a binary DRAT proof, in the encoding `ProofWriter` writes.
+fn read_binary(proof: &[u8]) -> Result<Vec<Step>, ProofError> {
+    let mut steps = Vec::new();
+    let mut bytes = proof.iter().copied();
+    while let Some(kind) = bytes.next() {
+        let malformed = ProofError::Malformed {
+            step: steps.len() + 1,
+        };
+        let mut literals = Vec::new();
+        loop {
+            let (mut code, mut shift) = (0u64, 0);
+            loop {
+                let byte = bytes.next().ok_or(malformed.clone())?;
+                code |= u64::from(byte & 0x7f) << shift;
+                shift += 7;
+                if byte & 0x80 == 0 {
+                    break;
+                }
+                if shift > 35 {
+                    return Err(malformed);
+                }
+            }
+            if code == 0 {
+                break;
+            }
+            let var = (code >> 1) as usize;
+            if !(1..=Var::MAX_INDEX).contains(&var) {
+                return Err(malformed);
+            }
+            literals.push(Lit::new(Var::new(var), code & 1 == 1));
+        }
+        steps.push(match kind {
+            b'a' => Step::Add {
+                id: 0,
+                literals,
+                hints: Vec::new(),
+            },
+            b'd' => Step::Delete(literals),
+            _ => return Err(malformed),
+        });
+    }
+    Ok(steps)
+}
+
+/// Values of the variables, by variable index, with the variables assigned
+/// since the last `clear`.
+#[derive(Default)]
+struct Values {
+    values: Vec<Option<bool>>,
+    assigned: Vec<Var>,
+}
+
+impl Values {
+    /// Whether `lit` is true, false, or unassigned (`None`).
+    fn get(&self, lit: Lit) -> Option<bool> {
+        let value = self.values.get(lit.var().index()).copied().flatten();
+        value.map(|value| value != lit.sign())
+    }
+
+    /// Makes `lit` true.
+    fn set(&mut self, lit: Lit) {
+        let var = lit.var();
+        if self.values.len() <= var.index() {
+            self.values.resize(var.index() + 1, None);
+        }
+        self.values[var.index()] = Some(!lit.sign());
+        self.assigned.push(var);
+    }
+
+    /// Makes the values assigned so far permanent: `clear` leaves them.
+    fn keep(&mut self) {
+        self.assigned.clear();
+    }
+
+    /// Unassigns the variables assigned since the last call, or `keep`.
+    fn clear(&mut self) {
+        for var in self.assigned.drain(..) {
+            self.values[var.index()] = None;
+        }
+    }
+
+    /// Whether assuming `literals` false, the clauses `hints` of `db`
+    /// become unit one after the other until one is falsified. `None` if a
+    /// hint names no clause of `db`, or is negative: RAT hints are not
+    /// supported.
+    fn follows(
+        &mut self,
+        literals: &[Lit],
+        hints: &[i64],
+        db: &HashMap<u64, Vec<Lit>>,
+    ) -> Option<bool> {
+        for &lit in literals {
+            match self.get(lit) {
+                Some(true) => return Some(true), // A tautology
+                Some(false) => {}
+                None => self.set(-lit),
+            }
+        }
+        for &hint in hints {
+            let clause = db.get(&u64::try_from(hint).ok()?)?;
+            let mut open = None;
+            for &lit in clause {
+                match self.get(lit) {
+                    Some(true) => return Some(false),
+                    Some(false) => {}
+                    None if open.is_some() => return Some(false),
+                    None => open = Some(lit),
+                }
+            }
+            match open {
+                Some(lit) => self.set(lit),
+                None => return Some(true),
+            }
+        }
+        Some(false)
+    }
+}
+
+/// Clause database of the DRAT check, with two watched literals per
+/// clause and the values that unit propagation fixes at the top level.
+#[derive(Default)]
+struct Drat {
+    clauses: Vec<Vec<Lit>>,
+    deleted: Vec<bool>,
+    // Clauses watching each literal, by literal index
+    watches: Vec<Vec<usize>>,
+    // Live clauses by their sorted literals, for deletions
+    by_literals: HashMap<Vec<Lit>, Vec<usize>>,
+    values: Values,
+    // The clause that fixed each variable at the top level
+    reasons: HashMap<Var, usize>,
+    trail: Vec<Lit>,
+    qhead: usize,
+    // Unit propagation at the top level reached a conflict
+    inconsistent: bool,
+}
+
+impl Drat {
+    /// Adds a clause whose literals are sorted and distinct, then
+    /// propagates at the top level.
+    fn add(&mut self, mut literals: Vec<Lit>) {
+        let index = self.clauses.len();
+        self.by_literals
+            .entry(literals.clone())
+            .or_default()
+            .push(index);
+        // Watch literals that are not false whenever possible
+        literals.sort_by_key(|&lit| self.values.get(lit) == Some(false));
+        if literals.len() >= 2 {
+            self.watch(literals[0]).push(index);
+            self.watch(literals[1]).push(index);
+        }
+        let first = literals.first().copied();
+        let second = literals.get(1).copied();
+        self.clauses.push(literals);
+        self.deleted.push(false);
+        if self.inconsistent {
+            return;
+        }
+        match first.map(|lit| self.values.get(lit)) {
+            None | Some(Some(false)) => self.inconsistent = true,
+            Some(None) if second.is_none_or(|lit| self.values.get(lit) == Some(false)) => {
+                self.assign(first.unwrap(), Some(index));
+                self.inconsistent = !self.propagate();
+            }
+            Some(_) => {}
+        }
+        // Later checks backtrack to here
+        self.values.keep();
+    }
+
+    fn watch(&mut self, lit: Lit) -> &mut Vec<usize> {
+        if self.watches.len() <= lit.index() {
+            self.watches.resize(lit.index() + 2, Vec::new());
+        }
+        &mut self.watches[lit.index()]
+    }
+
+    fn assign(&mut self, lit: Lit, reason: Option<usize>) {
+        self.values.set(lit);
+        if let Some(reason) = reason {
+            self.reasons.insert(lit.var(), reason);
+        }
+        self.trail.push(lit);
+    }
+
+    /// Deletes a clause given by its sorted, distinct literals, unless it
+    /// fixes a value at the top level. Deletions of clauses that are not
+    /// there are ignored.
+    fn delete(&mut self, literals: Vec<Lit>) {
+        let Some(indices) = self.by_literals.get_mut(&literals) else {
+            return;
+        };
+        let Some(&index) = indices.last() else {
+            return;
+        };
+        let locked = literals
+            .iter()
+            .any(|lit| self.reasons.get(&lit.var()) == Some(&index));
+        if !locked {
+            indices.pop();
+            self.deleted[index] = true;
+        }
+    }
+
+    /// Propagates the trail literals not propagated yet; false on a conflict.
+    fn propagate(&mut self) -> bool {
+        while self.qhead < self.trail.len() {
+            let falsified = -self.trail[self.qhead];
+            self.qhead += 1;
+            let mut watchers = std::mem::take(self.watch(falsified));
+            let mut conflict = false;
+            let mut i = 0;
+            while i < watchers.len() && !conflict {
+                let index = watchers[i];
+                if self.deleted[index] {
+                    watchers.swap_remove(i);
+                    continue;
+                }
+                let clause = &mut self.clauses[index];
+                if clause[0] == falsified {
+                    clause.swap(0, 1);
+                }
+                let first = clause[0];
+                if self.values.get(first) == Some(true) {
+                    i += 1;
+                    continue;
+                }
+                let values = &self.values;
+                let replacement = (2..clause.len()).find(|&k| values.get(clause[k]) != Some(false));
+                if let Some(k) = replacement {
+                    clause.swap(1, k);
+                    let lit = clause[1];
+                    self.watch(lit).push(index);
+                    watchers.swap_remove(i);
+                    continue;
+                }
+                match self.values.get(first) {
+                    Some(false) => conflict = true,
+                    _ => self.assign(first, Some(index)),
+                }
+                i += 1;
+            }
+            self.watch(falsified).extend(watchers);
+            if conflict {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Whether assuming every literal of `literals` false leads to a
+    /// conflict by unit propagation. The assignment is undone afterwards.
+    fn is_rup(&mut self, literals: &[Lit]) -> bool {
+        if self.inconsistent {
+            return true;
+        }
+        let start = self.trail.len();
+        let mut implied = false;
+        for &lit in literals {
+            match self.values.get(lit) {
+                Some(true) => {
+                    implied = true;
+                    break;
+                }
+                Some(false) => {}
+                None => self.assign(-lit, None),
+            }
+        }
+        implied = implied || !self.propagate();
+        for lit in self.trail.drain(start..) {
+            self.reasons.remove(&lit.var());
+        }
+        self.values.clear();
+        self.qhead = start;
+        implied
+    }
+
+    /// Whether `literals` is a resolution asymmetric tautology on its first
+    /// literal `p`: its resolvent with every clause containing `-p` is RUP.
+    fn is_rat(&mut self, literals: &[Lit]) -> bool {
+        let Some(&pivot) = literals.first() else {
+            return false;
+        };
+        for index in 0..self.clauses.len() {
+            if self.deleted[index] || !self.clauses[index].contains(&-pivot) {
+                continue;
+            }
+            let mut resolvent = literals.to_vec();
+            resolvent.extend(self.clauses[index].iter().filter(|&&lit| lit != -pivot));
+            if !self.is_rup(&resolvent) {
+                return false;
+            }
+        }
+        true
+    }
+}
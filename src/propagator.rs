@@ -0,0 +1,233 @@
+use std::cmp::Reverse;
+
+use crate::{ClauseRef, LBool, Lit, Model, Solver};
+
+/// A theory propagator that takes part in the CDCL search next to the
+/// clauses, in the manner of the IPASIR-UP interface: it follows the
+/// assignments of the search, implies literals of its own with reason
+/// clauses, adds clauses, and gets the last word on every model.
+///
+/// Literals are those of the variables of the solver; a propagator over
+/// variables of its own has them made with `Solver::with_vars` or by
+/// clauses over them first.
+pub trait Propagator: Send {
+    /// Called with every literal the search assigns, in trail order, and the
+    /// decision level it was assigned at. Literals fixed at level 0 are
+    /// passed on once.
+    fn notify_assignment(&mut self, lit: Lit, level: usize) {
+        let _ = (lit, level);
+    }
+
+    /// Called when the search backtracks to decision level `level`, undoing
+    /// the assignments notified above it.
+    fn notify_backtrack(&mut self, level: usize) {
+        let _ = level;
+    }
+
+    /// A literal implied by the assignments notified so far, or `None` if
+    /// there is none left. Called once the clauses have nothing left to
+    /// propagate, until it returns `None`. A literal that is false makes a
+    /// conflict.
+    fn propagate(&mut self) -> Option<Lit> {
+        None
+    }
+
+    /// The reason for `lit`, just returned by `propagate`: a clause
+    /// implied by the formula and the theory, made of `lit` and literals
+    /// that are false under the notified assignments. It is only asked for
+    /// if `lit` is not true yet, and is kept only while `lit` is assigned.
+    fn reason(&mut self, lit: Lit) -> Vec<Lit>;
+
+    /// A clause implied by the formula and the theory, for instance one
+    /// ruling out the current assignment, or `None` if there is none left.
+    /// Called after `propagate` and after `check_model`, until it returns
+    /// `None`. The clauses are kept like learnt ones, so clause database
+    /// reduction may delete them again.
+    fn add_clause(&mut self) -> Option<Vec<Lit>> {
+        None
+    }
+
+    /// Called with every total assignment that satisfies the clauses;
+    /// returning false rejects it, and `add_clause` must then give a clause
+    /// that it falsifies.
+    fn check_model(&mut self, model: &Model) -> bool {
+        let _ = model;
+        true
+    }
+}
+
+/// The propagator connected to a solver, with the number of trail literals
+/// it has been notified of.
+pub(crate) struct External {
+    propagator: Box<dyn Propagator>,
+    pub notified: usize,
+}
+
+impl External {
+    /// Tells the propagator about a backtrack to `level` that left `trail_len`
+    /// literals on the trail, if that undid any it was notified of.
+    pub fn backtracked(&mut self, level: usize, trail_len: usize) {
+        if self.notified > trail_len {
+            self.notified = trail_len;
+            self.propagator.notify_backtrack(level);
+        }
+    }
+}
+
+impl Solver {
+    /// Connects `propagator` to the solver, replacing the one connected
+    /// before. Variables removed by elimination are put back first, and while
+    /// a propagator is connected variables are neither eliminated nor
+    /// substituted, the search always runs `Engine::Cdcl`, and proofs cannot
+    /// be checked.
+    pub fn connect_propagator(&mut self, propagator: impl Propagator + 'static) {
+        self.backtrack_to(0);
+        self.restore_eliminated();
+        self.external = Some(External {
+            propagator: Box::new(propagator),
+            notified: 0,
+        });
+    }
+
+    /// Disconnects the propagator and hands it back, if one is connected.
+    pub fn disconnect_propagator(&mut self) -> Option<Box<dyn Propagator>> {
+        self.external.take().map(|external| external.propagator)
+    }
+
+    /// Propagates the clauses and the connected propagator until neither
+    /// implies anything more, returning a conflict if one is reached. Once
+    /// every variable is assigned the propagator checks the model. A clause
+    /// it adds may make the search backjump first.
+    pub(crate) fn propagate_with_external(&mut self) -> Option<ClauseRef> {
+        loop {
+            if let Some(conflict) = self.propagate() {
+                return Some(conflict);
+            }
+            let mut external = self.external.take()?;
+            let conflict = self.propagate_external(&mut external);
+            external.backtracked(self.trail.decision_level(), self.trail.len());
+            self.external = Some(external);
+            if conflict.is_some() || self.qhead == self.trail.len() {
+                return conflict;
+            }
+        }
+    }
+
+    /// One round of the propagator: notifies it of the new trail literals,
+    /// asserts the literals it implies and adds its clauses, stopping at a
+    /// conflict or as soon as a clause changes the trail.
+    fn propagate_external(&mut self, external: &mut External) -> Option<ClauseRef> {
+        for index in external.notified..self.trail.len() {
+            let lit = self.trail[index];
+            let level = self.levels[lit.var().index()];
+            external.propagator.notify_assignment(lit, level);
+        }
+        external.notified = self.trail.len();
+
+        while let Some(lit) = external.propagator.propagate() {
+            if self.assignments.value(lit) == LBool::True {
+                continue;
+            }
+            let reason = external.propagator.reason(lit);
+            assert!(
+                reason.contains(&lit)
+                    && reason
+                        .iter()
+                        .all(|&q| q == lit || self.assignments.value(q) == LBool::False),
+                "the reason of {lit} must contain it and falsify its other literals"
+            );
+            self.stats.external_propagations += 1;
+            let trail_len = self.trail.len();
+            let conflict = self.add_external_clause(reason, true);
+            if conflict.is_some() || self.trail.len() < trail_len {
+                return conflict;
+            }
+        }
+
+        let complete = self.qhead == self.trail.len() && self.trail.len() == self.num_vars();
+        let rejected = complete
+            && !external
+                .propagator
+                .check_model(&Model::from_assignments(&self.assignments));
+        let mut added = false;
+        while let Some(clause) = external.propagator.add_clause() {
+            added = true;
+            self.stats.external_clauses += 1;
+            let trail_len = self.trail.len();
+            let conflict = self.add_external_clause(clause, false);
+            if conflict.is_some() || self.trail.len() != trail_len {
+                return conflict;
+            }
+        }
+        assert!(
+            !rejected || added,
+            "a propagator rejecting a model must add a clause it falsifies"
+        );
+        None
+    }
+
+    /// Adds a clause of the propagator, as a learnt clause or, for a
+    /// `reason`, as a clause deleted again on backtracking. If the clause is
+    /// falsified, the search backjumps to the highest level among its
+    /// literals and the clause is returned as the conflict there. If it has a
+    /// single literal that is not false, that literal is asserted at the level
+    /// the clause became unit at, backjumping there first if needed.
+    fn add_external_clause(&mut self, mut literals: Vec<Lit>, reason: bool) -> Option<ClauseRef> {
+        literals.sort_unstable();
+        literals.dedup();
+        literals.sort_by_key(|&lit| match self.assignments.value(lit) {
+            LBool::False => (true, Reverse(self.levels[lit.var().index()])),
+            _ => (false, Reverse(0)),
+        });
+        let false_level = |solver: &Self, lit: Option<&Lit>| match lit {
+            Some(&lit) if solver.assignments.value(lit) == LBool::False => {
+                Some(solver.levels[lit.var().index()])
+            }
+            None => Some(0),
+            Some(_) => None,
+        };
+
+        if let Some(level) = false_level(self, literals.first()) {
+            // Falsified: a conflict on the highest level of its literals
+            if level < self.trail.decision_level() {
+                self.backtrack_to(level);
+            }
+            let cref = self.push_external_clause(&literals, reason);
+            if reason {
+                self.lazy_conflict = Some(cref);
+            }
+            return Some(cref);
+        }
+        let Some(level) = false_level(self, literals.get(1)) else {
+            self.push_external_clause(&literals, reason);
+            return None;
+        };
+        let first = literals[0];
+        let true_below = self.assignments.value(first) == LBool::True
+            && self.levels[first.var().index()] <= level;
+        if !true_below && level < self.trail.decision_level() {
+            self.backtrack_to(level);
+        }
+        let cref = self.push_external_clause(&literals, reason);
+        if !true_below {
+            self.enqueue(first, Some(cref));
+        }
+        None
+    }
+
+    fn push_external_clause(&mut self, literals: &[Lit], reason: bool) -> ClauseRef {
+        if reason {
+            return self.clauses.push_lazy_reason(literals);
+        }
+        let mut levels: Vec<usize> = literals
+            .iter()
+            .filter(|lit| self.assignments.is_assigned(lit.var()))
+            .map(|lit| self.levels[lit.var().index()])
+            .collect();
+        levels.sort_unstable();
+        levels.dedup();
+        let cref = self.clauses.push(literals, true, levels.len());
+        self.watch_clause(cref);
+        cref
+    }
+}
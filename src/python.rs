@@ -0,0 +1,119 @@
+// Python bindings, built with the `python` feature into an extension
+// module named `cnf_dpll_2wl` (`maturin build --release`, see
+// `pyproject.toml`). Literals are DIMACS integers on the Python side.
+
+use std::time::Duration;
+
+use pyo3::exceptions::{PyOSError, PyValueError};
+use pyo3::prelude::*;
+
+use crate::{Lit, SolveResult, Solver, SolverError};
+
+/// An incremental SAT solver over DIMACS literals.
+///
+/// ```python
+/// from cnf_dpll_2wl import Solver
+///
+/// solver = Solver()
+/// solver.add_clause([1, -3])
+/// solver.add_clause([2, 3, -1])
+/// if solver.solve(assumptions=[1]):
+///     print(solver.model())  # e.g. [1, 2, -3]
+/// ```
+#[pyclass(name = "Solver", module = "cnf_dpll_2wl", unsendable)]
+struct PySolver {
+    solver: Solver,
+}
+
+fn to_py_err(e: SolverError) -> PyErr {
+    match e {
+        SolverError::Io(e) => PyOSError::new_err(e.to_string()),
+        SolverError::Parse(e) => PyValueError::new_err(e.to_string()),
+    }
+}
+
+fn literals(values: &[i32]) -> PyResult<Vec<Lit>> {
+    values
+        .iter()
+        .map(|&v| Lit::try_from(v).map_err(|e| PyValueError::new_err(e.to_string())))
+        .collect()
+}
+
+#[pymethods]
+impl PySolver {
+    /// A solver over variables `1..=variable_count` with no clauses; more
+    /// variables are added as clauses use them.
+    #[new]
+    #[pyo3(signature = (variable_count = 0))]
+    fn new(variable_count: usize) -> Self {
+        Self {
+            solver: Solver::with_vars(variable_count),
+        }
+    }
+
+    /// Loads a DIMACS CNF file.
+    #[staticmethod]
+    fn from_file(path: &str) -> PyResult<Self> {
+        let solver = Solver::new(path).map_err(to_py_err)?;
+        Ok(Self { solver })
+    }
+
+    /// Parses a DIMACS CNF formula given as a string.
+    #[staticmethod]
+    fn from_dimacs(dimacs: &str) -> PyResult<Self> {
+        let solver = Solver::from_dimacs_str(dimacs).map_err(to_py_err)?;
+        Ok(Self { solver })
+    }
+
+    fn num_vars(&self) -> usize {
+        self.solver.num_vars()
+    }
+
+    /// Adds a clause of non-zero DIMACS literals, such as `[1, -2]`.
+    fn add_clause(&mut self, clause: Vec<i32>) -> PyResult<()> {
+        let clause = literals(&clause)?;
+        self.solver.add_clause(&clause).unwrap();
+        Ok(())
+    }
+
+    /// Solves under the given assumptions: `True` if satisfiable, `False`
+    /// if not, `None` if the timeout stopped the search. The interpreter
+    /// lock is released meanwhile.
+    #[pyo3(signature = (assumptions = Vec::new()))]
+    fn solve(&mut self, py: Python<'_>, assumptions: Vec<i32>) -> PyResult<Option<bool>> {
+        let assumptions = literals(&assumptions)?;
+        let solver = &mut self.solver;
+        let result = py.detach(|| solver.solve_with_assumptions(&assumptions));
+        Ok(match result {
+            SolveResult::Sat => Some(true),
+            SolveResult::Unsat => Some(false),
+            SolveResult::Unknown => None,
+        })
+    }
+
+    /// The model of the last satisfiable answer as a list of literals, one
+    /// per variable, or `None`.
+    fn model(&self) -> Option<Vec<i32>> {
+        let model = self.solver.model()?;
+        Some(model.to_dimacs())
+    }
+
+    /// The assumptions that made the last `solve` unsatisfiable.
+    fn failed_assumptions(&self) -> Vec<i32> {
+        let failed = self.solver.failed_assumptions();
+        failed.iter().map(|&lit| i32::from(lit)).collect()
+    }
+
+    /// Limits every later `solve` call to `seconds` of wall-clock time.
+    fn set_timeout(&mut self, seconds: f64) -> PyResult<()> {
+        let timeout = Duration::try_from_secs_f64(seconds)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        self.solver.set_timeout(timeout);
+        Ok(())
+    }
+}
+
+#[pymodule]
+fn cnf_dpll_2wl(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PySolver>()
+}
@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::time::Duration;
+
+use crate::error::{ParseError, SolverError};
+use crate::{
+    Instant, InterruptHandle, Lit, SolveResult, Solver, SolverConfig, Var, compression, dimacs,
+};
+
+/// Answer of `QbfSolver::solve`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QbfResult {
+    True,
+    False,
+    /// A timeout or interrupt stopped the search.
+    Unknown,
+}
+
+/// Solver for quantified boolean formulas with at most one alternation,
+/// `∃X. φ` and `∀X ∃Y. φ` (2QBF), read from QDIMACS files. Free variables
+/// are existential and outermost, and the innermost block, when universal,
+/// is removed by universal reduction, so `∃X ∀Y. φ` and `∀X ∃Y ∀Z. φ` are
+/// accepted as well.
+///
+/// A `∀∃` formula is decided by counterexample-guided abstraction
+/// refinement between two SAT solvers. The abstraction proposes values
+/// for `X`; the matrix solver looks for values of `Y` that satisfy `φ` with
+/// them. If there are none, the formula is false. Otherwise the abstraction
+/// learns that its next proposal must falsify a clause that those values of
+/// `Y` leave to `X`, and the formula is true once no proposal is left.
+pub struct QbfSolver {
+    variable_count: usize,
+    /// Universal variables of a `∀∃` formula; empty for an `∃` one.
+    universals: Vec<Var>,
+    is_universal: Vec<bool>,
+    /// Variables of the outermost block, which `witness` assigns.
+    outer: Vec<Var>,
+    clauses: Vec<Vec<Lit>>,
+    witness: Option<Vec<Lit>>,
+    refinements: u64,
+    timeout: Option<Duration>,
+    interrupt: InterruptHandle,
+    /// Configuration of both SAT solvers. No proof is written.
+    pub config: SolverConfig,
+}
+
+impl QbfSolver {
+    /// Loads a QDIMACS file, possibly compressed like the inputs of
+    /// `Solver::new`.
+    pub fn new(path: &str) -> Result<Self, SolverError> {
+        let file = BufReader::new(File::open(path)?);
+        Self::from_reader(compression::decompress(file)?)
+    }
+
+    /// Reads a QDIMACS formula from any buffered reader. Prefixes with more
+    /// alternations than the solver handles are rejected with
+    /// `ParseError::UnsupportedPrefix`.
+    pub fn from_reader(reader: impl BufRead) -> Result<Self, SolverError> {
+        let formula = dimacs::parse_qdimacs(reader)?;
+        let mut clauses = formula.clauses;
+
+        // Blocks of alternating quantifiers, free variables first
+        let mut quantified = vec![false; formula.variable_count + 1];
+        for (_, _, vars) in &formula.prefix {
+            for var in vars {
+                quantified[var.index()] = true;
+            }
+        }
+        let mut free: Vec<Var> = clauses
+            .iter()
+            .flatten()
+            .map(|lit| lit.var())
+            .filter(|var| !std::mem::replace(&mut quantified[var.index()], true))
+            .collect();
+        free.sort_unstable();
+        let mut blocks: Vec<(usize, bool, Vec<Var>)> = vec![(0, false, free)];
+        let prefix = formula
+            .prefix
+            .into_iter()
+            .filter(|block| !block.2.is_empty());
+        for (line, universal, vars) in prefix {
+            match blocks.last_mut() {
+                Some(last) if last.1 == universal => last.2.extend(vars),
+                _ => blocks.push((line, universal, vars)),
+            }
+        }
+        blocks.retain(|block| !block.2.is_empty());
+
+        // Universal reduction of an innermost universal block
+        if blocks.len() > 1 && blocks.last().is_some_and(|block| block.1) {
+            let (_, _, reduced) = blocks.pop().unwrap();
+            let mut is_reduced = vec![false; formula.variable_count + 1];
+            for var in reduced {
+                is_reduced[var.index()] = true;
+            }
+            for clause in &mut clauses {
+                let tautology = clause.iter().any(|&lit| clause.contains(&-lit));
+                if !tautology {
+                    clause.retain(|lit| !is_reduced[lit.var().index()]);
+                }
+            }
+        }
+        if let Some((line, _, _)) = blocks.iter().skip(1).find(|block| block.1) {
+            return Err(ParseError::UnsupportedPrefix { line: *line }.into());
+        }
+
+        let mut solver = Self {
+            variable_count: formula.variable_count,
+            universals: Vec::new(),
+            is_universal: vec![false; formula.variable_count + 1],
+            outer: Vec::new(),
+            clauses,
+            witness: None,
+            refinements: 0,
+            timeout: None,
+            interrupt: InterruptHandle::default(),
+            config: SolverConfig::default(),
+        };
+        if let Some((_, universal, vars)) = blocks.into_iter().next() {
+            if universal {
+                for var in &vars {
+                    solver.is_universal[var.index()] = true;
+                }
+                solver.universals = vars.clone();
+            }
+            solver.outer = vars;
+        }
+        Ok(solver)
+    }
+
+    /// Parses a QDIMACS formula held in memory.
+    pub fn from_qdimacs_str(qdimacs: &str) -> Result<Self, SolverError> {
+        Self::from_reader(qdimacs.as_bytes())
+    }
+
+    pub fn num_vars(&self) -> usize {
+        self.variable_count
+    }
+
+    pub fn num_clauses(&self) -> usize {
+        self.clauses.len()
+    }
+
+    /// Universal variables of a `∀∃` formula, empty for an existential one.
+    pub fn universals(&self) -> &[Var] {
+        &self.universals
+    }
+
+    /// Decides the formula.
+    pub fn solve(&mut self) -> QbfResult {
+        self.witness = None;
+        self.refinements = 0;
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+
+        let mut matrix = self.sat_solver();
+        for clause in &self.clauses {
+            matrix.push_clause(clause.clone());
+        }
+        if self.universals.is_empty() {
+            return match self.solve_under(&mut matrix, &[], deadline) {
+                SolveResult::Sat => {
+                    let model = matrix.model().expect("Sat answers leave a model");
+                    self.witness = Some(
+                        self.outer
+                            .iter()
+                            .map(|&var| Lit::new(var, !model.value(var)))
+                            .collect(),
+                    );
+                    QbfResult::True
+                }
+                SolveResult::Unsat => QbfResult::False,
+                SolveResult::Unknown => QbfResult::Unknown,
+            };
+        }
+
+        let mut abstraction = self.sat_solver();
+        // A literal implying every literal of a set of universal literals
+        // false, for each set a refinement needed so far
+        let mut falsified: HashMap<Vec<Lit>, Lit> = HashMap::new();
+        loop {
+            let candidate: Vec<Lit> = match self.solve_under(&mut abstraction, &[], deadline) {
+                SolveResult::Sat => {
+                    let model = abstraction.model().expect("Sat answers leave a model");
+                    self.universals
+                        .iter()
+                        .map(|&var| Lit::new(var, !model.value(var)))
+                        .collect()
+                }
+                SolveResult::Unsat => return QbfResult::True,
+                SolveResult::Unknown => return QbfResult::Unknown,
+            };
+            let model = match self.solve_under(&mut matrix, &candidate, deadline) {
+                SolveResult::Sat => matrix.model().expect("Sat answers leave a model"),
+                SolveResult::Unsat => {
+                    self.witness = Some(candidate);
+                    return QbfResult::False;
+                }
+                SolveResult::Unknown => return QbfResult::Unknown,
+            };
+
+            // The clauses not satisfied by the existential values of the
+            // model: the next candidate must falsify one of them
+            self.refinements += 1;
+            let mut refinement = Vec::new();
+            for clause in &self.clauses {
+                let existential = |lit: &&Lit| !self.is_universal[lit.var().index()];
+                if clause
+                    .iter()
+                    .filter(existential)
+                    .any(|&lit| model.lit_is_true(lit))
+                {
+                    continue;
+                }
+                let mut universal = clause.clone();
+                universal.retain(|lit| self.is_universal[lit.var().index()]);
+                universal.sort_unstable();
+                let lit = match universal[..] {
+                    [lit] => -lit,
+                    _ => *falsified.entry(universal).or_insert_with_key(|universal| {
+                        let lit = abstraction.new_var().positive();
+                        for &l in universal {
+                            abstraction.push_clause(vec![-lit, -l]);
+                        }
+                        lit
+                    }),
+                };
+                refinement.push(lit);
+            }
+            abstraction.push_clause(refinement);
+        }
+    }
+
+    /// A SAT solver over the variables of the formula, configured like this
+    /// one.
+    fn sat_solver(&self) -> Solver {
+        let mut solver = Solver::with_vars(self.variable_count);
+        solver.config = SolverConfig {
+            proof_path: None,
+            resolution_proof: false,
+            symmetry_breaking: false,
+            ..self.config.clone()
+        };
+        solver.interrupt = self.interrupt.clone();
+        solver
+    }
+
+    /// Solves under `assumptions` within what is left until `deadline`.
+    fn solve_under(
+        &self,
+        solver: &mut Solver,
+        assumptions: &[Lit],
+        deadline: Option<Instant>,
+    ) -> SolveResult {
+        if let Some(deadline) = deadline {
+            solver.set_timeout(deadline.saturating_duration_since(Instant::now()));
+        }
+        solver.solve_with_assumptions(assumptions)
+    }
+
+    /// Values of the outermost block that decide the last answer: after
+    /// `True` for an `∃` formula, values of its variables that satisfy it;
+    /// after `False` for a `∀∃` formula, values of the universal variables
+    /// under which it cannot be satisfied. `None` otherwise.
+    pub fn witness(&self) -> Option<&[Lit]> {
+        self.witness.as_deref()
+    }
+
+    /// Number of candidates refuted by the last `solve` call.
+    pub fn refinements(&self) -> u64 {
+        self.refinements
+    }
+
+    /// Limits every later `solve` call, all of its SAT calls together, to
+    /// `timeout` of wall-clock time.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = Some(timeout);
+    }
+
+    /// A handle that makes the current (or next) `solve` call return
+    /// `Unknown`.
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.interrupt.clone()
+    }
+}
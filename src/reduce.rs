@@ -0,0 +1,82 @@
+use crate::{ClauseRef, Solver};
+
+/// Share of the clause arena that deleted clauses may take up before it is compacted.
+const MAX_GARBAGE_FRACTION: f64 = 0.2;
+
+impl Solver {
+    /// Deletes the less useful half of the learnt clauses, ranked by literal
+    /// block distance (then length). Binary clauses, glue clauses with an LBD
+    /// of at most `config.glue_lbd`, and clauses that are currently the reason
+    /// for an assignment are always kept. Deleted clauses are only flagged:
+    /// propagation drops their watches when it meets them, and their memory
+    /// is reclaimed once enough of the arena is garbage.
+    pub(crate) fn reduce_learnt_clauses(&mut self) {
+        let mut candidates: Vec<ClauseRef> = self
+            .clauses
+            .iter()
+            .filter(|&cref| {
+                self.clauses.is_learnt(cref)
+                    && self.clauses[cref].len() > 2
+                    && self.clauses.lbd(cref) > self.config.glue_lbd
+                    && !self.is_locked(cref)
+            })
+            .collect();
+        candidates.sort_by_key(|&cref| (self.clauses.lbd(cref), self.clauses[cref].len()));
+
+        for &cref in &candidates[candidates.len() / 2..] {
+            self.stats.deleted_clauses += 1;
+            if let Some(proof) = self.proof.as_mut() {
+                proof.delete(self.clauses.id(cref), &self.clauses[cref]);
+            }
+            self.clauses.delete(cref);
+        }
+        self.collect_garbage_if_wasteful();
+    }
+
+    /// Whether clause `cref`, of three or more literals, is the reason for
+    /// the assignment of its first literal, which is where propagation puts
+    /// the implied literal.
+    pub(crate) fn is_locked(&self, cref: ClauseRef) -> bool {
+        let lit = self.clauses[cref][0];
+        self.reasons[lit.var().index()] == Some(cref)
+    }
+
+    /// Runs `collect_garbage` once deleted and shrunk clauses waste more than
+    /// `MAX_GARBAGE_FRACTION` of the arena.
+    pub(crate) fn collect_garbage_if_wasteful(&mut self) {
+        if self.clauses.wasted() as f64 > self.clauses.size() as f64 * MAX_GARBAGE_FRACTION {
+            self.collect_garbage();
+        }
+    }
+
+    /// Compacts the clause arena after deletions and moves every handle the
+    /// solver holds (watches, binary implications, reasons, the pending XOR
+    /// conflict) to the new clause
+    /// positions, dropping those of deleted clauses.
+    pub(crate) fn collect_garbage(&mut self) {
+        self.stats.reclaimed_bytes += (self.clauses.wasted() * size_of::<u32>()) as u64;
+        let relocation = self.clauses.collect_garbage();
+        for list in self.watch_lists.iter_mut() {
+            list.retain_mut(|cref| match relocation.get(*cref) {
+                Some(new) => {
+                    *cref = new;
+                    true
+                }
+                None => false,
+            });
+        }
+        for list in self.implications.iter_mut() {
+            list.retain_mut(|(_, cref)| match relocation.get(*cref) {
+                Some(new) => {
+                    *cref = new;
+                    true
+                }
+                None => false,
+            });
+        }
+        for reason in self.reasons.iter_mut() {
+            *reason = reason.and_then(|cref| relocation.get(cref));
+        }
+        self.lazy_conflict = self.lazy_conflict.and_then(|cref| relocation.get(cref));
+    }
+}
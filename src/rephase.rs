@@ -0,0 +1,58 @@
+use crate::config::Rephase;
+use crate::{Solver, Var};
+
+impl Solver {
+    /// Saves the values of the longest trail prefix seen without a conflict
+    /// as the best phases. Called on every conflict, before backjumping:
+    /// the levels below the conflicting one were propagated completely.
+    pub(crate) fn note_best_trail(&mut self) {
+        let level = self.trail.decision_level();
+        let consistent = self.trail.level_start(level);
+        if consistent <= self.best_trail {
+            return;
+        }
+        self.best_trail = consistent;
+        for index in 0..consistent {
+            let lit = self.trail[index];
+            self.best_phases[lit.var().index()] = !lit.sign();
+        }
+    }
+
+    /// Resets the saved phases at level 0, as `kind` says, and starts
+    /// looking for the best trail afresh.
+    pub(crate) fn rephase(&mut self, kind: Rephase) {
+        let vars = (1..=self.num_vars()).map(Var::new);
+        match kind {
+            Rephase::Original => {
+                for var in vars {
+                    self.phases[var.index()] = self.original_phase(var);
+                }
+            }
+            Rephase::Inverted => {
+                for var in vars {
+                    self.phases[var.index()] = !self.original_phase(var);
+                }
+            }
+            Rephase::Best => self.phases.clone_from(&self.best_phases),
+            Rephase::Random => {
+                for var in vars {
+                    self.phases[var.index()] = self.rng.next_bool();
+                }
+            }
+            Rephase::Walk => {
+                self.local_search(self.config.rephase_flips);
+            }
+        }
+        self.best_trail = 0;
+        self.stats.rephases += 1;
+    }
+
+    /// The phase `var` had when the first search started: the one chosen by
+    /// `jeroslow_wang`, or true.
+    fn original_phase(&self, var: Var) -> bool {
+        self.original_phases
+            .get(var.index())
+            .copied()
+            .unwrap_or(true)
+    }
+}
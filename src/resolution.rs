@@ -0,0 +1,274 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::proof::Derivation;
+use crate::{Lit, Solver, Var};
+
+/// A resolution refutation: a sequence of clauses, each an input clause
+/// or the resolvent of two clauses before it, ending with the empty
+/// clause. Only the steps the empty clause depends on are kept. Displayed,
+/// it is drawn as a tree rooted at the empty clause, in which a clause
+/// used more than once is expanded the first time only.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolutionProof {
+    steps: Vec<ResolutionStep>,
+}
+
+/// One clause of a `ResolutionProof`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolutionStep {
+    /// Sorted by variable.
+    pub clause: Vec<Lit>,
+    pub rule: ResolutionRule,
+}
+
+/// How a `ResolutionStep` is obtained.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResolutionRule {
+    /// An input clause, numbered from 1 in the order of the input, as in
+    /// LRAT proofs and `ClauseInfo::id`.
+    Input { id: u64 },
+    /// The resolvent on `pivot` of step `positive`, which contains the
+    /// positive literal of `pivot`, and step `negative`, which contains
+    /// its negation. Steps are indices into `ResolutionProof::steps`.
+    Resolve {
+        positive: usize,
+        negative: usize,
+        pivot: Var,
+    },
+}
+
+impl ResolutionProof {
+    /// The steps, each after those it is resolved from; the last one is
+    /// the empty clause.
+    pub fn steps(&self) -> &[ResolutionStep] {
+        &self.steps
+    }
+
+    /// The input clauses the refutation uses, by ID.
+    pub fn inputs(&self) -> impl Iterator<Item = u64> + '_ {
+        self.steps.iter().filter_map(|step| match step.rule {
+            ResolutionRule::Input { id } => Some(id),
+            ResolutionRule::Resolve { .. } => None,
+        })
+    }
+
+    /// Number of resolution steps, input clauses left out.
+    pub fn num_resolutions(&self) -> usize {
+        self.steps.len() - self.inputs().count()
+    }
+
+    /// Rebuilds the refutation ending with clause `root` out of the hints
+    /// of the clauses it depends on. Every hint list is a chain of clauses
+    /// that become unit in turn, the last one being falsified, under the
+    /// negation of the clause it derives; walked backwards from that last
+    /// clause, resolving on the literal each clause made true whenever the
+    /// negation of that literal is left, it becomes a chain of resolutions.
+    /// Returns `None` if a clause has no hints, or hints that are no such
+    /// chain.
+    fn build(derivations: &HashMap<u64, Derivation>, root: u64) -> Option<Self> {
+        // A clause only ever refers to clauses with lower IDs
+        let mut needed = HashSet::from([root]);
+        let mut stack = vec![root];
+        while let Some(id) = stack.pop() {
+            for &hint in derivations.get(&id)?.hints.iter().flatten() {
+                if needed.insert(hint) {
+                    stack.push(hint);
+                }
+            }
+        }
+        let mut ids: Vec<u64> = needed.into_iter().collect();
+        ids.sort_unstable();
+
+        let mut steps = Vec::new();
+        let mut nodes = HashMap::with_capacity(ids.len());
+        for id in ids {
+            let derivation = &derivations[&id];
+            let node = match &derivation.hints {
+                None => {
+                    let mut clause = derivation.literals.clone();
+                    clause.sort_unstable();
+                    steps.push(ResolutionStep {
+                        clause,
+                        rule: ResolutionRule::Input { id },
+                    });
+                    steps.len() - 1
+                }
+                Some(hints) => {
+                    resolve_chain(&derivation.literals, hints, derivations, &nodes, &mut steps)?
+                }
+            };
+            nodes.insert(id, node);
+        }
+        Some(Self::trim(steps, nodes[&root]))
+    }
+
+    /// Keeps only the steps `root` depends on, `root` last.
+    fn trim(steps: Vec<ResolutionStep>, root: usize) -> Self {
+        let mut used = vec![false; root + 1];
+        used[root] = true;
+        for index in (0..=root).rev() {
+            if !used[index] {
+                continue;
+            }
+            if let ResolutionRule::Resolve {
+                positive, negative, ..
+            } = steps[index].rule
+            {
+                used[positive] = true;
+                used[negative] = true;
+            }
+        }
+        let mut renumbered = vec![0; root + 1];
+        let mut kept = Vec::new();
+        for (index, mut step) in steps.into_iter().take(root + 1).enumerate() {
+            if !used[index] {
+                continue;
+            }
+            if let ResolutionRule::Resolve {
+                positive, negative, ..
+            } = &mut step.rule
+            {
+                *positive = renumbered[*positive];
+                *negative = renumbered[*negative];
+            }
+            renumbered[index] = kept.len();
+            kept.push(step);
+        }
+        Self { steps: kept }
+    }
+}
+
+/// Turns the hint chain `hints` of the clause `literals` into resolution
+/// steps appended to `steps`, and returns the index of the last one, whose
+/// clause is `literals` or a subset of it. `nodes` gives the step of every
+/// hint.
+fn resolve_chain(
+    literals: &[Lit],
+    hints: &[u64],
+    derivations: &HashMap<u64, Derivation>,
+    nodes: &HashMap<u64, usize>,
+    steps: &mut Vec<ResolutionStep>,
+) -> Option<usize> {
+    // Forwards: the literal every hint makes true, up to the falsified one
+    let mut falsified: HashSet<Lit> = literals.iter().copied().collect();
+    let mut implied = Vec::new();
+    let mut conflict = None;
+    for &hint in hints {
+        let clause = &derivations.get(&hint)?.literals;
+        if clause.iter().any(|&lit| falsified.contains(&-lit)) {
+            return None;
+        }
+        let mut open = clause.iter().filter(|&lit| !falsified.contains(lit));
+        match (open.next(), open.next()) {
+            (None, _) => {
+                conflict = Some(hint);
+                break;
+            }
+            (Some(&lit), None) => {
+                falsified.insert(-lit);
+                implied.push((hint, lit));
+            }
+            (Some(_), Some(_)) => return None,
+        }
+    }
+
+    // Backwards: resolve the falsified clause with the reasons it needs
+    let mut node = nodes[&conflict?];
+    let mut clause = steps[node].clause.clone();
+    for &(hint, lit) in implied.iter().rev() {
+        if !clause.contains(&-lit) {
+            continue;
+        }
+        let reason = nodes[&hint];
+        if !steps[reason].clause.contains(&lit) {
+            // Only falsified literals are left in the reason, which can
+            // take over from the clause derived so far
+            node = reason;
+            clause = steps[reason].clause.clone();
+            continue;
+        }
+        clause.retain(|&other| other != -lit);
+        clause.extend(steps[reason].clause.iter().filter(|&&other| other != lit));
+        clause.sort_unstable();
+        clause.dedup();
+        let (positive, negative) = if lit.sign() {
+            (node, reason)
+        } else {
+            (reason, node)
+        };
+        steps.push(ResolutionStep {
+            clause: clause.clone(),
+            rule: ResolutionRule::Resolve {
+                positive,
+                negative,
+                pivot: lit.var(),
+            },
+        });
+        node = steps.len() - 1;
+    }
+    debug_assert!(clause.iter().all(|lit| literals.contains(lit)));
+    Some(node)
+}
+
+impl fmt::Display for ResolutionProof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(root) = self.steps.len().checked_sub(1) else {
+            return Ok(());
+        };
+        let mut shown = vec![false; self.steps.len()];
+        // Steps to draw, with the prefix of their line and of their children
+        let mut stack = vec![(root, String::new(), String::new())];
+        while let Some((index, prefix, child_prefix)) = stack.pop() {
+            let step = &self.steps[index];
+            write!(f, "{prefix}[{}]", index + 1)?;
+            if step.clause.is_empty() {
+                write!(f, " ⊥")?;
+            }
+            for lit in &step.clause {
+                write!(f, " {lit}")?;
+            }
+            match step.rule {
+                ResolutionRule::Input { id } => writeln!(f, "  input clause {id}")?,
+                _ if shown[index] => writeln!(f, "  see above")?,
+                ResolutionRule::Resolve {
+                    positive,
+                    negative,
+                    pivot,
+                } => {
+                    writeln!(f, "  resolvent on {pivot}")?;
+                    shown[index] = true;
+                    stack.push((
+                        negative,
+                        format!("{child_prefix}└── "),
+                        format!("{child_prefix}    "),
+                    ));
+                    stack.push((
+                        positive,
+                        format!("{child_prefix}├── "),
+                        format!("{child_prefix}│   "),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Solver {
+    /// A resolution refutation of the formula after an `Unsat` answer with
+    /// `config.resolution_proof` set before the first `solve` call, rebuilt
+    /// from the hints that an LRAT proof would have. `None` without such an
+    /// answer, or if a step of the search has no resolution derivation, as
+    /// with XOR clauses, native pseudo-Boolean constraints and the
+    /// lookahead engine.
+    pub fn resolution_proof(&self) -> Option<ResolutionProof> {
+        let derivations = self.proof.as_ref()?.derivations()?;
+        let root = derivations
+            .iter()
+            .filter(|(_, derivation)| derivation.literals.is_empty())
+            .map(|(&id, _)| id)
+            .max()?;
+        ResolutionProof::build(derivations, root)
+    }
+}
@@ -0,0 +1,84 @@
+/// When to abandon the current search path and restart from decision level 0.
+/// Intervals are measured in conflicts.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RestartStrategy {
+    /// Never restart.
+    Never,
+    /// Restart after `unit * luby(i)` conflicts, where `luby` is the sequence
+    /// 1, 1, 2, 1, 1, 2, 4, 1, ...
+    Luby { unit: u64 },
+    /// Restart after `initial`, then `initial * factor`, `initial * factor²`, ... conflicts.
+    Geometric { initial: u64, factor: f64 },
+}
+
+impl Default for RestartStrategy {
+    fn default() -> Self {
+        Self::Luby { unit: 100 }
+    }
+}
+
+/// Tracks conflicts since the last restart against the current interval.
+pub(crate) struct RestartSchedule {
+    strategy: RestartStrategy,
+    restarts: u64,
+    conflicts: u64,
+    limit: Option<u64>,
+}
+
+impl RestartSchedule {
+    pub fn new(strategy: RestartStrategy) -> Self {
+        let mut schedule = Self {
+            strategy,
+            restarts: 0,
+            conflicts: 0,
+            limit: None,
+        };
+        schedule.limit = schedule.interval();
+        schedule
+    }
+
+    pub fn on_conflict(&mut self) {
+        self.conflicts += 1;
+    }
+
+    pub fn should_restart(&self) -> bool {
+        self.limit.is_some_and(|limit| self.conflicts >= limit)
+    }
+
+    /// Records that a restart happened and moves on to the next interval.
+    pub fn restarted(&mut self) {
+        self.restarts += 1;
+        self.conflicts = 0;
+        self.limit = self.interval();
+    }
+
+    fn interval(&self) -> Option<u64> {
+        match self.strategy {
+            RestartStrategy::Never => None,
+            RestartStrategy::Luby { unit } => Some(unit.max(1) * luby(self.restarts)),
+            RestartStrategy::Geometric { initial, factor } => {
+                let scaled = initial.max(1) as f64 * factor.powi(self.restarts as i32);
+                Some(scaled.min(u64::MAX as f64) as u64)
+            }
+        }
+    }
+}
+
+/// The `index`-th element (0-based) of the Luby sequence 1, 1, 2, 1, 1, 2, 4, ...
+pub(crate) fn luby(index: u64) -> u64 {
+    // Find the smallest complete subsequence 2^k - 1 long that contains `index`
+    let mut size = 1;
+    let mut exponent = 0;
+    while size < index + 1 {
+        exponent += 1;
+        size = 2 * size + 1;
+    }
+    let mut index = index;
+    while size - 1 != index {
+        size = (size - 1) >> 1;
+        exponent -= 1;
+        index %= size;
+    }
+    1 << exponent
+}
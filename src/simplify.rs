@@ -0,0 +1,87 @@
+use crate::{ClauseRef, LBool, Lit, Solver, Var};
+
+impl Solver {
+    /// Cleans the clause database with the values fixed at decision level
+    /// 0: the pending unit clauses are propagated, then clauses satisfied
+    /// by a fixed value are deleted and false literals are removed from the
+    /// others. Meant to be called between incremental `solve` calls, where
+    /// units keep being added and the dead clauses they leave behind would
+    /// otherwise slow propagation down. Clauses that are the reason for a
+    /// fixed value are kept as they are. Returns false if the formula
+    /// turned out to be unsatisfiable.
+    pub fn simplify(&mut self) -> bool {
+        self.backtrack_to(0);
+        if !self.initial_propagation() {
+            self.log_refutation();
+            return false;
+        }
+
+        let crefs: Vec<ClauseRef> = self
+            .clauses
+            .iter()
+            .filter(|&cref| !self.clauses.is_lazy_reason(cref))
+            .collect();
+        let mut changed = false;
+        for cref in crefs {
+            let clause = &self.clauses[cref];
+            if !clause
+                .iter()
+                .any(|&lit| self.assignments.value(lit) != LBool::Undef)
+            {
+                continue;
+            }
+            if clause
+                .iter()
+                .any(|&lit| self.reasons[lit.var().index()] == Some(cref))
+            {
+                continue;
+            }
+            changed = true;
+            let old = clause.to_vec();
+            if old
+                .iter()
+                .any(|&lit| self.assignments.value(lit) == LBool::True)
+            {
+                if let Some(proof) = self.proof.as_mut() {
+                    proof.delete(self.clauses.id(cref), &old);
+                }
+                self.clauses.delete(cref);
+                self.stats.satisfied_clauses += 1;
+                continue;
+            }
+            // Propagation left no unit and no falsified clause, so at least
+            // two literals remain
+            let literals: Vec<Lit> = old
+                .iter()
+                .copied()
+                .filter(|&lit| self.assignments.value(lit) == LBool::Undef)
+                .collect();
+            debug_assert!(literals.len() >= 2);
+            let old_id = self.clauses.id(cref);
+            let hints = if self.wants_hints() {
+                let fixed: Vec<Var> = old
+                    .iter()
+                    .filter(|&&lit| self.assignments.value(lit) == LBool::False)
+                    .map(|lit| lit.var())
+                    .collect();
+                let mut hints = self.unit_hints(&fixed);
+                hints.push(old_id);
+                hints
+            } else {
+                Vec::new()
+            };
+            self.clauses.shrink(cref, &literals);
+            if let Some(proof) = self.proof.as_mut() {
+                proof.add(self.clauses.id(cref), &literals, &hints);
+                proof.delete(old_id, &old);
+            }
+            self.stats.false_literals_removed += (old.len() - literals.len()) as u64;
+        }
+        // The watched literals of a shortened clause may be gone
+        if changed {
+            self.collect_garbage();
+            self.rewatch_all();
+        }
+        true
+    }
+}
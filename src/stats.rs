@@ -0,0 +1,79 @@
+use std::time::Duration;
+
+/// Counters collected while loading and solving a formula. Search counters
+/// accumulate over every `solve` call on the same solver.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stats {
+    pub decisions: u64,
+    /// Decisions on a variable picked at random (`SolverConfig::random_var_freq`).
+    pub random_decisions: u64,
+    /// Assigned literals whose watch lists were processed.
+    pub propagations: u64,
+    pub conflicts: u64,
+    pub restarts: u64,
+    pub learnt_clauses: u64,
+    /// Learnt clauses removed by clause database reduction.
+    pub deleted_clauses: u64,
+    pub max_decision_level: usize,
+    /// Input clauses dropped because they contain a literal and its negation.
+    pub tautologies_dropped: u64,
+    /// Input clauses that listed some literal more than once.
+    pub clauses_deduplicated: u64,
+    /// Input clauses removed by subsumption.
+    pub subsumed_clauses: u64,
+    /// Input clauses shortened by one literal through self-subsuming resolution.
+    pub strengthened_clauses: u64,
+    /// Variables replaced by an equivalent literal.
+    pub substituted_vars: u64,
+    /// Variables removed by bounded variable elimination.
+    pub eliminated_vars: u64,
+    /// Eliminated variables that a gate defined, so that only the
+    /// resolvents with the gate clauses were added.
+    pub gate_eliminations: u64,
+    /// Symmetries of the input broken by lex-leader clauses.
+    pub symmetry_generators: u64,
+    /// At-most-one groups that replaced the binary clauses between their
+    /// literals.
+    pub at_most_ones: u64,
+    /// Literals tried by failed literal probing.
+    pub literals_probed: u64,
+    /// Probed literals whose propagation failed, so their negation was fixed.
+    pub failed_literals: u64,
+    /// Values implied by Gaussian elimination over the XOR clauses that
+    /// watching them missed.
+    pub gauss_propagations: u64,
+    /// Conflicts found by Gaussian elimination.
+    pub gauss_conflicts: u64,
+    /// Values implied by natively propagated pseudo-Boolean constraints.
+    pub pb_propagations: u64,
+    /// Conflicts found by natively propagated pseudo-Boolean constraints.
+    pub pb_conflicts: u64,
+    /// Values implied by at-most-one groups.
+    pub amo_propagations: u64,
+    /// Learnt clauses shortened by vivification.
+    pub vivified_clauses: u64,
+    /// Clauses deleted by `Solver::simplify` as satisfied at level 0.
+    pub satisfied_clauses: u64,
+    /// Literals false at level 0 removed from clauses by `Solver::simplify`.
+    pub false_literals_removed: u64,
+    /// Variables flipped by local search.
+    pub flips: u64,
+    /// Times the saved phases were replaced by the result of local search.
+    pub rephases: u64,
+    /// Literals implied by a connected `Propagator`.
+    pub external_propagations: u64,
+    /// Clauses added by a connected `Propagator`.
+    pub external_clauses: u64,
+    /// Learnt clauses received from the other workers of a portfolio.
+    pub imported_clauses: u64,
+    /// Clause arena memory freed by garbage collection, in bytes.
+    pub reclaimed_bytes: u64,
+    /// Most memory in use at once as measured by `Solver::memory_usage`,
+    /// every thousand conflicts of the CDCL search and when `solve` returns,
+    /// in bytes.
+    pub peak_memory: u64,
+    /// Time spent reading and parsing the input (zero for solvers built in memory).
+    pub parse_time: Duration,
+    pub solve_time: Duration,
+}
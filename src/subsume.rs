@@ -0,0 +1,109 @@
+use crate::occ::OccLists;
+use crate::{ClauseRef, Lit, Solver};
+
+impl Solver {
+    /// Preprocessing pass over the input clauses: removes every clause that
+    /// is a superset of another one (subsumption), and drops `-l` from a
+    /// clause `D` when some clause `C` containing `l` satisfies
+    /// `C \ {l} ⊆ D` (self-subsuming resolution). Both steps keep the
+    /// formula equivalent. Runs on an empty trail, as it rewrites clauses
+    /// and rebuilds all watches.
+    pub(crate) fn subsume(&mut self) {
+        debug_assert_eq!(self.trail.len(), 0);
+
+        let mut occ = OccLists::new(&self.clauses, self.watch_lists.len());
+        let mut queue: Vec<ClauseRef> = self
+            .clauses
+            .iter()
+            .filter(|&cref| !self.clauses.is_learnt(cref))
+            .collect();
+        // Short clauses subsume the most, so try them first (they are popped last)
+        queue.sort_by_key(|&cref| std::cmp::Reverse(self.clauses[cref].len()));
+
+        let mut marks = vec![false; self.watch_lists.len()];
+        while let Some(cref) = queue.pop() {
+            if self.clauses.is_deleted(cref) {
+                continue;
+            }
+            let Some(pivot) = self.clauses[cref]
+                .iter()
+                .map(|l| l.var())
+                .min_by_key(|v| occ.count(v.positive()) + occ.count(v.negative()))
+            else {
+                continue; // The empty clause is handled by initial propagation
+            };
+
+            for &lit in &self.clauses[cref] {
+                marks[lit.index()] = true;
+            }
+            let mut candidates = occ.lookup(&self.clauses, pivot.positive()).to_vec();
+            candidates.extend_from_slice(occ.lookup(&self.clauses, pivot.negative()));
+            for other in candidates {
+                if other == cref || self.clauses.is_deleted(other) {
+                    continue;
+                }
+                match self.subsumption_check(cref, other, &marks) {
+                    Some(None) => {
+                        occ.remove(&mut self.clauses, other);
+                        self.stats.subsumed_clauses += 1;
+                        if let Some(proof) = self.proof.as_mut() {
+                            proof.delete(self.clauses.id(other), &self.clauses[other]);
+                        }
+                    }
+                    Some(Some(removed)) => {
+                        let old = self.clauses[other].to_vec();
+                        let strengthened: Vec<Lit> =
+                            old.iter().copied().filter(|&l| l != removed).collect();
+                        // `cref` forces `-removed`, which falsifies `other`
+                        let hints = [self.clauses.id(cref), self.clauses.id(other)];
+                        self.clauses.shrink(other, &strengthened);
+                        occ.remove_literal(other, removed);
+                        self.stats.strengthened_clauses += 1;
+                        if let Some(proof) = self.proof.as_mut() {
+                            proof.add(self.clauses.id(other), &strengthened, &hints);
+                            proof.delete(hints[1], &old);
+                        }
+                        queue.push(other);
+                    }
+                    None => {}
+                }
+            }
+            for &lit in &self.clauses[cref] {
+                marks[lit.index()] = false;
+            }
+        }
+
+        self.collect_garbage();
+        self.rewatch_all();
+    }
+
+    /// Compares clause `cref` (whose literals are set in `marks`) with clause
+    /// `other`: `Some(None)` if `cref` subsumes `other`, `Some(Some(l))` if it
+    /// does so once the literal `l` of `other` is flipped, so `l` can be
+    /// removed from `other`.
+    fn subsumption_check(
+        &self,
+        cref: ClauseRef,
+        other: ClauseRef,
+        marks: &[bool],
+    ) -> Option<Option<Lit>> {
+        let (c, d) = (&self.clauses[cref], &self.clauses[other]);
+        if c.len() > d.len() {
+            return None;
+        }
+        let mut matched = 0;
+        let mut flipped = None;
+        for &lit in d {
+            if marks[lit.index()] {
+                matched += 1;
+            } else if marks[(-lit).index()] {
+                if flipped.is_some() {
+                    return None;
+                }
+                flipped = Some(lit);
+                matched += 1;
+            }
+        }
+        (matched == c.len()).then_some(flipped)
+    }
+}
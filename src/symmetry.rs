@@ -0,0 +1,435 @@
+use std::collections::VecDeque;
+
+use crate::{Lit, Solver, Var};
+
+/// Node and edge visits of partition refinement one symmetry search may
+/// spend. Generators not found by then are not looked for.
+const SYMMETRY_EFFORT: u64 = 10_000_000;
+
+/// Variables of a symmetry, in index order, that its lex-leader clauses
+/// compare with their images. Longer chains break little more.
+const MAX_LEX_LEADER_LEN: usize = 50;
+
+/// The clause-literal graph of the input clauses: a node per literal,
+/// numbered by `Lit::index`, joined to its negation, then a node per
+/// clause, joined to its literals. Its automorphisms that keep literal
+/// nodes apart from clause nodes map literals to literals, negations to
+/// negations and clauses to clauses: symmetries of the formula.
+struct Graph {
+    /// Neighbors of every node, sorted.
+    adjacency: Vec<Vec<u32>>,
+    /// Number of literal nodes.
+    literals: usize,
+}
+
+/// An ordered partition of the nodes of a `Graph` into cells, each a range
+/// of `order`. The start of a node's cell serves as its color, so that the
+/// partitions of isomorphic graphs give corresponding nodes the same one.
+#[derive(Clone)]
+struct Partition {
+    order: Vec<u32>,
+    /// Index of every node in `order`.
+    position: Vec<u32>,
+    /// Start of the cell of every node.
+    cell: Vec<u32>,
+    /// End of every cell, indexed by its start.
+    end: Vec<u32>,
+}
+
+impl Partition {
+    /// The partition into nodes of equal `colors`, in increasing order of
+    /// color, and the starts of its cells.
+    fn new(colors: &[u32]) -> (Self, Vec<u32>) {
+        let mut order: Vec<u32> = (0..colors.len() as u32).collect();
+        order.sort_by_key(|&node| colors[node as usize]);
+        let mut position = vec![0; colors.len()];
+        for (index, &node) in order.iter().enumerate() {
+            position[node as usize] = index as u32;
+        }
+        let mut partition = Self {
+            order,
+            position,
+            cell: vec![0; colors.len()],
+            end: vec![0; colors.len()],
+        };
+        let mut starts = Vec::new();
+        for position in 0..colors.len() {
+            let node = partition.order[position] as usize;
+            if position == 0 || colors[partition.order[position - 1] as usize] != colors[node] {
+                starts.push(position as u32);
+            }
+            let start = *starts.last().unwrap();
+            partition.cell[node] = start;
+            partition.end[start as usize] = position as u32 + 1;
+        }
+        (partition, starts)
+    }
+
+    /// The nodes of the cell starting at `start`.
+    fn members(&self, start: u32) -> &[u32] {
+        &self.order[start as usize..self.end[start as usize] as usize]
+    }
+
+    fn cell_size(&self, node: usize) -> usize {
+        let start = self.cell[node];
+        (self.end[start as usize] - start) as usize
+    }
+
+    /// Moves `node` to index `target` of `order`.
+    fn place(&mut self, node: u32, target: u32) {
+        let other = self.order[target as usize];
+        let from = self.position[node as usize];
+        self.order.swap(from as usize, target as usize);
+        self.position[other as usize] = from;
+        self.position[node as usize] = target;
+    }
+
+    /// Splits `node` off the front of its cell, returning the start of the
+    /// cell it is now alone in.
+    fn individualize(&mut self, node: u32) -> u32 {
+        let start = self.cell[node as usize];
+        let end = self.end[start as usize];
+        self.place(node, start);
+        self.end[start as usize] = start + 1;
+        self.end[start as usize + 1] = end;
+        for position in start + 1..end {
+            self.cell[self.order[position as usize] as usize] = start + 1;
+        }
+        start
+    }
+
+    /// Whether both partitions have cells at the same places.
+    fn same_shape(&self, other: &Partition) -> bool {
+        self.order.iter().zip(&other.order).all(|(&a, &b)| {
+            self.end[self.cell[a as usize] as usize] == other.end[other.cell[b as usize] as usize]
+        })
+    }
+}
+
+impl Graph {
+    /// Splits the cells of `partition` until every two nodes of a cell
+    /// have as many neighbors in each cell (an equitable partition), by
+    /// their neighbors in the cells of `splitters` and in every cell split
+    /// off since, smaller counts first. Returns false once `work` runs out.
+    fn refine(&self, partition: &mut Partition, splitters: Vec<u32>, work: &mut u64) -> bool {
+        let mut queued = vec![false; self.adjacency.len()];
+        for &start in &splitters {
+            queued[start as usize] = true;
+        }
+        let mut queue = VecDeque::from(splitters);
+        let mut counts = vec![0u32; self.adjacency.len()];
+        let mut touched = Vec::new();
+        let (mut by_cell, mut fragments, mut ends) = (Vec::new(), Vec::new(), Vec::new());
+        while let Some(splitter) = queue.pop_front() {
+            queued[splitter as usize] = false;
+            let mut visits = 0;
+            for &node in partition.members(splitter) {
+                for &neighbor in &self.adjacency[node as usize] {
+                    if counts[neighbor as usize] == 0 {
+                        touched.push(neighbor);
+                    }
+                    counts[neighbor as usize] += 1;
+                }
+                visits += 1 + self.adjacency[node as usize].len();
+            }
+
+            // The touched nodes of each cell move to its end, by increasing
+            // count, behind those with none
+            by_cell.clear();
+            by_cell.extend(
+                touched
+                    .iter()
+                    .map(|&node| (partition.cell[node as usize], counts[node as usize], node)),
+            );
+            by_cell.sort_unstable();
+            for group in by_cell.chunk_by(|a, b| a.0 == b.0) {
+                let start = group[0].0;
+                let end = partition.end[start as usize];
+                let first = end - group.len() as u32;
+                for (target, &(_, _, node)) in (first..end).zip(group) {
+                    partition.place(node, target);
+                }
+                visits += group.len();
+
+                let count_at = |position: u32| counts[partition.order[position as usize] as usize];
+                fragments.clear();
+                if first > start {
+                    fragments.push(start);
+                }
+                for position in first..end {
+                    if position == first || count_at(position) != count_at(position - 1) {
+                        fragments.push(position);
+                    }
+                }
+                if fragments.len() == 1 {
+                    continue;
+                }
+                ends.clear();
+                ends.extend(fragments[1..].iter().copied().chain([end]));
+                for (&from, &to) in fragments.iter().zip(&ends) {
+                    partition.end[from as usize] = to;
+                    for position in from..to {
+                        partition.cell[partition.order[position as usize] as usize] = from;
+                    }
+                }
+                // A cell already queued splits by its fragments anyway;
+                // otherwise the largest one can be left out
+                let skipped = if queued[start as usize] {
+                    None
+                } else {
+                    let sizes = fragments.iter().zip(&ends).map(|(&f, &l)| l - f);
+                    let largest = sizes.clone().max().unwrap();
+                    let index = sizes.clone().position(|size| size == largest).unwrap();
+                    Some(fragments[index])
+                };
+                for &fragment in &fragments {
+                    if Some(fragment) != skipped && !queued[fragment as usize] {
+                        queued[fragment as usize] = true;
+                        queue.push_back(fragment);
+                    }
+                }
+            }
+            for node in touched.drain(..) {
+                counts[node as usize] = 0;
+            }
+            match work.checked_sub(visits as u64) {
+                Some(left) => *work = left,
+                None => return false,
+            }
+        }
+        true
+    }
+
+    /// Whether `perm` maps every edge to an edge.
+    fn is_automorphism(&self, perm: &[u32]) -> bool {
+        self.adjacency.iter().enumerate().all(|(node, neighbors)| {
+            let image = &self.adjacency[perm[node] as usize];
+            image.len() == neighbors.len()
+                && neighbors
+                    .iter()
+                    .all(|&n| image.binary_search(&perm[n as usize]).is_ok())
+        })
+    }
+}
+
+/// Search for generators of the automorphism group by individualization
+/// and refinement, as in nauty. The first path individualizes, level by
+/// level, the lowest literal node whose cell has others, until every
+/// literal has a cell of its own. Then, from the deepest level up, each
+/// other node of the individualized node's cell that no generator found so
+/// far maps it to is tried in its place, and the paths below followed in
+/// every way that keeps the cells of the first path; a path ending in the
+/// same cells as the first one gives a permutation, which is kept if it is
+/// an automorphism.
+struct Search<'a> {
+    graph: &'a Graph,
+    /// Partition at each level of the first path.
+    partitions: Vec<Partition>,
+    /// Individualized node of each level but the last.
+    bases: Vec<u32>,
+    work: u64,
+}
+
+impl<'a> Search<'a> {
+    /// Runs the search from the coloring `colors`, returning the generators
+    /// found as node permutations.
+    fn run(graph: &'a Graph, colors: &[u32]) -> Vec<Vec<u32>> {
+        let mut search = Self {
+            graph,
+            partitions: Vec::new(),
+            bases: Vec::new(),
+            work: SYMMETRY_EFFORT,
+        };
+        let (mut partition, splitters) = Partition::new(colors);
+        if !graph.refine(&mut partition, splitters, &mut search.work) {
+            return Vec::new();
+        }
+        loop {
+            // Once every literal has a cell of its own, only duplicate
+            // clauses are left to swap
+            let base = (0..graph.literals).find(|&node| partition.cell_size(node) > 1);
+            search.partitions.push(partition.clone());
+            let Some(base) = base else {
+                break;
+            };
+            search.bases.push(base as u32);
+            let cell = partition.individualize(base as u32);
+            if !graph.refine(&mut partition, vec![cell], &mut search.work) {
+                return Vec::new();
+            }
+        }
+
+        let mut generators = Vec::new();
+        // Orbits of the generators found so far, as a union-find forest
+        let mut orbits: Vec<u32> = (0..colors.len() as u32).collect();
+        for level in (0..search.bases.len()).rev() {
+            let partition = search.partitions[level].clone();
+            let base = search.bases[level];
+            for &node in partition.members(partition.cell[base as usize]) {
+                if find(&mut orbits, node) == find(&mut orbits, base) {
+                    continue;
+                }
+                let mut image = partition.clone();
+                let cell = image.individualize(node);
+                let Some(perm) = search.descend(level + 1, image, cell) else {
+                    if search.work == 0 {
+                        return generators;
+                    }
+                    continue;
+                };
+                for (from, &to) in perm.iter().enumerate() {
+                    let (a, b) = (find(&mut orbits, from as u32), find(&mut orbits, to));
+                    orbits[a as usize] = b;
+                }
+                generators.push(perm);
+            }
+        }
+        generators
+    }
+
+    /// Refines `image`, a partition that has just individualized the
+    /// counterpart of the base of level `level - 1` into the cell starting
+    /// at `cell`, and follows it down to an automorphism if any. Sets
+    /// `work` to 0 once it runs out.
+    fn descend(&mut self, level: usize, mut image: Partition, cell: u32) -> Option<Vec<u32>> {
+        if !self.graph.refine(&mut image, vec![cell], &mut self.work) {
+            self.work = 0;
+            return None;
+        }
+        let partition = &self.partitions[level];
+        if !image.same_shape(partition) {
+            return None;
+        }
+        let Some(&base) = self.bases.get(level) else {
+            // Map every node of the first path to the one at its place;
+            // nodes sharing a cell are clauses that occur twice
+            let mut perm = vec![0; image.order.len()];
+            for (&from, &to) in partition.order.iter().zip(&image.order) {
+                perm[from as usize] = to;
+            }
+            return self.graph.is_automorphism(&perm).then_some(perm);
+        };
+        let candidates = image.members(partition.cell[base as usize]).to_vec();
+        for node in candidates {
+            let mut next = image.clone();
+            let cell = next.individualize(node);
+            if let Some(perm) = self.descend(level + 1, next, cell) {
+                return Some(perm);
+            }
+            if self.work == 0 {
+                return None;
+            }
+        }
+        None
+    }
+}
+
+fn find(parents: &mut [u32], mut node: u32) -> u32 {
+    while parents[node as usize] != node {
+        let parent = parents[node as usize];
+        parents[node as usize] = parents[parent as usize];
+        node = parent;
+    }
+    node
+}
+
+impl Solver {
+    /// Symmetry breaking: finds permutations of the literals that map the
+    /// input clauses onto themselves, as automorphisms of their
+    /// clause-literal graph, and adds the lex-leader clauses of each of
+    /// them, which only keep the assignments that are no greater, read in
+    /// variable order, than their image under it. The smallest assignment
+    /// of every class of symmetric ones is kept, so satisfiability is too.
+    /// Variables that are frozen, in `assumptions`, or in XOR clauses or
+    /// native pseudo-Boolean constraints are left in place by every
+    /// symmetry. Runs on an empty trail before the first search.
+    pub(crate) fn break_symmetries(&mut self, assumptions: &[Lit]) {
+        debug_assert_eq!(self.trail.len(), 0);
+
+        let mut fixed = self.frozen.clone();
+        fixed[0] = true;
+        for lit in assumptions {
+            fixed[lit.var().index()] = true;
+        }
+        for var in self.xors.iter().flat_map(|xor| xor.vars()) {
+            fixed[var.index()] = true;
+        }
+        for (_, lit) in self.pbs.iter().flat_map(|pb| pb.constraint.terms()) {
+            fixed[lit.var().index()] = true;
+        }
+
+        let literal_nodes = self.watch_lists.len();
+        let mut adjacency: Vec<Vec<u32>> = (0..literal_nodes as u32).map(|n| vec![n ^ 1]).collect();
+        let mut occurs = vec![false; self.num_vars() + 1];
+        for cref in self.clauses.iter() {
+            if self.clauses.is_learnt(cref) || self.clauses.is_lazy_reason(cref) {
+                continue;
+            }
+            let node = adjacency.len() as u32;
+            let literals = &self.clauses[cref];
+            adjacency.push(literals.iter().map(|lit| lit.index() as u32).collect());
+            for lit in literals {
+                adjacency[lit.index()].push(node);
+                occurs[lit.var().index()] = true;
+            }
+        }
+        for neighbors in &mut adjacency {
+            neighbors.sort_unstable();
+        }
+        let graph = Graph {
+            adjacency,
+            literals: literal_nodes,
+        };
+
+        // Literals of the variables left in place get a color of their own
+        let colors: Vec<u32> = (0..graph.adjacency.len())
+            .map(|node| match node >> 1 {
+                _ if node >= literal_nodes => 1,
+                var if fixed[var] || !occurs[var] => 2 + node as u32,
+                _ => 0,
+            })
+            .collect();
+
+        let variable_count = self.num_vars();
+        for perm in Search::run(&graph, &colors) {
+            let images: Vec<(Lit, Lit)> = (1..=variable_count)
+                .map(|index| Var::new(index).positive())
+                .map(|lit| (lit, Lit::from_index(perm[lit.index()] as usize)))
+                .filter(|(lit, image)| lit != image)
+                .take(MAX_LEX_LEADER_LEN)
+                .collect();
+            if !images.is_empty() {
+                self.add_lex_leader(&images);
+                self.stats.symmetry_generators += 1;
+            }
+        }
+    }
+
+    /// Adds the clauses `x1 x2 … ≤ σ(x1) σ(x2) …` for the literal pairs
+    /// `(xi, σ(xi))` of `images`: each `xi → σ(xi)` holds as long as all
+    /// the pairs before it are equal, which a fresh variable per pair
+    /// tracks along the chain.
+    fn add_lex_leader(&mut self, images: &[(Lit, Lit)]) {
+        // Literal that is true while the pairs so far are equal
+        let mut equal: Option<Lit> = None;
+        for (index, &(lit, image)) in images.iter().enumerate() {
+            let guard = |mut clause: Vec<Lit>| {
+                clause.extend(equal.map(|e| -e));
+                clause
+            };
+            if image == -lit {
+                // Then `lit` is false, and the pair cannot be equal
+                self.push_clause(guard(vec![-lit]));
+                return;
+            }
+            self.push_clause(guard(vec![-lit, image]));
+            if index + 1 == images.len() {
+                return;
+            }
+            let next = self.new_var().positive();
+            self.push_clause(guard(vec![-lit, next]));
+            self.push_clause(guard(vec![image, next]));
+            equal = Some(next);
+        }
+    }
+}
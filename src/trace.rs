@@ -0,0 +1,121 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::Lit;
+
+/// Streams the events of the CDCL search as JSON lines, one object per
+/// line with an `"event"` field, literals written as DIMACS numbers:
+///
+/// ```text
+/// {"event":"decision","lit":-3,"level":1}
+/// {"event":"propagation","lit":5,"level":1,"reason":[5,3,-2]}
+/// {"event":"conflict","level":1,"clause":[-5,3,1]}
+/// {"event":"learnt","clause":[1,3],"lbd":1}
+/// {"event":"backjump","from":1,"to":0}
+/// {"event":"restart"}
+/// ```
+///
+/// Literals fixed at level 0 without a reason clause are propagations with
+/// an empty reason. Write failures do not interrupt solving; the first one
+/// is kept and reported by `finish`.
+pub(crate) struct TraceWriter {
+    out: Option<BufWriter<File>>,
+    error: Option<io::Error>,
+    /// Number of trail literals already written
+    pub traced: usize,
+}
+
+impl TraceWriter {
+    pub fn create(path: &Path) -> Self {
+        match File::create(path) {
+            Ok(file) => Self {
+                out: Some(BufWriter::new(file)),
+                error: None,
+                traced: 0,
+            },
+            Err(err) => Self {
+                out: None,
+                error: Some(err),
+                traced: 0,
+            },
+        }
+    }
+
+    pub fn decision(&mut self, lit: Lit, level: usize) {
+        self.write(|out| writeln!(out, r#"{{"event":"decision","lit":{lit},"level":{level}}}"#));
+    }
+
+    pub fn propagation(&mut self, lit: Lit, level: usize, reason: &[Lit]) {
+        self.write(|out| {
+            write!(
+                out,
+                r#"{{"event":"propagation","lit":{lit},"level":{level},"reason":"#
+            )?;
+            write_lits(out, reason)?;
+            out.write_all(b"}\n")
+        });
+    }
+
+    pub fn conflict(&mut self, level: usize, clause: &[Lit]) {
+        self.write(|out| {
+            write!(out, r#"{{"event":"conflict","level":{level},"clause":"#)?;
+            write_lits(out, clause)?;
+            out.write_all(b"}\n")
+        });
+    }
+
+    pub fn learnt(&mut self, clause: &[Lit], lbd: usize) {
+        self.write(|out| {
+            out.write_all(br#"{"event":"learnt","clause":"#)?;
+            write_lits(out, clause)?;
+            writeln!(out, r#","lbd":{lbd}}}"#)
+        });
+    }
+
+    pub fn backjump(&mut self, from: usize, to: usize) {
+        self.write(|out| writeln!(out, r#"{{"event":"backjump","from":{from},"to":{to}}}"#));
+    }
+
+    pub fn restart(&mut self) {
+        self.write(|out| out.write_all(b"{\"event\":\"restart\"}\n"));
+    }
+
+    fn write(&mut self, event: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>) {
+        let Some(out) = self.out.as_mut() else {
+            return;
+        };
+        if let Err(err) = event(out) {
+            self.fail(err);
+        }
+    }
+
+    /// Flushes the trace and returns the first error met while writing it.
+    pub fn finish(&mut self) -> io::Result<()> {
+        if let Some(out) = self.out.as_mut()
+            && let Err(err) = out.flush()
+        {
+            self.fail(err);
+        }
+        match self.error.take() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    fn fail(&mut self, err: io::Error) {
+        self.out = None;
+        self.error.get_or_insert(err);
+    }
+}
+
+fn write_lits(out: &mut impl Write, lits: &[Lit]) -> io::Result<()> {
+    out.write_all(b"[")?;
+    for (index, lit) in lits.iter().enumerate() {
+        if index > 0 {
+            out.write_all(b",")?;
+        }
+        write!(out, "{lit}")?;
+    }
+    out.write_all(b"]")
+}
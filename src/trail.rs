@@ -0,0 +1,59 @@
+use crate::Lit;
+
+/// Assignment stack of the solver. Literals are kept in the order they were
+/// assigned, and `limits` records where each decision level begins, so
+/// backtracking is a truncation rather than a restore of saved state.
+#[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Trail {
+    literals: Vec<Lit>,
+    limits: Vec<usize>,
+}
+
+impl Trail {
+    #[inline]
+    pub fn decision_level(&self) -> usize {
+        self.limits.len()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.literals.len()
+    }
+
+    #[inline]
+    pub fn push(&mut self, lit: Lit) {
+        self.literals.push(lit);
+    }
+
+    /// Trail position of the first literal assigned at `level` (which must be at least 1).
+    pub fn level_start(&self, level: usize) -> usize {
+        self.limits[level - 1]
+    }
+
+    /// Opens a new decision level starting at the current end of the trail.
+    pub fn new_decision_level(&mut self) {
+        self.limits.push(self.literals.len());
+    }
+
+    /// Removes every literal assigned above `level` and hands them back,
+    /// most recent last. Does nothing if `level` is not below the current one.
+    pub fn backtrack_to(&mut self, level: usize) -> std::vec::Drain<'_, Lit> {
+        let pos = self
+            .limits
+            .get(level)
+            .copied()
+            .unwrap_or(self.literals.len());
+        self.limits.truncate(level);
+        self.literals.drain(pos..)
+    }
+}
+
+impl std::ops::Index<usize> for Trail {
+    type Output = Lit;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Lit {
+        &self.literals[index]
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::HashSet;
+
+use crate::{Lit, SolveResult, Solver};
+
+impl Solver {
+    /// Solves the input clauses with a fresh selector literal added to each,
+    /// assuming every selector true; on `Unsat`, the failed selectors tell
+    /// which input clauses the refutation used, available from `unsat_core`.
+    /// The core is not minimal, but far cheaper than extracting a MUS.
+    ///
+    /// Clauses are numbered from 0 in the order they were added, through
+    /// `add_clause` or in the DIMACS input. Preprocessing rewrites the input
+    /// clauses, so the numbering only holds before the first `solve`. The
+    /// search runs on a copy of the formula and leaves this solver as it
+    /// was; on `Sat` the model is available from `model`. XOR clauses and
+    /// native pseudo-Boolean constraints are not numbered and always hold.
+    pub fn solve_with_selectors(&mut self) -> SolveResult {
+        self.unsat_core.clear();
+        self.unsat_core_clauses.clear();
+        self.model = None;
+        let clauses = self.input_clauses();
+        let mut copy = self.sibling();
+        let (selectors, positions): (Vec<Lit>, Vec<usize>) = clauses
+            .iter()
+            .enumerate()
+            .filter_map(|(position, literals)| {
+                if self.xor_derived.binary_search(&position).is_ok() {
+                    copy.push_clause(literals.clone());
+                    return None;
+                }
+                let selector = copy.new_var().positive();
+                let mut literals = literals.clone();
+                literals.push(-selector);
+                copy.push_clause(literals);
+                Some((selector, position))
+            })
+            .unzip();
+
+        let result = copy.solve_with_assumptions(&selectors);
+        match result {
+            SolveResult::Sat => {
+                let mut model = copy.model.take().expect("Sat answers leave a model");
+                model.truncate(self.num_vars());
+                self.model = Some(model);
+            }
+            SolveResult::Unsat => {
+                // Selectors were allocated right after the variables of the
+                // formula, one per stored clause; tautologies were not stored
+                let numbers: Vec<usize> = (0..)
+                    .filter(|number| self.dropped_inputs.binary_search(number).is_err())
+                    .take(selectors.len())
+                    .collect();
+                let first = self.num_vars() + 1;
+                let mut core: Vec<usize> = copy
+                    .failed_assumptions()
+                    .iter()
+                    .map(|selector| selector.var().index() - first)
+                    .collect();
+                core.sort_unstable();
+                self.unsat_core = core.iter().map(|&index| numbers[index]).collect();
+                self.unsat_core_clauses = core
+                    .iter()
+                    .map(|&index| clauses[positions[index]].clone())
+                    .collect();
+            }
+            SolveResult::Unknown => {}
+        }
+        result
+    }
+
+    /// After `solve_with_selectors` returned `Unsat`, the numbers of the
+    /// input clauses that are unsatisfiable together, in increasing order.
+    pub fn unsat_core(&self) -> &[usize] {
+        &self.unsat_core
+    }
+
+    /// The clauses of `unsat_core`, in the same order, as the solver
+    /// stores them: sorted, with repeated literals merged.
+    pub fn unsat_core_clauses(&self) -> &[Vec<Lit>] {
+        &self.unsat_core_clauses
+    }
+
+    /// After an `Unsat` answer with `config.resolution_proof` set, the
+    /// input clauses the empty clause was derived from, found by following
+    /// the hints of every clause back, by increasing ID (see
+    /// `ClauseInfo::id`) and with their literals as the solver stores them. It takes no search of its
+    /// own, unlike `solve_with_selectors`, and no more than the hints
+    /// need, but is not minimal either. `None` when `resolution_proof` is.
+    pub fn proof_core(&self) -> Option<Vec<(u64, &[Lit])>> {
+        let derivations = self.proof.as_ref()?.derivations()?;
+        let root = derivations
+            .iter()
+            .filter(|(_, derivation)| derivation.literals.is_empty())
+            .map(|(&id, _)| id)
+            .max()?;
+        let mut needed = HashSet::from([root]);
+        let mut stack = vec![root];
+        let mut core = Vec::new();
+        while let Some(id) = stack.pop() {
+            let derivation = derivations.get(&id)?;
+            let Some(hints) = &derivation.hints else {
+                core.push((id, &derivation.literals[..]));
+                continue;
+            };
+            if hints.is_empty() {
+                return None;
+            }
+            for &hint in hints {
+                if needed.insert(hint) {
+                    stack.push(hint);
+                }
+            }
+        }
+        core.sort_unstable_by_key(|&(id, _)| id);
+        Some(core)
+    }
+}
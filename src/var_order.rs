@@ -0,0 +1,434 @@
+use crate::Var;
+use crate::config::Branching;
+
+/// Decision order of the CDCL search, following `SolverConfig::branching`.
+/// Variables are inserted again when they are unassigned, and `pop_max`
+/// may return variables that are assigned or eliminated, which the caller
+/// skips.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum VarOrder {
+    Vsids(Vsids),
+    Vmtf(Vmtf),
+    Lrb(Lrb),
+}
+
+impl VarOrder {
+    /// Creates an order over variables `1..=variable_count`.
+    pub fn new(branching: Branching, variable_count: usize) -> Self {
+        match branching {
+            Branching::Vsids => Self::Vsids(Vsids::new(variable_count)),
+            Branching::Vmtf => Self::Vmtf(Vmtf::new(variable_count)),
+            Branching::Lrb => Self::Lrb(Lrb::new(variable_count)),
+        }
+    }
+
+    pub fn branching(&self) -> Branching {
+        match self {
+            Self::Vsids(_) => Branching::Vsids,
+            Self::Vmtf(_) => Branching::Vmtf,
+            Self::Lrb(_) => Branching::Lrb,
+        }
+    }
+
+    /// Extends the order to variables `1..=variable_count`.
+    pub fn grow(&mut self, variable_count: usize) {
+        match self {
+            Self::Vsids(vsids) => vsids.activity.grow(variable_count),
+            Self::Vmtf(vmtf) => vmtf.grow(variable_count),
+            Self::Lrb(lrb) => lrb.grow(variable_count),
+        }
+    }
+
+    /// Sets the initial score of every variable, `scores` being indexed by
+    /// variable; the variables of higher score are decided first.
+    pub fn initialize(&mut self, scores: &[f64]) {
+        match self {
+            Self::Vsids(Vsids { activity: heap, .. }) | Self::Lrb(Lrb { rates: heap, .. }) => {
+                for var in (1..scores.len()).map(Var::new) {
+                    heap.set_score(var, scores[var.index()]);
+                }
+            }
+            Self::Vmtf(vmtf) => vmtf.reorder(scores),
+        }
+    }
+
+    /// Called when `var` is assigned.
+    pub fn assigned(&mut self, var: Var) {
+        if let Self::Lrb(lrb) = self {
+            lrb.assigned(var);
+        }
+    }
+
+    /// Called when `var` becomes unassigned, or can be decided again.
+    pub fn insert(&mut self, var: Var) {
+        match self {
+            Self::Vsids(vsids) => vsids.activity.insert(var),
+            Self::Vmtf(vmtf) => vmtf.insert(var),
+            Self::Lrb(lrb) => lrb.insert(var),
+        }
+    }
+
+    /// Removes and returns the variable to decide next.
+    pub fn pop_max(&mut self) -> Option<Var> {
+        match self {
+            Self::Vsids(vsids) => vsids.activity.pop_max(),
+            Self::Vmtf(vmtf) => vmtf.pop_max(),
+            Self::Lrb(lrb) => lrb.rates.pop_max(),
+        }
+    }
+
+    /// Notes that `var` took part in a conflict.
+    pub fn bump(&mut self, var: Var) {
+        match self {
+            Self::Vsids(vsids) => vsids.bump(var),
+            Self::Vmtf(vmtf) => vmtf.bump(var),
+            Self::Lrb(lrb) => lrb.bump(var),
+        }
+    }
+
+    /// Called after every conflict, once its variables are bumped.
+    pub fn decay(&mut self, factor: f64) {
+        match self {
+            Self::Vsids(vsids) => vsids.decay(factor),
+            Self::Vmtf(vmtf) => vmtf.move_bumped(),
+            Self::Lrb(lrb) => lrb.on_conflict(),
+        }
+    }
+}
+
+/// Binary max-heap of variables keyed by a score per variable.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct ScoreHeap {
+    scores: Vec<f64>,
+    heap: Vec<Var>,
+    positions: Vec<Option<usize>>,
+}
+
+impl ScoreHeap {
+    /// Creates a heap holding variables `1..=variable_count`, all scored zero.
+    pub fn new(variable_count: usize) -> Self {
+        let mut heap = Self {
+            scores: vec![0.0],
+            heap: Vec::with_capacity(variable_count),
+            positions: vec![None],
+        };
+        heap.grow(variable_count);
+        heap
+    }
+
+    /// Extends the heap to variables `1..=variable_count`.
+    pub fn grow(&mut self, variable_count: usize) {
+        let old = self.scores.len() - 1;
+        self.scores.resize(variable_count + 1, 0.0);
+        self.positions.resize(variable_count + 1, None);
+        for var in old + 1..=variable_count {
+            self.insert(Var::new(var));
+        }
+    }
+
+    pub fn insert(&mut self, var: Var) {
+        if self.positions[var.index()].is_some() {
+            return;
+        }
+        self.positions[var.index()] = Some(self.heap.len());
+        self.heap.push(var);
+        self.sift_up(self.heap.len() - 1);
+    }
+
+    /// Removes and returns the variable with the highest score.
+    pub fn pop_max(&mut self) -> Option<Var> {
+        let top = *self.heap.first()?;
+        let last = self.heap.pop().unwrap();
+        self.positions[top.index()] = None;
+        if !self.heap.is_empty() {
+            self.heap[0] = last;
+            self.positions[last.index()] = Some(0);
+            self.sift_down(0);
+        }
+        Some(top)
+    }
+
+    pub fn score(&self, var: Var) -> f64 {
+        self.scores[var.index()]
+    }
+
+    /// Changes the score of `var`, moving it in the heap if it is there.
+    pub fn set_score(&mut self, var: Var, score: f64) {
+        let old = std::mem::replace(&mut self.scores[var.index()], score);
+        if let Some(pos) = self.positions[var.index()] {
+            if score > old {
+                self.sift_up(pos);
+            } else {
+                self.sift_down(pos);
+            }
+        }
+    }
+
+    /// Divides every score by `divisor`, which keeps their order.
+    pub fn scale_down(&mut self, divisor: f64) {
+        for score in self.scores.iter_mut() {
+            *score /= divisor;
+        }
+    }
+
+    fn sift_up(&mut self, mut pos: usize) {
+        let var = self.heap[pos];
+        while pos > 0 {
+            let parent = (pos - 1) / 2;
+            if self.scores[self.heap[parent].index()] >= self.scores[var.index()] {
+                break;
+            }
+            self.heap[pos] = self.heap[parent];
+            self.positions[self.heap[pos].index()] = Some(pos);
+            pos = parent;
+        }
+        self.heap[pos] = var;
+        self.positions[var.index()] = Some(pos);
+    }
+
+    fn sift_down(&mut self, mut pos: usize) {
+        let var = self.heap[pos];
+        loop {
+            let left = 2 * pos + 1;
+            if left >= self.heap.len() {
+                break;
+            }
+            let right = left + 1;
+            let child = if right < self.heap.len()
+                && self.scores[self.heap[right].index()] > self.scores[self.heap[left].index()]
+            {
+                right
+            } else {
+                left
+            };
+            if self.scores[self.heap[child].index()] <= self.scores[var.index()] {
+                break;
+            }
+            self.heap[pos] = self.heap[child];
+            self.positions[self.heap[pos].index()] = Some(pos);
+            pos = child;
+        }
+        self.heap[pos] = var;
+        self.positions[var.index()] = Some(pos);
+    }
+}
+
+/// VSIDS decision order: variables in a heap keyed by activity. Variables
+/// involved in recent conflicts are bumped, and all activities decay
+/// geometrically by growing the bump increment after every conflict.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Vsids {
+    activity: ScoreHeap,
+    increment: f64,
+}
+
+impl Vsids {
+    const RESCALE_LIMIT: f64 = 1e100;
+
+    /// Creates an order over variables `1..=variable_count`, all with zero activity.
+    pub fn new(variable_count: usize) -> Self {
+        Self {
+            activity: ScoreHeap::new(variable_count),
+            increment: 1.0,
+        }
+    }
+
+    pub fn bump(&mut self, var: Var) {
+        let activity = self.activity.score(var) + self.increment;
+        self.activity.set_score(var, activity);
+        if activity > Self::RESCALE_LIMIT {
+            self.activity.scale_down(Self::RESCALE_LIMIT);
+            self.increment /= Self::RESCALE_LIMIT;
+        }
+    }
+
+    /// Decays every activity by `factor` (in `(0, 1]`) in O(1) by inflating
+    /// the increment used for future bumps instead.
+    pub fn decay(&mut self, factor: f64) {
+        self.increment /= factor;
+    }
+}
+
+/// LRB decision order (learning-rate branching, Liang et al. 2016): each
+/// variable is scored by the exponential moving average of its learning
+/// rate, the share of the conflicts met while it was assigned that it took
+/// part in. Branching on variables with a high rate tends to produce
+/// conflicts quickly. The average is updated when the variable is
+/// unassigned, with a step size that shrinks from 0.4 to 0.06 over the
+/// first conflicts.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Lrb {
+    rates: ScoreHeap,
+    /// Conflicts so far
+    conflicts: u64,
+    /// Value of `conflicts` when each variable was last assigned
+    assigned_at: Vec<u64>,
+    /// Conflicts each variable took part in since it was assigned
+    participated: Vec<u64>,
+    step: f64,
+}
+
+impl Lrb {
+    const INITIAL_STEP: f64 = 0.4;
+    const MIN_STEP: f64 = 0.06;
+    const STEP_DECREMENT: f64 = 1e-6;
+
+    pub fn new(variable_count: usize) -> Self {
+        Self {
+            rates: ScoreHeap::new(variable_count),
+            conflicts: 0,
+            assigned_at: vec![0; variable_count + 1],
+            participated: vec![0; variable_count + 1],
+            step: Self::INITIAL_STEP,
+        }
+    }
+
+    pub fn grow(&mut self, variable_count: usize) {
+        self.rates.grow(variable_count);
+        self.assigned_at.resize(variable_count + 1, 0);
+        self.participated.resize(variable_count + 1, 0);
+    }
+
+    pub fn assigned(&mut self, var: Var) {
+        self.assigned_at[var.index()] = self.conflicts;
+        self.participated[var.index()] = 0;
+    }
+
+    /// Called when `var` becomes unassigned: moves its average towards the
+    /// learning rate of the interval it was assigned for.
+    pub fn insert(&mut self, var: Var) {
+        let interval = self.conflicts - self.assigned_at[var.index()];
+        if interval > 0 {
+            let rate = self.participated[var.index()] as f64 / interval as f64;
+            let average = (1.0 - self.step) * self.rates.score(var) + self.step * rate;
+            self.rates.set_score(var, average);
+        }
+        self.rates.insert(var);
+    }
+
+    pub fn bump(&mut self, var: Var) {
+        self.participated[var.index()] += 1;
+    }
+
+    pub fn on_conflict(&mut self) {
+        self.conflicts += 1;
+        self.step = (self.step - Self::STEP_DECREMENT).max(Self::MIN_STEP);
+    }
+}
+
+/// VMTF decision order (variable move-to-front, as in CaDiCaL and Kissat):
+/// a doubly-linked queue of the variables, to whose front the variables of
+/// each conflict are moved, keeping their relative order. Decisions take
+/// the unassigned variable closest to the front. Every move stamps the
+/// variable with a new time, so that a search pointer can skip the front
+/// part of the queue, which is known to be assigned, without a heap.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Vmtf {
+    /// Neighbour of each variable towards the back and the front of the
+    /// queue, as variable indices, 0 for none
+    prev: Vec<usize>,
+    next: Vec<usize>,
+    /// Time each variable was last moved to the front
+    stamps: Vec<u64>,
+    stamp: u64,
+    /// The variable at the front of the queue
+    front: usize,
+    /// Variables bumped since the last conflict
+    bumped: Vec<usize>,
+    /// Every variable closer to the front than this one is assigned; 0
+    /// when that holds for the whole queue
+    search: usize,
+}
+
+impl Vmtf {
+    pub fn new(variable_count: usize) -> Self {
+        let mut vmtf = Self {
+            prev: vec![0],
+            next: vec![0],
+            stamps: vec![0],
+            stamp: 0,
+            front: 0,
+            bumped: Vec::new(),
+            search: 0,
+        };
+        vmtf.grow(variable_count);
+        vmtf
+    }
+
+    /// Extends the queue to variables `1..=variable_count`, putting the
+    /// new ones in front.
+    pub fn grow(&mut self, variable_count: usize) {
+        let old = self.stamps.len() - 1;
+        self.prev.resize(variable_count + 1, 0);
+        self.next.resize(variable_count + 1, 0);
+        self.stamps.resize(variable_count + 1, 0);
+        for var in old + 1..=variable_count {
+            self.push_front(var);
+            self.search = var;
+        }
+    }
+
+    /// Rebuilds the queue with the variables in order of `scores`, the
+    /// highest in front.
+    pub fn reorder(&mut self, scores: &[f64]) {
+        let mut vars: Vec<usize> = (1..self.stamps.len()).collect();
+        vars.sort_by(|&a, &b| scores[a].total_cmp(&scores[b]));
+        self.front = 0;
+        self.bumped.clear();
+        for var in vars {
+            self.push_front(var);
+        }
+        self.search = self.front;
+    }
+
+    /// Called when `var` becomes unassigned.
+    pub fn insert(&mut self, var: Var) {
+        if self.stamps[var.index()] > self.stamps[self.search] {
+            self.search = var.index();
+        }
+    }
+
+    pub fn pop_max(&mut self) -> Option<Var> {
+        let var = self.search;
+        if var == 0 {
+            return None;
+        }
+        self.search = self.prev[var];
+        Some(Var::new(var))
+    }
+
+    /// Marks `var`, which is assigned, to be moved to the front.
+    pub fn bump(&mut self, var: Var) {
+        self.bumped.push(var.index());
+    }
+
+    /// Moves the variables bumped since the last call to the front, the
+    /// one closest to it before ending up in front.
+    pub fn move_bumped(&mut self) {
+        let mut bumped = std::mem::take(&mut self.bumped);
+        bumped.sort_unstable_by_key(|&var| self.stamps[var]);
+        for &var in &bumped {
+            if var == self.front {
+                continue;
+            }
+            if var == self.search {
+                self.search = self.prev[var];
+            }
+            let (prev, next) = (self.prev[var], self.next[var]);
+            self.next[prev] = next;
+            self.prev[next] = prev;
+            self.push_front(var);
+        }
+        bumped.clear();
+        self.bumped = bumped;
+    }
+
+    fn push_front(&mut self, var: usize) {
+        self.prev[var] = self.front;
+        self.next[var] = 0;
+        self.next[self.front] = var;
+        self.front = var;
+        self.stamp += 1;
+        self.stamps[var] = self.stamp;
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{ClauseRef, LBool, Lit, Solver};
+
+impl Solver {
+    /// Vivification of learnt clauses, run at level 0 between restarts. For
+    /// each clause the negations of its literals are assigned one by one:
+    /// a literal found false is implied by the earlier ones and dropped, and
+    /// a literal found true, or a conflict, means the clause can end there.
+    /// Stops after `config.vivify_effort` propagations. Returns false if a
+    /// conflict at level 0 shows the formula unsatisfiable.
+    pub(crate) fn vivify(&mut self) -> bool {
+        debug_assert_eq!(self.trail.decision_level(), 0);
+        let start = self.stats.propagations;
+        let phases = self.phases.clone();
+
+        let mut candidates: Vec<ClauseRef> = self
+            .clauses
+            .iter()
+            .filter(|&cref| {
+                self.clauses.is_learnt(cref)
+                    && !self.clauses.is_vivified(cref)
+                    && self.clauses[cref].len() > 2
+                    && !self.is_locked(cref)
+            })
+            .collect();
+        candidates.sort_by_key(|&cref| self.clauses.lbd(cref));
+
+        let mut consistent = true;
+        for cref in candidates {
+            if self.stats.propagations - start >= self.config.vivify_effort {
+                break;
+            }
+            self.clauses.set_vivified(cref);
+            let Some((shortened, hints)) = self.vivify_clause(cref) else {
+                continue;
+            };
+            self.stats.vivified_clauses += 1;
+            if !self.replace_clause(cref, shortened, &hints) {
+                consistent = false;
+                break;
+            }
+        }
+
+        self.phases = phases;
+        self.collect_garbage_if_wasteful();
+        consistent
+    }
+
+    /// The shortened literals of clause `cref` with the LRAT hints deriving
+    /// them (none unless an LRAT proof is written), or `None` if it cannot
+    /// be shortened.
+    fn vivify_clause(&mut self, cref: ClauseRef) -> Option<(Vec<Lit>, Vec<u64>)> {
+        let literals = self.clauses[cref].to_vec();
+        let mut kept = Vec::new();
+        // Clause falsified once the kept literals are: the clause itself
+        // unless propagation stopped early
+        let mut falsified = cref;
+        for &lit in &literals {
+            match self.assignments.value(lit) {
+                LBool::True => {
+                    kept.push(lit);
+                    falsified = self.reasons[lit.var().index()].unwrap_or(cref);
+                    break;
+                }
+                LBool::False => {}
+                LBool::Undef => {
+                    kept.push(lit);
+                    self.trail.new_decision_level();
+                    self.enqueue(-lit, None);
+                    if let Some(conflict) = self.propagate() {
+                        falsified = conflict;
+                        break;
+                    }
+                }
+            }
+        }
+        let shortened = kept.len() < literals.len();
+        let hints = if shortened && self.wants_hints() {
+            self.conflict_hints(falsified, &kept)
+        } else {
+            Vec::new()
+        };
+        self.backtrack_to(0);
+        shortened.then_some((kept, hints))
+    }
+
+    /// Swaps the literals of clause `cref` for the implied subset `literals`,
+    /// derived by `hints`, moving its watches and asserting it if it became
+    /// unit. Must be called at level 0; returns false on a conflict.
+    fn replace_clause(&mut self, cref: ClauseRef, literals: Vec<Lit>, hints: &[u64]) -> bool {
+        let old_id = self.clauses.id(cref);
+        let old = self.clauses[cref].to_vec();
+        for &lit in &old[..2] {
+            self.watch_lists[lit.index()].retain(|&other| other != cref);
+        }
+
+        self.clauses.shrink(cref, &literals);
+        if let Some(proof) = self.proof.as_mut() {
+            proof.add(self.clauses.id(cref), &literals, hints);
+            proof.delete(old_id, &old);
+        }
+        self.watch_clause(cref);
+
+        match self.clauses[cref] {
+            [] => false,
+            [unit] => match self.assignments.value(unit) {
+                LBool::True => true,
+                LBool::False => false,
+                LBool::Undef => {
+                    self.enqueue(unit, Some(cref));
+                    self.propagate().is_none()
+                }
+            },
+            _ => true,
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use crate::rng::Rng;
+use crate::{Instant, LBool, Lit, SolveResult, Solver, Var};
+
+/// Flips between two checks of the budgets, the time limit and the
+/// interrupts by `walksat_search`.
+const FLIPS_PER_ROUND: u64 = 1 << 14;
+
+/// ProbSAT flips a literal of a falsified clause with a probability
+/// proportional to `(BREAK_EPS + break)^-BREAK_EXPONENT`, where `break` is
+/// the number of clauses the flip falsifies. These are the values tuned
+/// for random 3-SAT.
+const BREAK_EPS: f64 = 0.9;
+const BREAK_EXPONENT: f64 = 2.06;
+
+/// Break counts with a weight of their own; higher ones share the last.
+const BREAK_WEIGHTS: usize = 32;
+
+/// A ProbSAT walk over the variables a solver leaves unassigned, and the
+/// input clauses its values do not satisfy yet, shortened to their
+/// unassigned literals.
+struct Walk {
+    clauses: Vec<Vec<Lit>>,
+    /// The clauses each literal occurs in
+    occurrences: Vec<Vec<usize>>,
+    /// Current value of every variable, the assigned ones included
+    values: Vec<bool>,
+    /// Number of true literals of each clause
+    true_count: Vec<u32>,
+    /// Clauses without a true literal, in no particular order
+    falsified: Vec<usize>,
+    /// Index of each clause in `falsified`, if it is there
+    position: Vec<usize>,
+    /// Input clauses already falsified by the solver's values, which no
+    /// flip can satisfy
+    stuck: usize,
+    /// The values that falsified the fewest clauses so far
+    best: Vec<bool>,
+    best_falsified: usize,
+    weights: [f64; BREAK_WEIGHTS],
+    flips: u64,
+    rng: Rng,
+}
+
+impl Walk {
+    /// A walk starting from the saved phases of `solver`.
+    fn new(solver: &Solver) -> Self {
+        let mut values = solver.phases.clone();
+        for var in (1..=solver.num_vars()).map(Var::new) {
+            match solver.assignments.value(var.positive()) {
+                LBool::True => values[var.index()] = true,
+                LBool::False => values[var.index()] = false,
+                LBool::Undef => {}
+            }
+        }
+
+        let mut clauses = Vec::new();
+        let mut occurrences = vec![Vec::new(); (solver.num_vars() + 1) * 2];
+        let mut stuck = 0;
+        let stored = solver
+            .clauses
+            .iter()
+            .filter(|&cref| !solver.clauses.is_learnt(cref))
+            .map(|cref| &solver.clauses[cref]);
+        let pairs: Vec<[Lit; 2]> = solver.amo_clauses().collect();
+        for literals in stored.chain(pairs.iter().map(|pair| &pair[..])) {
+            if literals
+                .iter()
+                .any(|&lit| solver.assignments.value(lit) == LBool::True)
+            {
+                continue;
+            }
+            let free: Vec<Lit> = literals
+                .iter()
+                .copied()
+                .filter(|&lit| solver.assignments.value(lit) == LBool::Undef)
+                .collect();
+            if free.is_empty() {
+                stuck += 1;
+                continue;
+            }
+            for &lit in &free {
+                occurrences[lit.index()].push(clauses.len());
+            }
+            clauses.push(free);
+        }
+
+        let mut walk = Self {
+            true_count: vec![0; clauses.len()],
+            position: vec![usize::MAX; clauses.len()],
+            clauses,
+            occurrences,
+            best: values.clone(),
+            values,
+            falsified: Vec::new(),
+            stuck,
+            best_falsified: usize::MAX,
+            weights: std::array::from_fn(|b| (BREAK_EPS + b as f64).powf(-BREAK_EXPONENT)),
+            flips: 0,
+            rng: Rng::new(solver.config.seed ^ solver.stats.flips),
+        };
+        walk.reset();
+        walk
+    }
+
+    /// Recounts the true literals of every clause from `values`.
+    fn reset(&mut self) {
+        self.falsified.clear();
+        for (index, clause) in self.clauses.iter().enumerate() {
+            let count = clause
+                .iter()
+                .filter(|&&lit| self.values[lit.var().index()] != lit.sign())
+                .count();
+            self.true_count[index] = count as u32;
+            self.position[index] = usize::MAX;
+            if count == 0 {
+                self.position[index] = self.falsified.len();
+                self.falsified.push(index);
+            }
+        }
+        self.note_best();
+    }
+
+    /// Starts over from random values.
+    fn randomize(&mut self) {
+        for value in self.values.iter_mut() {
+            *value = self.rng.next_bool();
+        }
+        self.reset();
+    }
+
+    fn note_best(&mut self) {
+        if self.falsified.len() < self.best_falsified {
+            self.best_falsified = self.falsified.len();
+            self.best.clone_from(&self.values);
+        }
+    }
+
+    /// Flips up to `max_flips` variables, stopping early once every clause
+    /// is satisfied.
+    fn run(&mut self, max_flips: u64) {
+        let mut probabilities = Vec::new();
+        for _ in 0..max_flips {
+            if self.falsified.is_empty() {
+                return;
+            }
+            let clause = self.falsified[self.rng.below(self.falsified.len())];
+            probabilities.clear();
+            probabilities.extend(self.clauses[clause].iter().map(|&lit| {
+                let breaks = self.break_count(lit.var());
+                self.weights[breaks.min(BREAK_WEIGHTS - 1)]
+            }));
+            let mut pick = self.rng.next_f64() * probabilities.iter().sum::<f64>();
+            let index = probabilities
+                .iter()
+                .position(|&p| {
+                    pick -= p;
+                    pick < 0.0
+                })
+                .unwrap_or(probabilities.len() - 1);
+            self.flip(self.clauses[clause][index].var());
+            self.note_best();
+        }
+    }
+
+    /// How many clauses flipping `var` would falsify: those whose only true
+    /// literal is that of `var`.
+    fn break_count(&self, var: Var) -> usize {
+        let true_lit = Lit::new(var, !self.values[var.index()]);
+        self.occurrences[true_lit.index()]
+            .iter()
+            .filter(|&&clause| self.true_count[clause] == 1)
+            .count()
+    }
+
+    fn flip(&mut self, var: Var) {
+        let was_true = Lit::new(var, !self.values[var.index()]);
+        self.values[var.index()] = !self.values[var.index()];
+        self.flips += 1;
+        let Self {
+            occurrences,
+            true_count,
+            falsified,
+            position,
+            ..
+        } = self;
+        for &clause in &occurrences[was_true.index()] {
+            true_count[clause] -= 1;
+            if true_count[clause] == 0 {
+                position[clause] = falsified.len();
+                falsified.push(clause);
+            }
+        }
+        for &clause in &occurrences[(-was_true).index()] {
+            true_count[clause] += 1;
+            if true_count[clause] == 1 {
+                let index = position[clause];
+                falsified.swap_remove(index);
+                if let Some(&moved) = falsified.get(index) {
+                    position[moved] = index;
+                }
+                position[clause] = usize::MAX;
+            }
+        }
+    }
+}
+
+impl Solver {
+    /// Runs ProbSAT local search over the input clauses for at most
+    /// `max_flips` flips, starting from the saved phases with the values
+    /// fixed at level 0 kept, and saves the best assignment it met as the
+    /// phases of the next search. Returns how many clauses that assignment
+    /// falsifies, 0 meaning it satisfies every clause. XOR and native
+    /// pseudo-Boolean constraints are not looked at.
+    pub fn local_search(&mut self, max_flips: u64) -> usize {
+        self.backtrack_to(0);
+        if !self.scored && self.config.jeroslow_wang {
+            self.jeroslow_wang();
+        }
+        let mut walk = Walk::new(self);
+        walk.run(max_flips);
+        self.stats.flips += walk.flips;
+        for var in (1..=self.num_vars()).map(Var::new) {
+            if !self.assignments.is_assigned(var) {
+                self.phases[var.index()] = walk.best[var.index()];
+            }
+        }
+        walk.best_falsified + walk.stuck
+    }
+
+    /// Local search (`Engine::WalkSat`), run like `search` once the input
+    /// is propagated at level 0. The assumptions are propagated first;
+    /// only a conflict there makes it answer `Unsat`, with the assumptions
+    /// propagated so far as the failed ones. Then the walk goes on until it
+    /// satisfies every clause, or until a budget or the time limit runs
+    /// out. Its model is assigned through the trail, so that XOR and
+    /// pseudo-Boolean constraints get checked too; if one is violated the
+    /// walk starts over from random values.
+    pub(crate) fn walksat_search(&mut self, assumptions: &[Lit]) -> SolveResult {
+        let conflicts_at_start = self.stats.conflicts;
+        let propagations_at_start = self.stats.propagations;
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+
+        for (index, &lit) in assumptions.iter().enumerate() {
+            match self.assignments.value(lit) {
+                LBool::True => {}
+                LBool::False => {
+                    self.failed_assumptions = self.analyze_final(lit);
+                    return SolveResult::Unsat;
+                }
+                LBool::Undef => {
+                    self.trail.new_decision_level();
+                    self.enqueue(lit, None);
+                    if self.propagate().is_some() {
+                        self.stats.conflicts += 1;
+                        self.failed_assumptions = assumptions[..=index].to_vec();
+                        return SolveResult::Unsat;
+                    }
+                }
+            }
+        }
+
+        let mut walk = Walk::new(self);
+        loop {
+            let flips = walk.flips;
+            walk.run(FLIPS_PER_ROUND);
+            self.stats.flips += walk.flips - flips;
+            if walk.falsified.is_empty() && walk.stuck == 0 {
+                if self.assign_all(&walk.values) {
+                    return SolveResult::Sat;
+                }
+                walk.randomize();
+            }
+            if self.should_stop(conflicts_at_start, propagations_at_start, deadline) {
+                return SolveResult::Unknown;
+            }
+        }
+    }
+
+    /// Decides every unassigned variable to its value in `values`, one
+    /// decision level each, and propagates. On a conflict the decisions are
+    /// undone and false is returned.
+    fn assign_all(&mut self, values: &[bool]) -> bool {
+        let level = self.trail.decision_level();
+        for var in (1..=self.num_vars()).map(Var::new) {
+            if self.assignments.is_assigned(var) || self.eliminated[var.index()] {
+                continue;
+            }
+            self.trail.new_decision_level();
+            self.stats.decisions += 1;
+            self.enqueue(Lit::new(var, !values[var.index()]), None);
+            if self.propagate().is_some() {
+                self.stats.conflicts += 1;
+                self.backtrack_to(level);
+                return false;
+            }
+        }
+        true
+    }
+}
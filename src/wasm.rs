@@ -0,0 +1,30 @@
+// JavaScript bindings, built with the `wasm` feature for the
+// wasm32-unknown-unknown target (`wasm-pack build --features wasm`).
+
+use js_sys::{Int32Array, Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+use crate::{SolveResult, Solver};
+
+/// Solves a DIMACS CNF formula, returning `{ status, model }`: `status` is
+/// `"SAT"`, `"UNSAT"` or `"UNKNOWN"`, and `model` an `Int32Array` holding
+/// every variable in order, signed by its value (`[1, -2, 3]` when only
+/// variable 2 is false), or `null`. A malformed formula throws an `Error`.
+#[wasm_bindgen]
+pub fn solve(dimacs: &str) -> Result<JsValue, JsError> {
+    let mut solver = Solver::from_dimacs_str(dimacs)?;
+    let status = match solver.solve() {
+        SolveResult::Sat => "SAT",
+        SolveResult::Unsat => "UNSAT",
+        SolveResult::Unknown => "UNKNOWN",
+    };
+    let model = match solver.model() {
+        Some(model) => Int32Array::from(&model.to_dimacs()[..]).into(),
+        None => JsValue::NULL,
+    };
+
+    let result = Object::new();
+    Reflect::set(&result, &"status".into(), &status.into()).unwrap();
+    Reflect::set(&result, &"model".into(), &model).unwrap();
+    Ok(result.into())
+}
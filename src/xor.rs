@@ -0,0 +1,221 @@
+use crate::{AddClauseError, Assignment, ClauseRef, Lit, Model, Solver, Var};
+
+/// A parity constraint: the XOR of its variables must equal `rhs`. Its CNF
+/// encoding takes `2^(n-1)` clauses, so it is kept and propagated on its
+/// own. The first two variables are the watched ones.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct XorClause {
+    vars: Vec<Var>,
+    rhs: bool,
+}
+
+impl XorClause {
+    /// Variables of the constraint, each occurring once.
+    pub fn vars(&self) -> &[Var] {
+        &self.vars
+    }
+
+    /// Whether an odd number of the variables must be true.
+    pub fn rhs(&self) -> bool {
+        self.rhs
+    }
+
+    /// The constraint as literals of which an odd number must be true, as
+    /// in an `x` line of a DIMACS file: the first literal is negated when
+    /// the parity is even.
+    pub fn to_literals(&self) -> Vec<Lit> {
+        let mut literals: Vec<Lit> = self.vars.iter().map(|v| v.positive()).collect();
+        if !self.rhs {
+            literals[0] = -literals[0];
+        }
+        literals
+    }
+
+    /// Whether `model` meets the parity.
+    pub(crate) fn is_satisfied(&self, model: &Model) -> bool {
+        let parity = self
+            .vars
+            .iter()
+            .fold(false, |parity, &v| parity ^ model.value(v));
+        parity == self.rhs
+    }
+}
+
+impl Solver {
+    /// Adds the constraint that an odd number of `lits` are true, given as
+    /// `Lit`s or as DIMACS integers: `[1, 2, -3]` is `x1 ⊕ x2 ⊕ ¬x3`, the
+    /// `x1 2 -3 0` line of CryptoMiniSat's DIMACS extension. Like
+    /// `add_clause`, it resets the solver to decision level 0 first.
+    ///
+    /// Constraints over one or two variables become clauses; longer ones
+    /// are propagated natively, with two watched variables each. DRAT proofs
+    /// do not cover reasoning with XOR clauses, and `solve_with_selectors`
+    /// keeps them out of the unsat core: they always hold.
+    pub fn add_xor_clause<L>(&mut self, lits: &[L]) -> Result<(), AddClauseError>
+    where
+        L: Copy + TryInto<Lit>,
+        AddClauseError: From<L::Error>,
+    {
+        let literals = lits
+            .iter()
+            .map(|&l| l.try_into())
+            .collect::<Result<Vec<Lit>, _>>()?;
+        self.push_xor(literals);
+        Ok(())
+    }
+
+    /// The XOR clauses propagated natively, over the variables left once
+    /// duplicates, values fixed at level 0 and equivalent variables are
+    /// taken out.
+    pub fn xor_clauses(&self) -> &[XorClause] {
+        &self.xors
+    }
+
+    /// Stores an XOR clause. Pairs of equal variables cancel out and
+    /// variables assigned at level 0 are folded into the parity; what is
+    /// left of fewer than three variables is stored as clauses.
+    pub(crate) fn push_xor(&mut self, literals: Vec<Lit>) {
+        let max_var = literals.iter().map(|l| l.var().index()).max();
+        self.reserve_vars(max_var.unwrap_or(0));
+        self.backtrack_to(0);
+        if literals.iter().any(|l| self.eliminated[l.var().index()]) {
+            self.restore_eliminated();
+        }
+
+        let mut rhs = true;
+        let mut vars: Vec<Var> = literals
+            .iter()
+            .map(|l| {
+                rhs ^= l.sign();
+                l.var()
+            })
+            .collect();
+        vars.sort_unstable();
+        let mut kept: Vec<Var> = Vec::with_capacity(vars.len());
+        for var in vars {
+            if kept.last() == Some(&var) {
+                kept.pop();
+            } else {
+                kept.push(var);
+            }
+        }
+        kept.retain(|&var| match self.assignments.var_value(var) {
+            Some(value) => {
+                rhs ^= value;
+                false
+            }
+            None => true,
+        });
+
+        match kept[..] {
+            [] => {
+                if rhs {
+                    self.push_clause(Vec::new());
+                }
+            }
+            [a] => {
+                self.xor_derived.push(self.clauses.len());
+                self.push_clause(vec![Lit::new(a, !rhs)]);
+            }
+            // One clause against each assignment of the wrong parity
+            [a, b] => {
+                for value in [false, true] {
+                    self.xor_derived.push(self.clauses.len());
+                    self.push_clause(vec![Lit::new(a, value), Lit::new(b, value ^ !rhs)]);
+                }
+            }
+            _ => {
+                let index = self.xors.len();
+                self.xor_watches[kept[0].index()].push(index);
+                self.xor_watches[kept[1].index()].push(index);
+                self.xors.push(XorClause { vars: kept, rhs });
+            }
+        }
+    }
+
+    /// Visits the XOR clauses watching `var`, which was just assigned. Each
+    /// moves the watch to another unassigned variable if it has one, and
+    /// otherwise implies the value of its other watched variable, or is in
+    /// conflict.
+    pub(crate) fn propagate_xors(&mut self, var: Var) -> Option<ClauseRef> {
+        let mut watching = std::mem::take(&mut self.xor_watches[var.index()]);
+        let mut conflict = None;
+        let mut i = 0;
+        while i < watching.len() {
+            let index = watching[i];
+            let xor = &mut self.xors[index];
+            if xor.vars[0] == var {
+                xor.vars.swap(0, 1);
+            }
+            let replacement =
+                (2..xor.vars.len()).find(|&k| !self.assignments.is_assigned(xor.vars[k]));
+            if let Some(k) = replacement {
+                xor.vars.swap(1, k);
+                self.xor_watches[xor.vars[1].index()].push(index);
+                watching.swap_remove(i);
+                continue;
+            }
+            i += 1;
+            let other = xor.vars[0];
+            if let Some(cref) = self.propagate_xor(index, other) {
+                conflict = Some(cref);
+                break;
+            }
+        }
+        self.xor_watches[var.index()].extend(watching);
+        conflict
+    }
+
+    /// With every variable of XOR clause `index` but `implied` assigned,
+    /// assigns `implied` the value that meets the parity, or returns the
+    /// conflict if it already has the other one.
+    fn propagate_xor(&mut self, index: usize, implied: Var) -> Option<ClauseRef> {
+        let xor = &self.xors[index];
+        let clause = parity_clause(&self.assignments, &xor.vars, xor.rhs, implied);
+        self.assert_lazy(clause)
+    }
+
+    /// Propagates the XOR clauses with at most one unassigned variable at
+    /// level 0, which a conflict may have cut off from propagation.
+    pub(crate) fn initial_xor_propagation(&mut self) -> bool {
+        for index in 0..self.xors.len() {
+            let xor = &self.xors[index];
+            let mut unassigned = xor
+                .vars
+                .iter()
+                .filter(|&&v| !self.assignments.is_assigned(v));
+            let implied = match (unassigned.next(), unassigned.next()) {
+                (None, _) => xor.vars[0],
+                (Some(&v), None) => v,
+                (Some(_), Some(_)) => continue,
+            };
+            if self.propagate_xor(index, implied).is_some() || self.propagate().is_some() {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// The clause that the parity constraint `vars = rhs` comes down to once
+/// every variable but `implied` is assigned: the literal of `implied` that
+/// meets the parity first, then the other variables with their current
+/// values, as false literals.
+pub(crate) fn parity_clause(
+    assignments: &Assignment,
+    vars: &[Var],
+    rhs: bool,
+    implied: Var,
+) -> Vec<Lit> {
+    let mut parity = rhs;
+    let mut clause = vec![Lit::new(implied, false)];
+    for &v in vars.iter().filter(|&&v| v != implied) {
+        let value = assignments.var_value(v) == Some(true);
+        parity ^= value;
+        clause.push(Lit::new(v, value));
+    }
+    // `implied` has to take the value `parity`
+    clause[0] = Lit::new(implied, !parity);
+    clause
+}
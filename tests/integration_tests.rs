@@ -1,18 +1,22 @@
 use assert_cmd::Command;
-use cnf_dpll_2wl::Solver;
+use assert_cmd::cargo::cargo_bin_cmd;
+use cnf_dpll_2wl::{Lit, ProofFormat, Solver, Var};
 use predicates::prelude::*;
-use std::io::Write;
-use tempfile::NamedTempFile;
 
-fn run_cnf(content: &str, expected: bool) {
-    let mut file = NamedTempFile::new().unwrap();
-    write!(file, "{}", content).unwrap();
-    let path = file.path().to_str().unwrap();
+fn lits(values: &[i32]) -> Vec<Lit> {
+    values.iter().map(|&v| Lit::try_from(v).unwrap()).collect()
+}
 
-    let mut solver = Solver::new(path).expect("Failed to load CNF");
-    let result = solver.solve();
+fn run_cnf(content: &str, expected: bool) {
+    let mut solver = Solver::from_dimacs_str(content).expect("Failed to load CNF");
+    let result = solver.solve().is_sat();
 
     assert_eq!(result, expected, "Failed on CNF:\n{}", content);
+    if result {
+        solver
+            .verify_model()
+            .expect("model must satisfy the formula");
+    }
 }
 
 #[test]
@@ -37,9 +41,12 @@ fn unit_negative() {
 
 #[test]
 fn empty_clause() {
-    // XXX for the simplicity sake, assume this is True.
-    // In the future, this will be fixed to be returning False
-    run_cnf("p cnf 0 1\n0\n", true);
+    run_cnf("p cnf 0 1\n0\n", false);
+}
+
+#[test]
+fn empty_clause_after_other_clauses() {
+    run_cnf("p cnf 2 3\n1 2 0\n-1 0\n0\n", false);
 }
 
 #[test]
@@ -135,7 +142,7 @@ fn test_2wl_long_clause_unit_propagation() {
 
 // Helper function to run the solver on a specific file path
 fn run_solver(file_path: &str) -> Command {
-    let mut cmd = Command::cargo_bin("cnf-dpll-2wl").unwrap();
+    let mut cmd = cargo_bin_cmd!("cnf-dpll-2wl");
     // Pass the example file as the first command-line argument
     cmd.arg(format!("examples/{}", file_path));
     cmd
@@ -143,11 +150,11 @@ fn run_solver(file_path: &str) -> Command {
 
 #[test]
 fn test_aim_sat_example() {
-    // Expected output for this specific SAT file is "SAT" followed by a valuation
+    // Expected output for this specific SAT file is "s SATISFIABLE" followed by `v` lines
     run_solver("aim-50-1_6-yes1-4.cnf")
         .assert()
-        .success()
-        .stdout(predicate::str::contains("SAT"));
+        .code(10)
+        .stdout(predicate::str::starts_with("s SATISFIABLE\nv "));
 }
 
 #[test]
@@ -155,24 +162,24 @@ fn test_hole6_sat_example() {
     // Expected output for 'hole6.cnf' is "UNSAT"
     run_solver("hole6.cnf")
         .assert()
-        .success()
-        .stdout(predicate::str::contains("SAT"));
+        .code(20)
+        .stdout("s UNSATISFIABLE\n");
 }
 
 #[test]
 fn test_simple_sat_example() {
     run_solver("test-SAT.cnf")
         .assert()
-        .success()
-        .stdout(predicate::str::contains("SAT"));
+        .code(10)
+        .stdout(predicate::str::starts_with("s SATISFIABLE\nv "));
 }
 
 #[test]
 fn test_simple_unsat_example() {
     run_solver("test-UNSAT.cnf")
         .assert()
-        .success()
-        .stdout(predicate::str::contains("UNSAT"));
+        .code(20)
+        .stdout("s UNSATISFIABLE\n");
 }
 
 #[test]
@@ -180,8 +187,8 @@ fn test_sudoku_puzzle() {
     // Sudoku puzzles encoded in CNF usually have a solution (SAT)
     run_solver("sudoku.cnf")
         .assert()
-        .success()
-        .stdout(predicate::str::contains("SAT"));
+        .code(10)
+        .stdout(predicate::str::starts_with("s SATISFIABLE\nv "));
 }
 
 #[test]
@@ -189,8 +196,8 @@ fn test_zebra_puzzle() {
     // The famous Zebra puzzle has a unique solution (SAT)
     run_solver("zebra.cnf")
         .assert()
-        .success()
-        .stdout(predicate::str::contains("SAT"));
+        .code(10)
+        .stdout(predicate::str::starts_with("s SATISFIABLE\nv "));
 }
 
 #[test]
@@ -281,3 +288,5193 @@ fn test_zombie_watcher_logic_error() {
 
     run_cnf("p cnf 3 4\n1 2 3 0\n-1 0\n-2 0\n-3 0\n", false);
 }
+
+#[test]
+fn test_hole6_unsat_with_clause_learning() {
+    // Pigeonhole instances are where plain backtracking struggles; with 1-UIP
+    // learning the solver must still prove them UNSAT.
+    let mut solver = Solver::new("examples/hole6.cnf").unwrap();
+    assert!(!solver.solve().is_sat());
+}
+
+#[test]
+fn test_learnt_clause_assertion() {
+    // Deciding 1=true forces 2 and 3, which clash on (-2 -3 4) and (-4).
+    // The learnt clause (-1) must flip 1 and lead to the model 1=false, 5=true.
+    run_cnf("p cnf 5 5\n-1 2 0\n-1 3 0\n-2 -3 4 0\n-4 0\n1 5 0\n", true);
+}
+
+#[test]
+fn test_backjump_skips_irrelevant_decision() {
+    // Decisions 1, 2, 3 conflict on (-1 -3 4)/(-1 -3 -4). The learnt clause
+    // (-1 -3) has nothing to do with 2, so the solver must jump straight back
+    // to level 1 and keep going from there.
+    run_cnf("p cnf 4 3\n-1 -3 4 0\n-1 -3 -4 0\n2 3 0\n", true);
+}
+
+#[test]
+fn test_deep_decision_stack() {
+    // (-i -i+1) for a long chain: every other variable becomes a decision,
+    // giving thousands of decision levels in a single search.
+    let n = 6000;
+    let mut cnf = format!("p cnf {} {}\n", n, n - 1);
+    for i in 1..n {
+        cnf.push_str(&format!("-{} -{} 0\n", i, i + 1));
+    }
+    run_cnf(&cnf, true);
+}
+
+#[test]
+fn test_vsids_decay_is_configurable() {
+    for decay in [0.5, 0.8, 0.99, 1.0] {
+        let mut solver = Solver::new("examples/hole6.cnf").unwrap();
+        solver.config.var_decay = decay;
+        assert!(
+            !solver.solve().is_sat(),
+            "hole6 must stay UNSAT with decay {decay}"
+        );
+
+        let mut solver = Solver::new("examples/zebra.cnf").unwrap();
+        solver.config.var_decay = decay;
+        assert!(
+            solver.solve().is_sat(),
+            "zebra must stay SAT with decay {decay}"
+        );
+    }
+}
+
+#[test]
+fn test_solver_config_builder() {
+    use cnf_dpll_2wl::{InvalidConfig, RestartStrategy, SolveResult, SolverConfig};
+
+    let config = SolverConfig::default()
+        .restarts(RestartStrategy::Geometric {
+            initial: 50,
+            factor: 1.3,
+        })
+        .var_decay(0.9)
+        .subsumption(true)
+        .elimination(true)
+        .conflict_limit(1_000_000)
+        .seed(7)
+        .build()
+        .unwrap();
+    assert_eq!(config.var_decay, 0.9);
+    assert_eq!(config.conflict_limit, Some(1_000_000));
+    let mut solver = Solver::with_config(config);
+    for clause in [[1, 2], [-1, 2], [1, -2], [-1, -2]] {
+        solver.add_clause(&clause).unwrap();
+    }
+    assert_eq!(solver.solve(), SolveResult::Unsat);
+    assert_eq!(solver.config.seed, 7);
+
+    for (config, setting) in [
+        (SolverConfig::default().var_decay(0.0), "var_decay"),
+        (
+            SolverConfig::default().restarts(RestartStrategy::Luby { unit: 0 }),
+            "restart",
+        ),
+        (
+            SolverConfig::default().random_var_freq(1.5),
+            "random_var_freq",
+        ),
+        (
+            SolverConfig::default()
+                .rephasing(true)
+                .rephase_schedule(Vec::new()),
+            "rephase_schedule",
+        ),
+    ] {
+        let err: InvalidConfig = config.build().unwrap_err();
+        assert_eq!(err.setting, setting);
+        assert!(err.to_string().starts_with(setting));
+    }
+}
+
+#[test]
+fn test_restart_strategies() {
+    use cnf_dpll_2wl::RestartStrategy;
+
+    let strategies = [
+        RestartStrategy::Never,
+        RestartStrategy::Luby { unit: 1 },
+        RestartStrategy::Luby { unit: 32 },
+        RestartStrategy::Geometric {
+            initial: 1,
+            factor: 1.5,
+        },
+    ];
+    for strategy in strategies {
+        for (file, expected) in [
+            ("hole6.cnf", false),
+            ("zebra.cnf", true),
+            ("aim-50-1_6-yes1-4.cnf", true),
+        ] {
+            let mut solver = Solver::new(&format!("examples/{file}")).unwrap();
+            solver.config.restart = strategy;
+            assert_eq!(
+                solver.solve().is_sat(),
+                expected,
+                "{file} with {strategy:?}"
+            );
+        }
+    }
+}
+
+// Small deterministic generator so randomized tests are reproducible
+fn random_3sat(seed: u64, vars: i32, clauses: usize) -> (String, Vec<Vec<i32>>) {
+    let mut state = seed
+        .wrapping_mul(6364136223846793005)
+        .wrapping_add(1442695040888963407);
+    let mut next = move |bound: i32| {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((state >> 33) % bound as u64) as i32
+    };
+    let mut cnf = format!("p cnf {} {}\n", vars, clauses);
+    let mut list = Vec::new();
+    for _ in 0..clauses {
+        let clause: Vec<i32> = (0..3)
+            .map(|_| {
+                let var = next(vars) + 1;
+                if next(2) == 0 { var } else { -var }
+            })
+            .collect();
+        for lit in &clause {
+            cnf.push_str(&format!("{} ", lit));
+        }
+        cnf.push_str("0\n");
+        list.push(clause);
+    }
+    (cnf, list)
+}
+
+fn brute_force(vars: i32, clauses: &[Vec<i32>]) -> bool {
+    (0u32..1 << vars).any(|bits| {
+        clauses.iter().all(|c| {
+            c.iter()
+                .any(|&lit| ((bits >> (lit.abs() - 1)) & 1 == 1) == (lit > 0))
+        })
+    })
+}
+
+#[test]
+fn test_aggressive_clause_database_reduction() {
+    for seed in 0..40 {
+        let (cnf, clauses) = random_3sat(seed, 12, 55);
+        let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+        solver.config.reduce_interval = 1;
+        solver.config.reduce_increment = 0;
+        solver.config.glue_lbd = 0;
+        solver.config.restart = cnf_dpll_2wl::RestartStrategy::Luby { unit: 1 };
+        assert_eq!(
+            solver.solve().is_sat(),
+            brute_force(12, &clauses),
+            "seed {seed}"
+        );
+    }
+
+    // Deleted clauses are eventually reclaimed from the arena
+    let mut solver = Solver::from_dimacs_str(&pigeonhole_cnf(6)).unwrap();
+    solver.config.reduce_interval = 10;
+    solver.config.reduce_increment = 0;
+    assert!(!solver.solve().is_sat());
+    assert!(solver.stats().deleted_clauses > 0);
+    assert!(solver.stats().reclaimed_bytes > 0);
+}
+
+#[test]
+fn test_garbage_collection_moves_clause_refs() {
+    use cnf_dpll_2wl::LBool;
+
+    // After compaction, the reason of every propagated literal still holds
+    // it, with every other literal false
+    let mut collected = 0;
+    for seed in 0..6 {
+        let (cnf, _) = random_3sat(seed, 150, 635);
+        let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+        solver.config.reduce_interval = 1;
+        solver.config.reduce_increment = 0;
+        solver.config.glue_lbd = 0;
+        if !solver.solve().is_sat() || solver.stats().reclaimed_bytes == 0 {
+            continue;
+        }
+        solver.verify_model().unwrap();
+        collected += 1;
+        for lit in solver.model().unwrap().iter() {
+            let Some(reason) = solver.reason(lit.var()) else {
+                continue;
+            };
+            let clause = solver.clause(reason);
+            assert!(clause.contains(&lit), "seed {seed}");
+            assert!(
+                clause
+                    .iter()
+                    .all(|&other| other == lit || solver.value(other) == LBool::False),
+                "seed {seed}"
+            );
+        }
+        assert!(
+            solver
+                .clause_infos()
+                .all(|info| solver.clause(info.cref) == info.literals)
+        );
+    }
+    assert!(collected > 0);
+
+    // Binary clauses deleted or shortened to two literals by `simplify`
+    // propagate from their new places only
+    let cnf = "p cnf 6 6\n5 -1 0\n5 2 0\n-1 2 0\n-6 -2 3 0\n5 0\n6 0\n";
+    let mut solver = Solver::from_dimacs_str(cnf).unwrap();
+    assert!(solver.simplify());
+    assert!(solver.stats().reclaimed_bytes > 0);
+    assert!(solver.solve_with_assumptions(&lits(&[1])).is_sat());
+    let reason = |var| {
+        let mut clause = solver
+            .clause(solver.reason(Var::new(var)).unwrap())
+            .to_vec();
+        clause.sort();
+        clause
+    };
+    let expected = |clause: &[i32]| {
+        let mut clause = lits(clause);
+        clause.sort();
+        clause
+    };
+    assert_eq!(reason(2), expected(&[-1, 2]));
+    assert_eq!(reason(3), expected(&[-2, 3]));
+}
+
+#[test]
+fn test_learnt_clause_minimization_modes() {
+    use cnf_dpll_2wl::Minimization;
+
+    for mode in [Minimization::Off, Minimization::Basic, Minimization::Deep] {
+        for seed in 0..30 {
+            let (cnf, clauses) = random_3sat(seed, 14, 60);
+            let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+            solver.config.minimize = mode;
+            assert_eq!(
+                solver.solve().is_sat(),
+                brute_force(14, &clauses),
+                "seed {seed}, {mode:?}"
+            );
+        }
+
+        let mut solver = Solver::new("examples/hole6.cnf").unwrap();
+        solver.config.minimize = mode;
+        assert!(!solver.solve().is_sat());
+    }
+}
+
+#[test]
+fn test_in_memory_clause_construction() {
+    use cnf_dpll_2wl::{AddClauseError, LBool};
+
+    let mut solver = Solver::with_vars(2);
+    solver.add_clause(&[1, 2]).unwrap();
+    solver.add_clause(&[-1, 3]).unwrap(); // Variable 3 is allocated on demand
+    assert_eq!(solver.num_vars(), 3);
+    assert_eq!(solver.add_clause(&[1, 0]), Err(AddClauseError::ZeroLiteral));
+    assert!(solver.solve().is_sat());
+
+    solver.add_clause(&[-3]).unwrap();
+    assert!(solver.solve().is_sat());
+    assert_eq!(solver.value(Lit::try_from(1).unwrap()), LBool::False);
+    assert_eq!(solver.value(Lit::try_from(2).unwrap()), LBool::True);
+
+    solver.add_clause(&[-2]).unwrap();
+    assert!(!solver.solve().is_sat());
+}
+
+#[test]
+fn test_new_variables() {
+    use cnf_dpll_2wl::Branching;
+
+    for branching in [Branching::Vsids, Branching::Lrb] {
+        let mut solver = Solver::with_vars(2);
+        solver.config.branching = branching;
+        solver.add_clause(&[1, 2]).unwrap();
+        let x = solver.new_var();
+        assert_eq!(x, Var::new(3));
+        let ys = solver.new_vars(3);
+        assert_eq!(ys, [4, 5, 6].map(Var::new));
+        assert_eq!(solver.new_vars(0), []);
+        assert_eq!(solver.num_vars(), 6);
+
+        // x <-> (1 and 2), and exactly one of the ys
+        solver
+            .add_clause(&[-x.positive(), Var::new(1).positive()])
+            .unwrap();
+        solver
+            .add_clause(&[-x.positive(), Var::new(2).positive()])
+            .unwrap();
+        let any: Vec<Lit> = ys.iter().map(|y| y.positive()).collect();
+        solver.add_clause(&any).unwrap();
+        for (i, &a) in ys.iter().enumerate() {
+            for &b in &ys[i + 1..] {
+                solver.add_clause(&[a.negative(), b.negative()]).unwrap();
+            }
+        }
+        assert!(solver.solve_with_assumptions(&[x.positive()]).is_sat());
+        let model = solver.model().unwrap();
+        assert!(model.value(Var::new(1)) && model.value(Var::new(2)));
+        assert_eq!(ys.iter().filter(|&&y| model.value(y)).count(), 1);
+        assert!(
+            !solver
+                .solve_with_assumptions(&[ys[0].negative(), ys[1].negative(), ys[2].negative()])
+                .is_sat()
+        );
+    }
+}
+
+#[test]
+fn test_incremental_clause_addition_matches_brute_force() {
+    for seed in 0..20 {
+        let (_, clauses) = random_3sat(seed, 10, 50);
+        let mut solver = Solver::with_vars(0);
+        for (i, clause) in clauses.iter().enumerate() {
+            solver.add_clause(clause).unwrap();
+            assert_eq!(
+                solver.solve().is_sat(),
+                brute_force(10, &clauses[..=i]),
+                "seed {seed}, after {} clauses",
+                i + 1
+            );
+        }
+    }
+}
+
+#[test]
+fn test_simplify_between_incremental_solves() {
+    let mut solver =
+        Solver::from_dimacs_str("p cnf 4 4\n1 2 3 0\n-1 2 4 0\n-2 3 0\n3 4 0\n").unwrap();
+    solver.add_clause(&[1]).unwrap();
+    assert!(solver.simplify());
+    // (1 2 3) is satisfied and (-1 2 4) loses -1; the unit is kept as the
+    // reason of 1
+    assert_eq!(solver.stats().satisfied_clauses, 1);
+    assert_eq!(solver.stats().false_literals_removed, 1);
+    let mut clauses: Vec<Vec<Lit>> = solver
+        .clauses()
+        .map(|clause| {
+            let mut clause = clause.to_vec();
+            clause.sort();
+            clause
+        })
+        .collect();
+    clauses.sort();
+    let mut expected = [&[1][..], &[2, 4], &[-2, 3], &[3, 4]].map(|clause| {
+        let mut clause = lits(clause);
+        clause.sort();
+        clause
+    });
+    expected.sort();
+    assert_eq!(clauses, expected);
+    assert!(solver.solve().is_sat());
+    solver.add_clause(&[-3]).unwrap();
+    assert!(solver.simplify());
+    assert_eq!(solver.num_clauses(), solver.num_learnt_clauses() + 4);
+    assert!(solver.solve().is_sat());
+    assert!(solver.model().unwrap().value(Var::new(4)));
+    solver.add_clause(&[-4]).unwrap();
+    assert!(!solver.simplify());
+    assert!(!solver.solve().is_sat());
+
+    // Units added one by one, simplifying before each search
+    for seed in 0..20 {
+        let (_, mut clauses) = random_3sat(seed, 10, 30);
+        let mut solver = Solver::with_vars(10);
+        for clause in &clauses {
+            solver.add_clause(clause).unwrap();
+        }
+        let (_, units) = random_3sat(seed + 1000, 10, 3);
+        for unit in units.iter().flatten() {
+            solver.add_clause(&[*unit]).unwrap();
+            clauses.push(vec![*unit]);
+            let sat = brute_force(10, &clauses);
+            assert!(solver.simplify() || !sat, "seed {seed}");
+            assert_eq!(solver.solve().is_sat(), sat, "seed {seed}");
+            if sat {
+                assert_eq!(solver.verify_model(), Ok(()));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_solve_with_assumptions() {
+    use cnf_dpll_2wl::{LBool, SolveResult};
+
+    let mut solver = Solver::with_vars(3);
+    solver.add_clause(&[1, 2]).unwrap();
+    solver.add_clause(&[-1, 3]).unwrap();
+
+    assert_eq!(
+        solver.solve_with_assumptions(&lits(&[-3])),
+        SolveResult::Sat
+    );
+    assert_eq!(solver.value(Lit::try_from(1).unwrap()), LBool::False);
+    assert_eq!(solver.value(Lit::try_from(2).unwrap()), LBool::True);
+    assert_eq!(
+        solver.solve_with_assumptions(&lits(&[-3, -2])),
+        SolveResult::Unsat
+    );
+    assert_eq!(
+        solver.solve_with_assumptions(&lits(&[1, -3])),
+        SolveResult::Unsat
+    );
+    // Assumptions never stick: the plain formula is still satisfiable
+    assert_eq!(solver.solve(), SolveResult::Sat);
+    assert_eq!(
+        solver.solve_with_assumptions(&lits(&[3, 1, 1])),
+        SolveResult::Sat
+    );
+
+    // `assume` holds for the next call only, alone or with more assumptions
+    solver.assume(lits(&[-3])[0]);
+    solver.assume(lits(&[-2])[0]);
+    assert_eq!(solver.solve(), SolveResult::Unsat);
+    assert_eq!(solver.solve(), SolveResult::Sat);
+    solver.assume(lits(&[-3])[0]);
+    assert_eq!(
+        solver.solve_with_assumptions(&lits(&[1])),
+        SolveResult::Unsat
+    );
+    assert_eq!(solver.num_vars(), 3);
+    assert_eq!(solver.num_clauses(), solver.clauses().count());
+    assert!(solver.num_clauses() >= 2);
+}
+
+#[test]
+fn test_solver_accessors() {
+    use cnf_dpll_2wl::{LBool, SolveResult};
+
+    let mut solver = Solver::with_vars(3);
+    assert_eq!((solver.num_vars(), solver.num_clauses()), (3, 0));
+    assert_eq!(solver.value(lits(&[1])[0]), LBool::Undef);
+    solver.add_clause(&[-1]).unwrap();
+    solver.add_clause(&[2, 3]).unwrap();
+    solver.add_clause(&[1, 5]).unwrap();
+    assert_eq!((solver.num_vars(), solver.num_clauses()), (5, 3));
+
+    // After `Sat`, the model: every literal and its negation are opposite
+    assert!(solver.solve().is_sat());
+    let model = solver.model().unwrap();
+    for lit in model.iter() {
+        assert_eq!(solver.value(lit), LBool::True);
+        assert_eq!(solver.value(-lit), LBool::False);
+    }
+    // After `Unsat` under assumptions, only what holds at level 0
+    solver.assume(lits(&[-2])[0]);
+    solver.assume(lits(&[-3])[0]);
+    assert_eq!(solver.solve(), SolveResult::Unsat);
+    assert_eq!(solver.value(lits(&[-1])[0]), LBool::True);
+    assert_eq!(solver.value(lits(&[5])[0]), LBool::True);
+    assert_eq!(solver.value(lits(&[2])[0]), LBool::Undef);
+    assert_eq!(solver.value(lits(&[-3])[0]), LBool::Undef);
+    assert_eq!(solver.num_clauses(), solver.clauses().count());
+}
+
+#[test]
+fn test_literal_values_are_complementary() {
+    use cnf_dpll_2wl::LBool;
+
+    // Both literals of a variable are read from the same assignment: one
+    // is true and the other false exactly when the variable has a level
+    let check = |solver: &Solver| {
+        for var in (1..=solver.num_vars()).map(Var::new) {
+            let (pos, neg) = (solver.value(var.positive()), solver.value(var.negative()));
+            let expected = match pos {
+                LBool::True => LBool::False,
+                LBool::False => LBool::True,
+                LBool::Undef => LBool::Undef,
+            };
+            assert_eq!(neg, expected, "{var}");
+            assert_eq!(solver.level(var).is_some(), pos != LBool::Undef, "{var}");
+            if let Some(value) = solver.value_at_level_zero(var.positive()) {
+                assert_eq!(solver.level(var), Some(0));
+                assert_eq!(pos == LBool::True, value);
+            }
+        }
+    };
+    for seed in 0..20 {
+        let (cnf, clauses) = random_3sat(seed, 12, 50);
+        let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+        check(&solver);
+        let assumptions: Vec<i32> = clauses[0].iter().map(|&l| -l).collect();
+        solver.solve_with_assumptions(&lits(&assumptions));
+        check(&solver);
+        solver.solve();
+        check(&solver);
+        let var = solver.new_var();
+        assert_eq!(solver.value(var.positive()), LBool::Undef);
+        check(&solver);
+    }
+}
+
+#[test]
+fn test_assumptions_match_brute_force() {
+    for seed in 0..30 {
+        let (_, clauses) = random_3sat(seed, 10, 35);
+        let mut solver = Solver::with_vars(10);
+        for clause in &clauses {
+            solver.add_clause(clause).unwrap();
+        }
+        for round in 0..6u64 {
+            let (_, picks) = random_3sat(seed * 100 + round, 10, 1);
+            let assumptions = &picks[0];
+            let mut constrained = clauses.clone();
+            constrained.extend(assumptions.iter().map(|&lit| vec![lit]));
+            assert_eq!(
+                solver.solve_with_assumptions(&lits(assumptions)).is_sat(),
+                brute_force(10, &constrained),
+                "seed {seed}, assumptions {assumptions:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_failed_assumptions() {
+    use cnf_dpll_2wl::SolveResult;
+
+    // 1 → 2 → 3, and 4 is unrelated
+    let mut solver = Solver::with_vars(4);
+    solver.add_clause(&[-1, 2]).unwrap();
+    solver.add_clause(&[-2, 3]).unwrap();
+
+    assert_eq!(
+        solver.solve_with_assumptions(&lits(&[4, 1, -3])),
+        SolveResult::Unsat
+    );
+    let mut failed: Vec<i32> = solver
+        .failed_assumptions()
+        .iter()
+        .map(|&lit| i32::from(lit))
+        .collect();
+    failed.sort();
+    assert_eq!(failed, vec![-3, 1]);
+
+    assert_eq!(solver.solve_with_assumptions(&lits(&[4])), SolveResult::Sat);
+    assert!(solver.failed_assumptions().is_empty());
+
+    // Unsatisfiable without any assumption: nothing to blame
+    solver.add_clause(&[-3]).unwrap();
+    solver.add_clause(&[1]).unwrap();
+    assert_eq!(
+        solver.solve_with_assumptions(&lits(&[4])),
+        SolveResult::Unsat
+    );
+    assert!(solver.failed_assumptions().is_empty());
+}
+
+#[test]
+fn test_failed_assumptions_form_an_unsat_core() {
+    for seed in 0..30 {
+        let (_, clauses) = random_3sat(seed, 10, 30);
+        let mut solver = Solver::with_vars(10);
+        for clause in &clauses {
+            solver.add_clause(clause).unwrap();
+        }
+        for round in 0..6u64 {
+            let (_, picks) = random_3sat(seed * 100 + round, 10, 2);
+            let assumptions: Vec<i32> = picks.concat();
+            if solver.solve_with_assumptions(&lits(&assumptions)).is_sat() {
+                continue;
+            }
+            let failed = solver.failed_assumptions().to_vec();
+            assert!(
+                failed
+                    .iter()
+                    .all(|&lit| assumptions.contains(&i32::from(lit)))
+            );
+            assert!(!solver.solve_with_assumptions(&failed).is_sat());
+        }
+    }
+}
+
+#[test]
+fn test_lit_and_var_conversions() {
+    use cnf_dpll_2wl::{AddClauseError, InvalidLiteral, LBool};
+
+    let lit = Lit::try_from(-7).unwrap();
+    assert_eq!(lit.var(), Var::new(7));
+    assert!(lit.sign());
+    assert_eq!(i32::from(lit.negate()), 7);
+    assert_eq!(-lit, Var::new(7).positive());
+    assert_eq!(lit.index() ^ 1, lit.negate().index());
+    assert_eq!(Lit::from_index(lit.index()), lit);
+    assert_eq!(Lit::from_index(2), Var::new(1).positive());
+    assert_eq!(lit.var().index(), 7);
+    assert_eq!(i32::from(Var::try_from(3).unwrap()), 3);
+
+    assert_eq!(Lit::try_from(0), Err(InvalidLiteral(0)));
+    assert!(Lit::try_from(i32::MIN).is_err());
+    assert!(Var::try_from(-3).is_err());
+
+    // Both typed literals and DIMACS integers are accepted by add_clause
+    let mut solver = Solver::with_vars(2);
+    solver.add_clause(&lits(&[1, -2])).unwrap();
+    solver.add_clause(&[2]).unwrap();
+    assert_eq!(
+        solver.add_clause(&[i32::MIN]),
+        Err(AddClauseError::OutOfRange(i32::MIN))
+    );
+    assert!(solver.solve().is_sat());
+    assert_eq!(solver.value(Lit::try_from(1).unwrap()), LBool::True);
+    assert_eq!(solver.value(Lit::try_from(-1).unwrap()), LBool::False);
+    assert_eq!(solver.value(Lit::try_from(2).unwrap()), LBool::True);
+}
+
+#[test]
+fn test_lit_from_index_decodes_both_polarities() {
+    // Each variable takes two consecutive indices, positive then negative
+    for dimacs in [1, -1, 2, -2, 7, -7, 1000, -1000] {
+        let lit = Lit::try_from(dimacs).unwrap();
+        assert_eq!(Lit::from_index(lit.index()), lit);
+    }
+    assert_eq!(i32::from(Lit::from_index(2)), 1);
+    assert_eq!(i32::from(Lit::from_index(3)), -1);
+    assert_eq!(i32::from(Lit::from_index(14)), 7);
+    assert_eq!(i32::from(Lit::from_index(15)), -7);
+
+    let last = Var::new(Var::MAX_INDEX);
+    assert_eq!(Lit::from_index(last.negative().index()), last.negative());
+    assert_eq!(Lit::from_index(last.positive().index()), last.positive());
+}
+
+fn parse_error(content: &str) -> cnf_dpll_2wl::ParseError {
+    match Solver::from_dimacs_str(content) {
+        Err(cnf_dpll_2wl::SolverError::Parse(err)) => err,
+        Err(err) => panic!("unexpected error {err}"),
+        Ok(_) => panic!("accepted invalid CNF:\n{content}"),
+    }
+}
+
+#[test]
+fn test_parse_errors() {
+    use cnf_dpll_2wl::ParseError;
+
+    assert_eq!(
+        parse_error("p cnf 2\n1 2 0\n"),
+        ParseError::InvalidHeader { line: 1 }
+    );
+    assert_eq!(
+        parse_error("p cnf 2 1\np cnf 2\n1 2 0\n"),
+        ParseError::InvalidHeader { line: 2 }
+    );
+    assert_eq!(
+        parse_error("p cnf 3 2\n1 2 0\n2\n3\n"),
+        ParseError::UnterminatedClause { line: 3 }
+    );
+    assert_eq!(
+        parse_error("p cnf 3 1\nc fine\n1 x2 0\n"),
+        ParseError::InvalidToken {
+            line: 3,
+            column: 3,
+            token: "x2".to_string()
+        }
+    );
+}
+
+#[test]
+fn test_clauses_may_span_lines() {
+    run_cnf("p cnf 3 3\n1 2\n 3 0 -1 0\n-2\n0\n", true);
+    run_cnf("p cnf 2 4\n1 2 0 -1 2 0\n1 -2\n0 -1 -2 0\n", false);
+}
+
+#[test]
+fn test_strict_parsing() {
+    use cnf_dpll_2wl::{ParseError, ParseOptions, SolverError};
+
+    let strict_error = |content: &str| {
+        // Accepted by default, rejected when strict
+        Solver::from_dimacs_str(content).unwrap();
+        match Solver::from_reader_with_options(content.as_bytes(), ParseOptions::strict()) {
+            Err(SolverError::Parse(err)) => err,
+            Err(err) => panic!("unexpected error {err}"),
+            Ok(_) => panic!("accepted non-strict CNF:\n{content}"),
+        }
+    };
+    assert_eq!(
+        strict_error("p cnf 3 2\n1 2 0 -3 0\n"),
+        ParseError::InvalidToken {
+            line: 2,
+            column: 7,
+            token: "-3".to_string()
+        }
+    );
+    assert_eq!(
+        strict_error("p cnf 3 1\n1 2\n3 0\n"),
+        ParseError::UnterminatedClause { line: 2 }
+    );
+    assert_eq!(
+        strict_error("pcnf cnf 2 1\n1 2 0\n"),
+        ParseError::InvalidHeader { line: 1 }
+    );
+    assert_eq!(
+        strict_error("p cnf 2 1\ncnf\n1 2 0\n"),
+        ParseError::InvalidToken {
+            line: 2,
+            column: 1,
+            token: "cnf".to_string()
+        }
+    );
+    assert_eq!(strict_error("1 2 0\n"), ParseError::MissingHeader);
+    assert_eq!(
+        strict_error("c only a comment\n"),
+        ParseError::MissingHeader
+    );
+    assert_eq!(
+        strict_error("p cnf 2 1\np cnf 2 1\n1 2 0\n"),
+        ParseError::InvalidHeader { line: 2 }
+    );
+    assert_eq!(
+        strict_error("p cnf 2 1\n1  -3 0\n"),
+        ParseError::LiteralOutOfRange {
+            line: 2,
+            column: 4,
+            literal: -3,
+            variables: 2
+        }
+    );
+    assert_eq!(
+        strict_error("p cnf 3 3\n1 2 0\n-3 0\n"),
+        ParseError::ClauseCountMismatch {
+            declared: 3,
+            found: 2
+        }
+    );
+    let strict = |content: &str| {
+        Solver::from_reader_with_options(content.as_bytes(), ParseOptions::strict()).map(|_| ())
+    };
+    assert!(strict("c a comment\nc\np cnf 2 2\n1 -2 0\nx1 2 0\n").is_ok());
+    assert!(strict("p cnf 2 1\n1 3 0\n").is_err());
+    assert!(strict("p cnf 2 2\n1 2 0\n").is_err());
+
+    run_solver("zebra.cnf").arg("--strict").assert().code(10);
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .args(["--strict", "-"])
+        .write_stdin("p cnf 2 2\n1 2 0 -1 0\n")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "line 2, column 7: invalid token `-1`",
+        ));
+}
+
+#[test]
+fn test_lenient_parsing() {
+    use cnf_dpll_2wl::ParseOptions;
+
+    // By default already, without a header the variables are those that
+    // occur, and a header may declare too few variables and clauses
+    let mut solver = Solver::from_dimacs_str("1 -2 0\n2 3 0 -3 0\n").unwrap();
+    assert_eq!(solver.num_vars(), 3);
+    assert!(solver.solve().is_sat());
+    let mut solver = Solver::from_dimacs_str("p cnf 2 1\n1 4 0\n-1 0\n-4 0\n").unwrap();
+    assert_eq!(solver.num_vars(), 4);
+    assert!(!solver.solve().is_sat());
+    // The first of two headers stands
+    let solver = Solver::from_dimacs_str("p cnf 3 1\n1 0\np cnf 2 1\n").unwrap();
+    assert_eq!(solver.num_vars(), 3);
+
+    let lenient = |content: &str| {
+        assert!(Solver::from_dimacs_str(content).is_err());
+        Solver::from_reader_with_options(content.as_bytes(), ParseOptions::lenient()).unwrap()
+    };
+    // A malformed header
+    assert_eq!(lenient("p cnf 5\n1 0\n").num_vars(), 1);
+    // SATLIB files end with `%` and a stray `0`
+    let mut solver = lenient("c uf3\np cnf 3 2\n 1 -2 3 0\n-1 2 0\n%\n0\n\n");
+    assert!(solver.solve().is_sat());
+    // The last clause may lack its `0`
+    let mut solver = lenient("p cnf 2 2\n1 2 0\n-1");
+    assert!(solver.solve().is_sat());
+    assert!(!solver.model().unwrap().value(Var::new(1)));
+
+    // `strict` wins over `lenient`
+    let both = ParseOptions {
+        strict: true,
+        lenient: true,
+        ..ParseOptions::default()
+    };
+    assert!(Solver::from_reader_with_options("1 0\n".as_bytes(), both).is_err());
+
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .args(["--lenient", "-"])
+        .write_stdin("p cnf 2 3\n1 2 0\n-1 0\n%\n0\n")
+        .assert()
+        .code(10)
+        .stdout("s SATISFIABLE\nv -1 2 0\n");
+    run_solver("zebra.cnf")
+        .args(["--lenient", "--strict"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_mapped_files_parse_like_readers() {
+    for file in [
+        "aim-50-1_6-yes1-4.cnf",
+        "hole6.cnf",
+        "sudoku.cnf",
+        "zebra.cnf",
+    ] {
+        let path = format!("examples/{file}");
+        let mapped = Solver::new(&path).unwrap();
+        let reader = std::io::BufReader::new(std::fs::File::open(&path).unwrap());
+        let read = Solver::from_reader(reader).unwrap();
+        assert_eq!(mapped.num_vars(), read.num_vars());
+        let clauses =
+            |solver: &Solver| -> Vec<Vec<Lit>> { solver.clauses().map(<[Lit]>::to_vec).collect() };
+        assert_eq!(clauses(&mapped), clauses(&read), "{file}");
+    }
+
+    // Windows line endings, and errors located as the reader does
+    let cnf = "p cnf 2 2\r\n1 -2 0\r\n-1 0\r\n";
+    let mut solver = Solver::from_dimacs_str(cnf).unwrap();
+    assert!(solver.solve().is_sat());
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    std::io::Write::write_all(&mut file, b"p cnf 2 1\n\xc3\xa9 1 x 0\n").unwrap();
+    let error =
+        |loaded: Result<Solver, cnf_dpll_2wl::SolverError>| loaded.err().unwrap().to_string();
+    let mapped = error(Solver::new(file.path().to_str().unwrap()));
+    let read = error(Solver::from_reader(&b"p cnf 2 1\n\xc3\xa9 1 x 0\n"[..]));
+    assert_eq!(mapped, read);
+    assert_eq!(mapped, "line 2, column 1: invalid token `\u{e9}`");
+}
+
+#[test]
+fn test_large_inputs_parse_like_readers() {
+    use cnf_dpll_2wl::ParseOptions;
+
+    // Enough clauses to be parsed on several threads, some spanning lines
+    // and some XORs, so that chunks end in the middle of clauses
+    let mut cnf = String::new();
+    let mut body = String::new();
+    let mut seed = 12345u64;
+    let clause_count = 400_000;
+    for i in 0..clause_count {
+        if i % 3 == 0 {
+            body.push('x');
+        }
+        for j in 0..3 {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            let var = (seed >> 33) % 1000 + 1;
+            let sign = if seed >> 63 == 0 { "" } else { "-" };
+            body.push_str(&format!("{sign}{var}"));
+            body.push(if j == 1 && i % 5 == 0 { '\n' } else { ' ' });
+        }
+        body.push_str(if i % 7 == 0 { "0 " } else { "0\n" });
+        if i % 1000 == 1 && i % 7 != 0 {
+            body.push_str("c comment\n");
+        }
+    }
+    cnf.push_str(&format!("c large\np cnf 1000 {clause_count}\n{body}"));
+    assert!(cnf.len() > 4 << 20);
+
+    let clauses =
+        |solver: &Solver| -> Vec<Vec<Lit>> { solver.clauses().map(<[Lit]>::to_vec).collect() };
+    let threads = |cnf: &str, options: ParseOptions| {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, cnf.as_bytes()).unwrap();
+        let options = ParseOptions {
+            threads: 4,
+            ..options
+        };
+        Solver::new_with_options(file.path().to_str().unwrap(), options)
+    };
+    let read = Solver::from_reader(cnf.as_bytes()).unwrap();
+    let parsed = threads(&cnf, ParseOptions::default()).unwrap();
+    assert_eq!(parsed.num_vars(), read.num_vars());
+    assert_eq!(clauses(&parsed), clauses(&read));
+
+    // Errors are reported at the same line
+    let error =
+        |loaded: Result<Solver, cnf_dpll_2wl::SolverError>| loaded.err().unwrap().to_string();
+    let bad = format!("{cnf}1 -x2 0\n");
+    let parsed = error(threads(&bad, ParseOptions::default()));
+    assert_eq!(parsed, error(Solver::from_reader(bad.as_bytes())));
+    assert!(parsed.starts_with(&format!("line {}", bad.lines().count())));
+
+    // A `%` line ends lenient input in whichever chunk it falls
+    let cut = cnf.len() / 3;
+    let cut = cut + cnf[cut..].find('\n').unwrap() + 1;
+    let ended = format!("{}%\n{}", &cnf[..cut], &cnf[cut..]);
+    let parsed = threads(&ended, ParseOptions::lenient()).unwrap();
+    assert!(Solver::from_reader(ended.as_bytes()).is_err());
+    let read =
+        Solver::from_reader_with_options(&cnf.as_bytes()[..cut], ParseOptions::lenient()).unwrap();
+    assert_eq!(clauses(&parsed), clauses(&read));
+}
+
+#[test]
+fn test_simplified_formulas_are_written_as_dimacs() {
+    // 1 is fixed, 2 and 3 remain, 4 is free: 1 and 4 disappear
+    let mut solver = Solver::with_vars(4);
+    for clause in [&[1][..], &[-1, 2, 3], &[-2, -3], &[1, 4]] {
+        solver.add_clause(clause).unwrap();
+    }
+    assert!(solver.preprocess());
+    let mut out = Vec::new();
+    solver.write_dimacs(&mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert_eq!(
+        out,
+        "c map 1 2\nc map 2 3\nc fixed 1\np cnf 2 2\n1 2 0\n-1 -2 0\n"
+    );
+    assert!(Solver::from_dimacs_str(&out).unwrap().solve().is_sat());
+    assert!(solver.solve().is_sat());
+
+    // Elimination and probing run once, and their result stays equisatisfiable
+    for (file, sat) in [("sudoku.cnf", true), ("hole6.cnf", false)] {
+        let mut solver = Solver::new(&format!("examples/{file}")).unwrap();
+        solver.config.subsumption = true;
+        solver.config.elimination = true;
+        solver.config.probing = true;
+        assert!(solver.preprocess());
+        let mut out = Vec::new();
+        solver.write_dimacs(&mut out).unwrap();
+        let mut simplified = Solver::from_bytes(&out).unwrap();
+        assert_eq!(simplified.solve().is_sat(), sat, "{file}");
+        assert_eq!(solver.solve().is_sat(), sat, "{file}");
+    }
+
+    let out = tempfile::NamedTempFile::new().unwrap();
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .arg("--simplify-only")
+        .arg(out.path())
+        .arg("examples/test-UNSAT.cnf")
+        .assert()
+        .code(20)
+        .stdout("s UNSATISFIABLE\n");
+    assert_eq!(
+        std::fs::read_to_string(out.path()).unwrap(),
+        "p cnf 0 1\n0\n"
+    );
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .arg("--simplify-only")
+        .arg(out.path())
+        .arg("examples/zebra.cnf")
+        .assert()
+        .code(0)
+        .stdout("");
+    let simplified = std::fs::read_to_string(out.path()).unwrap();
+    assert!(simplified.starts_with("c map 1 "));
+    assert!(
+        Solver::from_dimacs_str(&simplified)
+            .unwrap()
+            .solve()
+            .is_sat()
+    );
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .arg("--simplify-only")
+        .arg(out.path())
+        .args(["--all", "examples/zebra.cnf"])
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn test_missing_file_is_an_io_error() {
+    assert!(matches!(
+        Solver::new("examples/does-not-exist.cnf"),
+        Err(cnf_dpll_2wl::SolverError::Io(_))
+    ));
+}
+
+#[test]
+fn test_load_from_reader() {
+    let file = std::fs::File::open("examples/test-UNSAT.cnf").unwrap();
+    let mut solver = Solver::from_reader(std::io::BufReader::new(file)).unwrap();
+    assert!(!solver.solve().is_sat());
+
+    let mut solver = Solver::from_reader(&b"p cnf 2 1\n-1 2 0\n"[..]).unwrap();
+    assert!(solver.solve().is_sat());
+}
+
+#[test]
+fn test_cli_reads_stdin() {
+    let cnf = std::fs::read_to_string("examples/test-UNSAT.cnf").unwrap();
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .arg("-")
+        .write_stdin(cnf.clone())
+        .assert()
+        .code(20)
+        .stdout("s UNSATISFIABLE\n");
+
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .write_stdin("p cnf 2 2\n1 0\n-1 2 0\n")
+        .assert()
+        .code(10)
+        .stdout("s SATISFIABLE\nv 1 2 0\n");
+}
+
+#[test]
+fn test_cli_help_and_option_values() {
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--engine <ENGINE>"))
+        .stdout(predicate::str::contains("[default: vsids]"));
+    // Values given as the next argument or after `=` alike
+    run_solver("hole6.cnf")
+        .args(["--engine", "lookahead", "--pre=subsume,elim"])
+        .assert()
+        .code(20);
+    run_solver("hole6.cnf")
+        .arg("--pre=unroll")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unroll"));
+    run_solver("hole6.cnf")
+        .arg("--no-such-option")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--no-such-option"));
+}
+
+#[test]
+fn test_batch_mode() {
+    let output = cargo_bin_cmd!("cnf-dpll-2wl")
+        .args(["--jobs", "2", "examples/hole6.cnf", "examples/zebra.cnf"])
+        .arg("examples/test-UNSAT.cnf")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let table = String::from_utf8(output.stdout).unwrap();
+    let rows: Vec<Vec<&str>> = table
+        .lines()
+        .map(|line| line.split_whitespace().collect())
+        .collect();
+    assert_eq!(rows.len(), 4);
+    assert_eq!(rows[0], ["file", "answer", "time", "conflicts"]);
+    // In input order, whichever finishes first
+    assert_eq!(rows[1][..2], ["examples/hole6.cnf", "UNSAT"]);
+    assert_eq!(rows[2][..2], ["examples/zebra.cnf", "SAT"]);
+    assert_eq!(rows[3][..2], ["examples/test-UNSAT.cnf", "UNSAT"]);
+
+    // A directory stands for its files; a missing file is an error row
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .args(["examples", "missing.cnf"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("examples/sudoku.cnf"))
+        .stdout(predicate::str::is_match(r"missing\.cnf +ERROR").unwrap());
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .args([
+            "--proof",
+            "out.drat",
+            "examples/hole6.cnf",
+            "examples/zebra.cnf",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("only apply to a single input"));
+    run_solver("hole6.cnf")
+        .args(["--jobs", "2"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--jobs only applies to several inputs",
+        ));
+}
+
+#[test]
+fn test_verbosity_flags() {
+    let output = run_solver("hole6.cnf").arg("-vv").output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines[0], "c variables 42 clauses 133");
+    assert!(lines[1].split_whitespace().eq([
+        "c",
+        "conflicts",
+        "restarts",
+        "original",
+        "learnt",
+        "fixed",
+        "trail",
+        "time"
+    ]));
+    assert!(lines.iter().any(|line| line.starts_with("c * ")));
+    assert!(lines.iter().any(|line| line.starts_with("c R ")));
+    assert!(lines[lines.len() - 2].starts_with("c = "));
+    assert_eq!(lines[lines.len() - 1], "s UNSATISFIABLE");
+
+    // -v leaves out the restart lines
+    let output = run_solver("hole6.cnf").arg("-v").output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("c * "));
+    assert!(!stdout.contains("c R "));
+
+    run_solver("zebra.cnf")
+        .arg("-q")
+        .assert()
+        .code(10)
+        .stdout("s SATISFIABLE\n");
+    run_solver("zebra.cnf")
+        .args(["-q", "-v"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_benchmark_report() {
+    let report = tempfile::NamedTempFile::new().unwrap();
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .args(["--report", "csv"])
+        .arg(report.path())
+        .args(["examples/hole6.cnf", "examples/zebra.cnf", "missing.cnf"])
+        .assert()
+        .code(1);
+    let csv = std::fs::read_to_string(report.path()).unwrap();
+    let rows: Vec<Vec<&str>> = csv.lines().map(|line| line.split(',').collect()).collect();
+    assert_eq!(
+        rows[0],
+        [
+            "file",
+            "answer",
+            "time",
+            "decisions",
+            "conflicts",
+            "propagations",
+            "learnt_clauses",
+            "memory"
+        ]
+    );
+    assert_eq!(rows[1][..2], ["examples/hole6.cnf", "UNSAT"]);
+    assert!(rows[1][4].parse::<u64>().unwrap() > 0);
+    assert_eq!(rows[2][..2], ["examples/zebra.cnf", "SAT"]);
+    assert_eq!(rows[3][..2], ["missing.cnf", "ERROR"]);
+    assert_eq!(rows.len(), 4);
+
+    // A single input gets a single row
+    run_solver("test-UNSAT.cnf")
+        .args(["--report", "json"])
+        .arg(report.path())
+        .assert()
+        .code(20);
+    let json = std::fs::read_to_string(report.path()).unwrap();
+    assert!(json.starts_with("[\n  {\"file\":\"examples/test-UNSAT.cnf\",\"answer\":\"UNSAT\""));
+    assert!(json.ends_with("}\n]\n"));
+    assert_eq!(json.matches("\"memory\":").count(), 1);
+
+    run_solver("zebra.cnf")
+        .args(["--report", "xml", "out.xml"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("csv or json"));
+}
+
+#[test]
+fn test_cli_reports_parse_errors() {
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .write_stdin("p cnf 2 1\n1 -x2 0\n")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("line 2, column 3"));
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn test_gzip_input() {
+    use flate2::{Compression, write::GzEncoder};
+    use std::io::Write;
+
+    let mut file = tempfile::Builder::new()
+        .suffix(".cnf.gz")
+        .tempfile()
+        .unwrap();
+    let mut encoder = GzEncoder::new(&mut file, Compression::default());
+    encoder
+        .write_all(&std::fs::read("examples/hole6.cnf").unwrap())
+        .unwrap();
+    encoder.finish().unwrap();
+
+    let mut solver = Solver::new(file.path().to_str().unwrap()).unwrap();
+    assert!(!solver.solve().is_sat());
+    // The same bytes, without the file
+    let bytes = std::fs::read(file.path()).unwrap();
+    let mut solver = Solver::from_bytes(&bytes).unwrap();
+    assert!(!solver.solve().is_sat());
+}
+
+#[cfg(feature = "xz")]
+#[test]
+fn test_xz_input() {
+    use std::io::Write;
+    use xz2::write::XzEncoder;
+
+    let mut file = tempfile::Builder::new()
+        .suffix(".cnf.xz")
+        .tempfile()
+        .unwrap();
+    let mut encoder = XzEncoder::new(&mut file, 6);
+    encoder
+        .write_all(&std::fs::read("examples/zebra.cnf").unwrap())
+        .unwrap();
+    encoder.finish().unwrap();
+
+    let mut solver = Solver::new(file.path().to_str().unwrap()).unwrap();
+    assert!(solver.solve().is_sat());
+}
+
+#[cfg(not(feature = "gzip"))]
+#[test]
+fn test_gzip_input_without_feature() {
+    use std::io::Write;
+
+    let mut file = tempfile::Builder::new()
+        .suffix(".cnf.gz")
+        .tempfile()
+        .unwrap();
+    file.write_all(&[0x1f, 0x8b, 0x08, 0x00]).unwrap();
+    match Solver::new(file.path().to_str().unwrap()) {
+        Err(cnf_dpll_2wl::SolverError::Io(err)) => {
+            assert_eq!(err.kind(), std::io::ErrorKind::Unsupported)
+        }
+        _ => panic!("compressed input must be rejected without the gzip feature"),
+    }
+    assert!(matches!(
+        Solver::from_bytes(&[0x1f, 0x8b, 0x08, 0x00]),
+        Err(cnf_dpll_2wl::SolverError::Io(_))
+    ));
+    let mut solver = Solver::from_bytes(b"p cnf 2 1\n1 -2 0\n").unwrap();
+    assert!(solver.solve().is_sat());
+}
+
+fn dimacs_clauses(cnf: &str) -> Vec<Vec<i32>> {
+    let mut clauses = Vec::new();
+    let mut current = Vec::new();
+    for line in cnf
+        .lines()
+        .filter(|l| !l.starts_with('c') && !l.starts_with('p'))
+    {
+        for value in line.split_whitespace().map(|t| t.parse::<i32>().unwrap()) {
+            if value == 0 {
+                clauses.push(std::mem::take(&mut current));
+            } else if !current.contains(&value) {
+                current.push(value); // Like drat-trim, merge repeated literals
+            }
+        }
+    }
+    clauses
+}
+
+// Reverse unit propagation: assuming the lemma false must yield a conflict
+fn is_rup(db: &[Vec<i32>], lemma: &[i32]) -> bool {
+    let vars = db.iter().flatten().chain(lemma);
+    let max_var = vars.map(|l| l.unsigned_abs() as usize).max().unwrap_or(0);
+    let mut value: Vec<Option<bool>> = vec![None; max_var + 1];
+    for &lit in lemma {
+        let old = value[lit.unsigned_abs() as usize].replace(lit < 0);
+        if old == Some(lit > 0) {
+            return true;
+        }
+    }
+    loop {
+        let mut changed = false;
+        for clause in db {
+            let mut unassigned = None;
+            let mut open = 0;
+            let mut satisfied = false;
+            for &lit in clause {
+                match value[lit.unsigned_abs() as usize] {
+                    Some(v) if v == (lit > 0) => satisfied = true,
+                    Some(_) => {}
+                    None => {
+                        open += 1;
+                        unassigned = Some(lit);
+                    }
+                }
+            }
+            if satisfied {
+                continue;
+            }
+            match (open, unassigned) {
+                (0, _) => return true,
+                (1, Some(lit)) => {
+                    value[lit.unsigned_abs() as usize] = Some(lit > 0);
+                    changed = true;
+                }
+                _ => {}
+            }
+        }
+        if !changed {
+            return false;
+        }
+    }
+}
+
+fn check_drat(cnf: &str, proof: &str) {
+    let mut db = dimacs_clauses(cnf);
+    let mut refuted = false;
+    for line in proof.lines() {
+        if line.starts_with('d') {
+            continue; // Keeping deleted clauses only makes RUP checks easier
+        }
+        let lemma: Vec<i32> = line
+            .split_whitespace()
+            .map(|t| t.parse().unwrap())
+            .take_while(|&v| v != 0)
+            .collect();
+        assert!(is_rup(&db, &lemma), "lemma {lemma:?} is not RUP");
+        refuted |= lemma.is_empty();
+        db.push(lemma);
+    }
+    assert!(refuted, "proof does not derive the empty clause");
+}
+
+#[test]
+fn test_drat_proof_for_unsat_instances() {
+    for file in ["hole6.cnf", "test-UNSAT.cnf"] {
+        let cnf = std::fs::read_to_string(format!("examples/{file}")).unwrap();
+        let proof = tempfile::NamedTempFile::new().unwrap();
+        let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+        solver.config.proof_path = Some(proof.path().to_path_buf());
+        solver.config.reduce_interval = 20;
+        assert!(!solver.solve().is_sat());
+        solver.finish_proof().unwrap();
+        check_drat(&cnf, &std::fs::read_to_string(proof.path()).unwrap());
+    }
+}
+
+#[test]
+fn test_cli_proof_flag() {
+    let proof = tempfile::NamedTempFile::new().unwrap();
+    run_solver("hole6.cnf")
+        .arg("--proof")
+        .arg(proof.path())
+        .assert()
+        .code(20)
+        .stdout("s UNSATISFIABLE\n");
+    let cnf = std::fs::read_to_string("examples/hole6.cnf").unwrap();
+    check_drat(&cnf, &std::fs::read_to_string(proof.path()).unwrap());
+
+    run_solver("hole6.cnf")
+        .arg("--proof")
+        .arg("/nonexistent-dir/proof.drat")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot write proof"));
+}
+
+/// Turns a binary DRAT proof back into its textual form.
+fn decode_binary_drat(bytes: &[u8]) -> String {
+    let mut text = String::new();
+    let mut bytes = bytes.iter().copied();
+    while let Some(kind) = bytes.next() {
+        match kind {
+            b'a' => {}
+            b'd' => text.push_str("d "),
+            other => panic!("unexpected proof byte {other:#x}"),
+        }
+        loop {
+            let (mut code, mut shift) = (0u32, 0);
+            loop {
+                let byte = bytes.next().expect("truncated literal");
+                code |= u32::from(byte & 0x7f) << shift;
+                shift += 7;
+                if byte & 0x80 == 0 {
+                    break;
+                }
+            }
+            if code == 0 {
+                break;
+            }
+            let var = (code >> 1) as i32;
+            let lit = if code & 1 == 1 { -var } else { var };
+            text.push_str(&format!("{lit} "));
+        }
+        text.push_str("0\n");
+    }
+    text
+}
+
+#[test]
+fn test_binary_drat_proof() {
+    let cnf = std::fs::read_to_string("examples/hole6.cnf").unwrap();
+    let proof = tempfile::NamedTempFile::new().unwrap();
+    let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+    solver.config.proof_path = Some(proof.path().to_path_buf());
+    solver.config.proof_format = ProofFormat::BinaryDrat;
+    solver.config.reduce_interval = 20;
+    assert!(!solver.solve().is_sat());
+    solver.finish_proof().unwrap();
+    let bytes = std::fs::read(proof.path()).unwrap();
+    check_drat(&cnf, &decode_binary_drat(&bytes));
+
+    run_solver("hole6.cnf")
+        .args(["--proof-format", "binary", "--proof"])
+        .arg(proof.path())
+        .assert()
+        .code(20);
+    check_drat(
+        &cnf,
+        &decode_binary_drat(&std::fs::read(proof.path()).unwrap()),
+    );
+}
+
+/// Checks an LRAT proof strictly: every hint must be unit or falsified in
+/// turn under the negation of its clause, and the last one falsified.
+fn check_lrat(cnf: &str, proof: &str) {
+    let mut db: std::collections::HashMap<u64, Vec<i32>> = dimacs_clauses(cnf)
+        .into_iter()
+        .enumerate()
+        .map(|(i, clause)| (i as u64 + 1, clause))
+        .collect();
+    let mut last_id = db.len() as u64;
+    let mut refuted = false;
+    for line in proof.lines() {
+        let mut tokens = line.split_whitespace();
+        let id: u64 = tokens.next().unwrap().parse().unwrap();
+        let rest: Vec<&str> = tokens.collect();
+        if rest[0] == "d" {
+            for token in &rest[1..rest.len() - 1] {
+                let deleted: u64 = token.parse().unwrap();
+                assert!(db.remove(&deleted).is_some(), "{deleted} deleted twice");
+            }
+            continue;
+        }
+        assert!(id > last_id, "clause IDs must increase: {line}");
+        last_id = id;
+        let numbers: Vec<i64> = rest.iter().map(|t| t.parse().unwrap()).collect();
+        let split = numbers.iter().position(|&n| n == 0).unwrap();
+        let lemma: Vec<i32> = numbers[..split].iter().map(|&n| n as i32).collect();
+        let hints = &numbers[split + 1..numbers.len() - 1];
+
+        let mut value: std::collections::HashMap<i32, bool> =
+            lemma.iter().map(|&lit| (lit.abs(), lit < 0)).collect();
+        let mut conflict = false;
+        for &hint in hints {
+            let clause = db
+                .get(&(hint as u64))
+                .unwrap_or_else(|| panic!("hint {hint} of {id} is not a clause"));
+            let mut open = Vec::new();
+            for &lit in clause {
+                match value.get(&lit.abs()) {
+                    Some(&v) if v == (lit > 0) => panic!("hint {hint} of {id} is satisfied"),
+                    Some(_) => {}
+                    None => open.push(lit),
+                }
+            }
+            match open[..] {
+                [] => {
+                    conflict = true;
+                    break;
+                }
+                [lit] => {
+                    value.insert(lit.abs(), lit > 0);
+                }
+                _ => panic!("hint {hint} of {id} is not unit"),
+            }
+        }
+        assert!(conflict, "hints of {id} do not reach a conflict");
+        refuted |= lemma.is_empty();
+        db.insert(id, lemma);
+    }
+    assert!(refuted, "proof does not derive the empty clause");
+}
+
+#[test]
+fn test_lrat_proof() {
+    let cnf = std::fs::read_to_string("examples/hole6.cnf").unwrap();
+    let proof = tempfile::NamedTempFile::new().unwrap();
+    let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+    solver.config.proof_path = Some(proof.path().to_path_buf());
+    solver.config.proof_format = ProofFormat::Lrat;
+    solver.config.reduce_interval = 20;
+    assert!(!solver.solve().is_sat());
+    solver.finish_proof().unwrap();
+    check_lrat(&cnf, &std::fs::read_to_string(proof.path()).unwrap());
+
+    // Input clauses are numbered in input order
+    let solver = Solver::from_dimacs_str("p cnf 2 3\n1 2 0\n1 -1 0\n-2 0\n").unwrap();
+    let ids: Vec<u64> = solver.clause_infos().map(|info| info.id).collect();
+    assert_eq!(ids, [1, 3]);
+
+    // Every pass that changes clauses must give hints
+    let mut checked = 0;
+    for seed in 0..40 {
+        let (cnf, clauses) = random_3sat(seed, 12, 60);
+        if brute_force(12, &clauses) {
+            continue;
+        }
+        let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+        solver.config.proof_path = Some(proof.path().to_path_buf());
+        solver.config.proof_format = ProofFormat::Lrat;
+        solver.config.subsumption = seed % 2 == 0;
+        solver.config.elimination = seed % 3 == 0;
+        solver.config.probing = seed % 4 < 2;
+        solver.config.vivification = true;
+        solver.config.equivalences = true;
+        solver.config.reduce_interval = 5;
+        assert!(!solver.solve().is_sat(), "seed {seed}");
+        solver.finish_proof().unwrap();
+        check_lrat(&cnf, &std::fs::read_to_string(proof.path()).unwrap());
+        checked += 1;
+    }
+    assert!(checked > 5);
+
+    run_solver("test-UNSAT.cnf")
+        .args([
+            "--proof-format",
+            "lrat",
+            "--pre=equiv,subsume,elim",
+            "--probe",
+            "--proof",
+        ])
+        .arg(proof.path())
+        .assert()
+        .code(20);
+    let cnf = std::fs::read_to_string("examples/test-UNSAT.cnf").unwrap();
+    check_lrat(&cnf, &std::fs::read_to_string(proof.path()).unwrap());
+
+    run_solver("hole6.cnf")
+        .args(["--proof-format", "lrat", "--engine", "lookahead", "--proof"])
+        .arg(proof.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("needs the cdcl engine"));
+}
+
+#[test]
+fn test_proof_checker() {
+    use cnf_dpll_2wl::{ProofChecker, ProofError};
+
+    let proof = tempfile::NamedTempFile::new().unwrap();
+    for name in ["hole6.cnf", "test-UNSAT.cnf"] {
+        let path = format!("examples/{name}");
+        let checker = ProofChecker::new(&path).unwrap();
+        for format in [
+            ProofFormat::Drat,
+            ProofFormat::BinaryDrat,
+            ProofFormat::Lrat,
+        ] {
+            let mut solver = Solver::new(&path).unwrap();
+            solver.config.proof_path = Some(proof.path().to_path_buf());
+            solver.config.proof_format = format;
+            solver.config.reduce_interval = 20;
+            assert!(!solver.solve().is_sat());
+            solver.finish_proof().unwrap();
+            let check = checker
+                .check(&std::fs::read(proof.path()).unwrap())
+                .unwrap();
+            assert_eq!(check.format, format, "{name}");
+            assert!(check.added > 0);
+        }
+    }
+
+    // Proofs of preprocessed formulas use RAT for eliminated variables
+    for seed in 0..20 {
+        let (cnf, clauses) = random_3sat(seed, 12, 60);
+        if brute_force(12, &clauses) {
+            continue;
+        }
+        let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+        solver.config.proof_path = Some(proof.path().to_path_buf());
+        solver.config.elimination = true;
+        solver.config.subsumption = true;
+        solver.config.vivification = true;
+        assert!(!solver.solve().is_sat());
+        solver.finish_proof().unwrap();
+        let checker = ProofChecker::from_bytes(cnf.as_bytes()).unwrap();
+        checker
+            .check(&std::fs::read(proof.path()).unwrap())
+            .unwrap_or_else(|e| panic!("seed {seed}: {e}"));
+    }
+
+    let checker = ProofChecker::from_bytes(b"p cnf 2 4\n1 2 0\n-1 2 0\n1 -2 0\n-1 -2 0\n").unwrap();
+    assert!(checker.check(b"2 0\n0\n").is_ok());
+    assert_eq!(checker.check(b"2 0\n"), Err(ProofError::Incomplete));
+    assert_eq!(
+        checker.check(b"1 0\n-1 0\n0\n").map(|check| check.added),
+        Ok(3)
+    );
+    // Neither RUP nor RAT once 2 is known
+    let checker = ProofChecker::from_bytes(b"p cnf 3 2\n1 2 0\n-1 2 0\n").unwrap();
+    assert_eq!(
+        checker.check(b"2 0\n-2 0\n0\n"),
+        Err(ProofError::NotImplied {
+            step: 2,
+            clause: lits(&[-2]),
+        })
+    );
+    let checker = ProofChecker::from_bytes(b"p cnf 2 4\n1 2 0\n-1 2 0\n1 -2 0\n-1 -2 0\n").unwrap();
+    assert_eq!(
+        checker
+            .check(b"5 2 0 1 2 0\n6 0 5 3 4 0\n")
+            .map(|check| check.format),
+        Ok(ProofFormat::Lrat)
+    );
+    // Hints that stop short of a conflict, and one naming no clause
+    assert!(matches!(
+        checker.check(b"5 2 0 1 0\n"),
+        Err(ProofError::NotImplied { step: 1, .. })
+    ));
+    assert_eq!(
+        checker.check(b"5 2 0 9 2 0\n"),
+        Err(ProofError::Malformed { step: 1 })
+    );
+    assert_eq!(
+        checker.check(b"5 2 0 1 2 0\n5 d 1 0\n5 d 1 0\n"),
+        Err(ProofError::Malformed { step: 3 })
+    );
+
+    run_solver("hole6.cnf")
+        .arg("--proof")
+        .arg(proof.path())
+        .assert()
+        .code(20);
+    run_solver("hole6.cnf")
+        .args(["--stats", "--check-proof"])
+        .arg(proof.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("s VERIFIED"))
+        .stdout(predicate::str::contains("proof format"));
+    std::fs::write(proof.path(), "1 0\n0\n").unwrap();
+    run_solver("hole6.cnf")
+        .arg("--check-proof")
+        .arg(proof.path())
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("step 1: clause `1 0`"))
+        .stdout(predicate::str::contains("s NOT VERIFIED"));
+    run_solver("hole6.cnf")
+        .args(["--all", "--check-proof"])
+        .arg(proof.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--check-proof cannot be combined"));
+}
+
+/// Checks that every step of `proof` is an input clause of `cnf` or the
+/// resolvent of its premises, and that the last one is empty.
+fn check_resolution_proof(cnf: &str, proof: &cnf_dpll_2wl::ResolutionProof) {
+    use cnf_dpll_2wl::ResolutionRule;
+
+    let inputs = dimacs_clauses(cnf);
+    let steps = proof.steps();
+    for (index, step) in steps.iter().enumerate() {
+        match step.rule {
+            ResolutionRule::Input { id } => {
+                let mut clause = lits(&inputs[id as usize - 1]);
+                clause.sort();
+                clause.dedup();
+                assert_eq!(step.clause, clause, "input clause {id}");
+            }
+            ResolutionRule::Resolve {
+                positive,
+                negative,
+                pivot,
+            } => {
+                assert!(positive < index && negative < index);
+                assert!(steps[positive].clause.contains(&pivot.positive()));
+                assert!(steps[negative].clause.contains(&pivot.negative()));
+                let mut resolvent: Vec<Lit> = steps[positive]
+                    .clause
+                    .iter()
+                    .chain(&steps[negative].clause)
+                    .copied()
+                    .filter(|lit| lit.var() != pivot)
+                    .collect();
+                resolvent.sort();
+                resolvent.dedup();
+                assert_eq!(step.clause, resolvent, "step {index}");
+            }
+        }
+    }
+    assert!(steps.last().unwrap().clause.is_empty());
+}
+
+#[test]
+fn test_resolution_proof() {
+    let cnf = "p cnf 2 4\n1 2 0\n-1 2 0\n1 -2 0\n-1 -2 0\n";
+    let mut solver = Solver::from_dimacs_str(cnf).unwrap();
+    solver.config.resolution_proof = true;
+    assert!(!solver.solve().is_sat());
+    let proof = solver.resolution_proof().unwrap();
+    check_resolution_proof(cnf, &proof);
+    let mut inputs: Vec<u64> = proof.inputs().collect();
+    inputs.sort();
+    assert_eq!(inputs, [1, 2, 3, 4]);
+    assert!(
+        proof
+            .to_string()
+            .starts_with(&format!("[{}] ⊥", proof.steps().len()))
+    );
+
+    // Not recorded, or no refutation
+    let mut solver = Solver::from_dimacs_str(cnf).unwrap();
+    assert!(!solver.solve().is_sat());
+    assert!(solver.resolution_proof().is_none());
+    let mut solver = Solver::from_dimacs_str("p cnf 2 1\n1 2 0\n").unwrap();
+    solver.config.resolution_proof = true;
+    assert!(solver.solve().is_sat());
+    assert!(solver.resolution_proof().is_none());
+
+    // The derivations of every pass turn into resolutions
+    let mut checked = 0;
+    for seed in 0..30 {
+        let (cnf, clauses) = random_3sat(seed, 12, 60);
+        if brute_force(12, &clauses) {
+            continue;
+        }
+        let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+        solver.config.resolution_proof = true;
+        solver.config.subsumption = seed % 2 == 0;
+        solver.config.elimination = seed % 3 == 0;
+        solver.config.probing = seed % 4 < 2;
+        solver.config.vivification = true;
+        solver.config.reduce_interval = 5;
+        assert!(!solver.solve().is_sat(), "seed {seed}");
+        check_resolution_proof(&cnf, &solver.resolution_proof().unwrap());
+        checked += 1;
+    }
+    assert!(checked > 5);
+
+    run_solver("test-UNSAT.cnf")
+        .arg("--resolution-proof")
+        .assert()
+        .code(20)
+        .stdout(predicate::str::contains("c resolution proof: "))
+        .stdout(predicate::str::contains("input clause 1"));
+    run_solver("test-UNSAT.cnf")
+        .args(["--resolution-proof", "--engine", "lookahead"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("needs the cdcl engine"));
+}
+
+#[test]
+fn test_model_satisfies_random_formulas() {
+    use cnf_dpll_2wl::Var;
+
+    for seed in 0..20 {
+        let (cnf, clauses) = random_3sat(seed, 12, 40);
+        let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+        if !solver.solve().is_sat() {
+            assert!(solver.model().is_none());
+            continue;
+        }
+        let model = solver.model().unwrap();
+        assert_eq!(model.num_vars(), 12);
+        for clause in &clauses {
+            assert!(lits(clause).into_iter().any(|l| model.lit_is_true(l)));
+        }
+        for lit in model.iter() {
+            assert!(model.lit_is_true(lit));
+            assert_eq!(model.value(lit.var()), !lit.sign());
+        }
+        // One literal per variable, in order, signed by its value, as the
+        // WebAssembly and Python bindings hand the model out
+        let line = model.to_dimacs();
+        assert_eq!(line.len(), 12);
+        for (i, &lit) in line.iter().enumerate() {
+            assert_eq!(lit.unsigned_abs() as usize, i + 1);
+            assert_eq!(lit > 0, model.value(Var::new(i + 1)));
+        }
+        let expected: Vec<String> = line.iter().map(|v| v.to_string()).collect();
+        assert_eq!(model.to_dimacs_string(), expected.join(" ") + " 0");
+        assert_eq!(model.value(Var::new(1)), line[0] > 0);
+    }
+
+    let mut solver = Solver::with_vars(1);
+    solver.add_clause(&[1]).unwrap();
+    assert!(solver.solve().is_sat());
+    solver.add_clause(&[-1]).unwrap();
+    assert_eq!(solver.model().unwrap().to_dimacs_string(), "1 0");
+    assert!(!solver.solve().is_sat());
+    assert!(solver.model().is_none());
+}
+
+#[test]
+fn test_cli_value_lines() {
+    let output = run_solver("zebra.cnf").output().unwrap();
+    assert_eq!(output.status.code(), Some(10));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let mut lines = stdout.lines();
+    assert_eq!(lines.next(), Some("s SATISFIABLE"));
+
+    let mut values = Vec::new();
+    for line in lines {
+        assert!(
+            line.starts_with("v ") && line.len() <= 78,
+            "bad line {line:?}"
+        );
+        values.extend(line[2..].split(' ').map(|v| v.parse::<i32>().unwrap()));
+    }
+    assert_eq!(values.pop(), Some(0));
+    let cnf = std::fs::read_to_string("examples/zebra.cnf").unwrap();
+    for clause in dimacs_clauses(&cnf) {
+        assert!(clause.iter().any(|l| values.contains(l)));
+    }
+}
+
+#[test]
+fn test_verify_model() {
+    use cnf_dpll_2wl::UnsatisfiedClause;
+
+    let mut solver = Solver::from_dimacs_str("p cnf 3 3\n1 2 0\n-1 3 0\n-2 -3 0\n").unwrap();
+    assert!(solver.solve().is_sat());
+    assert_eq!(solver.verify_model(), Ok(()));
+
+    // The clauses are checked as they were added, against the model with
+    // the eliminated variables filled in
+    let cnf = std::fs::read_to_string("examples/zebra.cnf").unwrap();
+    let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+    solver.config.equivalences = true;
+    solver.config.subsumption = true;
+    solver.config.elimination = true;
+    assert!(solver.solve().is_sat());
+    assert!(solver.stats().eliminated_vars > 0);
+    assert_eq!(solver.verify_model(), Ok(()));
+    // A clause added after the answer is checked against the same model
+    let lit = solver.model().unwrap().iter().next().unwrap();
+    solver.add_clause(&[-lit]).unwrap();
+    assert_eq!(
+        solver.verify_model(),
+        Err(UnsatisfiedClause {
+            literals: vec![-lit],
+            xor: false,
+            pb: None,
+        })
+    );
+
+    run_solver("zebra.cnf")
+        .arg("--check-model")
+        .assert()
+        .code(10)
+        .stdout(predicate::str::starts_with("s SATISFIABLE"));
+}
+
+#[test]
+fn test_stats() {
+    let cnf = std::fs::read_to_string("examples/hole6.cnf").unwrap();
+    let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+    solver.config.reduce_interval = 20;
+    assert!(!solver.solve().is_sat());
+
+    let stats = solver.stats().clone();
+    assert!(stats.decisions > 0 && stats.propagations > stats.decisions);
+    // Every conflict but the final one at level 0 produces a learnt clause
+    assert_eq!(stats.learnt_clauses + 1, stats.conflicts);
+    assert!(stats.deleted_clauses > 0 && stats.deleted_clauses < stats.learnt_clauses);
+    assert!(stats.max_decision_level > 0 && stats.max_decision_level <= 42);
+    assert!(stats.solve_time > std::time::Duration::ZERO);
+
+    // Counters accumulate over later calls
+    solver.solve();
+    assert!(solver.stats().solve_time > stats.solve_time);
+    assert_eq!(solver.stats().conflicts, stats.conflicts);
+
+    run_solver("hole6.cnf")
+        .arg("--stats")
+        .assert()
+        .code(20)
+        .stdout(
+            predicate::str::contains("c conflicts")
+                .and(predicate::str::ends_with("s UNSATISFIABLE\n")),
+        );
+}
+
+#[test]
+fn test_budgets_return_unknown_and_resume() {
+    use cnf_dpll_2wl::SolveResult;
+
+    let cnf = std::fs::read_to_string("examples/hole6.cnf").unwrap();
+    let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+    solver.config.conflict_limit = Some(50);
+    assert_eq!(solver.solve(), SolveResult::Unknown);
+    assert!(solver.stats().conflicts >= 50);
+    assert!(solver.model().is_none());
+
+    // Each call gets a fresh budget and keeps what was learnt so far
+    let mut calls = 1;
+    while solver.solve() == SolveResult::Unknown {
+        calls += 1;
+        assert!(calls < 100, "search does not make progress");
+    }
+    assert!(calls > 1);
+    assert_eq!(solver.solve(), SolveResult::Unsat);
+
+    let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+    solver.config.propagation_limit = Some(100);
+    assert_eq!(solver.solve(), SolveResult::Unknown);
+    solver.config.propagation_limit = None;
+    assert_eq!(solver.solve(), SolveResult::Unsat);
+}
+
+#[test]
+fn test_memory_limit() {
+    use cnf_dpll_2wl::SolveResult;
+
+    let cnf = pigeonhole_cnf(7);
+    let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+    solver.config.memory_limit = Some(1);
+    assert_eq!(solver.solve(), SolveResult::Unknown);
+    assert!(solver.stats().deleted_clauses > 0);
+    assert!(solver.stats().peak_memory > 0);
+    solver.config.memory_limit = None;
+    assert_eq!(solver.solve(), SolveResult::Unsat);
+    assert!(solver.stats().peak_memory >= solver.memory_usage() as u64);
+
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .args(["--stats", "--mem-limit", "512", "examples/hole6.cnf"])
+        .assert()
+        .code(20)
+        .stdout(predicate::str::contains("c peak memory"));
+    run_solver("hole6.cnf")
+        .args(["--mem-limit", "0"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("positive number of megabytes"));
+}
+
+/// DIMACS for placing `holes + 1` pigeons into `holes` holes, which is
+/// unsatisfiable and exponentially hard for resolution.
+fn pigeonhole_cnf(holes: usize) -> String {
+    let var = |pigeon: usize, hole: usize| pigeon * holes + hole + 1;
+    let mut clauses = Vec::new();
+    for pigeon in 0..=holes {
+        let lits: Vec<String> = (0..holes).map(|h| var(pigeon, h).to_string()).collect();
+        clauses.push(lits.join(" "));
+    }
+    for hole in 0..holes {
+        for a in 0..=holes {
+            for b in a + 1..=holes {
+                clauses.push(format!("-{} -{}", var(a, hole), var(b, hole)));
+            }
+        }
+    }
+    let mut cnf = format!("p cnf {} {}\n", (holes + 1) * holes, clauses.len());
+    for clause in clauses {
+        cnf.push_str(&clause);
+        cnf.push_str(" 0\n");
+    }
+    cnf
+}
+
+#[test]
+fn test_timeout() {
+    use cnf_dpll_2wl::SolveResult;
+    use std::time::{Duration, Instant};
+
+    let mut solver = Solver::from_dimacs_str(&pigeonhole_cnf(11)).unwrap();
+    solver.set_timeout(Duration::from_millis(200));
+    let start = Instant::now();
+    assert_eq!(solver.solve(), SolveResult::Unknown);
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .args(["--timeout", "0.2"])
+        .write_stdin(pigeonhole_cnf(11))
+        .assert()
+        .code(0)
+        .stdout("s UNKNOWN\n");
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .args(["--timeout", "soon"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--timeout"));
+}
+
+#[test]
+fn test_interrupt_handle() {
+    use cnf_dpll_2wl::SolveResult;
+    use std::time::Duration;
+
+    let mut solver = Solver::from_dimacs_str(&pigeonhole_cnf(11)).unwrap();
+    let handle = solver.interrupt_handle();
+    let interrupter = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(100));
+        handle.interrupt();
+    });
+    assert_eq!(solver.solve(), SolveResult::Unknown);
+    interrupter.join().unwrap();
+
+    // A pending interrupt stops the next call once, then is forgotten
+    let mut solver =
+        Solver::from_dimacs_str(&std::fs::read_to_string("examples/hole6.cnf").unwrap()).unwrap();
+    solver.interrupt_handle().interrupt();
+    assert_eq!(solver.solve(), SolveResult::Unknown);
+    assert_eq!(solver.solve(), SolveResult::Unsat);
+}
+
+#[test]
+fn test_tautologies_and_duplicate_literals() {
+    let mut solver =
+        Solver::from_dimacs_str("p cnf 3 4\n1 -1 0\n2 2 3 0\n-2 0\n3 -2 3 2 0\n").unwrap();
+    assert_eq!(solver.stats().tautologies_dropped, 2);
+    assert_eq!(solver.stats().clauses_deduplicated, 2);
+    assert_eq!(solver.clauses().count(), 2);
+    assert_eq!(solver.clauses().next().unwrap(), lits(&[2, 3]));
+    assert!(solver.solve().is_sat());
+    assert_eq!(solver.verify_model(), Ok(()));
+
+    run_cnf("p cnf 1 2\n1 1 0\n-1 0\n", false);
+    run_cnf("p cnf 2 3\n1 1 2 2 0\n-1 -1 0\n-2 0\n", false);
+}
+
+#[test]
+fn test_subsumption_preprocessing() {
+    // (1 2) subsumes (1 2 3); (1 -2 4) self-subsumes against (1 2), leaving (1 4)
+    let cnf = "p cnf 4 4\n1 2 3 0\n1 2 0\n1 -2 4 0\n-3 -4 0\n";
+    let mut solver = Solver::from_dimacs_str(cnf).unwrap();
+    solver.config.subsumption = true;
+    assert!(solver.solve().is_sat());
+    assert_eq!(solver.stats().subsumed_clauses, 1);
+    assert_eq!(solver.stats().strengthened_clauses, 1);
+    let inputs: Vec<&[Lit]> = solver
+        .clause_infos()
+        .filter(|info| !info.learnt)
+        .map(|info| info.literals)
+        .collect();
+    assert_eq!(inputs.len(), 3);
+    assert!(inputs.contains(&&lits(&[1, 4])[..]));
+
+    for seed in 0..30 {
+        let (cnf, clauses) = random_3sat(seed, 10, 45);
+        let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+        solver.config.subsumption = true;
+        let proof = tempfile::NamedTempFile::new().unwrap();
+        solver.config.proof_path = Some(proof.path().to_path_buf());
+        let sat = solver.solve().is_sat();
+        assert_eq!(sat, brute_force(10, &clauses), "seed {seed}");
+        solver.finish_proof().unwrap();
+        if sat {
+            let model = solver.model().unwrap();
+            for clause in &clauses {
+                assert!(lits(clause).into_iter().any(|l| model.lit_is_true(l)));
+            }
+        } else {
+            check_drat(&cnf, &std::fs::read_to_string(proof.path()).unwrap());
+        }
+    }
+
+    run_solver("zebra.cnf")
+        .args(["--pre=subsume", "--check-model"])
+        .assert()
+        .code(10);
+}
+
+#[test]
+fn test_occurrence_lists_drop_removed_clauses() {
+    // (1 2) subsumes 28 clauses, enough tombstones to sweep every list;
+    // (1 -2 31) is strengthened to (1 31), which then subsumes (1 31 32)
+    let mut cnf = String::from("p cnf 33 32\n1 2 0\n");
+    for k in 3..=30 {
+        cnf.push_str(&format!("1 2 {k} 0\n"));
+    }
+    cnf.push_str("1 -2 31 0\n1 31 32 0\n-1 33 0\n");
+    let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+    solver.config.subsumption = true;
+    solver.config.elimination = false;
+    assert!(solver.preprocess());
+    assert_eq!(solver.stats().subsumed_clauses, 29);
+    assert_eq!(solver.stats().strengthened_clauses, 1);
+    let mut inputs: Vec<Vec<Lit>> = solver
+        .clauses()
+        .map(|clause| {
+            let mut clause = clause.to_vec();
+            clause.sort();
+            clause
+        })
+        .collect();
+    inputs.sort();
+    let mut expected = [lits(&[1, 2]), lits(&[1, 31]), lits(&[-1, 33])];
+    for clause in expected.iter_mut() {
+        clause.sort();
+    }
+    expected.sort();
+    assert_eq!(inputs, expected);
+
+    // Elimination indexes what subsumption left: 33 only occurs in
+    // (-1 33), and goes with it
+    let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+    solver.config.subsumption = true;
+    solver.config.elimination = true;
+    assert!(solver.solve().is_sat());
+    assert!(solver.stats().eliminated_vars > 0);
+    assert!(
+        solver
+            .clauses()
+            .all(|clause| !clause.contains(&lits(&[33])[0]))
+    );
+    solver.verify_model().unwrap();
+}
+
+#[test]
+fn test_variable_elimination() {
+    for seed in 0..30 {
+        let (cnf, clauses) = random_3sat(seed, 10, 42);
+        let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+        solver.config.elimination = true;
+        solver.config.subsumption = seed % 2 == 0;
+        let proof = tempfile::NamedTempFile::new().unwrap();
+        solver.config.proof_path = Some(proof.path().to_path_buf());
+        let sat = solver.solve().is_sat();
+        assert_eq!(sat, brute_force(10, &clauses), "seed {seed}");
+        solver.finish_proof().unwrap();
+        if sat {
+            // The model must cover the eliminated variables too
+            let model = solver.model().unwrap();
+            for clause in &clauses {
+                assert!(lits(clause).into_iter().any(|l| model.lit_is_true(l)));
+            }
+        } else {
+            check_drat(&cnf, &std::fs::read_to_string(proof.path()).unwrap());
+        }
+    }
+}
+
+#[test]
+fn test_elimination_is_undone_by_later_clauses() {
+    // x1 only links x2 and x3, so it is eliminated first
+    let mut solver = Solver::with_vars(3);
+    solver.add_clause(&[1, 2]).unwrap();
+    solver.add_clause(&[-1, 3]).unwrap();
+    solver.config.elimination = true;
+    assert!(solver.solve().is_sat());
+    assert!(solver.stats().eliminated_vars > 0);
+
+    solver.add_clause(&[-1]).unwrap();
+    solver.add_clause(&[-2]).unwrap();
+    assert!(!solver.solve().is_sat());
+
+    let mut solver = Solver::with_vars(3);
+    solver.add_clause(&[1, 2]).unwrap();
+    solver.add_clause(&[-1, 3]).unwrap();
+    solver.add_clause(&[-2, -3]).unwrap();
+    solver.config.elimination = true;
+    assert!(solver.solve().is_sat());
+    assert!(!solver.solve_with_assumptions(&lits(&[-1, 3])).is_sat());
+    assert!(solver.solve_with_assumptions(&lits(&[1])).is_sat());
+    assert!(solver.model().unwrap().lit_is_true(lits(&[1])[0]));
+}
+
+#[test]
+fn test_frozen_variables_are_not_removed() {
+    let formula = || {
+        let mut solver = Solver::with_vars(4);
+        for clause in [&[-1, 2][..], &[1, -2], &[1, 3, 4], &[-3, -4, 2], &[3, -4]] {
+            solver.add_clause(clause).unwrap();
+        }
+        solver.config.equivalences = true;
+        solver.config.elimination = true;
+        solver
+    };
+    let mentions = |solver: &Solver, var: usize| {
+        solver
+            .clauses()
+            .flatten()
+            .any(|lit| lit.var() == Var::new(var))
+    };
+
+    // Nothing frozen is eliminated or substituted, and later clauses over
+    // frozen variables need nothing put back
+    let mut solver = formula();
+    for var in 1..=4 {
+        solver.freeze(Var::new(var));
+    }
+    assert!(solver.is_frozen(Var::new(3)));
+    assert!(solver.solve().is_sat());
+    assert_eq!(solver.stats().eliminated_vars, 0);
+    assert_eq!(solver.stats().substituted_vars, 0);
+    solver.add_clause(&[-2]).unwrap();
+    solver.add_clause(&[-3]).unwrap();
+    assert!(!solver.solve().is_sat());
+
+    // A frozen variable represents its equivalent ones
+    let mut solver = formula();
+    solver.config.elimination = false;
+    solver.freeze(Var::new(2));
+    assert!(solver.solve().is_sat());
+    assert_eq!(solver.stats().substituted_vars, 1);
+    assert!(mentions(&solver, 2) && !mentions(&solver, 1));
+
+    // Melting undoes freezing, and freezing an eliminated variable puts
+    // it back
+    let mut solver = formula();
+    solver.freeze(Var::new(1));
+    solver.melt(Var::new(1));
+    assert!(!solver.is_frozen(Var::new(1)));
+    assert!(solver.solve().is_sat());
+    assert!(solver.stats().eliminated_vars > 0);
+    assert!(!mentions(&solver, 1));
+    solver.freeze(Var::new(1));
+    assert!(mentions(&solver, 1));
+    assert!(solver.solve_with_assumptions(&lits(&[1])).is_sat());
+    assert!(!solver.solve_with_assumptions(&lits(&[-1, 2])).is_sat());
+}
+
+#[test]
+fn test_failed_literal_probing() {
+    // Assuming 1 propagates both 2 and -2, so -1 holds at level 0
+    let mut solver = Solver::from_dimacs_str("p cnf 4 3\n-1 2 0\n-1 -2 0\n1 3 4 0\n").unwrap();
+    solver.config.probing = true;
+    assert!(solver.solve().is_sat());
+    assert_eq!(solver.stats().failed_literals, 1);
+    assert!(solver.stats().literals_probed >= 2);
+    assert!(!solver.model().unwrap().lit_is_true(lits(&[1])[0]));
+
+    for seed in 0..20 {
+        let (cnf, clauses) = random_3sat(seed, 10, 45);
+        let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+        solver.config.probing = true;
+        let proof = tempfile::NamedTempFile::new().unwrap();
+        solver.config.proof_path = Some(proof.path().to_path_buf());
+        let sat = solver.solve().is_sat();
+        assert_eq!(sat, brute_force(10, &clauses), "seed {seed}");
+        solver.finish_proof().unwrap();
+        if !sat {
+            check_drat(&cnf, &std::fs::read_to_string(proof.path()).unwrap());
+        }
+    }
+}
+
+#[test]
+fn test_vivification() {
+    use cnf_dpll_2wl::RestartStrategy;
+
+    let cnf = std::fs::read_to_string("examples/hole6.cnf").unwrap();
+    let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+    solver.config.vivification = true;
+    solver.config.restart = RestartStrategy::Luby { unit: 4 };
+    let proof = tempfile::NamedTempFile::new().unwrap();
+    solver.config.proof_path = Some(proof.path().to_path_buf());
+    assert!(!solver.solve().is_sat());
+    assert!(solver.stats().vivified_clauses > 0);
+    solver.finish_proof().unwrap();
+    check_drat(&cnf, &std::fs::read_to_string(proof.path()).unwrap());
+
+    for seed in 0..20 {
+        let (cnf, clauses) = random_3sat(seed, 14, 60);
+        let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+        solver.config.vivification = true;
+        solver.config.restart = RestartStrategy::Luby { unit: 1 };
+        assert_eq!(
+            solver.solve().is_sat(),
+            brute_force(14, &clauses),
+            "seed {seed}"
+        );
+    }
+}
+
+#[test]
+fn test_equivalent_literal_substitution() {
+    // 1 -> 2 -> 3 -> 1 makes all three equivalent
+    let cnf = "p cnf 5 5\n-1 2 0\n-2 3 0\n-3 1 0\n1 4 5 0\n-3 -4 0\n";
+    let mut solver = Solver::from_dimacs_str(cnf).unwrap();
+    solver.config.equivalences = true;
+    assert!(solver.solve().is_sat());
+    assert_eq!(solver.stats().substituted_vars, 2);
+    let model = solver.model().unwrap();
+    for clause in dimacs_clauses(cnf) {
+        assert!(lits(&clause).into_iter().any(|l| model.lit_is_true(l)));
+    }
+
+    // 1 -> 2 -> -1 -> 3 -> 1 puts 1 and -1 in one component
+    let mut solver =
+        Solver::from_dimacs_str("p cnf 3 4\n-1 2 0\n-2 -1 0\n1 3 0\n-3 1 0\n").unwrap();
+    solver.config.equivalences = true;
+    assert!(!solver.solve().is_sat());
+
+    // Binary-heavy random formulas, checked against brute force and DRAT
+    for seed in 0..30 {
+        let (_, mut clauses) = random_3sat(seed, 10, 40);
+        for clause in clauses.iter_mut().step_by(2) {
+            clause.pop();
+        }
+        let mut cnf = format!("p cnf 10 {}\n", clauses.len());
+        for clause in &clauses {
+            let line: Vec<String> = clause.iter().map(|l| l.to_string()).collect();
+            cnf.push_str(&format!("{} 0\n", line.join(" ")));
+        }
+        let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+        solver.config.equivalences = true;
+        let proof = tempfile::NamedTempFile::new().unwrap();
+        solver.config.proof_path = Some(proof.path().to_path_buf());
+        let sat = solver.solve().is_sat();
+        assert_eq!(sat, brute_force(10, &clauses), "seed {seed}");
+        solver.finish_proof().unwrap();
+        if sat {
+            let model = solver.model().unwrap();
+            for clause in &clauses {
+                assert!(lits(clause).into_iter().any(|l| model.lit_is_true(l)));
+            }
+        } else {
+            check_drat(&cnf, &std::fs::read_to_string(proof.path()).unwrap());
+        }
+    }
+}
+
+#[test]
+fn test_binary_clauses_use_implication_lists() {
+    // A long chain of binaries: 1 -> 2 -> ... -> 40
+    let chain: String = (1..40).map(|v| format!("-{v} {} 0\n", v + 1)).collect();
+    let solver = Solver::from_dimacs_str(&format!("p cnf 40 39\n{chain}")).unwrap();
+    assert_eq!(solver.num_clauses(), 39);
+    run_cnf(&format!("p cnf 40 41\n1 0\n-40 0\n{chain}"), false);
+    run_cnf(&format!("p cnf 40 40\n1 0\n{chain}"), true);
+
+    // Binaries mixed with longer clauses, including learnt binaries
+    for seed in 0..20 {
+        let (cnf, clauses) = random_3sat(seed, 12, 40);
+        let binaries: String = clauses
+            .iter()
+            .take(15)
+            .map(|c| format!("{} {} 0\n", c[0], c[1]))
+            .collect();
+        let cnf = cnf.replacen(" 40\n", " 55\n", 1) + &binaries;
+        let mut all = clauses.clone();
+        all.extend(clauses.iter().take(15).map(|c| c[..2].to_vec()));
+        run_cnf(&cnf, brute_force(12, &all));
+    }
+}
+
+#[test]
+fn test_clause_database_introspection() {
+    let mut solver = Solver::from_dimacs_str(&pigeonhole_cnf(5)).unwrap();
+    let inputs = solver.num_original_clauses();
+    assert_eq!(solver.num_learnt_clauses(), 0);
+    assert!(
+        solver
+            .clause_infos()
+            .all(|info| info.visits == 0 && info.lbd == 0)
+    );
+    assert!(!solver.solve().is_sat());
+
+    assert_eq!(solver.num_original_clauses(), inputs);
+    assert!(solver.num_learnt_clauses() > 0);
+    assert_eq!(
+        solver.clauses().count(),
+        inputs + solver.num_learnt_clauses()
+    );
+    let infos: Vec<_> = solver.clause_infos().collect();
+    assert!(
+        infos
+            .iter()
+            .zip(solver.clauses())
+            .all(|(info, clause)| info.literals == clause)
+    );
+    assert!(
+        infos
+            .iter()
+            .filter(|info| info.learnt)
+            .all(|info| info.lbd > 0)
+    );
+    // Conflict analysis resolved on some input clauses and learnt ones
+    for learnt in [false, true] {
+        assert!(
+            infos
+                .iter()
+                .any(|info| info.learnt == learnt && info.visits > 0 && info.activity > 0.0)
+        );
+    }
+    assert!(
+        infos
+            .iter()
+            .all(|info| (info.visits == 0) == (info.activity == 0.0))
+    );
+    let first = infos[0];
+    assert_eq!(solver.clause_info(first.cref), first);
+}
+
+#[test]
+fn test_clause_refs_address_stored_clauses() {
+    let sorted = |clause: &[Lit]| {
+        let mut clause = clause.to_vec();
+        clause.sort();
+        clause
+    };
+    let long: Vec<i32> = (1..=40).map(|v| if v % 3 == 0 { -v } else { v }).collect();
+    let given = [vec![1, -2], vec![2, 3, -4], long, vec![-1, 4, 5, 6]];
+    let mut solver = Solver::with_vars(0);
+    for clause in &given {
+        solver.add_clause(clause).unwrap();
+    }
+    let stored: Vec<_> = solver
+        .clause_infos()
+        .map(|info| (info.cref, info.literals.to_vec()))
+        .collect();
+    assert_eq!(stored.len(), given.len());
+    for ((cref, literals), clause) in stored.iter().zip(&given) {
+        assert_eq!(sorted(literals), sorted(&lits(clause)));
+        assert_eq!(solver.clause(*cref), literals);
+    }
+    let mut crefs: Vec<_> = stored.iter().map(|(cref, _)| *cref).collect();
+    crefs.dedup();
+    assert_eq!(crefs.len(), given.len());
+
+    // Clauses added and learnt later go elsewhere in the arena
+    let (_, clauses) = random_3sat(3, 12, 60);
+    for clause in &clauses {
+        let shifted: Vec<i32> = clause.iter().map(|&l| l + 40 * l.signum()).collect();
+        solver.add_clause(&shifted).unwrap();
+    }
+    solver.solve();
+    assert!(solver.num_learnt_clauses() > 0);
+    for (cref, literals) in &stored {
+        assert_eq!(solver.clause(*cref), literals);
+    }
+    let infos: Vec<_> = solver.clause_infos().collect();
+    assert!(
+        infos
+            .iter()
+            .all(|info| solver.clause(info.cref) == info.literals)
+    );
+}
+
+#[test]
+fn test_reasons_of_propagated_literals() {
+    let mut solver = Solver::from_dimacs_str("p cnf 4 3\n1 0\n-1 2 0\n-2 -3 4 0\n").unwrap();
+    assert!(solver.solve_with_assumptions(&lits(&[3])).is_sat());
+    let reason = |var| {
+        solver
+            .clause(solver.reason(Var::new(var)).unwrap())
+            .to_vec()
+    };
+    assert_eq!(reason(1), lits(&[1]));
+    assert!(reason(2).contains(&lits(&[2])[0]));
+    assert!(reason(4).contains(&lits(&[4])[0]));
+    assert_eq!(solver.reason(Var::new(3)), None); // Assumption
+
+    // Failed literal probing records the unit it derives
+    let mut solver = Solver::from_dimacs_str("p cnf 2 2\n1 2 0\n1 -2 0\n").unwrap();
+    solver.config.probing = true;
+    assert!(solver.solve().is_sat());
+    let unit = solver.reason(Var::new(1)).unwrap();
+    assert_eq!(solver.clause(unit), lits(&[1]));
+}
+
+#[test]
+fn test_each_trail_literal_is_propagated_once() {
+    // The assumption 1 implies 2, then 3 and so on, each through a binary
+    // and a ternary clause: every literal joins the trail, and has its
+    // watches processed, once
+    let n = 50;
+    let mut cnf = format!("p cnf {n} {}\n", 2 * (n - 1));
+    for v in 1..n {
+        cnf.push_str(&format!("-{v} {} 0\n-1 -{v} {} 0\n", v + 1, v + 1));
+    }
+    let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+    for call in 1..=2 {
+        assert!(solver.solve_with_assumptions(&lits(&[1])).is_sat());
+        assert_eq!(solver.stats().propagations, call * n as u64);
+        assert_eq!(solver.decision_level(), 1);
+        for v in 1..=n {
+            assert_eq!(solver.level(Var::new(v)), Some(1));
+        }
+        for v in 2..=n as i32 {
+            let reason = solver.clause(solver.reason(Var::new(v as usize)).unwrap());
+            assert!(reason.contains(&lits(&[v])[0]));
+        }
+    }
+}
+
+#[test]
+fn test_decision_levels() {
+    // 1 is a fact, 2 follows from it; 3 and 4 are left to the search
+    let mut solver = Solver::from_dimacs_str("p cnf 4 3\n1 0\n-1 2 0\n3 4 0\n").unwrap();
+    assert!(solver.solve().is_sat());
+    assert!(solver.decision_level() >= 1);
+    assert_eq!(solver.level(Var::new(1)), Some(0));
+    assert_eq!(solver.level(Var::new(2)), Some(0));
+    assert_eq!(solver.value_at_level_zero(lits(&[1])[0]), Some(true));
+    assert_eq!(solver.value_at_level_zero(lits(&[-2])[0]), Some(false));
+    assert_eq!(solver.value_at_level_zero(lits(&[3])[0]), None);
+    assert!(solver.level(Var::new(3)).unwrap() >= 1);
+
+    assert!(solver.solve_with_assumptions(&lits(&[-3])).is_sat());
+    assert_eq!(solver.level(Var::new(3)), Some(1));
+    assert_eq!(solver.value_at_level_zero(lits(&[4])[0]), None);
+
+    solver.add_clause(&[-3]).unwrap();
+    assert_eq!(solver.decision_level(), 0);
+    assert_eq!(solver.level(Var::new(4)), None);
+    assert!(solver.solve().is_sat());
+    assert_eq!(solver.value_at_level_zero(lits(&[4])[0]), Some(true));
+}
+
+fn count_models(vars: i32, clauses: &[Vec<i32>]) -> usize {
+    (0u32..1 << vars)
+        .filter(|bits| {
+            clauses.iter().all(|c| {
+                c.iter()
+                    .any(|&lit| ((bits >> (lit.abs() - 1)) & 1 == 1) == (lit > 0))
+            })
+        })
+        .count()
+}
+
+#[test]
+fn test_model_enumeration() {
+    for seed in 0..20 {
+        let (cnf, clauses) = random_3sat(seed, 8, 20);
+        for pre in [false, true] {
+            let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+            solver.config.elimination = pre;
+            solver.config.subsumption = pre;
+            let mut models = solver.iter_models();
+            let mut seen = std::collections::HashSet::new();
+            while let Some(model) = models.next() {
+                assert_eq!(models.solver().verify_model(), Ok(()));
+                for clause in &clauses {
+                    assert!(lits(clause).into_iter().any(|l| model.lit_is_true(l)));
+                }
+                assert!(seen.insert(model.to_dimacs_string()), "seed {seed}");
+            }
+            assert!(models.is_complete());
+            assert_eq!(seen.len(), count_models(8, &clauses), "seed {seed}");
+        }
+    }
+
+    let mut solver = Solver::from_dimacs_str("p cnf 1 2\n1 0\n-1 0\n").unwrap();
+    assert_eq!(solver.iter_models().count(), 0);
+    assert_eq!(Solver::with_vars(0).iter_models().count(), 1);
+}
+
+#[test]
+fn test_cli_all_models() {
+    let output = cargo_bin_cmd!("cnf-dpll-2wl")
+        .arg("--all")
+        .write_stdin("p cnf 3 2\n1 2 0\n-1 -2 0\n")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(10));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines[0], "s SATISFIABLE");
+    assert_eq!(lines.iter().filter(|l| l.starts_with("v ")).count(), 4);
+    assert_eq!(lines.last(), Some(&"c models 4"));
+
+    run_solver("hole6.cnf")
+        .args(["--max-models", "3"])
+        .assert()
+        .code(20)
+        .stdout("s UNSATISFIABLE\nc models 0\n");
+    run_solver("zebra.cnf")
+        .args(["--max-models", "2", "--check-model"])
+        .assert()
+        .code(10)
+        .stdout(predicate::str::ends_with("c models 1\n"));
+    run_solver("hole6.cnf")
+        .args(["--all", "--proof", "/tmp/unused.drat"])
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn test_projected_model_enumeration() {
+    for seed in 0..20 {
+        let (cnf, clauses) = random_3sat(seed, 8, 20);
+        let mut expected = std::collections::HashSet::new();
+        for bits in 0u32..1 << 8 {
+            let sat = clauses.iter().all(|c| {
+                c.iter()
+                    .any(|&lit| ((bits >> (lit.abs() - 1)) & 1 == 1) == (lit > 0))
+            });
+            if sat {
+                expected.insert(bits & 0b1111);
+            }
+        }
+
+        let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+        let shown: Vec<Var> = (1..=4).map(Var::new).collect();
+        let mut found = std::collections::HashSet::new();
+        for model in solver.enumerate_models_over(&shown) {
+            let bits = shown
+                .iter()
+                .enumerate()
+                .fold(0, |acc, (i, &v)| acc | (model.value(v) as u32) << i);
+            assert!(found.insert(bits), "seed {seed}: projection repeated");
+        }
+        assert_eq!(found, expected, "seed {seed}");
+    }
+
+    // Projecting onto a variable the formula never mentions
+    let mut solver = Solver::from_dimacs_str("p cnf 1 1\n1 0\n").unwrap();
+    assert_eq!(solver.enumerate_models_over(&[Var::new(3)]).count(), 2);
+}
+
+#[test]
+fn test_approximate_model_count() {
+    // Few models: counted exactly
+    let (cnf, clauses) = random_3sat(3, 8, 30);
+    let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+    let exact = count_models(8, &clauses);
+    assert_eq!(solver.approx_count(0.8, 0.2), Some((exact as f64, 1.0)));
+
+    for seed in 0..3 {
+        let (cnf, clauses) = random_3sat(seed, 14, 20);
+        let exact = count_models(14, &clauses) as f64;
+        let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+        solver.config.seed = seed;
+        let (estimate, confidence) = solver.approx_count(0.8, 0.2).unwrap();
+        assert_eq!(confidence, 0.8);
+        assert!(
+            estimate <= exact * 1.8 && estimate >= exact / 1.8,
+            "seed {seed}: estimate {estimate}, exact {exact}"
+        );
+        // The hash constraints stay out of the solver itself
+        assert!(solver.solve().is_sat());
+        assert_eq!(solver.verify_model(), Ok(()));
+    }
+}
+
+#[test]
+fn test_maxsat_optimum() {
+    use cnf_dpll_2wl::{MaxSatAlgorithm, MaxSatResult, MaxSatSolver};
+
+    for seed in 0..30 {
+        // Hard clauses from one random formula, soft clauses from another,
+        // clause `i` weighing `i + 1`, 1, or one of three large weights
+        let (_, hard) = random_3sat(seed, 8, 12);
+        let (_, soft) = random_3sat(seed + 100, 8, 40);
+        let weight = |i: usize| match seed % 3 {
+            0 => i as u64 + 1,
+            1 => 1,
+            _ => [1_000_000_000, 3_000_000_001, 7_000_000_003][i % 3],
+        };
+        let satisfies = |bits: u32, c: &Vec<i32>| {
+            c.iter()
+                .any(|&lit| ((bits >> (lit.abs() - 1)) & 1 == 1) == (lit > 0))
+        };
+        let best = (0u32..1 << 8)
+            .filter(|&bits| hard.iter().all(|c| satisfies(bits, c)))
+            .map(|bits| {
+                (0..soft.len())
+                    .filter(|&i| !satisfies(bits, &soft[i]))
+                    .map(weight)
+                    .sum()
+            })
+            .min();
+
+        for algorithm in [
+            MaxSatAlgorithm::Linear,
+            MaxSatAlgorithm::Binary,
+            MaxSatAlgorithm::Oll,
+        ] {
+            let mut solver = MaxSatSolver::with_vars(8);
+            solver.algorithm = algorithm;
+            for clause in &hard {
+                solver.add_hard(clause).unwrap();
+            }
+            for (i, clause) in soft.iter().enumerate() {
+                solver.add_soft(clause, weight(i)).unwrap();
+            }
+            let result = solver.solve();
+            let Some(best) = best else {
+                assert_eq!(result, MaxSatResult::Unsat);
+                continue;
+            };
+            assert_eq!(result, MaxSatResult::Optimum, "seed {seed}");
+            assert_eq!(solver.cost(), Some(best), "seed {seed}, {algorithm:?}");
+            let model = solver.model().unwrap();
+            assert_eq!(model.num_vars(), 8);
+            for clause in &hard {
+                assert!(lits(clause).into_iter().any(|l| model.lit_is_true(l)));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_cli_maxsat() {
+    let wcnf = "c two of the three soft clauses at best\np wcnf 3 6 10\n10 1 2 0\n10 -1 -2 0\n1 1 0\n1 2 0\n1 3 0\n1 -3 0\n";
+    for algo in ["linear", "binary", "oll"] {
+        cargo_bin_cmd!("cnf-dpll-2wl")
+            .arg(format!("--maxsat-algo={algo}"))
+            .write_stdin(wcnf)
+            .assert()
+            .code(30)
+            .stdout(predicate::str::starts_with("o 2\ns OPTIMUM FOUND\nv "));
+    }
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .arg("--maxsat")
+        .write_stdin("p wcnf 1 2 10\n10 1 0\n10 -1 0\n")
+        .assert()
+        .code(20)
+        .stdout("s UNSATISFIABLE\n");
+    // The 2022 format: no header, `h` before hard clauses
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .arg("--maxsat")
+        .write_stdin("c weighted\nh 1 2 0\nh -1 -2 0\n3 1 0\n5 2 0\n")
+        .assert()
+        .code(30)
+        .stdout("o 3\ns OPTIMUM FOUND\nv -1 2 0\n");
+    // Large weights take one totalizer output per sum, not per unit
+    for algo in ["linear", "binary", "oll"] {
+        cargo_bin_cmd!("cnf-dpll-2wl")
+            .arg(format!("--maxsat-algo={algo}"))
+            .write_stdin("h 1 2 0\n3000000 -1 0\n5000000 -2 0\n")
+            .assert()
+            .code(30)
+            .stdout("o 3000000\ns OPTIMUM FOUND\nv 1 -2 0\n");
+    }
+}
+
+#[test]
+fn test_core_guided_maxsat() {
+    use cnf_dpll_2wl::{MaxSatAlgorithm, MaxSatResult, MaxSatSolver};
+
+    // Pigeonhole 6 -> 5 as soft clauses: one pigeon must stay out
+    let mut solver = MaxSatSolver::with_vars(30);
+    solver.algorithm = MaxSatAlgorithm::Oll;
+    let var = |p: i32, h: i32| p * 5 + h + 1;
+    for p in 0..6 {
+        let clause: Vec<i32> = (0..5).map(|h| var(p, h)).collect();
+        solver.add_soft(&clause, 1_000_000_000 + p as u64).unwrap();
+    }
+    for h in 0..5 {
+        for p in 0..6 {
+            for q in p + 1..6 {
+                solver.add_hard(&[-var(p, h), -var(q, h)]).unwrap();
+            }
+        }
+    }
+    assert_eq!(solver.solve(), MaxSatResult::Optimum);
+    assert_eq!(solver.cost(), Some(1_000_000_000));
+
+    let mut solver = MaxSatSolver::with_vars(1);
+    solver.algorithm = MaxSatAlgorithm::Oll;
+    solver.add_hard(&[1]).unwrap();
+    solver.add_hard(&[-1]).unwrap();
+    solver.add_soft(&[1], 1).unwrap();
+    assert_eq!(solver.solve(), MaxSatResult::Unsat);
+}
+
+#[test]
+fn test_wcnf_formats() {
+    use cnf_dpll_2wl::{MaxSatResult, MaxSatSolver, ParseError, SolverError};
+
+    let old = "p wcnf 3 4 100\n100 1 2 3 0\n100 -1 0\n7 -2 0\n4 -3\n 0\n";
+    let new = "c the same formula\nh 1 2 3 0\nh -1 0\n7 -2 0\n4 -3 0\n";
+    for wcnf in [old, new] {
+        let mut solver = MaxSatSolver::from_wcnf_str(wcnf).unwrap();
+        assert_eq!(solver.num_vars(), 3);
+        assert_eq!(solver.num_soft_clauses(), 2);
+        assert_eq!(solver.solve(), MaxSatResult::Optimum);
+        assert_eq!(solver.cost(), Some(4));
+    }
+    // Without a top every clause is soft
+    let solver = MaxSatSolver::from_wcnf_str("p wcnf 2 2\n1 1 0\n9 -2 0\n").unwrap();
+    assert_eq!(solver.num_soft_clauses(), 2);
+
+    let parse_error = |wcnf: &str| match MaxSatSolver::from_wcnf_str(wcnf) {
+        Err(SolverError::Parse(err)) => err,
+        _ => panic!("accepted invalid WCNF:\n{wcnf}"),
+    };
+    assert_eq!(
+        parse_error("h 1 0\n0 -1 0\n"),
+        ParseError::InvalidToken {
+            line: 2,
+            column: 1,
+            token: "0".to_string()
+        }
+    );
+    assert_eq!(
+        parse_error("p wcnf 2 1 10\nh 1 0\n"),
+        ParseError::InvalidToken {
+            line: 2,
+            column: 1,
+            token: "h".to_string()
+        }
+    );
+    assert_eq!(
+        parse_error("h 1 0\np wcnf 1 1\n"),
+        ParseError::InvalidHeader { line: 2 }
+    );
+    assert_eq!(
+        parse_error("h 1 2\n"),
+        ParseError::UnterminatedClause { line: 1 }
+    );
+}
+
+#[test]
+fn test_unsat_core_with_selectors() {
+    let mut solver = Solver::from_dimacs_str("p cnf 3 4\n2 3 0\n1 0\n1 -1 0\n-1 0\n").unwrap();
+    assert!(!solver.solve_with_selectors().is_sat());
+    // `2 3` takes no part, and the tautology is numbered but dropped
+    assert_eq!(solver.unsat_core(), &[1, 3]);
+    // The solver itself is untouched
+    assert_eq!(solver.num_vars(), 3);
+
+    for seed in 0..20 {
+        let (cnf, clauses) = random_3sat(seed, 8, 60);
+        let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+        let result = solver.solve_with_selectors();
+        if result.is_sat() {
+            let model = solver.model().unwrap();
+            for clause in &clauses {
+                assert!(lits(clause).into_iter().any(|l| model.lit_is_true(l)));
+            }
+            continue;
+        }
+        let core: Vec<Vec<i32>> = solver
+            .unsat_core()
+            .iter()
+            .map(|&i| clauses[i].clone())
+            .collect();
+        assert!(core.len() < clauses.len(), "seed {seed}");
+        assert_eq!(count_models(8, &core), 0, "seed {seed}");
+    }
+}
+
+#[test]
+fn test_unsat_core_written_as_dimacs() {
+    let cnf = "p cnf 3 6\n2 3 0\n1 2 0\n-1 2 0\n1 -2 -2 0\n3 -3 0\n-1 -2 0\n";
+    let mut solver = Solver::from_dimacs_str(cnf).unwrap();
+    assert!(!solver.solve_with_selectors().is_sat());
+    assert_eq!(solver.unsat_core(), &[1, 2, 3, 5]);
+    assert_eq!(solver.unsat_core_clauses()[2], lits(&[1, -2]));
+
+    // From the proof: the same clauses by ID, with no search of their own
+    let mut solver = Solver::from_dimacs_str(cnf).unwrap();
+    assert!(!solver.solve().is_sat());
+    assert!(solver.proof_core().is_none());
+    let mut solver = Solver::from_dimacs_str(cnf).unwrap();
+    solver.config.resolution_proof = true;
+    assert!(!solver.solve().is_sat());
+    let ids: Vec<u64> = solver
+        .proof_core()
+        .unwrap()
+        .iter()
+        .map(|&(id, _)| id)
+        .collect();
+    assert_eq!(ids, [2, 3, 4, 6]);
+
+    for seed in 0..20 {
+        let (cnf, clauses) = random_3sat(seed, 8, 60);
+        let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+        solver.config.resolution_proof = true;
+        solver.config.elimination = true;
+        if solver.solve().is_sat() {
+            continue;
+        }
+        let core: Vec<Vec<i32>> = solver
+            .proof_core()
+            .unwrap()
+            .iter()
+            .map(|&(id, literals)| {
+                let clause = &clauses[id as usize - 1];
+                let mut expected = lits(clause);
+                expected.sort();
+                expected.dedup();
+                let mut literals = literals.to_vec();
+                literals.sort();
+                assert_eq!(literals, expected, "seed {seed}");
+                clause.clone()
+            })
+            .collect();
+        assert_eq!(count_models(8, &core), 0, "seed {seed}");
+    }
+
+    let core = tempfile::NamedTempFile::new().unwrap();
+    let proof = tempfile::NamedTempFile::new().unwrap();
+    for proof_args in [vec![], vec!["--proof".as_ref(), proof.path().as_os_str()]] {
+        cargo_bin_cmd!("cnf-dpll-2wl")
+            .arg("-")
+            .write_stdin(cnf)
+            .arg("--core")
+            .arg(core.path())
+            .args(proof_args)
+            .assert()
+            .code(20)
+            .stdout(predicate::str::contains("c core of 4 clauses written"));
+        let written = std::fs::read_to_string(core.path()).unwrap();
+        assert!(written.starts_with("p cnf 3 4\nc clause 2\n1 2 0\nc clause 3\n"));
+        assert!(written.ends_with("c clause 4\n1 -2 0\nc clause 6\n-1 -2 0\n"));
+        run_cnf(&written, false);
+    }
+    run_solver("hole6.cnf")
+        .arg("--all")
+        .arg("--core")
+        .arg(core.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--core only applies"));
+}
+
+/// Random XOR clauses over `vars` variables, of one to six literals each.
+fn random_xors(seed: u64, vars: i32, count: usize) -> Vec<Vec<i32>> {
+    let mut state = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+    let mut next = move |bound: i32| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state % bound as u64) as i32
+    };
+    (0..count)
+        .map(|_| {
+            let len = next(6) + 1;
+            (0..len)
+                .map(|_| {
+                    let var = next(vars) + 1;
+                    if next(2) == 0 { var } else { -var }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn xor_holds(xor: &[i32], bits: u32) -> bool {
+    xor.iter()
+        .filter(|&&lit| ((bits >> (lit.abs() - 1)) & 1 == 1) == (lit > 0))
+        .count()
+        % 2
+        == 1
+}
+
+#[test]
+fn test_xor_clauses() {
+    for seed in 0..40 {
+        let (cnf, clauses) = random_3sat(seed, 10, 16 + seed as usize % 10);
+        let xors = random_xors(seed, 10, 3 + seed as usize % 5);
+        let models = (0u32..1 << 10)
+            .filter(|&bits| {
+                xors.iter().all(|xor| xor_holds(xor, bits))
+                    && clauses.iter().all(|c| {
+                        c.iter()
+                            .any(|&lit| ((bits >> (lit.abs() - 1)) & 1 == 1) == (lit > 0))
+                    })
+            })
+            .count();
+
+        // XOR lines in the DIMACS input, or XOR clauses added afterwards
+        let mut solver = if seed % 2 == 0 {
+            let header = format!("p cnf 10 {}\n", clauses.len() + xors.len());
+            let mut dimacs = cnf.replacen(&format!("p cnf 10 {}\n", clauses.len()), &header, 1);
+            for xor in &xors {
+                let line: Vec<String> = xor.iter().map(|l| l.to_string()).collect();
+                dimacs.push_str(&format!("x{} 0\n", line.join(" ")));
+            }
+            Solver::from_dimacs_str(&dimacs).unwrap()
+        } else {
+            let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+            for xor in &xors {
+                solver.add_xor_clause(xor).unwrap();
+            }
+            solver
+        };
+        solver.config.equivalences = seed % 4 < 2;
+        solver.config.elimination = seed % 3 == 0;
+        solver.config.probing = seed % 5 == 0;
+        solver.config.gauss = seed % 3 != 1;
+
+        let mut found = 0;
+        let mut iter = solver.iter_models();
+        while let Some(model) = iter.next() {
+            assert_eq!(iter.solver().verify_model(), Ok(()), "seed {seed}");
+            for xor in &xors {
+                let odd = lits(xor)
+                    .into_iter()
+                    .filter(|&l| model.lit_is_true(l))
+                    .count()
+                    % 2;
+                assert_eq!(odd, 1, "seed {seed}: {xor:?}");
+            }
+            found += 1;
+        }
+        assert_eq!(found, models, "seed {seed}");
+    }
+}
+
+#[test]
+fn test_xor_lines() {
+    let solver = Solver::from_dimacs_str("p cnf 4 3\nx1 2 -3 4 0\nx -2 2 3 0\n1 2 0\n").unwrap();
+    // `x -2 2 3` is the unit `-3`, and `x1 2 -3 4` has even parity
+    let xors = solver.xor_clauses();
+    assert_eq!(xors.len(), 1);
+    assert_eq!(
+        xors[0].vars(),
+        &[Var::new(1), Var::new(2), Var::new(3), Var::new(4)]
+    );
+    assert!(!xors[0].rhs());
+
+    // A long XOR chain that is unsatisfiable as a whole
+    let mut solver = Solver::with_vars(30);
+    for i in 1..30 {
+        solver.add_xor_clause(&[i, i + 1, 31]).unwrap();
+    }
+    solver.add_xor_clause(&[1, 30]).unwrap();
+    solver.add_xor_clause(&[31]).unwrap();
+    assert!(!solver.solve().is_sat());
+
+    assert_eq!(
+        parse_error("p cnf 2 1\nx1 2\n"),
+        cnf_dpll_2wl::ParseError::UnterminatedClause { line: 2 }
+    );
+    assert_eq!(
+        parse_error("p cnf 2 1\nx1 x2 0\n"),
+        cnf_dpll_2wl::ParseError::InvalidToken {
+            line: 2,
+            column: 4,
+            token: "x2".to_string()
+        }
+    );
+
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .arg("--check-model")
+        .write_stdin("p cnf 3 4\nx1 2 3 0\n-1 0\nx -2 -3 0\n-3 0\n")
+        .assert()
+        .code(10)
+        .stdout("s SATISFIABLE\nv -1 2 -3 0\n");
+}
+
+#[test]
+fn test_gauss_elimination() {
+    // Overlapping XOR clauses whose sum contradicts one more: only their
+    // combination shows it, so watching them one at a time has to search
+    let mut xors: Vec<Vec<i32>> = (1..=40).map(|i| vec![i, i + 1, i + 3]).collect();
+    let mut sum = std::collections::BTreeSet::new();
+    for xor in &xors {
+        for &v in xor {
+            if !sum.remove(&v) {
+                sum.insert(v);
+            }
+        }
+    }
+    let last: Vec<i32> = sum.into_iter().collect();
+    assert!(last.len() >= 3);
+    // The sum of 40 odd XORs is even, so requiring it odd is contradictory
+    xors.push(last);
+
+    for gauss in [false, true] {
+        let mut solver = Solver::with_vars(43);
+        solver.config.gauss = gauss;
+        for xor in &xors {
+            solver.add_xor_clause(xor).unwrap();
+        }
+        assert!(!solver.solve().is_sat());
+        assert_eq!(solver.stats().decisions == 0, gauss);
+    }
+
+    // Without the last clause the system is satisfiable, and the matrix
+    // implies values as soon as enough variables are decided
+    let mut solver = Solver::with_vars(43);
+    solver.config.gauss = true;
+    for xor in &xors[..40] {
+        solver.add_xor_clause(xor).unwrap();
+    }
+    assert!(solver.solve().is_sat());
+    assert_eq!(solver.verify_model(), Ok(()));
+    assert!(solver.stats().gauss_propagations > 0);
+
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .args(["--gauss", "--stats"])
+        .write_stdin("p cnf 4 3\nx1 2 3 0\nx2 3 4 0\nx1 4 0\n")
+        .assert()
+        .code(20)
+        .stdout(predicate::str::contains("c gauss conflicts"));
+}
+
+/// Terms, whether the comparison is `≥` (rather than `≤`), and the bound.
+type RandomPb = (Vec<(i64, i32)>, bool, i64);
+
+/// Random `Σ a_i·l_i ≥ k` (or `≤ k`) constraints over `vars` variables,
+/// with negative coefficients and repeated variables in the mix.
+fn random_pb(seed: u64, vars: i32, count: usize) -> Vec<RandomPb> {
+    let mut state = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+    let mut next = move |bound: i64| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state % bound as u64) as i64
+    };
+    (0..count)
+        .map(|_| {
+            let terms = (0..next(6) + 1)
+                .map(|_| {
+                    let var = next(vars as i64) as i32 + 1;
+                    let lit = if next(2) == 0 { var } else { -var };
+                    (next(17) - 8, lit)
+                })
+                .collect();
+            (terms, next(2) == 0, next(21) - 5)
+        })
+        .collect()
+}
+
+fn pb_holds(terms: &[(i64, i32)], at_least: bool, bound: i64, bits: u32) -> bool {
+    let sum: i64 = terms
+        .iter()
+        .filter(|&&(_, lit)| ((bits >> (lit.abs() - 1)) & 1 == 1) == (lit > 0))
+        .map(|&(a, _)| a)
+        .sum();
+    if at_least { sum >= bound } else { sum <= bound }
+}
+
+#[test]
+fn test_pseudo_boolean_constraints() {
+    use cnf_dpll_2wl::{PbConstraint, PbEncoding};
+
+    for seed in 0..60 {
+        let constraints = random_pb(seed, 6, 1 + seed as usize % 3);
+        let expected: std::collections::HashSet<u32> = (0u32..1 << 6)
+            .filter(|&bits| {
+                constraints
+                    .iter()
+                    .all(|(terms, at_least, bound)| pb_holds(terms, *at_least, *bound, bits))
+            })
+            .collect();
+
+        for encoding in [PbEncoding::Bdd, PbEncoding::Adder, PbEncoding::Native] {
+            let mut solver = Solver::with_vars(6);
+            solver.config.pb_encoding = encoding;
+            for (terms, at_least, bound) in &constraints {
+                let terms: Vec<(i64, Lit)> = terms
+                    .iter()
+                    .map(|&(a, lit)| (a, Lit::try_from(lit).unwrap()))
+                    .collect();
+                let constraint = if *at_least {
+                    PbConstraint::at_least(&terms, *bound)
+                } else {
+                    PbConstraint::at_most(&terms, *bound)
+                };
+                assert!(
+                    constraint
+                        .terms()
+                        .iter()
+                        .all(|&(a, _)| a <= constraint.bound())
+                );
+                solver.add_pb_constraint(&constraint);
+            }
+
+            // Projected onto the input variables, the models are exactly
+            // the assignments that meet every constraint
+            let shown: Vec<Var> = (1..=6).map(Var::new).collect();
+            let found: std::collections::HashSet<u32> = solver
+                .enumerate_models_over(&shown)
+                .map(|model| {
+                    shown
+                        .iter()
+                        .enumerate()
+                        .fold(0, |acc, (i, &v)| acc | (model.value(v) as u32) << i)
+                })
+                .collect();
+            assert_eq!(found, expected, "seed {seed}, {encoding:?}");
+        }
+    }
+
+    // `2·x1 + 3·-x1 ≥ 3` is `-x1 ≥ 1` once merged, and `x1 + x2 ≥ 3` is out of reach
+    let x1 = Lit::try_from(1).unwrap();
+    let x2 = Lit::try_from(2).unwrap();
+    let merged = PbConstraint::at_least(&[(2, x1), (3, -x1)], 3);
+    assert_eq!(merged.terms(), &[(1, -x1)]);
+    assert_eq!(merged.bound(), 1);
+    let mut solver = Solver::with_vars(2);
+    solver.add_pb_constraint(&PbConstraint::at_least(&[(1, x1), (1, x2)], 3));
+    assert!(!solver.solve().is_sat());
+}
+
+#[test]
+fn test_opb_input() {
+    use cnf_dpll_2wl::{MaxSatResult, MaxSatSolver, OpbProblem, ParseError, PbConstraint};
+
+    let opb = "* #variable= 4 #constraint= 2\nmin: +2 x1 -1 x3 ;\n+1 x1 +2 ~x2 +1 x3 >= 2 ;\n+1 x2 -1 x3 = 0;\n";
+    let problem = OpbProblem::from_opb_str(opb).unwrap();
+    assert_eq!(problem.variable_count, 4);
+    // The equality is two constraints
+    assert_eq!(problem.constraints.len(), 3);
+    let x = |v: i32| Lit::try_from(v).unwrap();
+    assert_eq!(problem.objective, Some(vec![(2, x(1)), (-1, x(3))]));
+    assert_eq!(
+        problem.constraints[0],
+        PbConstraint::at_least(&[(1, x(1)), (2, x(-2)), (1, x(3))], 2)
+    );
+
+    // x3 forces x2, and then x1: the optimum leaves all three false
+    let mut solver = MaxSatSolver::with_vars(problem.variable_count);
+    for constraint in &problem.constraints {
+        solver.add_pb_constraint(constraint.clone());
+    }
+    solver.add_soft(&[-1], 2).unwrap();
+    solver.add_soft(&[3], 1).unwrap();
+    assert_eq!(solver.solve(), MaxSatResult::Optimum);
+    let model = solver.model().unwrap();
+    assert_eq!(model.num_vars(), 4);
+    assert_eq!(problem.objective_value(&model), Some(0));
+
+    let parse_error = |opb: &str| match OpbProblem::from_opb_str(opb) {
+        Err(cnf_dpll_2wl::SolverError::Parse(e)) => e,
+        other => panic!("expected a parse error, got {other:?}"),
+    };
+    assert_eq!(
+        parse_error("+1 x1 >= 1 ;\n+1 x2 >= 1\n"),
+        ParseError::UnterminatedConstraint { line: 2 }
+    );
+    assert_eq!(
+        parse_error("+1 x1 +1 y2 >= 1 ;\n"),
+        ParseError::InvalidToken {
+            line: 1,
+            column: 10,
+            token: "y2".into()
+        }
+    );
+    assert_eq!(
+        parse_error("+1 x1 >= ;\n"),
+        ParseError::InvalidToken {
+            line: 1,
+            column: 10,
+            token: ";".into()
+        }
+    );
+    assert!(matches!(
+        parse_error("+1 x1 >= 1 ;\nmin: +1 x1 ;\n"),
+        ParseError::InvalidToken { line: 2, .. }
+    ));
+}
+
+#[test]
+fn test_cli_opb() {
+    let opb =
+        "min: +1 x1 +1 x2 +1 x3 ;\n+1 x1 +1 x2 >= 1 ;\n+1 x2 +1 x3 >= 1 ;\n+1 x1 +1 x3 >= 1 ;\n";
+    for encoding in ["bdd", "adder"] {
+        cargo_bin_cmd!("cnf-dpll-2wl")
+            .args(["--format=opb", "--check-model"])
+            .arg(format!("--pb-encoding={encoding}"))
+            .write_stdin(opb)
+            .assert()
+            .code(30)
+            .stdout(predicate::str::starts_with("o 2\ns OPTIMUM FOUND\nv "));
+    }
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .arg("--format=opb")
+        .write_stdin("min: +3000000 x1 +5000000 x2 ;\n+1 x1 +1 x2 >= 1 ;\n")
+        .assert()
+        .code(30)
+        .stdout("o 3000000\ns OPTIMUM FOUND\nv x1 -x2\n");
+    // Without an objective, a decision problem
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .arg("--format=opb")
+        .write_stdin("+3 x1 +2 ~x2 >= 4 ;\n")
+        .assert()
+        .code(10)
+        .stdout("s SATISFIABLE\nv x1 -x2\n");
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .arg("--format=opb")
+        .write_stdin("+1 x1 +1 x2 >= 2 ;\n+1 x1 +1 x2 <= 1 ;\n")
+        .assert()
+        .code(20)
+        .stdout("s UNSATISFIABLE\n");
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .args(["--format=opb", "--all"])
+        .write_stdin("+1 x1 >= 1 ;\n")
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn test_native_pseudo_boolean_propagation() {
+    use cnf_dpll_2wl::{PbConstraint, PbEncoding};
+
+    // Pigeonhole 7 -> 6 with a cardinality constraint per hole: no clause
+    // is stored, and every conflict comes from the constraints
+    let var = |p: i32, h: i32| Lit::try_from(p * 6 + h + 1).unwrap();
+    let mut solver = Solver::with_vars(42);
+    solver.config.pb_encoding = PbEncoding::Native;
+    for p in 0..7 {
+        let terms: Vec<(i64, Lit)> = (0..6).map(|h| (1, var(p, h))).collect();
+        solver.add_pb_constraint(&PbConstraint::at_least(&terms, 1));
+    }
+    for h in 0..6 {
+        let terms: Vec<(i64, Lit)> = (0..7).map(|p| (1, var(p, h))).collect();
+        solver.add_pb_constraint(&PbConstraint::at_most(&terms, 1));
+    }
+    assert_eq!(solver.pb_constraints().count(), 6);
+    assert!(!solver.solve().is_sat());
+    assert!(solver.stats().pb_conflicts > 0);
+
+    // Half of 100 weighted variables, under assumptions that change
+    // between calls
+    let mut solver = Solver::with_vars(100);
+    solver.config.pb_encoding = PbEncoding::Native;
+    let terms: Vec<(i64, Lit)> = (1..=100)
+        .map(|v| (v as i64 % 7 + 1, Lit::try_from(v).unwrap()))
+        .collect();
+    let total: i64 = terms.iter().map(|&(a, _)| a).sum();
+    solver.add_pb_constraint(&PbConstraint::at_least(&terms, total / 2));
+    solver.add_pb_constraint(&PbConstraint::at_most(&terms, total / 2));
+    for k in 0..10 {
+        let assumptions = lits(&[k * 10 + 1, -(k * 10 + 5)]);
+        assert!(solver.solve_with_assumptions(&assumptions).is_sat());
+        assert_eq!(solver.verify_model(), Ok(()));
+        let model = solver.model().unwrap();
+        let sum: i64 = terms
+            .iter()
+            .filter(|&&(_, lit)| model.lit_is_true(lit))
+            .map(|&(a, _)| a)
+            .sum();
+        assert_eq!(sum, total / 2);
+    }
+    assert!(solver.stats().pb_propagations > 0);
+
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .args(["--format=opb", "--pb-encoding=native", "--check-model"])
+        .write_stdin("min: +1 x1 +1 x2 +1 x3 ;\n+1 x1 +1 x2 >= 1 ;\n+1 x2 +1 x3 >= 1 ;\n")
+        .assert()
+        .code(30)
+        .stdout("o 1\ns OPTIMUM FOUND\nv -x1 x2 -x3\n");
+}
+
+/// A random formula over variables `a`..`e`, `depth` connectives deep.
+fn random_formula(next: &mut impl FnMut(u64) -> u64, depth: u32) -> cnf_dpll_2wl::Formula {
+    use cnf_dpll_2wl::Formula;
+
+    let leaf = |next: &mut dyn FnMut(u64) -> u64| match next(12) {
+        0 => Formula::Const(next(2) == 0),
+        n => Formula::var(["a", "b", "c", "d", "e"][n as usize % 5]),
+    };
+    if depth == 0 {
+        return leaf(next);
+    }
+    match next(7) {
+        0 => leaf(next),
+        1 => !random_formula(next, depth - 1),
+        2 => Formula::and(
+            (0..next(4))
+                .map(|_| random_formula(next, depth - 1))
+                .collect::<Vec<_>>(),
+        ),
+        3 => Formula::or(
+            (0..next(4))
+                .map(|_| random_formula(next, depth - 1))
+                .collect::<Vec<_>>(),
+        ),
+        4 => random_formula(next, depth - 1).xor(random_formula(next, depth - 1)),
+        5 => random_formula(next, depth - 1).iff(random_formula(next, depth - 1)),
+        _ => Formula::ite(
+            random_formula(next, depth - 1),
+            random_formula(next, depth - 1),
+            random_formula(next, depth - 1),
+        ),
+    }
+}
+
+#[test]
+fn test_tseitin_encoding() {
+    use cnf_dpll_2wl::{Formula, Tseitin};
+
+    let names = ["a", "b", "c", "d", "e"];
+    for seed in 0..200u64 {
+        let mut state = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+        let mut next = move |bound: u64| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state % bound
+        };
+        let formula = random_formula(&mut next, 4);
+        // The same subformula twice is defined once
+        let both = Formula::and([formula.clone(), formula.clone()]);
+        let expected: std::collections::HashSet<u32> = (0u32..1 << 5)
+            .filter(|&bits| {
+                formula.eval(&|name| {
+                    let i = names.iter().position(|&n| n == name).unwrap();
+                    bits >> i & 1 == 1
+                })
+            })
+            .collect();
+
+        let mut solver = Solver::with_vars(0);
+        let mut tseitin = Tseitin::new();
+        // Allocated up front, so that every name has a variable
+        let vars: Vec<Var> = names.iter().map(|n| tseitin.var(&mut solver, n)).collect();
+        tseitin.assert(&mut solver, &both);
+        let mut found = std::collections::HashSet::new();
+        for model in solver.enumerate_models_over(&vars) {
+            let bits = vars
+                .iter()
+                .enumerate()
+                .fold(0, |acc, (i, &v)| acc | (model.value(v) as u32) << i);
+            found.insert(bits);
+
+            // Every auxiliary variable has the value of its subformula
+            let values = |name: &str| model.value(tseitin.var_of(name).unwrap());
+            for var in (1..=model.num_vars()).map(Var::new) {
+                match (tseitin.name_of(var), tseitin.definition(var)) {
+                    (Some(name), None) => assert_eq!(values(name), model.value(var)),
+                    (None, Some(definition)) => {
+                        assert_eq!(definition.eval(&values), model.value(var), "seed {seed}")
+                    }
+                    (None, None) => {} // Stands for the constants
+                    (Some(_), Some(_)) => panic!("{var:?} is both named and defined"),
+                }
+            }
+        }
+        assert_eq!(found, expected, "seed {seed}: {formula:?}");
+    }
+
+    // (a → b) ∧ a ∧ ¬b is contradictory
+    let (a, b) = (Formula::var("a"), Formula::var("b"));
+    let mut solver = Solver::with_vars(0);
+    let mut tseitin = Tseitin::new();
+    tseitin.assert(&mut solver, &a.clone().implies(b.clone()));
+    tseitin.assert(&mut solver, &Formula::and([a, !b]));
+    assert!(!solver.solve().is_sat());
+    assert_eq!(tseitin.var_of("c"), None);
+}
+
+#[test]
+fn test_formula_parser() {
+    use cnf_dpll_2wl::{Formula, ParseError, Tseitin};
+
+    let formula = Formula::parse("(a & !b) -> (c | d)").unwrap();
+    let (a, b, c, d) = ["a", "b", "c", "d"].map(Formula::var).into();
+    assert_eq!(
+        formula,
+        Formula::and([a.clone(), !b.clone()]).implies(Formula::or([c.clone(), d.clone()]))
+    );
+    // Precedence, associativity and chains of the same operator
+    assert_eq!(
+        Formula::parse("a | b & ~c | d").unwrap(),
+        Formula::or([a.clone(), Formula::and([b.clone(), !c.clone()]), d.clone()])
+    );
+    assert_eq!(
+        "a -> b -> c".parse::<Formula>().unwrap(),
+        a.clone().implies(b.clone().implies(c.clone()))
+    );
+    assert_eq!(
+        Formula::parse("a ^ b <-> c ? true : false").unwrap(),
+        Formula::ite(
+            a.clone().xor(b.clone()).iff(c.clone()),
+            Formula::Const(true),
+            Formula::Const(false)
+        )
+    );
+
+    // Printing gives back an equivalent formula
+    let names = ["a", "b", "c", "d", "e"];
+    for seed in 0..100u64 {
+        let mut state = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+        let mut next = move |bound: u64| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state % bound
+        };
+        let formula = random_formula(&mut next, 4);
+        let printed = formula.to_string();
+        let parsed = Formula::parse(&printed).unwrap();
+        for bits in 0u32..1 << 5 {
+            let values =
+                |name: &str| bits >> names.iter().position(|&n| n == name).unwrap() & 1 == 1;
+            assert_eq!(parsed.eval(&values), formula.eval(&values), "{printed}");
+        }
+    }
+
+    // Fed to the Tseitin encoder, with the names looked up afterwards
+    let mut solver = Solver::with_vars(0);
+    let mut tseitin = Tseitin::new();
+    tseitin.assert(&mut solver, &formula);
+    tseitin.assert(&mut solver, &Formula::parse("a & !b & !c").unwrap());
+    assert!(solver.solve().is_sat());
+    let model = solver.model().unwrap();
+    assert!(model.value(tseitin.var_of("d").unwrap()));
+    assert_eq!(
+        tseitin.named_values(&model),
+        [("a", true), ("b", false), ("c", false), ("d", true)]
+    );
+
+    assert_eq!(Formula::parse("a &"), Err(ParseError::IncompleteFormula));
+    assert_eq!(Formula::parse(""), Err(ParseError::IncompleteFormula));
+    let invalid = |line, column, token: &str| ParseError::InvalidToken {
+        line,
+        column,
+        token: token.into(),
+    };
+    assert_eq!(Formula::parse("a & )"), Err(invalid(1, 5, ")")));
+    assert_eq!(Formula::parse("(a | b\n  c)"), Err(invalid(2, 3, "c")));
+    assert_eq!(Formula::parse("a & 1b"), Err(invalid(1, 5, "1b")));
+    assert_eq!(Formula::parse("a ? b"), Err(ParseError::IncompleteFormula));
+}
+
+/// A random and-inverter graph in ASCII AIGER: `inputs` inputs, then
+/// `gates` and gates over earlier literals, the last one as the output.
+fn random_aag(seed: u64, inputs: u64, gates: u64) -> String {
+    let mut state = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+    let mut next = move |bound: u64| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state % bound
+    };
+    let max_var = inputs + gates;
+    let mut aag = format!("aag {max_var} {inputs} 0 1 {gates}\n");
+    for i in 1..=inputs {
+        aag.push_str(&format!("{}\n", 2 * i));
+    }
+    aag.push_str(&format!("{}\n", 2 * max_var + next(2)));
+    for lhs in inputs + 1..=max_var {
+        // Constants included, and operands in decreasing order as the
+        // binary format wants them
+        let mut rhs = [next(2 * lhs), next(2 * lhs)];
+        rhs.sort_unstable_by(|a, b| b.cmp(a));
+        aag.push_str(&format!("{} {} {}\n", 2 * lhs, rhs[0], rhs[1]));
+    }
+    aag
+}
+
+/// The binary AIGER form of an ASCII graph without latches whose gates are
+/// numbered in order.
+fn aag_to_aig(aag: &str) -> Vec<u8> {
+    let lines: Vec<&str> = aag.lines().collect();
+    let header: Vec<u64> = lines[0][4..]
+        .split(' ')
+        .map(|n| n.parse().unwrap())
+        .collect();
+    let (inputs, outputs) = (header[1] as usize, header[3] as usize);
+    let mut aig = format!("aig {}\n", &lines[0][4..]).into_bytes();
+    for line in &lines[1 + inputs..1 + inputs + outputs] {
+        aig.extend_from_slice(format!("{line}\n").as_bytes());
+    }
+    for line in &lines[1 + inputs + outputs..] {
+        let gate: Vec<u64> = line.split(' ').map(|n| n.parse().unwrap()).collect();
+        for mut delta in [gate[0] - gate[1], gate[1] - gate[2]] {
+            while delta >= 0x80 {
+                aig.push(delta as u8 & 0x7f | 0x80);
+                delta >>= 7;
+            }
+            aig.push(delta as u8);
+        }
+    }
+    aig
+}
+
+#[test]
+fn test_aiger_input() {
+    use cnf_dpll_2wl::{Aiger, ParseError};
+
+    for seed in 0..100 {
+        let aag = random_aag(seed, 4, 12);
+        let aiger = Aiger::from_aag_str(&aag).unwrap();
+        assert_eq!(Aiger::from_reader(&aag_to_aig(&aag)[..]).unwrap(), aiger);
+
+        // Simulate the graph on every input vector
+        let simulate = |bits: u32| {
+            let mut values = vec![false; aiger.max_var + 1];
+            for (i, &input) in aiger.inputs.iter().enumerate() {
+                values[input as usize / 2] = bits >> i & 1 == 1;
+            }
+            let value = |values: &[bool], lit: u64| values[lit as usize / 2] != (lit % 2 == 1);
+            for &(lhs, rhs0, rhs1) in &aiger.ands {
+                values[lhs as usize / 2] = value(&values, rhs0) && value(&values, rhs1);
+            }
+            value(&values, aiger.outputs[0])
+        };
+        let satisfiable = (0u32..16).any(simulate);
+
+        let mut solver = aiger.to_solver();
+        assert_eq!(solver.solve().is_sat(), satisfiable, "seed {seed}");
+        if satisfiable {
+            let values = aiger.input_values(&solver.model().unwrap());
+            let bits = values
+                .iter()
+                .enumerate()
+                .fold(0, |acc, (i, &v)| acc | (v as u32) << i);
+            assert!(simulate(bits), "seed {seed}");
+        }
+    }
+
+    // A miter of x ⊕ y against (x ∨ y) ∧ ¬(x ∧ y): the circuits agree
+    let miter = "aag 11 2 0 1 9\n2\n4\n23\n6 5 2\n8 4 3\n10 9 7\n12 5 3\n14 4 2\n16 15 13\n18 17 11\n20 16 10\n22 21 19\ni0 x\ni1 y\nc\nmiter\n";
+    let aiger = Aiger::from_aag_str(miter).unwrap();
+    assert_eq!(aiger.input_names, [Some("x".into()), Some("y".into())]);
+    assert!(!aiger.to_solver().solve().is_sat());
+
+    let parse_error = |aag: &str| match Aiger::from_aag_str(aag) {
+        Err(cnf_dpll_2wl::SolverError::Parse(e)) => e,
+        other => panic!("expected a parse error, got {other:?}"),
+    };
+    assert_eq!(parse_error("aag 1 1 0\n"), ParseError::InvalidAigerHeader);
+    assert_eq!(
+        parse_error("aag 1 1 0 1 0 0 0 1 0\n2\n2\n"),
+        ParseError::InvalidAigerHeader
+    );
+    // Counts far beyond the file are an error, not an allocation
+    assert_eq!(
+        parse_error("aag 3 2147483648 0 1 0\n2\n"),
+        ParseError::UnexpectedEnd { line: 3 }
+    );
+    assert_eq!(
+        parse_error("aag 3 1 0 18446744073709551615 0 1\n"),
+        ParseError::InvalidAigerHeader
+    );
+    assert!(matches!(
+        Aiger::from_reader(&b"aig 3 18446744073709551615 1 0 0\n"[..]),
+        Err(cnf_dpll_2wl::SolverError::Parse(
+            ParseError::InvalidAigerHeader
+        ))
+    ));
+    assert_eq!(
+        parse_error("aag 18446744073709551615 1 0 1 0\n2\n"),
+        ParseError::InvalidAigerHeader
+    );
+    assert_eq!(
+        ParseError::InvalidAigerHeader.to_string(),
+        "line 1: invalid AIGER header"
+    );
+    assert_eq!(
+        parse_error("aag 1 1 0 1 0\n2\n4\n"),
+        ParseError::LiteralOutOfRange {
+            line: 3,
+            column: 1,
+            literal: 4,
+            variables: 1
+        }
+    );
+    assert_eq!(
+        parse_error("aag 2 1 0 1 1\n2\n4\n"),
+        ParseError::UnexpectedEnd { line: 4 }
+    );
+    assert_eq!(
+        parse_error("aag 2 1 0 1 1\n2\n4\n5 2 2\n"),
+        ParseError::InvalidToken {
+            line: 4,
+            column: 1,
+            token: "5".into()
+        }
+    );
+
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .args(["--format=aiger", "--check-model"])
+        .write_stdin("aag 3 2 0 1 1\n2\n4\n6\n6 5 2\n")
+        .assert()
+        .code(10)
+        .stdout("s SATISFIABLE\nv 1 -2 0\n");
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .arg("--format=aiger")
+        .write_stdin(miter)
+        .assert()
+        .code(20)
+        .stdout("s UNSATISFIABLE\n");
+}
+
+#[test]
+fn test_icnf_input() {
+    use cnf_dpll_2wl::{Icnf, IcnfStep, ParseError};
+
+    let icnf = Icnf::from_icnf_str("c trace\np inccnf\n1 2 0\na -1\n0\n-2 3 0\na 0\n").unwrap();
+    assert_eq!(icnf.variable_count, 3);
+    assert_eq!(
+        icnf.steps,
+        [
+            IcnfStep::Clause(lits(&[1, 2])),
+            IcnfStep::Assume(lits(&[-1])),
+            IcnfStep::Clause(lits(&[-2, 3])),
+            IcnfStep::Assume(Vec::new()),
+        ]
+    );
+    assert_eq!(icnf.clauses().count(), 2);
+    assert_eq!(icnf.query_count(), 2);
+
+    // Random traces: each query answers like brute force over the clauses
+    // so far with the assumptions as units
+    for seed in 0..20 {
+        let (_, clauses) = random_3sat(seed, 10, 48);
+        let mut trace = String::from("p inccnf\n");
+        let mut expected = String::new();
+        for (round, chunk) in clauses.chunks(12).enumerate() {
+            for clause in chunk {
+                let line: Vec<String> = clause.iter().map(i32::to_string).collect();
+                trace.push_str(&format!("{} 0\n", line.join(" ")));
+            }
+            let prefix = &clauses[..12 * round + chunk.len()];
+            let assumptions = [1 + round as i32, -(5 + round as i32)];
+            trace.push_str(&format!("a {} {} 0\n", assumptions[0], assumptions[1]));
+            let mut assumed = prefix.to_vec();
+            assumed.extend(assumptions.iter().map(|&a| vec![a]));
+            expected.push_str(if count_models(10, &assumed) > 0 {
+                "s SATISFIABLE\n"
+            } else {
+                "s UNSATISFIABLE\n"
+            });
+        }
+        let output = cargo_bin_cmd!("cnf-dpll-2wl")
+            .args(["--format=icnf", "--check-model"])
+            .write_stdin(trace)
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let statuses: String = stdout
+            .lines()
+            .filter(|line| line.starts_with("s "))
+            .map(|line| format!("{line}\n"))
+            .collect();
+        assert_eq!(statuses, expected, "seed {seed}");
+        let code = if expected.ends_with("s SATISFIABLE\n") {
+            10
+        } else {
+            20
+        };
+        assert_eq!(output.status.code(), Some(code), "seed {seed}");
+    }
+
+    let parse_error = |icnf: &str| match Icnf::from_icnf_str(icnf) {
+        Err(cnf_dpll_2wl::SolverError::Parse(e)) => e,
+        other => panic!("expected a parse error, got {other:?}"),
+    };
+    assert_eq!(parse_error("1 2 0\n"), ParseError::MissingHeader);
+    assert_eq!(
+        parse_error("p cnf 2 1\n1 2 0\n"),
+        ParseError::InvalidHeader { line: 1 }
+    );
+    assert_eq!(
+        parse_error("p inccnf\n1 2 0\na 1\n"),
+        ParseError::UnterminatedClause { line: 3 }
+    );
+    assert_eq!(
+        parse_error("p inccnf\n1 a 0\n"),
+        ParseError::InvalidToken {
+            line: 2,
+            column: 3,
+            token: "a".into()
+        }
+    );
+
+    // Failed assumptions follow an unsatisfiable answer; without queries
+    // the clauses are solved once
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .arg("--format=icnf")
+        .write_stdin("p inccnf\n-1 -2 0\na 1 2 3 0\na 1 0\n")
+        .assert()
+        .code(10)
+        .stdout(predicate::str::starts_with("s UNSATISFIABLE\nf "))
+        .stdout(predicate::str::contains("s SATISFIABLE\nv 1 -2 "));
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .arg("--format=icnf")
+        .write_stdin("p inccnf\n1 0\n-1 0\n")
+        .assert()
+        .code(20)
+        .stdout("s UNSATISFIABLE\nf 0\n");
+}
+
+#[test]
+fn test_group_mus() {
+    use cnf_dpll_2wl::{MusSolver, ParseError, SolveResult, SolverError};
+
+    let gcnf = "c example\np gcnf 3 7 4\n{0} 1 2 0\n{1} -1 0\n{2} -2 0\n{2} 3 0\n{3} -1 2 0\n{4} 1\n-3 0\n{4} -2 0\n";
+    let mut solver = MusSolver::from_gcnf_str(gcnf).unwrap();
+    assert_eq!(
+        (solver.num_vars(), solver.num_clauses(), solver.num_groups()),
+        (3, 7, 4)
+    );
+    assert_eq!(solver.solve(), SolveResult::Unsat);
+    assert_eq!(solver.mus(), &[1, 2]);
+
+    // Background clauses unsatisfiable on their own, then no MUS at all
+    let mut solver = MusSolver::with_vars(1);
+    solver.add_clause(0, &[1]).unwrap();
+    solver.add_clause(0, &[-1]).unwrap();
+    solver.add_clause(3, &[1]).unwrap();
+    assert_eq!(solver.solve(), SolveResult::Unsat);
+    assert!(solver.mus().is_empty());
+    let mut solver = MusSolver::with_vars(2);
+    solver.add_clause(1, &[1, 2]).unwrap();
+    solver.add_clause(2, &[-1]).unwrap();
+    assert_eq!(solver.solve(), SolveResult::Sat);
+
+    // Every group of the MUS is needed, and the MUS is unsatisfiable
+    let mut checked = 0;
+    for seed in 0..30 {
+        let (_, clauses) = random_3sat(seed, 8, 50);
+        let group = |index: usize| (index * 7 + seed as usize) % 13;
+        let mut solver = MusSolver::with_vars(8);
+        for (index, clause) in clauses.iter().enumerate() {
+            solver.add_clause(group(index), clause).unwrap();
+        }
+        let result = solver.solve();
+        assert_eq!(result.is_sat(), brute_force(8, &clauses), "seed {seed}");
+        if result.is_sat() {
+            continue;
+        }
+        let mus = solver.mus().to_vec();
+        let select = |groups: &[usize]| -> Vec<Vec<i32>> {
+            (0..clauses.len())
+                .filter(|&index| group(index) == 0 || groups.contains(&group(index)))
+                .map(|index| clauses[index].clone())
+                .collect()
+        };
+        assert!(!brute_force(8, &select(&mus)), "seed {seed}");
+        for left_out in &mus {
+            let rest: Vec<usize> = mus.iter().copied().filter(|g| g != left_out).collect();
+            assert!(
+                brute_force(8, &select(&rest)),
+                "seed {seed}: {left_out} not needed"
+            );
+        }
+        assert!(solver.checks() >= mus.len() as u64);
+        checked += 1;
+    }
+    assert!(checked > 5);
+
+    let parse_error = |gcnf: &str| match MusSolver::from_gcnf_str(gcnf) {
+        Err(SolverError::Parse(err)) => err,
+        _ => panic!("{gcnf:?} should not parse"),
+    };
+    assert_eq!(
+        parse_error("p cnf 1 1\n{1} 1 0\n"),
+        ParseError::InvalidHeader { line: 1 }
+    );
+    assert_eq!(parse_error("{1} 1 0\n"), ParseError::MissingHeader);
+    for (gcnf, column) in [
+        ("p gcnf 1 1 1\n{2} 1 0\n", 1),
+        ("p gcnf 1 1 1\n1 0\n", 1),
+        ("p gcnf 1 2 1\n{1} 1 {1} -1 0\n", 7),
+        ("p gcnf 1 1 1\n{1} x1 0\n", 5),
+    ] {
+        assert!(
+            matches!(parse_error(gcnf), ParseError::InvalidToken { line: 2, column: c, .. } if c == column),
+            "{gcnf:?}"
+        );
+    }
+    // The clause count is not checked, as in CNF files
+    assert!(MusSolver::from_gcnf_str("p gcnf 1 2 1\n{1} 1 0\n").is_ok());
+
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .arg("--format=gcnf")
+        .write_stdin(gcnf)
+        .assert()
+        .code(20)
+        .stdout("s UNSATISFIABLE\nv 1 2 0\n");
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .args(["--format=gcnf", "--proof", "proof.drat"])
+        .write_stdin(gcnf)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_qbf_solver() {
+    use cnf_dpll_2wl::{ParseError, QbfResult, QbfSolver};
+
+    // Random ∀x1..x4 ∃x5..x10 formulas against brute force, the
+    // existential block split over two lines
+    let mut answers = [0; 2];
+    for seed in 0..60 {
+        let (_, clauses) = random_3sat(seed, 10, 4 + seed as usize % 12);
+        let body: String = clauses
+            .iter()
+            .map(|c| format!("{} {} {} 0\n", c[0], c[1], c[2]))
+            .collect();
+        let qdimacs = format!(
+            "p cnf 10 {}\na 1 2 3 4 0\ne 5 6 7 0\ne 8 9 10 0\n{body}",
+            clauses.len()
+        );
+        let holds = |bits: u32| {
+            clauses.iter().all(|c| {
+                c.iter()
+                    .any(|&lit| ((bits >> (lit.abs() - 1)) & 1 == 1) == (lit > 0))
+            })
+        };
+        let expected = (0u32..16).all(|x| (0u32..64).any(|y| holds(x | y << 4)));
+        answers[expected as usize] += 1;
+
+        let mut solver = QbfSolver::from_qdimacs_str(&qdimacs).unwrap();
+        let universals: Vec<usize> = solver.universals().iter().map(|v| v.index()).collect();
+        assert_eq!(universals, [1, 2, 3, 4]);
+        let result = solver.solve();
+        assert_eq!(result == QbfResult::True, expected, "seed {seed}");
+        match result {
+            QbfResult::True => assert!(solver.witness().is_none()),
+            _ => {
+                // No existential values satisfy the formula under the
+                // universal counterexample
+                let witness = solver.witness().unwrap();
+                let x = witness
+                    .iter()
+                    .filter(|l| !l.sign())
+                    .fold(0, |acc, l| acc | 1 << (l.var().index() - 1));
+                assert_eq!(witness.len(), 4);
+                assert!(!(0u32..64).any(|y| holds(x | y << 4)), "seed {seed}");
+            }
+        }
+    }
+    assert!(answers[0] > 0 && answers[1] > 0, "{answers:?}");
+
+    // ∃∀: the universal literals are reduced away, and the witness assigns
+    // the existential block
+    let mut solver =
+        QbfSolver::from_qdimacs_str("p cnf 3 3\ne 1 2 0\na 3 0\n1 3 0\n-1 2 -3 0\n3 -3 0\n")
+            .unwrap();
+    assert_eq!(solver.solve(), QbfResult::True);
+    assert_eq!(solver.witness(), Some(&lits(&[1, 2])[..]));
+    let mut solver = QbfSolver::from_qdimacs_str("p cnf 2 1\ne 1 0\na 2 0\n2 0\n").unwrap();
+    assert_eq!(solver.solve(), QbfResult::False);
+    // A universal block alone is false unless every clause is a tautology
+    let mut solver = QbfSolver::from_qdimacs_str("p cnf 2 1\na 1 2 0\n1 -2 0\n").unwrap();
+    assert_eq!(solver.solve(), QbfResult::False);
+    assert_eq!(solver.witness(), Some(&lits(&[-1, 2])[..]));
+
+    let parse_error = |qdimacs: &str| match QbfSolver::from_qdimacs_str(qdimacs) {
+        Err(cnf_dpll_2wl::SolverError::Parse(e)) => e,
+        other => panic!("expected a parse error, got {:?}", other.map(|_| ())),
+    };
+    assert_eq!(
+        parse_error("p cnf 3 1\ne 1 0\na 2 0\ne 3 0\n1 2 3 0\n"),
+        ParseError::UnsupportedPrefix { line: 3 }
+    );
+    // Free variables are quantified outside the universal block
+    assert_eq!(
+        parse_error("p cnf 3 1\na 1 0\ne 2 0\n1 2 3 0\n"),
+        ParseError::UnsupportedPrefix { line: 2 }
+    );
+    assert_eq!(
+        parse_error("p cnf 2 1\na 1 0\n1 2 0\ne 2 0\n"),
+        ParseError::InvalidToken {
+            line: 4,
+            column: 1,
+            token: "e".into()
+        }
+    );
+    assert_eq!(
+        parse_error("p cnf 2 1\na 1 0\ne 1 2 0\n1 2 0\n"),
+        ParseError::InvalidToken {
+            line: 3,
+            column: 3,
+            token: "1".into()
+        }
+    );
+    assert_eq!(
+        parse_error("p cnf 2 1\na 3 0\n1 2 0\n"),
+        ParseError::LiteralOutOfRange {
+            line: 2,
+            column: 3,
+            literal: 3,
+            variables: 2
+        }
+    );
+
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .arg("--format=qdimacs")
+        .write_stdin("p cnf 2 2\na 1 0\ne 2 0\n1 2 0\n-1 -2 0\n")
+        .assert()
+        .code(10)
+        .stdout("s cnf 1 2 2\n");
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .arg("--format=qdimacs")
+        .write_stdin("p cnf 2 3\na 1 0\ne 2 0\n1 2 0\n-1 -2 0\n2 0\n")
+        .assert()
+        .code(20)
+        .stdout("s cnf 0 2 3\nV 1 0\n");
+}
+
+#[test]
+fn test_terminate_and_learn_callbacks() {
+    use cnf_dpll_2wl::SolveResult;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    // The terminate callback stops the search after a few polls
+    let mut solver = Solver::from_dimacs_str(&pigeonhole_cnf(9)).unwrap();
+    let polls = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&polls);
+    solver.set_terminate(move || counter.fetch_add(1, Ordering::Relaxed) >= 100);
+    assert_eq!(solver.solve(), SolveResult::Unknown);
+    assert_eq!(polls.load(Ordering::Relaxed), 101);
+
+    // Learnt clauses are passed on up to the length limit, and are implied
+    // by the formula
+    let mut solver = Solver::from_dimacs_str(&pigeonhole_cnf(5)).unwrap();
+    let learnt = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&learnt);
+    solver.set_learn(3, move |clause| sink.lock().unwrap().push(clause.to_vec()));
+    assert_eq!(solver.solve(), SolveResult::Unsat);
+    let learnt = learnt.lock().unwrap();
+    assert!(!learnt.is_empty());
+    for clause in learnt.iter() {
+        assert!(clause.len() <= 3);
+        let mut check = Solver::from_dimacs_str(&pigeonhole_cnf(5)).unwrap();
+        let negated: Vec<Lit> = clause.iter().map(|&lit| -lit).collect();
+        assert_ne!(check.solve_with_assumptions(&negated), SolveResult::Sat);
+    }
+}
+
+#[test]
+fn test_event_callbacks() {
+    use cnf_dpll_2wl::{RestartStrategy, SolveResult};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct Events {
+        decisions: Vec<(Lit, usize)>,
+        conflicts: u64,
+        learnt: u64,
+        restarts: u64,
+    }
+
+    // Each callback fires as often as the counters say
+    let mut solver = Solver::from_dimacs_str(&pigeonhole_cnf(5)).unwrap();
+    solver.config.restart = RestartStrategy::Luby { unit: 4 };
+    let events = Arc::new(Mutex::new(Events::default()));
+    let sink = Arc::clone(&events);
+    solver.on_decision(move |lit, level| sink.lock().unwrap().decisions.push((lit, level)));
+    let sink = Arc::clone(&events);
+    solver.on_conflict(move |clause, level| {
+        assert!(!clause.is_empty() && level <= 30); // one level per variable at most
+        sink.lock().unwrap().conflicts += 1;
+    });
+    let sink = Arc::clone(&events);
+    solver.on_learned_clause(move |clause, lbd| {
+        assert!(lbd <= clause.len());
+        sink.lock().unwrap().learnt += 1;
+    });
+    let sink = Arc::clone(&events);
+    solver.on_restart(move |stats, config| {
+        sink.lock().unwrap().restarts += 1;
+        assert_eq!(stats.restarts, sink.lock().unwrap().restarts);
+        // Tuning as the search goes
+        config.var_decay = 0.9;
+    });
+    assert_eq!(solver.solve(), SolveResult::Unsat);
+    let events = events.lock().unwrap();
+    let stats = solver.stats();
+    assert_eq!(events.decisions.len() as u64, stats.decisions);
+    assert!(events.decisions.iter().all(|&(_, level)| level >= 1));
+    assert_eq!(events.conflicts, stats.conflicts);
+    assert_eq!(events.learnt, stats.learnt_clauses);
+    assert_eq!(events.restarts, stats.restarts);
+    assert!(events.restarts > 0);
+    assert_eq!(solver.config.var_decay, 0.9);
+
+    // A conflict callback can stop the search through an interrupt handle,
+    // which is polled once the conflicts in a row are resolved
+    let mut solver = Solver::from_dimacs_str(&pigeonhole_cnf(8)).unwrap();
+    let handle = solver.interrupt_handle();
+    solver.on_conflict(move |_, _| handle.interrupt());
+    assert_eq!(solver.solve(), SolveResult::Unknown);
+    assert!(solver.stats().conflicts < 10);
+}
+
+#[test]
+fn test_external_propagator() {
+    use cnf_dpll_2wl::{Model, Propagator, SolveResult};
+    use std::collections::HashSet;
+
+    /// At most one variable of each group is true
+    struct AtMostOne {
+        groups: Vec<Vec<Var>>,
+        trail: Vec<(Lit, usize)>,
+        emitted: HashSet<Lit>,
+        reasons: Vec<(Lit, Vec<Lit>)>,
+    }
+
+    impl Propagator for AtMostOne {
+        fn notify_assignment(&mut self, lit: Lit, level: usize) {
+            self.trail.push((lit, level));
+        }
+
+        fn notify_backtrack(&mut self, level: usize) {
+            self.trail.retain(|&(_, l)| l <= level);
+            self.emitted.clear();
+        }
+
+        fn propagate(&mut self) -> Option<Lit> {
+            for &(lit, _) in &self.trail {
+                let Some(group) = self
+                    .groups
+                    .iter()
+                    .find(|g| !lit.sign() && g.contains(&lit.var()))
+                else {
+                    continue;
+                };
+                for &other in group {
+                    let implied = other.negative();
+                    if other != lit.var() && self.emitted.insert(implied) {
+                        self.reasons.push((implied, vec![implied, -lit]));
+                        return Some(implied);
+                    }
+                }
+            }
+            None
+        }
+
+        fn reason(&mut self, lit: Lit) -> Vec<Lit> {
+            let index = self.reasons.iter().rposition(|(l, _)| *l == lit).unwrap();
+            self.reasons.swap_remove(index).1
+        }
+    }
+
+    // Pigeonhole with the holes left to the propagator
+    let (pigeons, holes) = (6, 5);
+    let var = |pigeon: usize, hole: usize| Var::new(pigeon * holes + hole + 1);
+    let mut solver = Solver::with_vars(pigeons * holes);
+    for pigeon in 0..pigeons {
+        let clause: Vec<i32> = (0..holes).map(|h| var(pigeon, h).index() as i32).collect();
+        solver.add_clause(&clause).unwrap();
+    }
+    let groups = (0..holes)
+        .map(|h| (0..pigeons).map(|p| var(p, h)).collect())
+        .collect();
+    solver.connect_propagator(AtMostOne {
+        groups,
+        trail: Vec::new(),
+        emitted: HashSet::new(),
+        reasons: Vec::new(),
+    });
+    assert_eq!(solver.solve(), SolveResult::Unsat);
+    assert!(solver.stats().external_propagations > 0);
+
+    // With one pigeon fewer every hole holds at most one
+    let mut solver = Solver::with_vars(pigeons * holes);
+    for pigeon in 0..pigeons - 1 {
+        let clause: Vec<i32> = (0..holes).map(|h| var(pigeon, h).index() as i32).collect();
+        solver.add_clause(&clause).unwrap();
+    }
+    let groups: Vec<Vec<Var>> = (0..holes)
+        .map(|h| (0..pigeons).map(|p| var(p, h)).collect())
+        .collect();
+    solver.connect_propagator(AtMostOne {
+        groups: groups.clone(),
+        trail: Vec::new(),
+        emitted: HashSet::new(),
+        reasons: Vec::new(),
+    });
+    assert_eq!(solver.solve(), SolveResult::Sat);
+    let model = solver.model().unwrap();
+    for group in &groups {
+        assert!(group.iter().filter(|&&v| model.value(v)).count() <= 1);
+    }
+    assert!(solver.disconnect_propagator().is_some());
+
+    /// Accepts only models with an odd number of true variables, blocking
+    /// the others
+    struct OddParity {
+        blocked: Option<Vec<Lit>>,
+    }
+
+    impl Propagator for OddParity {
+        fn reason(&mut self, _: Lit) -> Vec<Lit> {
+            unreachable!()
+        }
+
+        fn add_clause(&mut self) -> Option<Vec<Lit>> {
+            self.blocked.take()
+        }
+
+        fn check_model(&mut self, model: &Model) -> bool {
+            let odd = model.iter().filter(|lit| !lit.sign()).count() % 2 == 1;
+            if !odd {
+                self.blocked = Some(model.iter().map(|lit| -lit).collect());
+            }
+            odd
+        }
+    }
+
+    let mut solver = Solver::with_vars(4);
+    solver.add_clause(&[-1, -2]).unwrap();
+    solver.connect_propagator(OddParity { blocked: None });
+    assert_eq!(solver.solve(), SolveResult::Sat);
+    let model = solver.model().unwrap();
+    assert_eq!(model.iter().filter(|lit| !lit.sign()).count() % 2, 1);
+    // The only model left has two true variables
+    for clause in [[1], [-2], [3], [-4]] {
+        solver.add_clause(&clause).unwrap();
+    }
+    assert_eq!(solver.solve(), SolveResult::Unsat);
+    assert!(solver.stats().external_clauses > 0);
+}
+
+#[test]
+fn test_portfolio_solver() {
+    use cnf_dpll_2wl::{PortfolioSolver, SolveResult};
+
+    // Every worker reaches the same answers as a single solver
+    for seed in 0..20 {
+        let (cnf, clauses) = random_3sat(seed, 12, 50);
+        let mut portfolio = PortfolioSolver::new(Solver::from_dimacs_str(&cnf).unwrap(), 4);
+        let result = portfolio.solve();
+        assert_eq!(result.is_sat(), brute_force(12, &clauses), "seed {seed}");
+        assert!(portfolio.winner().is_some_and(|winner| winner < 4));
+        if result.is_sat() {
+            portfolio.verify_model().unwrap();
+            let model = portfolio.model().unwrap();
+            assert!(
+                clauses
+                    .iter()
+                    .all(|c| c.iter().any(|&l| model.lit_is_true(lits(&[l])[0])))
+            );
+        }
+    }
+    let hole6 = std::fs::read_to_string("examples/hole6.cnf").unwrap();
+    let mut portfolio = PortfolioSolver::new(Solver::from_dimacs_str(&hole6).unwrap(), 3);
+    assert_eq!(portfolio.solve(), SolveResult::Unsat);
+    assert!(portfolio.stats().conflicts > 0);
+    assert!(portfolio.into_solver().model().is_none());
+
+    // An interrupt stops every worker once
+    let mut portfolio = PortfolioSolver::new(Solver::from_dimacs_str(&hole6).unwrap(), 3);
+    portfolio.interrupt_handle().interrupt();
+    assert_eq!(portfolio.solve(), SolveResult::Unknown);
+    assert_eq!(portfolio.winner(), None);
+    assert_eq!(portfolio.solve(), SolveResult::Unsat);
+
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .args(["--threads", "4", "--check-model", "examples/sudoku.cnf"])
+        .assert()
+        .code(10)
+        .stdout(predicate::str::starts_with("s SATISFIABLE\nv "));
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .args(["--threads", "2", "examples/hole6.cnf"])
+        .assert()
+        .code(20);
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .args(["--threads", "2", "--all", "examples/hole6.cnf"])
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn test_portfolio_clause_sharing() {
+    use cnf_dpll_2wl::PortfolioSolver;
+
+    // Answers stay right while workers exchange clauses
+    for seed in 0..6 {
+        let (cnf, _) = random_3sat(seed, 60, 256);
+        let expected = Solver::from_dimacs_str(&cnf).unwrap().solve();
+        let mut portfolio = PortfolioSolver::new(Solver::from_dimacs_str(&cnf).unwrap(), 3);
+        portfolio.share_lbd = 8;
+        assert_eq!(portfolio.solve(), expected, "seed {seed}");
+        if expected.is_sat() {
+            portfolio.verify_model().unwrap();
+        }
+    }
+
+    let hole = pigeonhole_cnf(7);
+    let mut portfolio = PortfolioSolver::new(Solver::from_dimacs_str(&hole).unwrap(), 3);
+    portfolio.share_lbd = usize::MAX;
+    assert!(!portfolio.solve().is_sat());
+    assert!(portfolio.stats().imported_clauses > 0);
+
+    let mut portfolio = PortfolioSolver::new(Solver::from_dimacs_str(&hole).unwrap(), 3);
+    portfolio.share_lbd = 0;
+    assert!(!portfolio.solve().is_sat());
+    assert_eq!(portfolio.stats().imported_clauses, 0);
+}
+
+#[cfg(feature = "capi")]
+#[test]
+fn test_ipasir() {
+    use cnf_dpll_2wl::capi::*;
+    use std::ffi::{CStr, c_int, c_void};
+
+    extern "C" fn stop(data: *mut c_void) -> c_int {
+        let polls = unsafe { &mut *data.cast::<u32>() };
+        *polls += 1;
+        (*polls > 10) as c_int
+    }
+    extern "C" fn count(data: *mut c_void, clause: *mut i32) {
+        let (learnt, longest) = unsafe { &mut *data.cast::<(u32, usize)>() };
+        let len = (0..)
+            .take_while(|&i| unsafe { *clause.add(i) } != 0)
+            .count();
+        *learnt += 1;
+        *longest = (*longest).max(len);
+    }
+
+    unsafe {
+        let signature = CStr::from_ptr(ipasir_signature()).to_str().unwrap();
+        assert!(signature.starts_with("cnf-dpll-2wl"));
+
+        let solver = ipasir_init();
+        for lit in [1, 2, 0, -1, 3, 0, -2, -3, 0] {
+            ipasir_add(solver, lit);
+        }
+        assert_eq!(ipasir_solve(solver), 10);
+        let values: Vec<i32> = (1..=3).map(|v| ipasir_val(solver, v)).collect();
+        assert!(values == [1, -2, 3] || values == [-1, 2, -3], "{values:?}");
+        assert_eq!(ipasir_val(solver, 7), 0);
+
+        // Assumptions last for one call, and the failed ones are reported
+        ipasir_assume(solver, 1);
+        ipasir_assume(solver, 2);
+        ipasir_assume(solver, -4);
+        assert_eq!(ipasir_solve(solver), 20);
+        assert_eq!(ipasir_failed(solver, -4), 0);
+        assert_eq!(ipasir_failed(solver, 1) + ipasir_failed(solver, 2), 2);
+        assert_eq!(ipasir_solve(solver), 10);
+        ipasir_add(solver, 1);
+        ipasir_add(solver, 0);
+        ipasir_add(solver, 2);
+        ipasir_add(solver, 0);
+        assert_eq!(ipasir_solve(solver), 20);
+        // Without a model there are no values, and no panic either
+        assert_eq!(ipasir_val(solver, 1), 0);
+        ipasir_release(solver);
+
+        // INT_MIN is no literal: its clause is dropped, the assumption
+        // ignored, and it has no value
+        let solver = ipasir_init();
+        assert_eq!(ipasir_val(solver, 1), 0);
+        for lit in [1, 0, -1, i32::MIN, 0, 2, 0] {
+            ipasir_add(solver, lit);
+        }
+        ipasir_assume(solver, i32::MIN);
+        assert_eq!(ipasir_solve(solver), 10);
+        assert_eq!(ipasir_val(solver, 1), 1);
+        assert_eq!(ipasir_val(solver, i32::MIN), 0);
+        assert_eq!(ipasir_val(solver, 0), 0);
+        ipasir_add(solver, -2);
+        ipasir_add(solver, 0);
+        assert_eq!(ipasir_solve(solver), 20);
+        assert_eq!(ipasir_failed(solver, i32::MIN), 0);
+        ipasir_release(solver);
+
+        let solver = ipasir_init();
+        for line in pigeonhole_cnf(9).lines().skip(1) {
+            for token in line.split_whitespace() {
+                ipasir_add(solver, token.parse().unwrap());
+            }
+        }
+        let mut polls = 0u32;
+        let mut learnt = (0u32, 0usize);
+        ipasir_set_terminate(solver, (&raw mut polls).cast(), Some(stop));
+        ipasir_set_learn(solver, (&raw mut learnt).cast(), 4, Some(count));
+        assert_eq!(ipasir_solve(solver), 0);
+        assert_eq!(polls, 11);
+        assert!(learnt.1 <= 4);
+        ipasir_release(solver);
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_checkpoint_and_resume() {
+    use cnf_dpll_2wl::{PbConstraint, PbEncoding, SolveResult};
+
+    // A search cut short by its conflict budget carries on after resuming,
+    // learnt clauses and statistics included
+    let mut solver = Solver::from_dimacs_str(&pigeonhole_cnf(7)).unwrap();
+    solver.config.conflict_limit = Some(300);
+    assert_eq!(solver.solve(), SolveResult::Unknown);
+    let checkpoint = tempfile::NamedTempFile::new().unwrap();
+    let path = checkpoint.path().to_str().unwrap();
+    solver.save(path).unwrap();
+    let mut resumed = Solver::resume(path).unwrap();
+    assert_eq!(resumed.num_vars(), solver.num_vars());
+    assert_eq!(resumed.clauses().count(), solver.clauses().count());
+    assert_eq!(resumed.stats(), solver.stats());
+    assert_eq!(resumed.config.conflict_limit, Some(300));
+    resumed.config.conflict_limit = None;
+    assert_eq!(resumed.solve(), SolveResult::Unsat);
+
+    // Level-0 values, XOR clauses and native pseudo-Boolean constraints are
+    // kept, and the solver saved is left as it was
+    let mut solver = Solver::from_dimacs_str("p cnf 6 3\n1 0\n-1 2 3 0\n-4 -5 0\n").unwrap();
+    solver.add_xor_clause(&[3, 4, 6]).unwrap();
+    solver.config.pb_encoding = PbEncoding::Native;
+    let terms: Vec<(i64, Lit)> = lits(&[2, 3, 4, 5, 6]).into_iter().map(|l| (1, l)).collect();
+    solver.add_pb_constraint(&PbConstraint::at_most(&terms, 2));
+    assert_eq!(solver.solve(), SolveResult::Sat);
+    solver.save(path).unwrap();
+    let mut resumed = Solver::resume(path).unwrap();
+    assert_eq!(resumed.value_at_level_zero(lits(&[1])[0]), Some(true));
+    assert_eq!(resumed.xor_clauses(), solver.xor_clauses());
+    for solver in [&mut solver, &mut resumed] {
+        assert_eq!(
+            solver.solve_with_assumptions(&lits(&[-2])),
+            SolveResult::Sat
+        );
+        solver.verify_model().unwrap();
+        assert_eq!(
+            solver.solve_with_assumptions(&lits(&[2, 3, 4])),
+            SolveResult::Unsat
+        );
+    }
+
+    // Anything but a checkpoint is refused
+    std::fs::write(path, "p cnf 1 1\n1 0\n").unwrap();
+    assert!(matches!(
+        Solver::resume(path),
+        Err(cnf_dpll_2wl::SolverError::Io(_))
+    ));
+}
+
+#[test]
+fn test_lookahead_engine() {
+    use cnf_dpll_2wl::{Engine, PbConstraint, PbEncoding, SolveResult};
+
+    for seed in 0..40 {
+        let (cnf, clauses) = random_3sat(seed, 12, 52);
+        let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+        solver.config.engine = Engine::Lookahead;
+        let sat = solver.solve().is_sat();
+        assert_eq!(sat, brute_force(12, &clauses), "seed {seed}");
+        if sat {
+            solver.verify_model().unwrap();
+        }
+    }
+
+    // Pigeonhole formulas are refuted with a DRAT proof
+    for cnf in [
+        std::fs::read_to_string("examples/hole6.cnf").unwrap(),
+        pigeonhole_cnf(5),
+    ] {
+        let proof = tempfile::NamedTempFile::new().unwrap();
+        let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+        solver.config.engine = Engine::Lookahead;
+        solver.config.proof_path = Some(proof.path().to_path_buf());
+        assert_eq!(solver.solve(), SolveResult::Unsat);
+        assert!(solver.stats().failed_literals > 0);
+        solver.finish_proof().unwrap();
+        check_drat(&cnf, &std::fs::read_to_string(proof.path()).unwrap());
+    }
+
+    // Assumptions, XOR clauses and native pseudo-Boolean constraints
+    let mut solver = Solver::with_vars(6);
+    solver.config.engine = Engine::Lookahead;
+    solver.config.pb_encoding = PbEncoding::Native;
+    solver.add_clause(&[-1, 2]).unwrap();
+    solver.add_clause(&[-2, 3]).unwrap();
+    solver.add_xor_clause(&[3, 4, 5]).unwrap();
+    let terms: Vec<(i64, Lit)> = lits(&[4, 5, 6]).into_iter().map(|l| (1, l)).collect();
+    solver.add_pb_constraint(&PbConstraint::at_least(&terms, 2));
+    assert_eq!(
+        solver.solve_with_assumptions(&lits(&[1, -3])),
+        SolveResult::Unsat
+    );
+    let failed = solver.failed_assumptions().to_vec();
+    assert!(!failed.is_empty() && failed.iter().all(|lit| lits(&[1, -3]).contains(lit)));
+    assert_eq!(solver.solve_with_assumptions(&lits(&[1])), SolveResult::Sat);
+    solver.verify_model().unwrap();
+    let model = solver.model().unwrap();
+    assert!(model.value(Var::new(3)));
+
+    run_solver("hole6.cnf")
+        .arg("--engine=lookahead")
+        .assert()
+        .code(20)
+        .stdout("s UNSATISFIABLE\n");
+    run_solver("test-SAT.cnf")
+        .arg("--engine=lookahead")
+        .arg("--check-model")
+        .assert()
+        .code(10);
+    run_solver("hole6.cnf")
+        .arg("--engine=vsids")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown engine vsids"));
+}
+
+#[test]
+fn test_walksat_engine() {
+    use cnf_dpll_2wl::{Engine, SolveResult};
+    use std::time::Duration;
+
+    for seed in 0..10 {
+        let (cnf, _) = random_3sat(seed, 150, 570);
+        if !Solver::from_dimacs_str(&cnf).unwrap().solve().is_sat() {
+            continue;
+        }
+        let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+        solver.config.engine = Engine::WalkSat;
+        solver.config.seed = seed;
+        assert_eq!(solver.solve(), SolveResult::Sat, "seed {seed}");
+        solver.verify_model().unwrap();
+        assert!(solver.stats().flips > 0);
+    }
+
+    // Local search never proves unsatisfiability, but a conflict among the
+    // assumptions is found by propagation
+    let mut solver = Solver::from_dimacs_str(&pigeonhole_cnf(4)).unwrap();
+    solver.config.engine = Engine::WalkSat;
+    solver.set_timeout(Duration::from_millis(100));
+    assert_eq!(solver.solve(), SolveResult::Unknown);
+    let mut solver = Solver::with_vars(4);
+    solver.config.engine = Engine::WalkSat;
+    solver.add_clause(&[-1, 2]).unwrap();
+    solver.add_clause(&[-2, 3]).unwrap();
+    assert_eq!(
+        solver.solve_with_assumptions(&lits(&[4, 1, -3])),
+        SolveResult::Unsat
+    );
+    assert!(solver.failed_assumptions().contains(&lits(&[-3])[0]));
+    assert_eq!(solver.solve_with_assumptions(&lits(&[1])), SolveResult::Sat);
+    assert!(solver.model().unwrap().value(Var::new(3)));
+
+    // As an oracle, the walk leaves its model in the saved phases, which
+    // CDCL then follows without a conflict
+    let (cnf, _) = random_3sat(3, 100, 380);
+    let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+    assert_eq!(solver.local_search(1_000_000), 0);
+    assert!(solver.solve().is_sat());
+    assert_eq!(solver.stats().conflicts, 0);
+
+    run_solver("test-SAT.cnf")
+        .arg("--engine=walksat")
+        .arg("--check-model")
+        .arg("--stats")
+        .assert()
+        .code(10)
+        .stdout(predicate::str::contains("c flips"));
+}
+
+#[test]
+fn test_local_search_rephasing() {
+    use cnf_dpll_2wl::SolveResult;
+
+    for seed in 0..20 {
+        let (cnf, clauses) = random_3sat(seed, 14, 60);
+        let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+        solver.config.rephasing = true;
+        solver.config.rephase_interval = 1;
+        let sat = solver.solve().is_sat();
+        assert_eq!(sat, brute_force(14, &clauses), "seed {seed}");
+        if sat {
+            solver.verify_model().unwrap();
+        }
+    }
+
+    let mut solver = Solver::from_dimacs_str(&pigeonhole_cnf(6)).unwrap();
+    solver.config.rephasing = true;
+    solver.config.rephase_interval = 50;
+    solver.config.rephase_flips = 1000;
+    assert_eq!(solver.solve(), SolveResult::Unsat);
+    assert!(solver.stats().rephases > 0);
+    assert!(solver.stats().flips > 0);
+
+    run_solver("hole6.cnf")
+        .arg("--rephase")
+        .arg("--stats")
+        .assert()
+        .code(20)
+        .stdout(predicate::str::contains("c rephases"));
+}
+
+#[test]
+fn test_branching_heuristics() {
+    use cnf_dpll_2wl::{Branching, SolveResult};
+
+    for branching in [Branching::Vmtf, Branching::Lrb] {
+        for seed in 0..40 {
+            let (cnf, clauses) = random_3sat(seed, 12, 52);
+            let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+            solver.config.branching = branching;
+            let sat = solver.solve().is_sat();
+            assert_eq!(sat, brute_force(12, &clauses), "{branching:?} seed {seed}");
+            if sat {
+                solver.verify_model().unwrap();
+            }
+            // Clauses and variables added between calls join the order
+            solver.add_clause(&[13, -1]).unwrap();
+            assert_eq!(solver.solve().is_sat(), sat);
+        }
+
+        for cnf in [
+            std::fs::read_to_string("examples/hole6.cnf").unwrap(),
+            pigeonhole_cnf(7),
+        ] {
+            let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+            solver.config.branching = branching;
+            solver.config.reduce_interval = 100;
+            assert_eq!(solver.solve(), SolveResult::Unsat);
+        }
+        let sudoku = std::fs::read_to_string("examples/sudoku.cnf").unwrap();
+        let mut solver = Solver::from_dimacs_str(&sudoku).unwrap();
+        solver.config.branching = branching;
+        assert!(solver.solve().is_sat());
+        solver.verify_model().unwrap();
+    }
+
+    for branching in ["vmtf", "lrb"] {
+        run_solver("zebra.cnf")
+            .arg(format!("--branching={branching}"))
+            .arg("--check-model")
+            .assert()
+            .code(10);
+    }
+    run_solver("zebra.cnf")
+        .arg("--branching=chb")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown branching heuristic chb"));
+}
+
+#[test]
+fn test_lrb_branching() {
+    use cnf_dpll_2wl::Branching;
+    use std::sync::{Arc, Mutex};
+
+    let cnf = std::fs::read_to_string("examples/zebra.cnf").unwrap();
+    let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+    solver.config.branching = Branching::Lrb;
+    assert!(solver.solve().is_sat());
+    solver.verify_model().unwrap();
+    let cnf = std::fs::read_to_string("examples/hole6.cnf").unwrap();
+    let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+    solver.config.branching = Branching::Lrb;
+    assert!(!solver.solve().is_sat());
+    assert!(solver.stats().conflicts > 0);
+
+    // Deciding 1 implies 2, 3 and 4, and 2 and 3 conflict: 1, 2 and 3 take
+    // part in the only conflict met while they were assigned, and their
+    // learning-rate averages rise above that of 4, which took part in none.
+    // After -1 is learnt, 2 or 3 is decided before 4, which would win the
+    // tie between equal scores
+    let mut solver =
+        Solver::from_dimacs_str("p cnf 4 4\n-1 2 0\n-1 3 0\n-1 4 0\n-2 -3 0\n").unwrap();
+    solver.config.branching = Branching::Lrb;
+    solver.config.jeroslow_wang = false;
+    let decisions = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&decisions);
+    solver.on_decision(move |lit, _| sink.lock().unwrap().push(lit.var().index()));
+    assert!(solver.solve().is_sat());
+    assert_eq!(solver.stats().conflicts, 1);
+    let decisions = decisions.lock().unwrap();
+    assert_eq!(decisions[0], 1, "{decisions:?}");
+    assert!([2, 3].contains(&decisions[1]), "{decisions:?}");
+    assert_eq!(decisions.last(), Some(&4), "{decisions:?}");
+}
+
+#[test]
+fn test_random_decisions() {
+    let run = |cnf: &str, seed: u64| {
+        let mut solver = Solver::from_dimacs_str(cnf).unwrap();
+        solver.config.random_var_freq = 0.2;
+        solver.config.random_phase_freq = 0.2;
+        solver.config.seed = seed;
+        let sat = solver.solve().is_sat();
+        if sat {
+            solver.verify_model().unwrap();
+        }
+        (sat, solver.stats().clone())
+    };
+    for seed in 0..30 {
+        let (cnf, clauses) = random_3sat(seed, 14, 60);
+        let (sat, stats) = run(&cnf, seed);
+        assert_eq!(sat, brute_force(14, &clauses), "seed {seed}");
+        // The same seed makes the same choices
+        let (_, again) = run(&cnf, seed);
+        assert_eq!(
+            (stats.decisions, stats.conflicts, stats.random_decisions),
+            (again.decisions, again.conflicts, again.random_decisions)
+        );
+    }
+    let (sat, stats) = run(&pigeonhole_cnf(6), 1);
+    assert!(!sat);
+    assert!(stats.random_decisions > 0);
+
+    let conflicts = || {
+        let output = run_solver("hole6.cnf")
+            .args(["--random-freq", "0.1", "--seed", "7", "--stats"])
+            .assert()
+            .code(20)
+            .get_output()
+            .stdout
+            .clone();
+        let stdout = String::from_utf8(output).unwrap();
+        let line = stdout.lines().find(|l| l.starts_with("c conflicts"));
+        line.unwrap().split_whitespace().nth(2).unwrap().to_string()
+    };
+    assert_eq!(conflicts(), conflicts());
+    run_solver("hole6.cnf")
+        .args(["--random-freq", "1.5"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--random-freq needs a probability",
+        ));
+}
+
+#[test]
+fn test_jeroslow_wang_initialization() {
+    use cnf_dpll_2wl::Branching;
+
+    // The negative literals of 1, 2 and 3 weigh more, each occurring in
+    // two binary clauses, so the first descent sets them false
+    let cnf = "p cnf 4 4\n-1 -2 0\n-1 -3 0\n-2 -3 0\n1 2 3 4 0\n";
+    for branching in [Branching::Vsids, Branching::Vmtf, Branching::Lrb] {
+        let mut solver = Solver::from_dimacs_str(cnf).unwrap();
+        solver.config.branching = branching;
+        assert!(solver.solve().is_sat());
+        let model = solver.model().unwrap();
+        let values: Vec<bool> = (1..=4).map(|v| model.value(Var::new(v))).collect();
+        assert_eq!(values, [false, false, false, true], "{branching:?}");
+        assert_eq!(solver.stats().conflicts, 0);
+        assert_eq!(solver.stats().decisions, 3);
+    }
+
+    // Without it, the first variable decided is set true
+    let mut solver = Solver::from_dimacs_str(cnf).unwrap();
+    solver.config.jeroslow_wang = false;
+    assert!(solver.solve().is_sat());
+    let model = solver.model().unwrap();
+    assert!((1..=3).any(|v| model.value(Var::new(v))));
+}
+
+#[test]
+fn test_rephase_schedule() {
+    use cnf_dpll_2wl::{Rephase, SolveResult};
+
+    let kinds = [
+        Rephase::Original,
+        Rephase::Inverted,
+        Rephase::Best,
+        Rephase::Random,
+        Rephase::Walk,
+    ];
+    for kind in kinds {
+        for seed in 0..20 {
+            let (cnf, clauses) = random_3sat(seed, 14, 60);
+            let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+            solver.config.rephasing = true;
+            solver.config.rephase_interval = 1;
+            solver.config.rephase_schedule = vec![kind];
+            let sat = solver.solve().is_sat();
+            assert_eq!(sat, brute_force(14, &clauses), "{kind:?} seed {seed}");
+            if sat {
+                solver.verify_model().unwrap();
+            }
+        }
+    }
+
+    // Without a walk in the schedule, no flips are spent
+    let mut solver = Solver::from_dimacs_str(&pigeonhole_cnf(6)).unwrap();
+    solver.config.rephasing = true;
+    solver.config.rephase_interval = 20;
+    solver.config.rephase_schedule = vec![Rephase::Best, Rephase::Inverted, Rephase::Random];
+    assert_eq!(solver.solve(), SolveResult::Unsat);
+    assert!(solver.stats().rephases >= 3);
+    assert_eq!(solver.stats().flips, 0);
+
+    run_solver("hole6.cnf")
+        .arg("--rephase-schedule=best,original,walk")
+        .arg("--stats")
+        .assert()
+        .code(20)
+        .stdout(predicate::str::contains("c rephases"));
+    run_solver("hole6.cnf")
+        .arg("--rephase-schedule=best,target")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown rephasing target"));
+}
+
+#[test]
+fn test_dump_implication_graph() {
+    // 1 → 2 → 3 and (2 ∧ 4) → 5, with 1 assumed and 4 a unit clause
+    let mut solver = Solver::with_vars(5);
+    solver.add_clause(&[-1, 2]).unwrap();
+    solver.add_clause(&[-2, 3]).unwrap();
+    solver.add_clause(&[-2, -4, 5]).unwrap();
+    solver.add_clause(&[4]).unwrap();
+    assert!(solver.solve_with_assumptions(&lits(&[1])).is_sat());
+    let mut dot = Vec::new();
+    solver.dump_implication_graph(&mut dot).unwrap();
+    let dot = String::from_utf8(dot).unwrap();
+    assert!(dot.starts_with("digraph implications {\n"));
+    assert!(dot.ends_with("}\n"));
+    for line in [
+        "subgraph cluster_0 {",
+        "\"4\" [label=\"4 @0\", shape=ellipse];",
+        "subgraph cluster_1 {",
+        "\"1\" [label=\"1 @1\", shape=box];",
+        "\"3\" [label=\"3 @1\", shape=ellipse];",
+        "\"1\" -> \"2\";",
+        "\"2\" -> \"3\";",
+        "\"2\" -> \"5\";",
+        "\"4\" -> \"5\";",
+    ] {
+        assert!(dot.contains(line), "{line} missing from\n{dot}");
+    }
+
+    let graph = tempfile::NamedTempFile::new().unwrap();
+    run_solver("zebra.cnf")
+        .arg("--dump-graph")
+        .arg(graph.path())
+        .assert()
+        .code(10);
+    let dot = std::fs::read_to_string(graph.path()).unwrap();
+    assert!(dot.starts_with("digraph implications {"));
+    assert!(dot.contains(" -> "));
+}
+
+#[test]
+fn test_search_trace() {
+    use cnf_dpll_2wl::SolveResult;
+
+    let trace = tempfile::NamedTempFile::new().unwrap();
+    let mut solver = Solver::with_vars(3);
+    for clause in [[1, 2], [1, -2], [-1, 3], [-1, -3]] {
+        solver.add_clause(&clause).unwrap();
+    }
+    solver.config.trace_path = Some(trace.path().to_path_buf());
+    assert_eq!(solver.solve(), SolveResult::Unsat);
+    solver.finish_trace().unwrap();
+    let text = std::fs::read_to_string(trace.path()).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert!(lines.iter().all(|line| line.starts_with("{\"event\":")));
+    assert!(lines[0].starts_with("{\"event\":\"decision\","));
+    for event in ["propagation", "conflict", "learnt", "backjump"] {
+        let prefix = format!("{{\"event\":\"{event}\",");
+        assert!(
+            lines.iter().any(|line| line.starts_with(&prefix)),
+            "{event} missing from\n{text}"
+        );
+    }
+    // The learnt unit is propagated at level 0 and falsifies a clause there
+    assert!(lines.contains(&"{\"event\":\"backjump\",\"from\":1,\"to\":0}"));
+    assert!(
+        lines
+            .last()
+            .unwrap()
+            .starts_with("{\"event\":\"conflict\",\"level\":0,")
+    );
+
+    run_solver("zebra.cnf")
+        .arg("--trace")
+        .arg(trace.path())
+        .assert()
+        .code(10);
+    let text = std::fs::read_to_string(trace.path()).unwrap();
+    assert!(text.contains("\"event\":\"decision\""));
+    run_solver("zebra.cnf")
+        .args(["--trace", "trace.jsonl", "--threads", "2"])
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn test_interpolant() {
+    use cnf_dpll_2wl::Formula;
+    use std::collections::HashSet;
+
+    fn names(formula: &Formula, out: &mut Vec<String>) {
+        match formula {
+            Formula::Const(_) => {}
+            Formula::Var(name) => out.push(name.clone()),
+            Formula::Not(f) => names(f, out),
+            Formula::And(fs) | Formula::Or(fs) => fs.iter().for_each(|f| names(f, out)),
+            Formula::Xor(a, b) | Formula::Iff(a, b) => {
+                names(a, out);
+                names(b, out);
+            }
+            Formula::Ite(c, t, e) => {
+                names(c, out);
+                names(t, out);
+                names(e, out);
+            }
+        }
+    }
+
+    // x1 ∧ (x1 → x2) against ¬x2: x2 is what they share
+    let cnf = "p cnf 2 3\n1 0\n-1 2 0\n-2 0\n";
+    let mut solver = Solver::from_dimacs_str(cnf).unwrap();
+    solver.config.resolution_proof = true;
+    assert!(!solver.solve().is_sat());
+    let interpolant = solver.interpolant(&[1, 2]).unwrap();
+    for x2 in [false, true] {
+        assert_eq!(interpolant.eval(&|name| name == "x2" && x2), x2);
+    }
+    assert_eq!(solver.interpolant(&[]), Some(Formula::Const(true)));
+    assert_eq!(solver.interpolant(&[1, 2, 3]), Some(Formula::Const(false)));
+
+    let mut checked = 0;
+    for seed in 0..30 {
+        let (cnf, clauses) = random_3sat(seed, 10, 50);
+        if brute_force(10, &clauses) {
+            continue;
+        }
+        let mut solver = Solver::from_dimacs_str(&cnf).unwrap();
+        solver.config.resolution_proof = true;
+        solver.config.elimination = seed % 2 == 0;
+        assert!(!solver.solve().is_sat(), "seed {seed}");
+        let a: Vec<u64> = (1..=25).collect();
+        let interpolant = solver.interpolant(&a).unwrap();
+        let (a_clauses, b_clauses) = clauses.split_at(25);
+
+        let vars_of = |clauses: &[Vec<i32>]| -> HashSet<String> {
+            clauses
+                .iter()
+                .flatten()
+                .map(|lit| format!("x{}", lit.abs()))
+                .collect()
+        };
+        let (a_vars, b_vars) = (vars_of(a_clauses), vars_of(b_clauses));
+        let mut used = Vec::new();
+        names(&interpolant, &mut used);
+        assert!(
+            used.iter()
+                .all(|name| a_vars.contains(name) && b_vars.contains(name)),
+            "seed {seed}"
+        );
+
+        for bits in 0u32..1 << 10 {
+            let value = |lit: i32| ((bits >> (lit.abs() - 1)) & 1 == 1) == (lit > 0);
+            let satisfies = |clauses: &[Vec<i32>]| {
+                clauses
+                    .iter()
+                    .all(|clause| clause.iter().any(|&lit| value(lit)))
+            };
+            let holds = interpolant.eval(&|name: &str| value(name[1..].parse().unwrap()));
+            assert!(!satisfies(a_clauses) || holds, "seed {seed}");
+            assert!(!holds || !satisfies(b_clauses), "seed {seed}");
+        }
+        checked += 1;
+    }
+    assert!(checked > 5);
+
+    // Only with a recorded refutation
+    let mut solver = Solver::from_dimacs_str(cnf).unwrap();
+    assert!(!solver.solve().is_sat());
+    assert!(solver.interpolant(&[1]).is_none());
+}
+
+#[test]
+fn test_symmetry_breaking() {
+    let mut plain = Solver::new("examples/hole6.cnf").unwrap();
+    assert!(!plain.solve().is_sat());
+    let mut solver = Solver::new("examples/hole6.cnf").unwrap();
+    solver.config.symmetry_breaking = true;
+    assert!(!solver.solve().is_sat());
+    assert!(solver.stats().symmetry_generators > 0);
+    assert!(solver.stats().conflicts * 10 < plain.stats().conflicts);
+
+    // Formulas closed under swapping 1 and 2, 3 and 4, and 5 with -6
+    let image = |lit: i32| match lit {
+        1 | 3 => lit + 1,
+        2 | 4 => lit - 1,
+        -1 | -3 => lit - 1,
+        -2 | -4 => lit + 1,
+        5 | -5 => -lit.signum() * 6,
+        6 | -6 => -lit.signum() * 5,
+        _ => lit,
+    };
+    for seed in 0..40 {
+        let (_, mut clauses) = random_3sat(seed, 8, 14);
+        let images: Vec<Vec<i32>> = clauses
+            .iter()
+            .map(|clause| clause.iter().map(|&lit| image(lit)).collect())
+            .collect();
+        clauses.extend(images);
+        let mut solver = Solver::with_vars(8);
+        for clause in &clauses {
+            solver.add_clause(&lits(clause)).unwrap();
+        }
+        solver.config.symmetry_breaking = true;
+        // A frozen variable keeps its place, which the swaps do not
+        let frozen = seed % 2 == 1;
+        if frozen {
+            solver.freeze(Var::new(1));
+        }
+        let sat = solver.solve().is_sat();
+        assert_eq!(sat, brute_force(8, &clauses), "seed {seed}");
+        if sat {
+            let model = solver.model().unwrap();
+            for clause in &clauses {
+                assert!(
+                    clause
+                        .iter()
+                        .any(|&lit| model.lit_is_true(Lit::try_from(lit).unwrap()))
+                );
+            }
+        }
+        assert_eq!(
+            solver.stats().symmetry_generators > 0,
+            !frozen,
+            "seed {seed}"
+        );
+    }
+
+    run_solver("hole6.cnf")
+        .args(["--pre=symmetry", "--stats"])
+        .assert()
+        .code(20)
+        .stdout(predicate::str::is_match(r"c symmetries broken +[1-9]").unwrap());
+    // Models leave out the variables of the lex-leader clauses
+    cargo_bin_cmd!("cnf-dpll-2wl")
+        .args(["--pre=symmetry", "--check-model", "-"])
+        .write_stdin("p cnf 3 2\n1 2 3 0\n-1 -2 0\n")
+        .assert()
+        .code(10)
+        .stdout(predicate::str::is_match(r"(?m)^v( -?[123]){3} 0$").unwrap());
+    run_solver("hole6.cnf")
+        .args(["--pre=symmetry", "--all"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--pre=symmetry only applies to a single search",
+        ));
+}
+
+#[test]
+fn test_at_most_one_detection() {
+    let mut plain = Solver::new("examples/sudoku.cnf").unwrap();
+    plain.config.subsumption = true;
+    assert!(plain.solve().is_sat());
+    let mut solver = Solver::new("examples/sudoku.cnf").unwrap();
+    solver.config.subsumption = true;
+    solver.config.at_most_one = true;
+    assert!(solver.solve().is_sat());
+    assert_eq!(solver.verify_model(), Ok(()));
+    assert!(solver.stats().at_most_ones > 0);
+    assert!(solver.stats().amo_propagations > 0);
+    assert!(solver.at_most_ones().all(|group| group.len() >= 3));
+    assert!(solver.num_original_clauses() < plain.num_original_clauses());
+
+    // Written back as clauses, the groups give their binary clauses again
+    let mut solver = Solver::new("examples/hole6.cnf").unwrap();
+    let inputs = solver.num_original_clauses();
+    solver.config.at_most_one = true;
+    assert!(solver.preprocess());
+    assert_eq!(solver.stats().at_most_ones, 6);
+    assert!(solver.num_original_clauses() < inputs);
+    let mut out = Vec::new();
+    solver.write_dimacs(&mut out).unwrap();
+    let mut written = Solver::from_bytes(&out).unwrap();
+    assert_eq!(written.num_original_clauses(), inputs);
+    assert!(!written.solve().is_sat());
+
+    // Random formulas over a group of five literals, pairwise exclusive
+    let group = [1, -2, 3, 4, -5];
+    for seed in 0..40 {
+        let (_, mut clauses) = random_3sat(seed, 9, 20);
+        for (i, &a) in group.iter().enumerate() {
+            for &b in &group[i + 1..] {
+                clauses.push(vec![-a, -b]);
+            }
+        }
+        let mut solver = Solver::with_vars(9);
+        for clause in &clauses {
+            solver.add_clause(&lits(clause)).unwrap();
+        }
+        solver.config.at_most_one = true;
+        let sat = solver.solve().is_sat();
+        assert_eq!(sat, brute_force(9, &clauses), "seed {seed}");
+        assert!(solver.stats().at_most_ones > 0, "seed {seed}");
+        if sat {
+            assert_eq!(solver.verify_model(), Ok(()), "seed {seed}");
+            let model = solver.model().unwrap();
+            for clause in &clauses {
+                assert!(
+                    clause
+                        .iter()
+                        .any(|&lit| model.lit_is_true(Lit::try_from(lit).unwrap()))
+                );
+            }
+        }
+        // Later clauses and assumptions see the groups too
+        clauses.push(vec![1, 3]);
+        solver.add_clause(&[1, 3]).unwrap();
+        assert_eq!(
+            solver.solve().is_sat(),
+            brute_force(9, &clauses),
+            "seed {seed}"
+        );
+        let assumed = solver.solve_with_assumptions(&lits(&[-2, 4]));
+        assert!(!assumed.is_sat(), "seed {seed}");
+    }
+
+    run_solver("zebra.cnf")
+        .args(["--pre=subsume,amo", "--stats", "--check-model"])
+        .assert()
+        .code(10)
+        .stdout(predicate::str::is_match(r"c at-most-one groups +[1-9]").unwrap());
+    // Clauses learnt from the groups follow from their binary clauses
+    let proof = tempfile::NamedTempFile::new().unwrap();
+    run_solver("hole6.cnf")
+        .arg("--pre=amo")
+        .arg("--proof")
+        .arg(proof.path())
+        .assert()
+        .code(20);
+    let cnf = std::fs::read_to_string("examples/hole6.cnf").unwrap();
+    check_drat(&cnf, &std::fs::read_to_string(proof.path()).unwrap());
+}
+
+#[test]
+fn test_gate_extraction() {
+    use cnf_dpll_2wl::{Formula, Gate, Model, Tseitin};
+
+    // Every connective comes back as the gate of its Tseitin variable
+    let formula = Formula::parse("((a & b & !c) | (d ^ e)) <-> (f ? g : !a)").unwrap();
+    let mut solver = Solver::with_vars(0);
+    let mut tseitin = Tseitin::new();
+    tseitin.encode(&mut solver, &formula);
+    let gates: Vec<String> = solver.extract_gates().iter().map(Gate::to_string).collect();
+    assert_eq!(
+        gates,
+        [
+            "4 = and(1, 2, -3)",
+            "7 = xor(5, 6)",
+            "-8 = and(-4, -7)",
+            "11 = ite(9, 10, -1)",
+            "-12 = xor(8, 11)",
+        ]
+    );
+
+    let holds = |gate: &Gate, model: &Model| {
+        let value = |lit: Lit| model.lit_is_true(lit);
+        let function = match gate {
+            Gate::And { inputs, .. } => inputs.iter().all(|&lit| value(lit)),
+            Gate::Xor { inputs, .. } => inputs.iter().fold(false, |acc, &lit| acc ^ value(lit)),
+            Gate::Ite {
+                condition,
+                then,
+                otherwise,
+                ..
+            } => value(if value(*condition) { *then } else { *otherwise }),
+        };
+        value(gate.output()) == function
+    };
+    let names = ["a", "b", "c", "d", "e"];
+    let mut gate_count = 0;
+    let mut gate_eliminations = 0;
+    for seed in 0..200u64 {
+        let mut state = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+        let mut next = move |bound: u64| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state % bound
+        };
+        let formula = random_formula(&mut next, 4);
+        let eval = |value: &dyn Fn(&str) -> bool| formula.eval(&|name| value(name));
+
+        // Every gate found holds in every model of the encoding
+        let mut solver = Solver::with_vars(0);
+        let mut tseitin = Tseitin::new();
+        for name in names {
+            tseitin.var(&mut solver, name);
+        }
+        tseitin.encode(&mut solver, &formula);
+        let gates = solver.extract_gates();
+        gate_count += gates.len();
+        for model in solver.iter_models() {
+            for gate in &gates {
+                assert!(holds(gate, &model), "seed {seed}: {gate}");
+            }
+        }
+
+        // Elimination through the gates keeps the answer and the models
+        let mut solver = Solver::with_vars(0);
+        let mut tseitin = Tseitin::new();
+        for name in names {
+            tseitin.var(&mut solver, name);
+        }
+        tseitin.assert(&mut solver, &formula);
+        solver.config.elimination = true;
+        let expected = (0u32..1 << 5).any(|bits| {
+            eval(&|name| {
+                let i = names.iter().position(|&n| n == name).unwrap();
+                bits >> i & 1 == 1
+            })
+        });
+        assert_eq!(solver.solve().is_sat(), expected, "seed {seed}: {formula}");
+        if expected {
+            let model = solver.model().unwrap();
+            assert!(eval(&|name| model.value(tseitin.var_of(name).unwrap())));
+        }
+        gate_eliminations += solver.stats().gate_eliminations;
+    }
+    assert!(gate_count > 200);
+    assert!(gate_eliminations > 0);
+}
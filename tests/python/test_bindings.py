@@ -0,0 +1,50 @@
+# Tests of the Python bindings, run with pytest once the module is built:
+# `maturin develop && pytest tests/python`.
+
+import pytest
+
+from cnf_dpll_2wl import Solver
+
+
+def test_model_is_one_signed_literal_per_variable():
+    solver = Solver()
+    solver.add_clause([1, -3])
+    solver.add_clause([2, 3, -1])
+    assert solver.num_vars() == 3
+    assert solver.solve(assumptions=[1, -2])
+    assert solver.model() == [1, -2, 3]
+
+
+def test_unsatisfiable_assumptions():
+    solver = Solver(3)
+    solver.add_clause([1, -3])
+    solver.add_clause([2, 3, -1])
+    assert solver.model() is None
+    assert solver.solve(assumptions=[-1, 3]) is False
+    assert solver.model() is None
+    assert set(solver.failed_assumptions()) <= {-1, 3}
+    assert solver.failed_assumptions()
+    # Assumptions hold for one call only
+    assert solver.solve() is True
+
+
+def test_dimacs_input():
+    solver = Solver.from_dimacs("p cnf 2 2\n1 2 0\n-1 0\n")
+    assert solver.solve()
+    assert solver.model() == [-1, 2]
+    with pytest.raises(ValueError, match="line 2"):
+        Solver.from_dimacs("p cnf 2 1\n1 x 0\n")
+    with pytest.raises(OSError):
+        Solver.from_file("no/such/file.cnf")
+
+
+def test_invalid_arguments():
+    solver = Solver()
+    with pytest.raises(ValueError):
+        solver.add_clause([1, 0])
+    with pytest.raises(ValueError):
+        solver.solve(assumptions=[0])
+    with pytest.raises(ValueError):
+        solver.set_timeout(-1.0)
+    solver.set_timeout(10.0)
+    assert solver.solve()